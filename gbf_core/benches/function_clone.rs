@@ -0,0 +1,47 @@
+#![feature(test)]
+
+extern crate test;
+
+use gbf_core::basic_block::BasicBlockType;
+use gbf_core::function::{Function, FunctionId};
+use test::Bencher;
+
+/// The number of blocks in the benchmarked function.
+const BLOCK_COUNT: usize = 10_000;
+
+/// Builds a function whose blocks form a chain, with a back edge every ten blocks.
+fn large_function() -> Function {
+    let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    let mut previous = function.get_entry_basic_block_id();
+    for address in 1..BLOCK_COUNT {
+        let block = function
+            .create_block(BasicBlockType::Normal, address)
+            .unwrap();
+        function.add_edge(previous, block).unwrap();
+        if address % 10 == 0 {
+            function.add_edge(block, previous).unwrap();
+        }
+        previous = block;
+    }
+    function
+}
+
+/// Cloning shares the blocks and graph, so it costs a reference count bump.
+#[bench]
+fn bench_clone(b: &mut Bencher) {
+    let function = large_function();
+    b.iter(|| test::black_box(function.clone()));
+}
+
+/// Modifying a clone copies the blocks and graph first, which is what every clone
+/// cost before they were shared.
+#[bench]
+fn bench_clone_and_modify(b: &mut Bencher) {
+    let function = large_function();
+    let entry = function.get_entry_basic_block_id();
+    b.iter(|| {
+        let mut clone = function.clone();
+        clone.get_basic_block_by_id_mut(entry).unwrap();
+        test::black_box(clone)
+    });
+}
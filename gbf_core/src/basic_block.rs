@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     cfg_dot::RenderableNode,
     instruction::Instruction,
+    opcode::Opcode,
     utils::{
         html_encode, Gs2BytecodeAddress, GBF_BLUE, GBF_GREEN, GBF_RED, GBF_YELLOW,
         OPERAND_TRUNCATE_LENGTH,
@@ -180,6 +181,51 @@ impl BasicBlock {
         self.instructions.last()
     }
 
+    /// Gets the instruction that terminates the block. This is the block's last
+    /// instruction, which is not necessarily a branch: a block can also end because
+    /// the next instruction is the target of a jump.
+    ///
+    /// # Returns
+    /// - A reference to the terminating instruction, or `None` if the block is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::basic_block::{BasicBlock, BasicBlockId, BasicBlockType};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut block = BasicBlock::new(BasicBlockId::new(0, BasicBlockType::Normal, 0));
+    /// assert!(block.terminator().is_none());
+    ///
+    /// block.add_instruction(Instruction::new(Opcode::PushNumber, 0));
+    /// block.add_instruction(Instruction::new(Opcode::Ret, 1));
+    /// assert_eq!(block.terminator().unwrap().address, 1);
+    /// ```
+    pub fn terminator(&self) -> Option<&Instruction> {
+        self.instructions.last()
+    }
+
+    /// Gets the opcode of the instruction that terminates the block.
+    ///
+    /// # Returns
+    /// - The opcode of the terminating instruction, or `None` if the block is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::basic_block::{BasicBlock, BasicBlockId, BasicBlockType};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut block = BasicBlock::new(BasicBlockId::new(0, BasicBlockType::Normal, 0));
+    /// assert_eq!(block.terminator_opcode(), None);
+    ///
+    /// block.add_instruction(Instruction::new(Opcode::Ret, 0));
+    /// assert_eq!(block.terminator_opcode(), Some(Opcode::Ret));
+    /// ```
+    pub fn terminator_opcode(&self) -> Option<Opcode> {
+        self.terminator().map(|instruction| instruction.opcode)
+    }
+
     /// Get the number of instructions in the block.
     ///
     /// # Returns
@@ -211,6 +257,62 @@ impl BasicBlock {
     pub fn is_empty(&self) -> bool {
         self.instructions.is_empty()
     }
+
+    /// Gets the instructions up to and including the first block-terminating
+    /// instruction, without modifying the block. Well-formed bytecode never has
+    /// instructions after the terminator, but malformed modules can, and they would
+    /// otherwise be decompiled into statements that never execute.
+    ///
+    /// # Returns
+    /// - The instructions that can execute.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::basic_block::{BasicBlock, BasicBlockId, BasicBlockType};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut block = BasicBlock::new(BasicBlockId::new(0, BasicBlockType::Normal, 0));
+    /// block.add_instruction(Instruction::new(Opcode::Ret, 0));
+    /// block.add_instruction(Instruction::new(Opcode::PushNumber, 1));
+    /// assert_eq!(block.instructions_until_terminator().len(), 1);
+    /// assert_eq!(block.len(), 2);
+    /// ```
+    pub fn instructions_until_terminator(&self) -> &[Instruction] {
+        match self
+            .instructions
+            .iter()
+            .position(|instruction| instruction.opcode.is_block_end())
+        {
+            Some(index) => &self.instructions[..=index],
+            None => &self.instructions,
+        }
+    }
+
+    /// Drops any instructions that follow the first block-terminating instruction.
+    /// See `instructions_until_terminator`.
+    ///
+    /// # Returns
+    /// - The number of instructions that were removed.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::basic_block::{BasicBlock, BasicBlockId, BasicBlockType};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut block = BasicBlock::new(BasicBlockId::new(0, BasicBlockType::Normal, 0));
+    /// block.add_instruction(Instruction::new(Opcode::Ret, 0));
+    /// block.add_instruction(Instruction::new(Opcode::PushNumber, 1));
+    /// assert_eq!(block.truncate_after_terminator(), 1);
+    /// assert_eq!(block.len(), 1);
+    /// ```
+    pub fn truncate_after_terminator(&mut self) -> usize {
+        let kept = self.instructions_until_terminator().len();
+        let removed = self.instructions.len() - kept;
+        self.instructions.truncate(kept);
+        removed
+    }
 }
 
 // == Implementations ==
@@ -318,28 +420,7 @@ impl RenderableNode for BasicBlock {
         } else {
             // Render each instruction as a table row with indentation.
             for inst in &self.instructions {
-                // Get the string of an operand, if it exists, or a space.
-                // If the resulting operand exceeds OPERAND_TRUNCATE_LENGTH,
-                // truncate it and append an ellipsis.
-
-                let operand = inst
-                    .operand
-                    .as_ref()
-                    .map(|op| {
-                        let mut op_str = op.to_string();
-                        if op_str.len() > OPERAND_TRUNCATE_LENGTH {
-                            op_str.truncate(OPERAND_TRUNCATE_LENGTH);
-                            op_str.push_str("...");
-                        }
-
-                        if !op_str.is_empty() {
-                            op_str
-                        } else {
-                            " ".to_string()
-                        }
-                    })
-                    .unwrap_or_else(|| " ".to_string());
-                let operand = html_encode(operand);
+                let operand = html_encode(render_operand(inst));
 
                 writeln!(
                     &mut label,
@@ -364,6 +445,49 @@ impl RenderableNode for BasicBlock {
 
         label
     }
+
+    /// Render the block as plain text lines, one per instruction.
+    fn render_lines(&self) -> Vec<String> {
+        if self.id.block_type == BasicBlockType::ModuleEnd {
+            return vec!["Module End".to_string()];
+        }
+
+        self.instructions
+            .iter()
+            .map(|inst| {
+                format!(
+                    "{:04X}  {}  {}",
+                    inst.address,
+                    inst.opcode,
+                    render_operand(inst)
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+}
+
+/// Get the string of an instruction's operand, if it exists, or a space.
+/// If the resulting operand exceeds OPERAND_TRUNCATE_LENGTH, it is truncated
+/// and an ellipsis is appended.
+fn render_operand(inst: &Instruction) -> String {
+    inst.operand
+        .as_ref()
+        .map(|op| {
+            let mut op_str = op.to_string();
+            if op_str.len() > OPERAND_TRUNCATE_LENGTH {
+                op_str.truncate(OPERAND_TRUNCATE_LENGTH);
+                op_str.push_str("...");
+            }
+
+            if !op_str.is_empty() {
+                op_str
+            } else {
+                " ".to_string()
+            }
+        })
+        .unwrap_or_else(|| " ".to_string())
 }
 
 #[cfg(test)]
@@ -407,6 +531,23 @@ mod tests {
         assert_eq!(block.len(), 1);
     }
 
+    #[test]
+    fn test_basic_block_truncate_after_terminator() {
+        let mut block = BasicBlock::new(BasicBlockId::new(0, BasicBlockType::Normal, 0));
+        assert_eq!(block.truncate_after_terminator(), 0);
+
+        block.add_instruction(Instruction::new(Opcode::PushNumber, 0));
+        block.add_instruction(Instruction::new(Opcode::Ret, 1));
+        assert_eq!(block.truncate_after_terminator(), 0);
+        assert_eq!(block.len(), 2);
+
+        block.add_instruction(Instruction::new(Opcode::PushNumber, 2));
+        block.add_instruction(Instruction::new(Opcode::Ret, 3));
+        assert_eq!(block.truncate_after_terminator(), 2);
+        assert_eq!(block.len(), 2);
+        assert_eq!(block.last_instruction().unwrap().address, 1);
+    }
+
     #[test]
     fn test_basic_block_is_empty() {
         let block = BasicBlock::new(BasicBlockId::new(0, BasicBlockType::Normal, 23));
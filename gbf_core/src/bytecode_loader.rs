@@ -1,10 +1,11 @@
 #![deny(missing_docs)]
 
 use crate::{
-    graal_io::{GraalIoError, GraalReader},
+    graal_io::{ChecksumReader, GraalIoError, GraalReader},
     instruction::Instruction,
     opcode::{Opcode, OpcodeError},
-    operand::{Operand, OperandError},
+    operand::OperandError,
+    operand_decoder::{default_operand_decoders, DecodedOperand, OperandDecoder, OperandInput},
     utils::Gs2BytecodeAddress,
 };
 
@@ -18,6 +19,16 @@ use petgraph::graph::{DiGraph, NodeIndex};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// The default maximum number of instructions the loader will decode.
+pub const DEFAULT_MAX_INSTRUCTIONS: usize = 10_000_000;
+
+/// The default maximum number of blocks the loader will build a graph for.
+pub const DEFAULT_MAX_BLOCKS: usize = 1_000_000;
+
+/// The magic signature at the start of a bytecode header. The header is the magic,
+/// followed by the version as a big-endian `u32`.
+pub const GS2BC_MAGIC: [u8; 4] = *b"GS2B";
+
 /// Error type for bytecode operations.
 #[derive(Error, Debug, Clone, Serialize)]
 pub enum BytecodeLoaderError {
@@ -29,9 +40,10 @@ pub enum BytecodeLoaderError {
     #[error("Invalid section length for {0}: {1}")]
     InvalidSectionLength(SectionType, u32),
 
-    /// Error when string index is out of bounds.
-    #[error("String index {0} is out of bounds. Length: {1}")]
-    StringIndexOutOfBounds(usize, usize),
+    /// Error when string index is out of bounds. Includes the address of the instruction
+    /// that referenced the string.
+    #[error("String index {0} is out of bounds at address {2}. Length: {1}")]
+    StringIndexOutOfBounds(usize, usize, Gs2BytecodeAddress),
 
     /// Error for when there is no previous instruction when setting an operand.
     #[error("No previous instruction to set operand")]
@@ -41,6 +53,19 @@ pub enum BytecodeLoaderError {
     #[error("Block at address {0} is unreachable")]
     UnreachableBlock(Gs2BytecodeAddress),
 
+    /// Error for when the module has more instructions than the configured maximum.
+    #[error("Module exceeds the maximum of {0} instructions")]
+    TooManyInstructions(usize),
+
+    /// Error for when the module has more blocks than the configured maximum.
+    #[error("Module exceeds the maximum of {0} blocks")]
+    TooManyBlocks(usize),
+
+    /// Error for when data from a section is requested, but the section was skipped
+    /// while loading.
+    #[error("Section {0} was not loaded")]
+    SectionNotLoaded(SectionType),
+
     /// Error for when an I/O error occurs.
     #[error("GraalIo error: {0}")]
     GraalIo(#[from] GraalIoError),
@@ -52,6 +77,30 @@ pub enum BytecodeLoaderError {
     /// Error for when an invalid operand is encountered.
     #[error("Invalid operand: {0}")]
     InvalidOperand(#[from] OperandError),
+
+    /// Error for when an instruction's address does not match its position in the
+    /// instruction list.
+    #[error("Instruction at index {0} has address {1}")]
+    InstructionAddressMismatch(usize, Gs2BytecodeAddress),
+
+    /// Error for when the address arithmetic of an instruction overflows, such as a
+    /// negative jump target or an address past the largest possible address.
+    #[error("Address arithmetic overflowed at address {0}")]
+    AddressOverflow(Gs2BytecodeAddress),
+
+    /// Error for when a jump instruction has no target operand.
+    #[error("Jump instruction at address {0} has no target")]
+    MissingJumpTarget(Gs2BytecodeAddress),
+
+    /// Error for when the functions section defines the same name more than once, and
+    /// duplicate function names are not allowed.
+    #[error("Function {0} at address {1} is already defined")]
+    DuplicateFunctionName(String, Gs2BytecodeAddress),
+
+    /// Error for when a header is expected, but the bytecode does not start with
+    /// `GS2BC_MAGIC`.
+    #[error("Invalid magic: {0:#010X}")]
+    InvalidMagic(u32),
 }
 
 impl std::fmt::Display for SectionType {
@@ -82,9 +131,79 @@ pub enum SectionType {
     Instructions = 4,
 }
 
+/// A set of sections for the loader to parse. Sections that are not in the mask
+/// are skipped using their length, without being decoded.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct SectionMask(u8);
+
+// Each section is stored in the bit indexed by its section type value.
+
+impl SectionMask {
+    /// A mask containing every section.
+    pub const ALL: SectionMask = SectionMask(0b11110);
+
+    /// A mask containing no sections.
+    pub const NONE: SectionMask = SectionMask(0);
+
+    /// Returns a copy of the mask with the given section added.
+    ///
+    /// # Arguments
+    /// - `section`: The section to add.
+    ///
+    /// # Returns
+    /// - The new `SectionMask`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::{SectionMask, SectionType};
+    ///
+    /// let mask = SectionMask::NONE.with(SectionType::Functions);
+    /// assert!(mask.contains(SectionType::Functions));
+    /// assert!(!mask.contains(SectionType::Strings));
+    /// ```
+    pub fn with(self, section: SectionType) -> Self {
+        SectionMask(self.0 | Self::bit(section))
+    }
+
+    /// Checks if the mask contains the given section.
+    ///
+    /// # Arguments
+    /// - `section`: The section to check.
+    ///
+    /// # Returns
+    /// - `true` if the section is in the mask, `false` otherwise.
+    pub fn contains(self, section: SectionType) -> bool {
+        self.0 & Self::bit(section) != 0
+    }
+
+    fn bit(section: SectionType) -> u8 {
+        1 << (section as u32)
+    }
+}
+
+impl Default for SectionMask {
+    fn default() -> Self {
+        SectionMask::ALL
+    }
+}
+
+impl From<SectionType> for SectionMask {
+    fn from(section: SectionType) -> Self {
+        SectionMask::NONE.with(section)
+    }
+}
+
 /// A builder for a BytecodeLoader.
 pub struct BytecodeLoaderBuilder<R> {
     reader: R,
+    sections: SectionMask,
+    max_instructions: usize,
+    max_blocks: usize,
+    strict_function_names: bool,
+    expect_header: bool,
+    compute_checksum: bool,
+    lenient_strings: bool,
+    operand_decoders: HashMap<Opcode, Box<dyn OperandDecoder>>,
 }
 
 impl<R: std::io::Read> BytecodeLoaderBuilder<R> {
@@ -104,7 +223,189 @@ impl<R: std::io::Read> BytecodeLoaderBuilder<R> {
     /// let builder = BytecodeLoaderBuilder::new(reader);
     /// ```
     pub fn new(reader: R) -> Self {
-        Self { reader }
+        Self {
+            reader,
+            sections: SectionMask::ALL,
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+            max_blocks: DEFAULT_MAX_BLOCKS,
+            strict_function_names: false,
+            expect_header: false,
+            compute_checksum: false,
+            lenient_strings: false,
+            operand_decoders: HashMap::new(),
+        }
+    }
+
+    /// Sets the maximum number of instructions to decode. Loading a module with more
+    /// instructions fails instead of exhausting memory.
+    ///
+    /// # Arguments
+    /// - `max_instructions`: The maximum number of instructions.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader).max_instructions(100_000);
+    /// ```
+    pub fn max_instructions(mut self, max_instructions: usize) -> Self {
+        self.max_instructions = max_instructions;
+        self
+    }
+
+    /// Sets the maximum number of blocks to build a graph for. Loading a module with
+    /// more blocks fails instead of exhausting memory.
+    ///
+    /// # Arguments
+    /// - `max_blocks`: The maximum number of blocks.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader).max_blocks(10_000);
+    /// ```
+    pub fn max_blocks(mut self, max_blocks: usize) -> Self {
+        self.max_blocks = max_blocks;
+        self
+    }
+
+    /// Sets which sections the loader should parse. All other sections are skipped.
+    ///
+    /// Decoding instructions requires the functions and strings sections, so requesting
+    /// `SectionType::Instructions` loads those sections as well.
+    ///
+    /// # Arguments
+    /// - `sections`: The sections to parse.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::{BytecodeLoaderBuilder, SectionMask, SectionType};
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader)
+    ///     .sections(SectionMask::NONE.with(SectionType::Functions));
+    /// ```
+    pub fn sections(mut self, sections: SectionMask) -> Self {
+        self.sections = sections;
+        self
+    }
+
+    /// Sets whether a function name that is defined more than once is an error. By
+    /// default, a warning is logged and the duplicate is renamed by appending its
+    /// address, e.g. `onCreated@12`.
+    ///
+    /// # Arguments
+    /// - `strict_function_names`: Whether duplicate function names are an error.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader).strict_function_names(true);
+    /// ```
+    pub fn strict_function_names(mut self, strict_function_names: bool) -> Self {
+        self.strict_function_names = strict_function_names;
+        self
+    }
+
+    /// Sets whether the bytecode starts with a header, which is `GS2BC_MAGIC` followed
+    /// by the version. By default, the bytecode starts directly with the first section.
+    ///
+    /// # Arguments
+    /// - `expect_header`: Whether the bytecode starts with a header.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader).expect_header(true);
+    /// ```
+    pub fn expect_header(mut self, expect_header: bool) -> Self {
+        self.expect_header = expect_header;
+        self
+    }
+
+    /// Sets whether the loader computes a CRC-32 checksum of every byte it reads, which
+    /// is available from `BytecodeLoader::content_checksum` after loading.
+    ///
+    /// # Arguments
+    /// - `compute_checksum`: Whether to compute the checksum.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader).compute_checksum(true);
+    /// ```
+    pub fn compute_checksum(mut self, compute_checksum: bool) -> Self {
+        self.compute_checksum = compute_checksum;
+        self
+    }
+
+    /// Sets whether a string that runs into the end of the bytecode without a null
+    /// terminator is accepted instead of failing to load. The bytes read before the end
+    /// are used as the string, and a warning is logged. Strict parsing is the default.
+    ///
+    /// # Arguments
+    /// - `lenient_strings`: Whether to accept unterminated strings.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    ///
+    /// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+    /// let builder = BytecodeLoaderBuilder::new(reader).lenient_strings(true);
+    /// ```
+    pub fn lenient_strings(mut self, lenient_strings: bool) -> Self {
+        self.lenient_strings = lenient_strings;
+        self
+    }
+
+    /// Registers a decoder for the operand that follows an opcode, replacing the default
+    /// decoder for that opcode if there is one. The opcode becomes an operand opcode, so
+    /// its operand is attached to the previous instruction. See `OperandDecoder` for an
+    /// example.
+    ///
+    /// # Arguments
+    /// - `opcode`: The opcode to decode the operand of.
+    /// - `decoder`: The decoder to use.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeLoaderBuilder`.
+    pub fn operand_decoder(
+        mut self,
+        opcode: Opcode,
+        decoder: impl OperandDecoder + 'static,
+    ) -> Self {
+        self.operand_decoders.insert(opcode, Box::new(decoder));
+        self
     }
 
     /// Builds a `BytecodeLoader` from the builder.
@@ -119,10 +420,33 @@ impl<R: std::io::Read> BytecodeLoaderBuilder<R> {
     /// - `BytecodeLoaderError::NoPreviousInstruction` if there is no previous instruction when setting an operand.
     /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
     /// - `BytecodeLoaderError::OpcodeError` if an invalid opcode is encountered.
+    /// - `BytecodeLoaderError::TooManyInstructions` if the module has too many instructions.
+    /// - `BytecodeLoaderError::TooManyBlocks` if the module has too many blocks.
+    /// - `BytecodeLoaderError::DuplicateFunctionName` if a function name is defined more
+    ///   than once and `strict_function_names` is set.
+    /// - `BytecodeLoaderError::InvalidMagic` if `expect_header` is set and the bytecode
+    ///   does not start with `GS2BC_MAGIC`.
     pub fn build(self) -> Result<BytecodeLoader<R>, BytecodeLoaderError> {
+        let sections = if self.sections.contains(SectionType::Instructions) {
+            self.sections
+                .with(SectionType::Functions)
+                .with(SectionType::Strings)
+        } else {
+            self.sections
+        };
+
         let mut loader = BytecodeLoader {
+            sections,
+            max_instructions: self.max_instructions,
+            max_blocks: self.max_blocks,
+            strict_function_names: self.strict_function_names,
+            expect_header: self.expect_header,
+            version: None,
+            gs1_flags: 0,
             block_breaks: BTreeSet::new(),
-            reader: GraalReader::new(self.reader),
+            reader: GraalReader::new(ChecksumReader::new(self.reader, self.compute_checksum))
+                .lenient_strings(self.lenient_strings),
+            operand_decoders: self.operand_decoders,
             function_map: HashMap::new(),
             strings: Vec::new(),
             instructions: Vec::new(),
@@ -137,9 +461,33 @@ impl<R: std::io::Read> BytecodeLoaderBuilder<R> {
 
 /// A structure for loading bytecode from a reader.
 pub struct BytecodeLoader<R: Read> {
-    reader: GraalReader<R>,
+    reader: GraalReader<ChecksumReader<R>>,
     strings: Vec<String>,
 
+    /// The sections that were parsed by the loader.
+    sections: SectionMask,
+
+    /// The maximum number of instructions to decode.
+    max_instructions: usize,
+
+    /// The maximum number of blocks to build a graph for.
+    max_blocks: usize,
+
+    /// Whether a function name that is defined more than once is an error.
+    strict_function_names: bool,
+
+    /// Whether the bytecode starts with a header.
+    expect_header: bool,
+
+    /// The operand decoders that take precedence over the default ones.
+    operand_decoders: HashMap<Opcode, Box<dyn OperandDecoder>>,
+
+    /// The version from the header, if one was read.
+    version: Option<u32>,
+
+    /// The contents of the flags section.
+    gs1_flags: u32,
+
     /// A map of function names to their addresses.
     pub function_map: HashMap<Option<String>, Gs2BytecodeAddress>,
 
@@ -159,6 +507,77 @@ pub struct BytecodeLoader<R: Read> {
     pub block_address_to_function: HashMap<Gs2BytecodeAddress, Option<String>>,
 }
 
+impl BytecodeLoader<std::io::Empty> {
+    /// Creates a loader from instructions that are already decoded, splitting them into
+    /// blocks and attributing the blocks to functions the same way loading bytecode does.
+    ///
+    /// # Arguments
+    /// - `instructions`: The instructions, where each instruction's address is its index.
+    /// - `function_map`: A map of function names to their addresses. The entry function
+    ///   is added at address 0 if it is missing.
+    /// - `strings`: The strings used by the instructions.
+    ///
+    /// # Returns
+    /// - A `Result` containing the `BytecodeLoader` if successful.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::InstructionAddressMismatch` if an instruction's address does
+    ///   not match its index.
+    /// - `BytecodeLoaderError::MissingJumpTarget` if a jump instruction has no operand.
+    /// - `BytecodeLoaderError::InvalidOperand` if a jump target or function address is past
+    ///   the end of the instructions.
+    /// - `BytecodeLoaderError::AddressOverflow` if a jump target is negative.
+    /// - `BytecodeLoaderError::TooManyInstructions` if there are too many instructions.
+    /// - `BytecodeLoaderError::TooManyBlocks` if there are too many blocks.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use gbf_core::bytecode_loader::BytecodeLoader;
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let instructions = vec![Instruction::new(Opcode::Ret, 0)];
+    /// let loader = BytecodeLoader::from_instructions(instructions, HashMap::new(), Vec::new())
+    ///     .unwrap();
+    /// assert!(loader.is_instruction_reachable(0));
+    /// ```
+    pub fn from_instructions(
+        instructions: Vec<Instruction>,
+        mut function_map: HashMap<Option<String>, Gs2BytecodeAddress>,
+        strings: Vec<String>,
+    ) -> Result<Self, BytecodeLoaderError> {
+        if instructions.len() > DEFAULT_MAX_INSTRUCTIONS {
+            return Err(BytecodeLoaderError::TooManyInstructions(
+                DEFAULT_MAX_INSTRUCTIONS,
+            ));
+        }
+        function_map.entry(None).or_insert(0);
+
+        let mut loader = BytecodeLoader {
+            sections: SectionMask::ALL,
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+            max_blocks: DEFAULT_MAX_BLOCKS,
+            strict_function_names: false,
+            expect_header: false,
+            version: None,
+            gs1_flags: 0,
+            block_breaks: BTreeSet::new(),
+            reader: GraalReader::new(ChecksumReader::new(std::io::empty(), false)),
+            operand_decoders: HashMap::new(),
+            function_map,
+            strings,
+            instructions,
+            raw_block_graph: DiGraph::new(),
+            raw_block_address_to_node: HashMap::new(),
+            block_address_to_function: HashMap::new(),
+        };
+        loader.split_blocks()?;
+        loader.build_block_graph()?;
+        Ok(loader)
+    }
+}
+
 impl<R: Read> BytecodeLoader<R> {
     /// Asserts that the section length is correct.
     ///
@@ -188,14 +607,39 @@ impl<R: Read> BytecodeLoader<R> {
 
     /// Reads the flags section from the reader.
     ///
-    /// We don't actually need to do anything with the flags section, so we just read it and ignore it.
+    /// The decompiler does not use the flags, but they are kept so the module can be
+    /// written back out unchanged.
     fn read_gs1_flags(&mut self) -> Result<(), BytecodeLoaderError> {
         let section_length = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
-        let _flags = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
+        let flags = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
 
         // assert that the section length is correct
         Self::expect_section_length(SectionType::Gs1Flags, 4, section_length)?;
 
+        self.gs1_flags = flags;
+        Ok(())
+    }
+
+    /// Reads the header from the reader, and stores the version.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::InvalidMagic` if the bytecode does not start with `GS2BC_MAGIC`.
+    /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
+    fn read_header(&mut self) -> Result<(), BytecodeLoaderError> {
+        let magic = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
+        if magic != u32::from_be_bytes(GS2BC_MAGIC) {
+            return Err(BytecodeLoaderError::InvalidMagic(magic));
+        }
+        self.version = Some(self.reader.read_u32().map_err(BytecodeLoaderError::from)?);
+        Ok(())
+    }
+
+    /// Skips over a section without decoding it.
+    fn skip_section(&mut self) -> Result<(), BytecodeLoaderError> {
+        let section_length = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
+        self.reader
+            .skip(section_length as u64)
+            .map_err(BytecodeLoaderError::from)?;
         Ok(())
     }
 
@@ -216,6 +660,8 @@ impl<R: Read> BytecodeLoader<R> {
     /// # Errors
     /// - `BytecodeLoaderError::InvalidSectionLength` if the section length is incorrect.
     /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
+    /// - `BytecodeLoaderError::DuplicateFunctionName` if a function name is defined more
+    ///   than once and `strict_function_names` is set.
     fn read_functions(&mut self) -> Result<(), BytecodeLoaderError> {
         let section_length = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
 
@@ -229,16 +675,27 @@ impl<R: Read> BytecodeLoader<R> {
         while bytes_read < section_length {
             let function_location =
                 self.reader.read_u32().map_err(BytecodeLoaderError::from)? as Gs2BytecodeAddress;
-            let function_name = self
-                .reader
-                .read_string()
-                .map_err(BytecodeLoaderError::from)?;
-            self.function_map
-                .insert(Some(function_name.clone()), function_location);
-            bytes_read += 4 + function_name.len() as u32;
-            bytes_read += 1; // Null terminator
-
-            self.insert_block_start(function_location);
+            let (function_name, string_length) = self.read_section_string()?;
+            bytes_read += 4 + string_length;
+
+            // Two functions with the same name would otherwise overwrite each other in the map
+            let key = if self.function_map.contains_key(&Some(function_name.clone())) {
+                if self.strict_function_names {
+                    return Err(BytecodeLoaderError::DuplicateFunctionName(
+                        function_name,
+                        function_location,
+                    ));
+                }
+                let renamed = format!("{}@{}", function_name, function_location);
+                warn!(
+                    "Function {} at address {} is already defined, renaming it to {}",
+                    function_name, function_location, renamed
+                );
+                renamed
+            } else {
+                function_name
+            };
+            self.function_map.insert(Some(key), function_location);
         }
 
         // assert that the section length is correct
@@ -261,13 +718,9 @@ impl<R: Read> BytecodeLoader<R> {
         // For each string, use self.reader.read_string() to get the string. We should only read up to section_length bytes.
         let mut bytes_read = 0;
         while bytes_read < section_length {
-            let string = self
-                .reader
-                .read_string()
-                .map_err(BytecodeLoaderError::from)?;
-            self.strings.push(string.clone());
-            bytes_read += string.len() as u32;
-            bytes_read += 1; // Null terminator
+            let (string, string_length) = self.read_section_string()?;
+            self.strings.push(string);
+            bytes_read += string_length;
         }
 
         // assert that the section length is correct
@@ -276,6 +729,36 @@ impl<R: Read> BytecodeLoader<R> {
         Ok(())
     }
 
+    /// Reads a null-terminated string in a section.
+    ///
+    /// # Returns
+    /// - The string, and the number of bytes it took up in the section. This includes
+    ///   the null terminator, unless the string was cut off by the end of the bytecode.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs, or if the bytecode ends
+    ///   before the string starts. Lenient strings would otherwise read an empty string
+    ///   without consuming any bytes, forever.
+    fn read_section_string(&mut self) -> Result<(String, u32), BytecodeLoaderError> {
+        let unterminated_strings = self.reader.unterminated_strings();
+        let string = self
+            .reader
+            .read_string()
+            .map_err(BytecodeLoaderError::from)?;
+        let terminator = if self.reader.unterminated_strings() == unterminated_strings {
+            1
+        } else {
+            0
+        };
+        if string.is_empty() && terminator == 0 {
+            return Err(
+                GraalIoError::Io(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)).into(),
+            );
+        }
+        let length = string.len() as u32 + terminator;
+        Ok((string, length))
+    }
+
     /// Read one opcode from the reader and return it.
     fn read_opcode(&mut self) -> Result<Opcode, BytecodeLoaderError> {
         let opcode_byte = self.reader.read_u8().map_err(BytecodeLoaderError::from)?;
@@ -283,70 +766,42 @@ impl<R: Read> BytecodeLoader<R> {
         Ok(opcode)
     }
 
-    /// Read one operand from the reader and return it along with the number of bytes read.
+    /// Read one operand from the reader, using the decoder for the opcode, and return it
+    /// along with the number of bytes read. Returns `None` if the opcode has no operand
+    /// decoder, which means it starts a new instruction.
+    ///
+    /// # Arguments
+    /// - `opcode`: The opcode that was read.
+    /// - `address`: The address of the instruction the operand belongs to, if there is one.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::NoPreviousInstruction` if the opcode is an operand, but
+    ///   there is no instruction for it to belong to.
     fn read_operand(
         &mut self,
         opcode: Opcode,
-    ) -> Result<Option<(Operand, usize)>, BytecodeLoaderError> {
-        match opcode {
-            Opcode::ImmStringByte => {
-                let string_index = self.reader.read_u8().map_err(BytecodeLoaderError::from)?;
-                let string = self.strings.get(string_index as usize).ok_or(
-                    BytecodeLoaderError::StringIndexOutOfBounds(
-                        string_index as usize,
-                        self.strings.len(),
-                    ),
-                )?;
-                Ok(Some((Operand::new_string(string), 1)))
-            }
-            Opcode::ImmStringShort => {
-                let string_index = self.reader.read_u16().map_err(BytecodeLoaderError::from)?;
-                let string = self.strings.get(string_index as usize).ok_or(
-                    BytecodeLoaderError::StringIndexOutOfBounds(
-                        string_index as usize,
-                        self.strings.len(),
-                    ),
-                )?;
-                Ok(Some((Operand::new_string(string), 2)))
-            }
-            Opcode::ImmStringInt => {
-                let string_index = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
-                let string = self.strings.get(string_index as usize).ok_or(
-                    BytecodeLoaderError::StringIndexOutOfBounds(
-                        string_index as usize,
-                        self.strings.len(),
-                    ),
-                )?;
-                Ok(Some((Operand::new_string(string), 4)))
-            }
-            Opcode::ImmByte => {
-                let value = self.reader.read_u8().map_err(BytecodeLoaderError::from)?;
-                Ok(Some((Operand::new_number(value as i8 as i32), 1)))
-            }
-            Opcode::ImmShort => {
-                let value = self.reader.read_u16().map_err(BytecodeLoaderError::from)?;
-                Ok(Some((Operand::new_number(value as i16 as i32), 2)))
-            }
-            Opcode::ImmInt => {
-                let value = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
-                Ok(Some((Operand::new_number(value as i32), 4)))
-            }
-            Opcode::ImmFloat => {
-                let value = self
-                    .reader
-                    .read_string()
-                    .map_err(BytecodeLoaderError::from)?;
-                Ok(Some((Operand::new_float(value.clone()), value.len() + 1)))
-            }
-            _ => Ok(None),
-        }
+        address: Option<Gs2BytecodeAddress>,
+    ) -> Result<Option<(DecodedOperand, usize)>, BytecodeLoaderError> {
+        let Some(decoder) = self
+            .operand_decoders
+            .get(&opcode)
+            .or_else(|| default_operand_decoders().get(&opcode))
+        else {
+            return Ok(None);
+        };
+
+        let mut input = LoaderOperandInput {
+            reader: &mut self.reader,
+            strings: &self.strings,
+            address: address.ok_or(BytecodeLoaderError::NoPreviousInstruction)?,
+            bytes_read: 0,
+        };
+        let operand = decoder.decode(opcode, &mut input)?;
+        Ok(Some((operand, input.bytes_read)))
     }
 
     /// Reads the instructions section from the reader. This section contains the bytecode instructions.
     fn read_instructions(&mut self) -> Result<(), BytecodeLoaderError> {
-        // Add the first block start address
-        self.insert_block_start(0);
-
         let section_length = self.reader.read_u32().map_err(BytecodeLoaderError::from)?;
 
         let mut bytes_read = 0;
@@ -354,7 +809,11 @@ impl<R: Read> BytecodeLoader<R> {
             let opcode = self.read_opcode()?;
             bytes_read += 1;
 
-            let operand = self.read_operand(opcode)?;
+            let address = self
+                .instructions
+                .last()
+                .map(|instruction| instruction.address);
+            let operand = self.read_operand(opcode, address)?;
 
             if let Some(operand) = operand {
                 // Separate scope for mutable borrow of instructions
@@ -364,36 +823,94 @@ impl<R: Read> BytecodeLoader<R> {
                         .last_mut()
                         .ok_or(BytecodeLoaderError::NoPreviousInstruction)?;
 
-                    last_instruction.set_operand(operand.0.clone());
+                    last_instruction.set_operand(operand.0.operand);
+                    last_instruction.set_original_immediate_kind(opcode);
+                    if let Some(string_index) = operand.0.string_index {
+                        last_instruction.set_original_string_index(string_index);
+                    }
                 }
 
                 bytes_read += operand.1 as u32;
-
-                debug_assert!(self.instructions.last().is_some());
-
-                // We can unwrap here because we know that the last instruction exists in the scope above
-                if self.instructions.last().unwrap().opcode.has_jump_target() {
-                    self.insert_block_start(operand.0.get_number_value()? as Gs2BytecodeAddress);
-                }
             } else {
                 // Create a new instruction
                 let address = self.instructions.len();
-                self.instructions.push(Instruction::new(opcode, address));
-
-                if opcode.is_block_end() {
-                    let current_address = address as Gs2BytecodeAddress;
-                    self.insert_block_start(current_address + 1);
+                if address >= self.max_instructions {
+                    return Err(BytecodeLoaderError::TooManyInstructions(
+                        self.max_instructions,
+                    ));
                 }
+                self.instructions.push(Instruction::new(opcode, address));
             }
         }
 
         // Verify the section length
         Self::expect_section_length(SectionType::Instructions, section_length, bytes_read)?;
 
+        Ok(())
+    }
+
+    /// Gets the address of the instruction after the given address.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::AddressOverflow` if the next address does not fit in an address.
+    fn next_address(
+        address: Gs2BytecodeAddress,
+    ) -> Result<Gs2BytecodeAddress, BytecodeLoaderError> {
+        address
+            .checked_add(1)
+            .ok_or(BytecodeLoaderError::AddressOverflow(address))
+    }
+
+    /// Finds where blocks start: at the entry point, at each function, at each jump
+    /// target, and after each block-ending instruction.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::InstructionAddressMismatch` if an instruction's address does
+    ///   not match its index.
+    /// - `BytecodeLoaderError::MissingJumpTarget` if a jump instruction has no operand.
+    /// - `BytecodeLoaderError::AddressOverflow` if a jump target is negative, or the
+    ///   address after an instruction overflows.
+    /// - `BytecodeLoaderError::InvalidOperand` if a block starts past the end of the instructions.
+    fn split_blocks(&mut self) -> Result<(), BytecodeLoaderError> {
         // Handle the case of empty instructions
         if self.instructions.is_empty() {
             warn!("No instructions were loaded.");
-            self.block_breaks.clear();
+            return Ok(());
+        }
+
+        // Add the first block start address, and the start of each function
+        self.insert_block_start(0);
+        let function_addresses: Vec<_> = self.function_map.values().copied().collect();
+        for address in function_addresses {
+            self.insert_block_start(address);
+        }
+
+        let mut block_starts = Vec::new();
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if instruction.address != index {
+                return Err(BytecodeLoaderError::InstructionAddressMismatch(
+                    index,
+                    instruction.address,
+                ));
+            }
+
+            if instruction.opcode.has_jump_target() {
+                let target = instruction
+                    .operand
+                    .as_ref()
+                    .ok_or(BytecodeLoaderError::MissingJumpTarget(instruction.address))?
+                    .get_number_value()?;
+                let target = Gs2BytecodeAddress::try_from(target)
+                    .map_err(|_| BytecodeLoaderError::AddressOverflow(instruction.address))?;
+                block_starts.push(target);
+            }
+
+            if instruction.opcode.is_block_end() {
+                block_starts.push(Self::next_address(instruction.address)?);
+            }
+        }
+        for address in block_starts {
+            self.insert_block_start(address);
         }
 
         // Validate all addresses
@@ -423,10 +940,22 @@ impl<R: Read> BytecodeLoader<R> {
     /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
     /// - `BytecodeLoaderError::OpcodeError` if an invalid opcode is encountered.
     /// - `BytecodeLoaderError::InvalidOperand` if an invalid operand is encountered.
+    /// - `BytecodeLoaderError::AddressOverflow` if a jump target is negative.
+    /// - `BytecodeLoaderError::TooManyInstructions` if the module has too many instructions.
+    /// - `BytecodeLoaderError::TooManyBlocks` if the module has too many blocks.
+    /// - `BytecodeLoaderError::InvalidMagic` if the header has an invalid magic.
     fn load(&mut self) -> Result<(), BytecodeLoaderError> {
+        if self.expect_header {
+            self.read_header()?;
+        }
+
         // TODO: I know there will only be 4 sections, but I'd like to make this more dynamic.
         for _ in 0..4 {
             let section_type = self.read_section_type()?;
+            if !self.sections.contains(section_type) {
+                self.skip_section()?;
+                continue;
+            }
             match section_type {
                 SectionType::Gs1Flags => {
                     self.read_gs1_flags()?;
@@ -443,6 +972,28 @@ impl<R: Read> BytecodeLoader<R> {
             }
         }
 
+        // Without instructions, there are no blocks to build a graph from.
+        if !self.sections.contains(SectionType::Instructions) {
+            return Ok(());
+        }
+
+        self.split_blocks()?;
+        self.build_block_graph()
+    }
+
+    /// Builds the graph of blocks from the block breaks, and attributes each reachable
+    /// block to the function it belongs to.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::TooManyBlocks` if the module has too many blocks.
+    /// - `BytecodeLoaderError::InvalidOperand` if a jump operand is not a number.
+    /// - `BytecodeLoaderError::AddressOverflow` if a jump target is negative, or the
+    ///   address after an instruction overflows.
+    fn build_block_graph(&mut self) -> Result<(), BytecodeLoaderError> {
+        if self.block_breaks.len() > self.max_blocks {
+            return Err(BytecodeLoaderError::TooManyBlocks(self.max_blocks));
+        }
+
         // After reading in all of the block breaks, we can now create the graph.
         for block_break in self.block_breaks.iter() {
             let node = self.raw_block_graph.add_node(*block_break);
@@ -453,10 +1004,9 @@ impl<R: Read> BytecodeLoader<R> {
         for instruction in self.instructions.iter() {
             let current_instruction_address = instruction.address as Gs2BytecodeAddress;
             let current_block_address = self.find_block_start_address(current_instruction_address);
+            let next_instruction_address = Self::next_address(current_instruction_address)?;
             // if the instruction is the last instruction in the block
-            let is_block_end = self
-                .block_breaks
-                .contains(&(current_instruction_address + 1));
+            let is_block_end = self.block_breaks.contains(&next_instruction_address);
 
             // If the current instruction is a jump, then we need to add an edge to the target block start
             if instruction.opcode.has_jump_target() {
@@ -469,9 +1019,11 @@ impl<R: Read> BytecodeLoader<R> {
                     .unwrap();
 
                 // Unwrap here because we know that the operand exists due to a previous check in
-                // `read_instructions`
-                let target_address =
-                    instruction.operand.as_ref().unwrap().get_number_value()? as Gs2BytecodeAddress;
+                // `split_blocks`
+                let target_address = Gs2BytecodeAddress::try_from(
+                    instruction.operand.as_ref().unwrap().get_number_value()?,
+                )
+                .map_err(|_| BytecodeLoaderError::AddressOverflow(current_instruction_address))?;
 
                 // Also unwrap here because we know that the target address exists in the block breaks
                 let target_node = self.raw_block_address_to_node.get(&target_address).unwrap();
@@ -490,13 +1042,10 @@ impl<R: Read> BytecodeLoader<R> {
                     // logic of the loader.
                     .unwrap();
 
-                // Find the next block start address
-                let next_block_address = current_instruction_address + 1;
-
-                // Also unwrap here because we know that the target address exists in the block breaks
+                // Also unwrap here because we know that the next address exists in the block breaks
                 let target_node = self
                     .raw_block_address_to_node
-                    .get(&next_block_address)
+                    .get(&next_instruction_address)
                     .unwrap();
 
                 self.raw_block_graph
@@ -562,10 +1111,12 @@ impl<R: Read> BytecodeLoader<R> {
     /// # Errors
     /// - `BytecodeLoaderError::UnreachableBlock` if the block is unreachable, and therefore,
     ///   the function name cannot be determined.
+    /// - `BytecodeLoaderError::SectionNotLoaded` if the instructions section was skipped.
     pub fn get_function_name_for_address(
         &self,
         address: Gs2BytecodeAddress,
     ) -> Result<Option<String>, BytecodeLoaderError> {
+        self.expect_section_loaded(SectionType::Instructions)?;
         let block_start = self.find_block_start_address(address);
         Ok(self
             .block_address_to_function
@@ -575,6 +1126,95 @@ impl<R: Read> BytecodeLoader<R> {
             .clone())
     }
 
+    /// Gets the version from the bytecode header.
+    ///
+    /// # Returns
+    /// - The version, or `None` if the loader did not expect a header.
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+
+    /// Gets the CRC-32 checksum of every byte the loader read. Skipped sections are
+    /// read as well, so they are part of the checksum.
+    ///
+    /// # Returns
+    /// - The checksum, or `None` if `BytecodeLoaderBuilder::compute_checksum` was not set.
+    pub fn content_checksum(&self) -> Option<u32> {
+        self.reader.get_ref().checksum()
+    }
+
+    /// Gets the number of bytes the loader read.
+    pub fn bytes_consumed(&self) -> u64 {
+        self.reader.get_ref().bytes_read()
+    }
+
+    /// Gets the sections that were parsed by the loader.
+    ///
+    /// # Returns
+    /// - The `SectionMask` of loaded sections.
+    pub fn loaded_sections(&self) -> SectionMask {
+        self.sections
+    }
+
+    /// Get the contents of the flags section. The meaning of the flags is not
+    /// documented, so they are kept as is.
+    ///
+    /// # Returns
+    /// - The flags, or `0` if the loader was created from decoded instructions.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::SectionNotLoaded` if the flags section was skipped.
+    pub fn get_gs1_flags(&self) -> Result<u32, BytecodeLoaderError> {
+        self.expect_section_loaded(SectionType::Gs1Flags)?;
+        Ok(self.gs1_flags)
+    }
+
+    /// Get the function map, keyed by function name.
+    ///
+    /// # Returns
+    /// - A map of function names to their addresses. The entry function has no name.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::SectionNotLoaded` if the functions section was skipped.
+    pub fn get_function_map(
+        &self,
+    ) -> Result<&HashMap<Option<String>, Gs2BytecodeAddress>, BytecodeLoaderError> {
+        self.expect_section_loaded(SectionType::Functions)?;
+        Ok(&self.function_map)
+    }
+
+    /// Get the strings used in the module.
+    ///
+    /// # Returns
+    /// - The strings, in the order they appear in the strings section.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::SectionNotLoaded` if the strings section was skipped.
+    pub fn get_strings(&self) -> Result<&[String], BytecodeLoaderError> {
+        self.expect_section_loaded(SectionType::Strings)?;
+        Ok(&self.strings)
+    }
+
+    /// Get the decoded instructions of the module.
+    ///
+    /// # Returns
+    /// - The instructions, in address order.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::SectionNotLoaded` if the instructions section was skipped.
+    pub fn get_instructions(&self) -> Result<&[Instruction], BytecodeLoaderError> {
+        self.expect_section_loaded(SectionType::Instructions)?;
+        Ok(&self.instructions)
+    }
+
+    /// Returns an error if the given section was not loaded.
+    fn expect_section_loaded(&self, section: SectionType) -> Result<(), BytecodeLoaderError> {
+        if !self.sections.contains(section) {
+            return Err(BytecodeLoaderError::SectionNotLoaded(section));
+        }
+        Ok(())
+    }
+
     /// Checks if an instruction is reachable.
     ///
     /// # Arguments
@@ -619,9 +1259,74 @@ impl<R: Read> BytecodeLoader<R> {
     }
 }
 
+/// The `OperandInput` the loader passes to operand decoders, which counts the bytes the
+/// decoder reads.
+struct LoaderOperandInput<'a, R: Read> {
+    reader: &'a mut GraalReader<ChecksumReader<R>>,
+    strings: &'a [String],
+
+    /// The address of the instruction the operand is attached to.
+    address: Gs2BytecodeAddress,
+
+    /// The number of bytes read so far.
+    bytes_read: usize,
+}
+
+impl<R: Read> OperandInput for LoaderOperandInput<'_, R> {
+    fn read_u8(&mut self) -> Result<u8, BytecodeLoaderError> {
+        let value = self.reader.read_u8()?;
+        self.bytes_read += 1;
+        Ok(value)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, BytecodeLoaderError> {
+        let value = self.reader.read_u16()?;
+        self.bytes_read += 2;
+        Ok(value)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, BytecodeLoaderError> {
+        let value = self.reader.read_u32()?;
+        self.bytes_read += 4;
+        Ok(value)
+    }
+
+    fn read_string(&mut self) -> Result<String, BytecodeLoaderError> {
+        let unterminated_strings = self.reader.unterminated_strings();
+        let value = self.reader.read_string()?;
+        self.bytes_read += value.len();
+        if self.reader.unterminated_strings() == unterminated_strings {
+            self.bytes_read += 1;
+        }
+        Ok(value)
+    }
+
+    fn resolve_string(&self, string_index: usize) -> Result<&str, BytecodeLoaderError> {
+        self.strings.get(string_index).map(String::as_str).ok_or(
+            BytecodeLoaderError::StringIndexOutOfBounds(
+                string_index,
+                self.strings.len(),
+                self.address,
+            ),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{bytecode_loader::BytecodeLoaderBuilder, utils::Gs2BytecodeAddress};
+    use std::collections::HashMap;
+
+    use crate::{
+        bytecode_loader::{
+            BytecodeLoader, BytecodeLoaderBuilder, BytecodeLoaderError, SectionMask, SectionType,
+        },
+        bytecode_writer::BytecodeWriter,
+        instruction::Instruction,
+        opcode::Opcode,
+        operand::Operand,
+        operand_decoder::{DecodedOperand, OperandDecoder, OperandInput},
+        utils::Gs2BytecodeAddress,
+    };
 
     #[test]
     fn test_load() {
@@ -679,6 +1384,115 @@ mod tests {
         assert_eq!(loader.instructions[4].opcode, crate::opcode::Opcode::Ret);
     }
 
+    /// Decodes a string index and a number as one string operand, such as `abc3`.
+    struct SuffixedStringDecoder;
+
+    impl OperandDecoder for SuffixedStringDecoder {
+        fn decode(
+            &self,
+            _opcode: Opcode,
+            input: &mut dyn OperandInput,
+        ) -> Result<DecodedOperand, BytecodeLoaderError> {
+            let string_index = input.read_u8()? as usize;
+            let suffix = input.read_u8()?;
+            let string = format!("{}{}", input.resolve_string(string_index)?, suffix);
+            Ok(DecodedOperand::with_string_index(
+                Operand::new_string(string),
+                string_index,
+            ))
+        }
+    }
+
+    #[test]
+    fn test_custom_operand_decoder() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x61, 0x62, 0x63, 0x00, // String: "abc"
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x05, // Length: 5
+            0x15, // Opcode: PushString
+            0xF1, // Opcode: ImmStringShort
+            0x00, 0x03, // Operand: string 0, suffix 3
+            0x07, // Opcode: Ret
+        ];
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .operand_decoder(Opcode::ImmStringShort, SuffixedStringDecoder)
+            .build()
+            .unwrap();
+
+        assert_eq!(loader.instructions.len(), 2);
+        assert_eq!(
+            loader.instructions[0].operand,
+            Some(Operand::new_string("abc3"))
+        );
+        assert_eq!(loader.instructions[0].original_string_index(), Some(0));
+        assert_eq!(loader.instructions[1].opcode, Opcode::Ret);
+
+        // The default decoder reads the two bytes as one string index
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode)).build();
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::StringIndexOutOfBounds(3, 1, 0))
+        ));
+    }
+
+    #[test]
+    fn test_content_checksum() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x09, // Length: 9
+            0x00, 0x00, 0x00, 0x00, // Function location: 0
+            0x6d, 0x61, 0x69, 0x6e, // Function name: "main"
+            0x00, // Null terminator
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x61, 0x62, 0x63, 0x00, // String: "abc"
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x0c, // Length: 12
+            0x01, // Opcode: Jmp
+            0xF3, // Opcode: ImmByte
+            0x01, // Operand: 1
+            0x14, // Opcode: PushNumber
+            0xF4, // Opcode: ImmShort
+            0x00, 0x01, // Operand: 1
+            0x15, // Opcode: PushString
+            0xF0, // Opcode: ImmStringByte
+            0x00, // Operand: 0
+            0x1b, // Opcode: PushPi
+            0x07, // Opcode: Ret
+        ];
+
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .compute_checksum(true)
+            .build()
+            .unwrap();
+        assert_eq!(loader.content_checksum(), Some(0x19525442));
+        assert_eq!(loader.bytes_consumed(), bytecode.len() as u64);
+
+        // Skipped sections are still read, so they count towards the checksum
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .sections(SectionMask::NONE)
+            .compute_checksum(true)
+            .build()
+            .unwrap();
+        assert_eq!(loader.content_checksum(), Some(0x19525442));
+
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(loader.content_checksum(), None);
+        assert_eq!(loader.bytes_consumed(), bytecode.len() as u64);
+    }
+
     #[test]
     fn test_complex_load() {
         let reader = std::io::Cursor::new(vec![
@@ -903,6 +1717,59 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_load_lenient_strings() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x01, // Length: 1
+            0x07, // Opcode: Ret
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x07, // Length: 7
+            0x61, 0x62, 0x63, 0x00, // String: "abc"
+            0x64, 0x65, 0x66, // String: "def", truncated before the null terminator
+        ];
+
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone())).build();
+        assert!(result.is_err());
+
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode))
+            .lenient_strings(true)
+            .build()
+            .unwrap();
+        assert_eq!(loader.strings, vec!["abc".to_string(), "def".to_string()]);
+
+        // The strings section claims more bytes than the bytecode has left
+        let mut truncated = bytecode_prefix_without_strings();
+        truncated.extend([
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x10, // Length: 16
+            0x61, 0x62, 0x63, 0x00, // String: "abc"
+        ]);
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(truncated))
+            .lenient_strings(true)
+            .build();
+        assert!(matches!(result, Err(BytecodeLoaderError::GraalIo(_))));
+    }
+
+    /// The sections of `test_load_lenient_strings` that come before the strings section.
+    fn bytecode_prefix_without_strings() -> Vec<u8> {
+        vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x01, // Length: 1
+            0x07, // Opcode: Ret
+        ]
+    }
+
     #[test]
     fn test_load_invalid_section_length() {
         let reader = std::io::Cursor::new(vec![
@@ -965,7 +1832,176 @@ mod tests {
 
         let result = BytecodeLoaderBuilder::new(reader).build();
 
-        assert!(result.is_err());
+        // The PushString instruction at address 2 references the out of bounds string.
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::StringIndexOutOfBounds(1, 1, 2))
+        ));
+    }
+
+    #[test]
+    fn test_load_string_index_without_instruction() {
+        let reader = std::io::Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x02, // Length: 2
+            0xF0, // Opcode: ImmStringByte
+            0x01, // Operand: 1 (out of bounds)
+        ]);
+
+        // There is no instruction to blame for the string, so no address is made up
+        let result = BytecodeLoaderBuilder::new(reader).build();
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::NoPreviousInstruction)
+        ));
+    }
+
+    #[test]
+    fn test_load_selected_sections() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x09, // Length: 9
+            0x00, 0x00, 0x00, 0x00, // Function location: 0
+            0x6d, 0x61, 0x69, 0x6e, // Function name: "main"
+            0x00, // Null terminator
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x61, 0x62, 0x63, 0x00, // String: "abc"
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x03, // Length: 3
+            0xF3, // Opcode: ImmByte (invalid without a previous instruction)
+            0x01, // Operand: 1
+            0x07, // Opcode: Ret
+        ];
+
+        // Only load the functions and strings sections
+        let sections = SectionMask::NONE
+            .with(SectionType::Functions)
+            .with(SectionType::Strings);
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .sections(sections)
+            .build()
+            .unwrap();
+
+        assert_eq!(loader.loaded_sections(), sections);
+        assert_eq!(
+            loader
+                .get_function_map()
+                .unwrap()
+                .get(&Some("main".to_string())),
+            Some(&0)
+        );
+        assert_eq!(loader.get_strings().unwrap(), ["abc".to_string()]);
+        assert!(matches!(
+            loader.get_instructions(),
+            Err(BytecodeLoaderError::SectionNotLoaded(
+                SectionType::Instructions
+            ))
+        ));
+        assert!(matches!(
+            loader.get_function_name_for_address(0),
+            Err(BytecodeLoaderError::SectionNotLoaded(
+                SectionType::Instructions
+            ))
+        ));
+
+        // Skipping everything still validates the section headers
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .sections(SectionMask::NONE)
+            .build()
+            .unwrap();
+        assert!(loader.get_function_map().is_err());
+        assert!(loader.get_strings().is_err());
+
+        // Requesting instructions also loads the sections they depend on
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode))
+            .sections(SectionType::Instructions.into())
+            .build();
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::NoPreviousInstruction)
+        ));
+    }
+
+    #[test]
+    fn test_load_limits() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x05, // Length: 5
+            0x02, // Opcode: Jeq
+            0xF3, // Opcode: ImmByte
+            0x02, // Operand: 2
+            0x1b, // Opcode: PushPi
+            0x07, // Opcode: Ret
+        ];
+
+        // Blocks start at 0, 1, 2 and 3
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .max_instructions(3)
+            .max_blocks(4)
+            .build()
+            .unwrap();
+        assert_eq!(loader.instructions.len(), 3);
+
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .max_instructions(2)
+            .build();
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::TooManyInstructions(2))
+        ));
+
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode))
+            .max_blocks(3)
+            .build();
+        assert!(matches!(result, Err(BytecodeLoaderError::TooManyBlocks(3))));
+    }
+
+    #[test]
+    fn test_section_mask() {
+        assert_eq!(SectionMask::default(), SectionMask::ALL);
+        assert!(!SectionMask::NONE.contains(SectionType::Gs1Flags));
+
+        let mask = SectionMask::from(SectionType::Strings);
+        assert!(mask.contains(SectionType::Strings));
+        assert!(!mask.contains(SectionType::Functions));
+
+        let mask = SectionMask::NONE
+            .with(SectionType::Gs1Flags)
+            .with(SectionType::Functions)
+            .with(SectionType::Strings)
+            .with(SectionType::Instructions);
+        assert_eq!(mask, SectionMask::ALL);
+    }
+
+    #[test]
+    fn test_load_truncated_skipped_section() {
+        let reader = std::io::Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x08, // Length: 8 (longer than the remaining data)
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+        ]);
+        let result = BytecodeLoaderBuilder::new(reader)
+            .sections(SectionMask::NONE)
+            .build();
+        assert!(matches!(result, Err(BytecodeLoaderError::GraalIo(_))));
     }
 
     #[test]
@@ -1027,6 +2063,83 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_load_duplicate_function_names() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
+            0x00, 0x00, 0x00, 0x04, // Length: 4
+            0x00, 0x00, 0x00, 0x00, // Flags: 0
+            0x00, 0x00, 0x00, 0x02, // Section type: Functions
+            0x00, 0x00, 0x00, 0x12, // Length: 18
+            0x00, 0x00, 0x00, 0x01, // Function location: 1
+            0x6d, 0x61, 0x69, 0x6e, 0x00, // Function name: "main"
+            0x00, 0x00, 0x00, 0x02, // Function location: 2
+            0x6d, 0x61, 0x69, 0x6e, 0x00, // Function name: "main"
+            0x00, 0x00, 0x00, 0x03, // Section type: Strings
+            0x00, 0x00, 0x00, 0x00, // Length: 0
+            0x00, 0x00, 0x00, 0x04, // Section type: Instructions
+            0x00, 0x00, 0x00, 0x03, // Length: 3
+            0x07, // Opcode: Ret
+            0x07, // Opcode: Ret
+            0x07, // Opcode: Ret
+        ];
+
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(loader.function_map.get(&Some("main".to_string())), Some(&1));
+        assert_eq!(
+            loader.function_map.get(&Some("main@2".to_string())),
+            Some(&2)
+        );
+        assert_eq!(
+            loader.get_function_name_for_address(2).unwrap(),
+            Some("main@2".to_string())
+        );
+
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode))
+            .strict_function_names(true)
+            .build();
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::DuplicateFunctionName(name, 2)) if name == "main"
+        ));
+    }
+
+    #[test]
+    fn test_load_header() {
+        let sections = vec![
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x00, // Gs1Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // Functions, length 0
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, // Strings, length 0
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, // Instructions, length 1
+            0x07, // Opcode: Ret
+        ];
+
+        let mut bytecode = b"GS2B".to_vec();
+        bytecode.extend([0x00, 0x00, 0x00, 0x02]); // Version: 2
+        bytecode.extend(sections.iter());
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(bytecode))
+            .expect_header(true)
+            .build()
+            .unwrap();
+        assert_eq!(loader.version(), Some(2));
+        assert_eq!(loader.instructions.len(), 1);
+
+        // Without a header, nothing changes
+        let loader = BytecodeLoaderBuilder::new(std::io::Cursor::new(sections.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(loader.version(), None);
+
+        // The first section type is not a valid magic
+        let result = BytecodeLoaderBuilder::new(std::io::Cursor::new(sections))
+            .expect_header(true)
+            .build();
+        assert!(matches!(result, Err(BytecodeLoaderError::InvalidMagic(1))));
+    }
+
     #[test]
     fn test_operands() {
         let reader = std::io::Cursor::new(vec![
@@ -1132,6 +2245,44 @@ mod tests {
         assert_eq!(loader.instructions[7].operand, None);
         assert_eq!(loader.instructions[8].opcode, crate::opcode::Opcode::Ret);
         assert_eq!(loader.instructions[8].operand, None);
+
+        // The immediate opcode of each operand is kept, even where a smaller one would fit
+        let immediate_kinds: Vec<_> = loader
+            .instructions
+            .iter()
+            .map(|instruction| instruction.original_immediate_kind())
+            .collect();
+        assert_eq!(
+            immediate_kinds,
+            vec![
+                Some(crate::opcode::Opcode::ImmByte),
+                Some(crate::opcode::Opcode::ImmShort),
+                Some(crate::opcode::Opcode::ImmInt),
+                Some(crate::opcode::Opcode::ImmFloat),
+                Some(crate::opcode::Opcode::ImmStringByte),
+                Some(crate::opcode::Opcode::ImmStringShort),
+                Some(crate::opcode::Opcode::ImmStringInt),
+                None,
+                None,
+            ]
+        );
+
+        // Writing the module back out and reloading it gives the same module
+        let mut bytes = Vec::new();
+        BytecodeWriter::new(&loader.function_map, &loader.strings, &loader.instructions)
+            .write(&mut bytes)
+            .unwrap();
+        let reloaded = BytecodeLoaderBuilder::new(bytes.as_slice())
+            .build()
+            .unwrap();
+        assert_eq!(reloaded.function_map, loader.function_map);
+        assert_eq!(reloaded.strings, loader.strings);
+        assert_eq!(reloaded.instructions.len(), loader.instructions.len());
+        for (reloaded, original) in reloaded.instructions.iter().zip(&loader.instructions) {
+            assert_eq!(reloaded.opcode, original.opcode);
+            assert_eq!(reloaded.address, original.address);
+            assert_eq!(reloaded.operand, original.operand);
+        }
     }
 
     #[test]
@@ -1237,4 +2388,18 @@ mod tests {
         let loader = BytecodeLoaderBuilder::new(reader).build();
         assert!(loader.is_err());
     }
+
+    #[test]
+    fn test_negative_jump_target() {
+        let instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(-1)),
+            Instruction::new(Opcode::Ret, 1),
+        ];
+        let result =
+            BytecodeLoader::from_instructions(instructions, HashMap::from([(None, 0)]), Vec::new());
+        assert!(matches!(
+            result,
+            Err(BytecodeLoaderError::AddressOverflow(0))
+        ));
+    }
 }
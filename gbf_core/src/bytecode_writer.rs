@@ -0,0 +1,496 @@
+#![deny(missing_docs)]
+
+use std::{collections::HashMap, io::Write};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    bytecode_loader::SectionType,
+    graal_io::{GraalIoError, GraalWriter},
+    instruction::Instruction,
+    opcode::Opcode,
+    operand::Operand,
+    utils::Gs2BytecodeAddress,
+};
+
+/// Error type for writing bytecode.
+#[derive(Error, Debug, Clone, Serialize)]
+pub enum BytecodeWriterError {
+    /// Error for when an instruction's address does not match its position in the
+    /// instruction list.
+    #[error("Instruction at index {0} has address {1}")]
+    InstructionAddressMismatch(usize, Gs2BytecodeAddress),
+
+    /// Error for when a section is too long for its length to fit in a `u32`.
+    #[error("Section {0} is too long: {1} bytes")]
+    SectionTooLong(SectionType, usize),
+
+    /// Error for when a function address does not fit in a `u32`.
+    #[error("Function {0} has an invalid address: {1}")]
+    InvalidFunctionAddress(String, Gs2BytecodeAddress),
+
+    /// Error for when an I/O error occurs.
+    #[error("GraalIo error: {0}")]
+    GraalIo(#[from] GraalIoError),
+}
+
+/// A structure for writing a module back out as bytecode, in the format read by
+/// `BytecodeLoader`.
+///
+/// By default, each operand is written with the smallest immediate opcode that fits it,
+/// and each string is referenced by its first index in the strings section. The
+/// `original_immediate_kind` and `original_string_index` the loader recorded are ignored
+/// unless `BytecodeWriter::preserve_encoding` is set.
+pub struct BytecodeWriter<'a> {
+    /// A map of function names to their addresses. The entry function, which has no
+    /// name, is not written.
+    function_map: &'a HashMap<Option<String>, Gs2BytecodeAddress>,
+
+    /// The strings section to start from.
+    strings: &'a [String],
+
+    /// The instructions to write, where each instruction's address is its index.
+    instructions: &'a [Instruction],
+
+    /// The contents of the flags section.
+    gs1_flags: u32,
+
+    /// Whether to keep the original encoding of each operand where it still applies.
+    preserve_encoding: bool,
+}
+
+impl<'a> BytecodeWriter<'a> {
+    /// Creates a new `BytecodeWriter`.
+    ///
+    /// # Arguments
+    /// - `function_map`: A map of function names to their addresses.
+    /// - `strings`: The strings section to start from. Strings used by the instructions
+    ///   that are missing from it are added to the end, so loaded string indices are kept.
+    /// - `instructions`: The instructions, where each instruction's address is its index.
+    ///
+    /// # Returns
+    /// - A new `BytecodeWriter` instance.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use gbf_core::bytecode_writer::BytecodeWriter;
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let function_map = HashMap::new();
+    /// let instructions = vec![Instruction::new(Opcode::Ret, 0)];
+    /// let writer = BytecodeWriter::new(&function_map, &[], &instructions);
+    /// ```
+    pub fn new(
+        function_map: &'a HashMap<Option<String>, Gs2BytecodeAddress>,
+        strings: &'a [String],
+        instructions: &'a [Instruction],
+    ) -> Self {
+        Self {
+            function_map,
+            strings,
+            instructions,
+            gs1_flags: 0,
+            preserve_encoding: false,
+        }
+    }
+
+    /// Sets the contents of the flags section, such as the flags returned by
+    /// `BytecodeLoader::get_gs1_flags`. The flags are `0` by default.
+    ///
+    /// # Arguments
+    /// - `gs1_flags`: The flags to write.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeWriter`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use gbf_core::bytecode_writer::BytecodeWriter;
+    ///
+    /// let function_map = HashMap::new();
+    /// let writer = BytecodeWriter::new(&function_map, &[], &[]).gs1_flags(1);
+    /// ```
+    pub fn gs1_flags(mut self, gs1_flags: u32) -> Self {
+        self.gs1_flags = gs1_flags;
+        self
+    }
+
+    /// Sets whether to keep the original encoding of each operand. An operand is written
+    /// with its `original_immediate_kind` if its value still fits it, and a string is
+    /// referenced by its `original_string_index` if that index still holds the string.
+    /// Operands without an original encoding, or whose original encoding no longer
+    /// applies, use the smallest encoding as usual. Off by default.
+    ///
+    /// # Arguments
+    /// - `preserve_encoding`: Whether to keep the original encoding.
+    ///
+    /// # Returns
+    /// - The updated `BytecodeWriter`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use gbf_core::bytecode_writer::BytecodeWriter;
+    ///
+    /// let function_map = HashMap::new();
+    /// let writer = BytecodeWriter::new(&function_map, &[], &[]).preserve_encoding(true);
+    /// ```
+    pub fn preserve_encoding(mut self, preserve_encoding: bool) -> Self {
+        self.preserve_encoding = preserve_encoding;
+        self
+    }
+
+    /// Writes the bytecode to a writer.
+    ///
+    /// # Arguments
+    /// - `writer`: The writer to write the bytecode to.
+    ///
+    /// # Errors
+    /// - `BytecodeWriterError::InstructionAddressMismatch` if an instruction's address does
+    ///   not match its index.
+    /// - `BytecodeWriterError::SectionTooLong` if a section is too long.
+    /// - `BytecodeWriterError::InvalidFunctionAddress` if a function address is too large.
+    /// - `BytecodeWriterError::GraalIo` if an I/O error occurs.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use gbf_core::bytecode_loader::BytecodeLoaderBuilder;
+    /// use gbf_core::bytecode_writer::BytecodeWriter;
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    /// use gbf_core::operand::Operand;
+    ///
+    /// let function_map = HashMap::new();
+    /// let instructions = vec![
+    ///     Instruction::new_with_operand(Opcode::PushString, 0, Operand::new_string("abc")),
+    ///     Instruction::new(Opcode::Ret, 1),
+    /// ];
+    /// let mut bytes = Vec::new();
+    /// BytecodeWriter::new(&function_map, &[], &instructions)
+    ///     .write(&mut bytes)
+    ///     .unwrap();
+    ///
+    /// let loader = BytecodeLoaderBuilder::new(bytes.as_slice()).build().unwrap();
+    /// assert_eq!(loader.get_strings().unwrap(), ["abc"]);
+    /// let loaded = loader.get_instructions().unwrap();
+    /// assert_eq!(loaded[0].operand, Some(Operand::new_string("abc")));
+    /// assert_eq!(loaded[1].opcode, Opcode::Ret);
+    /// ```
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), BytecodeWriterError> {
+        let mut writer = GraalWriter::new(writer);
+        let (strings, string_indices) = self.string_table();
+
+        let flags = self.gs1_flags.to_be_bytes();
+        Self::write_section(&mut writer, SectionType::Gs1Flags, &flags)?;
+
+        let functions = self.encode_functions()?;
+        Self::write_section(&mut writer, SectionType::Functions, &functions)?;
+
+        let mut strings_section = GraalWriter::new(Vec::new());
+        for string in &strings {
+            strings_section.write_string(string)?;
+        }
+        Self::write_section(
+            &mut writer,
+            SectionType::Strings,
+            &strings_section.into_inner(),
+        )?;
+
+        let instructions = self.encode_instructions(&string_indices)?;
+        Self::write_section(&mut writer, SectionType::Instructions, &instructions)?;
+        Ok(())
+    }
+
+    /// Builds the strings section: the given strings, followed by any strings used by
+    /// the instructions that are not among them.
+    ///
+    /// # Returns
+    /// - The strings, and the index each string is referenced by. A string that appears
+    ///   more than once is referenced by its first index.
+    fn string_table(&self) -> (Vec<&'a str>, HashMap<&'a str, usize>) {
+        let mut strings: Vec<&str> = Vec::with_capacity(self.strings.len());
+        let mut string_indices = HashMap::new();
+        for string in self.strings {
+            string_indices
+                .entry(string.as_str())
+                .or_insert(strings.len());
+            strings.push(string);
+        }
+
+        for instruction in self.instructions {
+            if let Some(Operand::String(string)) = &instruction.operand {
+                string_indices.entry(string.as_str()).or_insert_with(|| {
+                    strings.push(string);
+                    strings.len() - 1
+                });
+            }
+        }
+        (strings, string_indices)
+    }
+
+    /// Encodes the functions section, sorted by address so the output is deterministic.
+    fn encode_functions(&self) -> Result<Vec<u8>, BytecodeWriterError> {
+        let mut functions: Vec<_> = self
+            .function_map
+            .iter()
+            .filter_map(|(name, address)| name.as_ref().map(|name| (*address, name)))
+            .collect();
+        functions.sort();
+
+        let mut section = GraalWriter::new(Vec::new());
+        for (address, name) in functions {
+            let location = u32::try_from(address)
+                .map_err(|_| BytecodeWriterError::InvalidFunctionAddress(name.clone(), address))?;
+            section.write_u32(location)?;
+            section.write_string(name)?;
+        }
+        Ok(section.into_inner())
+    }
+
+    /// Encodes the instructions section. Each operand is written with the smallest
+    /// immediate opcode that fits it, or its original one if the encoding is preserved.
+    fn encode_instructions(
+        &self,
+        string_indices: &HashMap<&str, usize>,
+    ) -> Result<Vec<u8>, BytecodeWriterError> {
+        let mut section = GraalWriter::new(Vec::new());
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if instruction.address != index {
+                return Err(BytecodeWriterError::InstructionAddressMismatch(
+                    index,
+                    instruction.address,
+                ));
+            }
+            section.write_u8(instruction.opcode.to_byte())?;
+
+            let original_kind = instruction
+                .original_immediate_kind()
+                .filter(|_| self.preserve_encoding);
+            match &instruction.operand {
+                None => {}
+                Some(Operand::Number(value)) => {
+                    let fits_byte = i8::try_from(*value).is_ok();
+                    let fits_short = i16::try_from(*value).is_ok();
+                    let kind = match original_kind {
+                        Some(Opcode::ImmByte) if fits_byte => Opcode::ImmByte,
+                        Some(Opcode::ImmShort) if fits_short => Opcode::ImmShort,
+                        Some(Opcode::ImmInt) => Opcode::ImmInt,
+                        _ if fits_byte => Opcode::ImmByte,
+                        _ if fits_short => Opcode::ImmShort,
+                        _ => Opcode::ImmInt,
+                    };
+                    section.write_u8(kind.to_byte())?;
+                    match kind {
+                        Opcode::ImmByte => section.write_u8(*value as u8)?,
+                        Opcode::ImmShort => section.write_u16(*value as u16)?,
+                        _ => section.write_u32(*value as u32)?,
+                    }
+                }
+                Some(Operand::Float(value)) => {
+                    section.write_u8(Opcode::ImmFloat.to_byte())?;
+                    section.write_string(value)?;
+                }
+                Some(Operand::String(value)) => {
+                    // The original index is only kept while it still holds the string
+                    let original_index = instruction.original_string_index().filter(|index| {
+                        self.preserve_encoding && self.strings.get(*index) == Some(value)
+                    });
+                    // Every string operand was added to the table by `string_table`
+                    let string_index =
+                        original_index.unwrap_or_else(|| string_indices[value.as_str()]);
+                    let fits_byte = u8::try_from(string_index).is_ok();
+                    let fits_short = u16::try_from(string_index).is_ok();
+                    let kind = match original_kind {
+                        Some(Opcode::ImmStringByte) if fits_byte => Opcode::ImmStringByte,
+                        Some(Opcode::ImmStringShort) if fits_short => Opcode::ImmStringShort,
+                        Some(Opcode::ImmStringInt) => Opcode::ImmStringInt,
+                        _ if fits_byte => Opcode::ImmStringByte,
+                        _ if fits_short => Opcode::ImmStringShort,
+                        _ => Opcode::ImmStringInt,
+                    };
+                    section.write_u8(kind.to_byte())?;
+                    match kind {
+                        Opcode::ImmStringByte => section.write_u8(string_index as u8)?,
+                        Opcode::ImmStringShort => section.write_u16(string_index as u16)?,
+                        _ => {
+                            let string_index = u32::try_from(string_index).map_err(|_| {
+                                BytecodeWriterError::SectionTooLong(
+                                    SectionType::Strings,
+                                    string_index,
+                                )
+                            })?;
+                            section.write_u32(string_index)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(section.into_inner())
+    }
+
+    /// Writes a section: its type, its length, and its contents.
+    fn write_section<W: Write>(
+        writer: &mut GraalWriter<W>,
+        section_type: SectionType,
+        contents: &[u8],
+    ) -> Result<(), BytecodeWriterError> {
+        let length = u32::try_from(contents.len())
+            .map_err(|_| BytecodeWriterError::SectionTooLong(section_type, contents.len()))?;
+        writer.write_u32(section_type as u32)?;
+        writer.write_u32(length)?;
+        writer.write(contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        bytecode_loader::BytecodeLoaderBuilder, bytecode_writer::BytecodeWriter,
+        instruction::Instruction, opcode::Opcode, operand::Operand,
+    };
+
+    #[test]
+    fn test_write_smallest_immediates() {
+        let numbers = [0, -128, 127, -129, 128, i16::MAX as i32, 70000, i32::MIN];
+        let mut instructions: Vec<_> = numbers
+            .iter()
+            .enumerate()
+            .map(|(address, number)| {
+                Instruction::new_with_operand(
+                    Opcode::PushNumber,
+                    address,
+                    Operand::new_number(*number),
+                )
+            })
+            .collect();
+
+        // Enough strings that the last one needs a short index, and one used twice
+        let strings: Vec<String> = (0..300).map(|index| format!("s{}", index)).collect();
+        for string in ["s299", "s0", "new", "new"] {
+            instructions.push(Instruction::new_with_operand(
+                Opcode::PushString,
+                instructions.len(),
+                Operand::new_string(string),
+            ));
+        }
+        instructions.push(Instruction::new(Opcode::Ret, instructions.len()));
+
+        let mut function_map = HashMap::new();
+        function_map.insert(Some("main".to_string()), 0);
+        let mut bytes = Vec::new();
+        BytecodeWriter::new(&function_map, &strings, &instructions)
+            .write(&mut bytes)
+            .unwrap();
+
+        let loader = BytecodeLoaderBuilder::new(bytes.as_slice())
+            .build()
+            .unwrap();
+        let loaded = loader.get_instructions().unwrap();
+        let immediate_kinds: Vec<_> = loaded
+            .iter()
+            .map(|instruction| instruction.original_immediate_kind())
+            .collect();
+        assert_eq!(
+            immediate_kinds,
+            vec![
+                Some(Opcode::ImmByte),
+                Some(Opcode::ImmByte),
+                Some(Opcode::ImmByte),
+                Some(Opcode::ImmShort),
+                Some(Opcode::ImmShort),
+                Some(Opcode::ImmShort),
+                Some(Opcode::ImmInt),
+                Some(Opcode::ImmInt),
+                Some(Opcode::ImmStringShort),
+                Some(Opcode::ImmStringByte),
+                Some(Opcode::ImmStringShort),
+                Some(Opcode::ImmStringShort),
+                None,
+            ]
+        );
+        for (loaded, instruction) in loaded.iter().zip(&instructions) {
+            assert_eq!(loaded.operand, instruction.operand);
+        }
+
+        // The new string is added once, after the existing strings
+        let loaded_strings = loader.get_strings().unwrap();
+        assert_eq!(loaded_strings.len(), 301);
+        assert_eq!(loaded_strings[..300], strings[..]);
+        assert_eq!(loaded_strings[300], "new");
+        assert_eq!(loaded[10].original_string_index(), Some(300));
+        assert_eq!(
+            loader
+                .get_function_map()
+                .unwrap()
+                .get(&Some("main".to_string())),
+            Some(&0)
+        );
+    }
+
+    #[test]
+    fn test_write_preserve_encoding() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, // Gs1Flags, length 4
+            0x12, 0x34, 0x56, 0x78, // Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // Functions, length 0
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x04, // Strings, length 4
+            0x61, 0x00, // "a"
+            0x61, 0x00, // "a"
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x10, // Instructions, length 16
+            0x14, 0xf5, 0x00, 0x00, 0x00, 0x01, // PushNumber 1, as an int
+            0x15, 0xf1, 0x00, 0x01, // PushString "a", as a short at the second index
+            0x20, // Pop
+            0x20, // Pop
+            0x14, 0xf3, 0x00, // PushNumber 0
+            0x07, // Ret
+        ];
+        let loader = BytecodeLoaderBuilder::new(bytecode.as_slice())
+            .build()
+            .unwrap();
+        assert_eq!(loader.get_gs1_flags().unwrap(), 0x12345678);
+
+        let write = |preserve_encoding: bool| {
+            let mut bytes = Vec::new();
+            BytecodeWriter::new(
+                loader.get_function_map().unwrap(),
+                loader.get_strings().unwrap(),
+                loader.get_instructions().unwrap(),
+            )
+            .gs1_flags(loader.get_gs1_flags().unwrap())
+            .preserve_encoding(preserve_encoding)
+            .write(&mut bytes)
+            .unwrap();
+            bytes
+        };
+        assert_eq!(write(true), bytecode);
+
+        // Without it, the smallest immediates and the first index of each string are used
+        let instructions: &[u8] = &[
+            0x14, 0xf3, 0x01, // PushNumber 1
+            0x15, 0xf0, 0x00, // PushString "a"
+            0x20, 0x20, 0x14, 0xf3, 0x00, 0x07,
+        ];
+        let bytes = write(false);
+        assert_eq!(bytes[..36], bytecode[..36]);
+        assert_eq!(bytes[36..40], [0x00, 0x00, 0x00, 0x0c]);
+        assert_eq!(bytes[40..], *instructions);
+    }
+
+    #[test]
+    fn test_write_address_mismatch() {
+        let instructions = vec![Instruction::new(Opcode::Ret, 1)];
+        let result = BytecodeWriter::new(&HashMap::new(), &[], &instructions).write(Vec::new());
+        assert!(matches!(
+            result,
+            Err(super::BytecodeWriterError::InstructionAddressMismatch(0, 1))
+        ));
+    }
+}
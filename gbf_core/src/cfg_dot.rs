@@ -9,6 +9,48 @@ use crate::utils::GBF_DARK_GRAY;
 pub trait RenderableNode {
     /// Renders the node as a Graphviz label.
     fn render_node(&self, padding: usize) -> String;
+
+    /// Renders the node as plain text lines, for formats without HTML-like labels. By
+    /// default, each table row of the Graphviz label becomes a line, or each line if
+    /// the label has no table, with the markup removed.
+    fn render_lines(&self) -> Vec<String> {
+        let label = self.render_node(0);
+        let rows: Vec<&str> = if label.contains("</TR>") {
+            label.split("</TR>").collect()
+        } else {
+            label.lines().collect()
+        };
+        rows.into_iter()
+            .map(label_row_to_text)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+/// Removes the tags from a row of an HTML-like label, and decodes its entities.
+fn label_row_to_text(row: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in row.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                // Cells are separated by whitespace
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
 }
 
 /// Trait for resolving NodeIndex to renderable metadata.
@@ -505,4 +547,28 @@ mod tests {
         )));
         assert!(!dot_output.contains("N1")); // Node B should not be rendered.
     }
+
+    #[test]
+    fn test_default_render_lines() {
+        let node = MockNode {
+            label: "Node A".to_string(),
+        };
+        assert_eq!(node.render_lines(), vec!["Node A"]);
+
+        let node = MockNode {
+            label: r#"<TABLE>
+    <TR>
+        <TD ALIGN="LEFT"><FONT COLOR="red">0004</FONT></TD>
+        <TD ALIGN="LEFT">PushString</TD>
+        <TD ALIGN="LEFT">&quot;a &amp; b&quot;</TD>
+    </TR>
+    <TR><TD>Ret</TD></TR>
+</TABLE>"#
+                .to_string(),
+        };
+        assert_eq!(
+            node.render_lines(),
+            vec!["0004 PushString \"a & b\"", "Ret"]
+        );
+    }
 }
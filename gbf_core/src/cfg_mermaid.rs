@@ -0,0 +1,297 @@
+#![deny(missing_docs)]
+
+use std::collections::BTreeMap;
+
+use petgraph::graph::DiGraph;
+use petgraph::visit::{EdgeRef, IntoNodeReferences};
+
+use crate::cfg_dot::{NodeResolver, RenderableNode};
+use crate::utils::GBF_DARK_GRAY;
+
+/// Trait to print the graph as a Mermaid flowchart. The graph must also implement `NodeResolver`.
+pub trait MermaidRenderableGraph: NodeResolver {
+    /// Renders the graph as a Mermaid flowchart.
+    fn render_mermaid(&self, config: CfgMermaidConfig) -> String;
+}
+
+/// Configuration options for rendering a Mermaid flowchart.
+#[derive(Debug)]
+pub struct CfgMermaidConfig {
+    /// The direction of the flowchart layout, such as `TB` or `LR`.
+    pub direction: String,
+    /// The fill color of the nodes.
+    pub fillcolor: String,
+    /// The text color of the nodes.
+    pub fontcolor: String,
+}
+
+impl Default for CfgMermaidConfig {
+    fn default() -> Self {
+        Self {
+            direction: "TB".to_string(),
+            fillcolor: GBF_DARK_GRAY.to_string(),
+            fontcolor: "#ffffff".to_string(),
+        }
+    }
+}
+
+/// A builder for `CfgMermaid` instances.
+pub struct CfgMermaidBuilder {
+    config: CfgMermaidConfig,
+}
+
+impl CfgMermaidBuilder {
+    /// Creates a new `CfgMermaidBuilder` with default configuration.
+    pub fn new() -> Self {
+        Self {
+            config: CfgMermaidConfig::default(),
+        }
+    }
+
+    /// Sets the direction of the flowchart layout.
+    pub fn direction(mut self, direction: &str) -> Self {
+        self.config.direction = direction.to_string();
+        self
+    }
+
+    /// Sets the fill color of the nodes.
+    pub fn fillcolor(mut self, fillcolor: &str) -> Self {
+        self.config.fillcolor = fillcolor.to_string();
+        self
+    }
+
+    /// Sets the text color of the nodes.
+    pub fn fontcolor(mut self, fontcolor: &str) -> Self {
+        self.config.fontcolor = fontcolor.to_string();
+        self
+    }
+
+    /// Builds the `CfgMermaid` instance.
+    pub fn build(self) -> CfgMermaid {
+        CfgMermaid {
+            config: self.config,
+        }
+    }
+}
+
+/// The main struct for rendering Mermaid flowcharts.
+pub struct CfgMermaid {
+    /// The configuration for rendering the flowchart.
+    pub config: CfgMermaidConfig,
+}
+
+impl CfgMermaid {
+    /// Renders the Mermaid representation of a `DiGraph` using the provided resolver.
+    ///
+    /// Nodes are labeled with the lines from `RenderableNode::render_lines`. Since
+    /// Mermaid has no per-edge attributes, edges of the same color are grouped into a
+    /// single `linkStyle`, and nodes with a border color are assigned a `classDef`
+    /// named after that color.
+    ///
+    /// # Arguments
+    /// - `graph`: The directed graph to render.
+    /// - `resolver`: An object that resolves each node index to a data structure that can be rendered.
+    ///
+    /// # Returns
+    /// - A `String` containing the Mermaid flowchart of the graph.
+    pub fn render<R, N, E>(&self, graph: &DiGraph<N, E>, resolver: &R) -> String
+    where
+        R: NodeResolver,
+    {
+        let mut mermaid = String::new();
+        mermaid.push_str(&format!("flowchart {}\n", self.config.direction));
+        mermaid.push_str(&format!(
+            "    classDef default fill:{},color:{},font-family:monospace,text-align:left;\n",
+            self.config.fillcolor, self.config.fontcolor
+        ));
+
+        // Nodes, grouped by their border color
+        let mut border_classes: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (node_index, _node_data) in graph.node_references() {
+            if let Some(data) = resolver.resolve(node_index) {
+                let label = data
+                    .render_lines()
+                    .iter()
+                    .map(mermaid_encode)
+                    .collect::<Vec<_>>()
+                    .join("<br/>");
+                mermaid.push_str(&format!("    N{}[\"{}\"]\n", node_index.index(), label));
+
+                if let Some(border_color) = resolver.resolve_border_color(node_index) {
+                    border_classes
+                        .entry(border_color)
+                        .or_default()
+                        .push(node_index.index());
+                }
+            }
+        }
+
+        // Edges, grouped by their color. Mermaid numbers links in the order they are declared.
+        let mut link_styles: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        let mut link_index = 0;
+        for edge in graph.edge_references() {
+            let source = edge.source();
+            let target = edge.target();
+
+            if resolver.resolve(source).is_some() && resolver.resolve(target).is_some() {
+                let edge_color = resolver.resolve_edge_color(source, target);
+                mermaid.push_str(&format!(
+                    "    N{} --> N{}\n",
+                    source.index(),
+                    target.index()
+                ));
+                link_styles.entry(edge_color).or_default().push(link_index);
+                link_index += 1;
+            }
+        }
+
+        for (color, links) in link_styles {
+            let links = links.iter().map(usize::to_string).collect::<Vec<_>>();
+            mermaid.push_str(&format!(
+                "    linkStyle {} stroke:{}\n",
+                links.join(","),
+                color
+            ));
+        }
+
+        for (color, nodes) in border_classes {
+            let class_name = format!("border{}", color.trim_start_matches('#'));
+            let nodes = nodes.iter().map(|n| format!("N{}", n)).collect::<Vec<_>>();
+            mermaid.push_str(&format!(
+                "    classDef {} stroke:{},stroke-width:2px;\n",
+                class_name, color
+            ));
+            mermaid.push_str(&format!("    class {} {};\n", nodes.join(","), class_name));
+        }
+
+        mermaid
+    }
+}
+
+/// Encodes a string for use in a quoted Mermaid label, using Mermaid's entity codes.
+fn mermaid_encode<S>(input: S) -> String
+where
+    S: AsRef<str>,
+{
+    input
+        .as_ref()
+        .replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+// == Implementations ==
+impl Default for CfgMermaidBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::NodeIndex;
+    use std::collections::HashMap;
+
+    struct MockNode {
+        lines: Vec<String>,
+    }
+
+    impl RenderableNode for MockNode {
+        fn render_node(&self, _padding: usize) -> String {
+            self.lines.join("\n")
+        }
+
+        fn render_lines(&self) -> Vec<String> {
+            self.lines.clone()
+        }
+    }
+
+    struct MockResolver {
+        nodes: HashMap<NodeIndex, MockNode>,
+    }
+
+    impl NodeResolver for MockResolver {
+        type NodeData = MockNode;
+
+        fn resolve(&self, node_index: NodeIndex) -> Option<&Self::NodeData> {
+            self.nodes.get(&node_index)
+        }
+
+        fn resolve_edge_color(&self, _source: NodeIndex, target: NodeIndex) -> String {
+            if target.index() == 2 {
+                "#00ff00".to_string()
+            } else {
+                "#ff0000".to_string()
+            }
+        }
+
+        fn resolve_border_color(&self, node_index: NodeIndex) -> Option<String> {
+            (node_index.index() == 0).then(|| "#98ff64".to_string())
+        }
+    }
+
+    fn mock_node(lines: &[&str]) -> MockNode {
+        MockNode {
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_cfgmermaid_render() {
+        let mut graph = DiGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(a, b, ());
+        graph.add_edge(a, c, ());
+        graph.add_edge(b, c, ());
+
+        let resolver = MockResolver {
+            nodes: vec![
+                (a, mock_node(&["Node A", "x = \"<#>\""])),
+                (b, mock_node(&["Node B"])),
+                (c, mock_node(&["Node C"])),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let output = CfgMermaidBuilder::new()
+            .direction("LR")
+            .build()
+            .render(&graph, &resolver);
+
+        assert!(output.starts_with("flowchart LR\n"));
+        assert!(output.contains(&format!("classDef default fill:{}", GBF_DARK_GRAY)));
+        assert!(output.contains("N0[\"Node A<br/>x = #quot;#lt;#35;#gt;#quot;\"]"));
+        assert!(output.contains("N0 --> N1\n"));
+        assert!(output.contains("N0 --> N2\n"));
+        assert!(output.contains("N1 --> N2\n"));
+        assert!(output.contains("linkStyle 1,2 stroke:#00ff00\n"));
+        assert!(output.contains("linkStyle 0 stroke:#ff0000\n"));
+        assert!(output.contains("classDef border98ff64 stroke:#98ff64,stroke-width:2px;"));
+        assert!(output.contains("class N0 border98ff64;"));
+    }
+
+    #[test]
+    fn test_cfgmermaid_missing_node() {
+        let mut graph = DiGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, ());
+
+        let resolver = MockResolver {
+            nodes: vec![(b, mock_node(&["Node B"]))].into_iter().collect(),
+        };
+
+        let output = CfgMermaidBuilder::default()
+            .build()
+            .render(&graph, &resolver);
+
+        assert!(output.contains("N1[\"Node B\"]"));
+        assert!(!output.contains("N0"));
+        assert!(!output.contains("linkStyle"));
+    }
+}
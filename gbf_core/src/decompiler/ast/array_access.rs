@@ -49,7 +49,10 @@ impl PartialEq for ArrayAccessNode {
 
 #[cfg(test)]
 mod tests {
-    use crate::decompiler::ast::{new_array_access, new_id, new_num};
+    use crate::decompiler::ast::{
+        bin_op::BinOpType, emit, new_array_access, new_bin_op, new_id, new_member_access, new_num,
+        AstNodeError,
+    };
 
     #[test]
     fn test_array_access_node() {
@@ -67,4 +70,26 @@ mod tests {
         let array_access = new_array_access(arr, index);
         assert_eq!(crate::decompiler::ast::emit(array_access), "arr[5]");
     }
+
+    #[test]
+    fn test_member_array_chain_emit() -> Result<(), AstNodeError> {
+        // player.inventory[3].name
+        let inventory = new_member_access(new_id("player"), new_id("inventory"))?;
+        let item = new_array_access(inventory, new_num(3));
+        let name = new_member_access(item, new_id("name"))?;
+        assert_eq!(emit(name.clone()), "player.inventory[3].name");
+
+        // Inside an operation, the chain still has no parentheses
+        let sum = new_bin_op(name, new_num(1), BinOpType::Add)?;
+        assert_eq!(emit(sum), "player.inventory[3].name + 1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_access_operation_emit() -> Result<(), AstNodeError> {
+        let sum = new_bin_op(new_id("a"), new_id("b"), BinOpType::Add)?;
+        let access = new_array_access(sum.clone(), sum);
+        assert_eq!(emit(access), "(a + b)[a + b]");
+        Ok(())
+    }
 }
@@ -0,0 +1,83 @@
+#![deny(missing_docs)]
+
+use super::{
+    array_access::ArrayAccessNode,
+    expr::{ExprKind, ExprTypeHint},
+    identifier::IdentifierNode,
+    member_access::MemberAccessNode,
+    meta::Metadata,
+    ptr::P,
+};
+
+/// Represents an expression that can be the target of an assignment.
+#[derive(Debug, Clone, Copy)]
+pub enum AssignableKind<'a> {
+    /// Represents an identifier, such as `foo`.
+    Identifier(&'a P<IdentifierNode>),
+    /// Represents a member access, such as `foo.bar`.
+    MemberAccess(&'a P<MemberAccessNode>),
+    /// Represents an array access, such as `foo[0]`.
+    ArrayAccess(&'a P<ArrayAccessNode>),
+}
+
+impl<'a> AssignableKind<'a> {
+    /// Returns the assignable view of an expression.
+    ///
+    /// # Arguments
+    /// - `expr`: The expression to view.
+    ///
+    /// # Returns
+    /// - The `AssignableKind`, or `None` if the expression cannot be assigned to.
+    pub fn from_expr(expr: &'a ExprKind) -> Option<Self> {
+        match expr {
+            ExprKind::Identifier(id) => Some(AssignableKind::Identifier(id)),
+            ExprKind::MemberAccess(member) => Some(AssignableKind::MemberAccess(member)),
+            ExprKind::ArrayAccess(array) => Some(AssignableKind::ArrayAccess(array)),
+            _ => None,
+        }
+    }
+
+    /// Returns the recovered type hint of the expression.
+    ///
+    /// The hint is kept in the node's metadata, so it does not take part in equality.
+    pub fn type_hint(&self) -> ExprTypeHint {
+        self.metadata().type_hint()
+    }
+
+    /// Returns the metadata of the expression.
+    fn metadata(&self) -> &'a Metadata {
+        match self {
+            AssignableKind::Identifier(id) => id.metadata(),
+            AssignableKind::MemberAccess(member) => member.metadata(),
+            AssignableKind::ArrayAccess(array) => array.metadata(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decompiler::ast::{expr::ExprKind, new_id, new_id_with_version};
+
+    use super::*;
+
+    #[test]
+    fn test_type_hint() {
+        let id: ExprKind = new_id("player").into();
+        let builtin = id.clone().with_type_hint(ExprTypeHint::BuiltinNamespace);
+        assert_eq!(
+            AssignableKind::from_expr(&builtin).unwrap().type_hint(),
+            ExprTypeHint::BuiltinNamespace
+        );
+        assert_eq!(
+            AssignableKind::from_expr(&id).unwrap().type_hint(),
+            ExprTypeHint::Unknown
+        );
+    }
+
+    #[test]
+    fn test_type_hint_excluded_from_equality() {
+        let id: ExprKind = new_id_with_version("player", 1.into()).into();
+        let builtin = id.clone().with_type_hint(ExprTypeHint::BuiltinNamespace);
+        assert_eq!(id, builtin);
+    }
+}
@@ -8,9 +8,11 @@ use super::{
 };
 use crate::decompiler::ast::AstVisitable;
 
-/// Represents a statement node in the AST, such as `variable = value`.
+/// Represents a statement node in the AST, such as `variable = value`. When the
+/// assigned value is used inline, the node is an expression instead, such as
+/// `(variable = value) > 0`.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
-#[convert_to(StatementKind::Assignment, AstKind::Statement)]
+#[convert_to(StatementKind::Assignment, ExprKind::Assignment, AstKind::Statement)]
 pub struct AssignmentNode {
     /// The left-hand side of the statement, usually a variable.
     pub lhs: ExprKind,
@@ -52,7 +54,13 @@ impl PartialEq for AssignmentNode {
 #[cfg(test)]
 mod tests {
     use crate::decompiler::ast::{
-        emit, new_assignment, new_id, new_member_access, new_str, AstNodeError,
+        bin_op::BinOpType,
+        emit,
+        expr::ExprKind,
+        new_array_access, new_assignment, new_bin_op, new_fn_call, new_id, new_id_with_version,
+        new_member_access, new_num, new_str,
+        visitors::{emit_context::EmitContextBuilder, emitter::Gs2Emitter, AstVisitor},
+        AstKind, AstNodeError,
     };
 
     #[test]
@@ -69,4 +77,191 @@ mod tests {
         assert_eq!(emit(stmt), "player.chat = \"Hello, world!\";");
         Ok(())
     }
+
+    #[test]
+    fn test_assignment_expression_emit() -> Result<(), AstNodeError> {
+        // (temp.x = getValue) > 0
+        let assignment: ExprKind = new_assignment(
+            new_member_access(new_id("temp"), new_id("x"))?,
+            new_id("getValue"),
+        )
+        .into();
+        let cmp = new_bin_op(assignment.clone(), new_num(0), BinOpType::Greater)?;
+        assert_eq!(emit(cmp), "(temp.x = getValue) > 0");
+
+        // The root of an expression needs no parentheses
+        assert_eq!(emit(assignment), "temp.x = getValue");
+        Ok(())
+    }
+
+    #[test]
+    fn test_assignment_and_equality_emit() -> Result<(), AstNodeError> {
+        // An assignment emits `=`, and a comparison emits `==`
+        assert_eq!(emit(new_assignment(new_id("a"), new_id("b"))), "a = b;");
+        let assignment: ExprKind = new_assignment(new_id("a"), new_id("b")).into();
+        assert_eq!(emit(assignment.clone()), "a = b");
+        let equal = new_bin_op(new_id("a"), new_id("b"), BinOpType::Equal)?;
+        assert_eq!(emit(equal.clone()), "a == b");
+
+        // a = (a = b) == (a == b)
+        let stmt = new_assignment(
+            new_id("a"),
+            new_bin_op(assignment, equal, BinOpType::Equal)?,
+        );
+        assert_eq!(emit(stmt), "a = (a = b) == (a == b);");
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_circuit_compound_assignment_emit() -> Result<(), AstNodeError> {
+        // x = x || "default";
+        let stmt = new_assignment(
+            new_id("x"),
+            new_bin_op(new_id("x"), new_str("default"), BinOpType::LogicalOr)?,
+        );
+        assert_eq!(emit(stmt), "x ||= \"default\";");
+
+        // player.ready = player.ready && (a || b);
+        let stmt = new_assignment(
+            new_member_access(new_id("player"), new_id("ready"))?,
+            new_bin_op(
+                new_member_access(new_id("player"), new_id("ready"))?,
+                new_bin_op(new_id("a"), new_id("b"), BinOpType::LogicalOr)?,
+                BinOpType::LogicalAnd,
+            )?,
+        );
+        assert_eq!(emit(stmt), "player.ready &&= a || b;");
+
+        // The first operand must be the assignment target.
+        let stmt = new_assignment(
+            new_id("x"),
+            new_bin_op(new_id("y"), new_id("x"), BinOpType::LogicalOr)?,
+        );
+        assert_eq!(emit(stmt), "x = y || x;");
+        Ok(())
+    }
+
+    #[test]
+    fn test_compound_assignment_emit() -> Result<(), AstNodeError> {
+        // x = x + y;
+        let stmt = new_assignment(
+            new_id("x"),
+            new_bin_op(new_id("x"), new_id("y"), BinOpType::Add)?,
+        );
+        assert_eq!(emit(stmt), "x += y;");
+
+        // Different SSA versions are different values.
+        let stmt = new_assignment(
+            new_id_with_version("x", 2.into()),
+            new_bin_op(
+                new_id_with_version("x", 1.into()),
+                new_id("y"),
+                BinOpType::Add,
+            )?,
+        );
+        assert_eq!(emit(stmt), "x = x + y;");
+
+        // x = x - 1;
+        let stmt = new_assignment(
+            new_id("x"),
+            new_bin_op(new_id("x"), new_num(1), BinOpType::Sub)?,
+        );
+        assert_eq!(emit(stmt), "x--;");
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_and_member_compound_assignment_emit() -> Result<(), AstNodeError> {
+        let element = || -> Result<ExprKind, AstNodeError> {
+            let arr = new_member_access(new_id("temp"), new_id("a"))?;
+            Ok(new_array_access(arr, new_member_access(new_id("temp"), new_id("i"))?).into())
+        };
+        let member = || new_member_access(new_id("obj"), new_id("x"));
+
+        // temp.a[temp.i] = temp.a[temp.i] + 1;
+        let stmt = new_assignment(
+            element()?,
+            new_bin_op(element()?, new_num(1), BinOpType::Add)?,
+        );
+        assert_eq!(emit(stmt), "temp.a[temp.i]++;");
+
+        // temp.a[temp.i] = temp.a[temp.i] + 3;
+        let stmt = new_assignment(
+            element()?,
+            new_bin_op(element()?, new_num(3), BinOpType::Add)?,
+        );
+        assert_eq!(emit(stmt), "temp.a[temp.i] += 3;");
+
+        // obj.x = obj.x - 2;
+        let stmt = new_assignment(
+            member()?,
+            new_bin_op(member()?, new_num(2), BinOpType::Sub)?,
+        );
+        assert_eq!(emit(stmt), "obj.x -= 2;");
+
+        // A different element is not the assignment target.
+        let other = new_array_access(new_member_access(new_id("temp"), new_id("a"))?, new_num(0));
+        let stmt = new_assignment(element()?, new_bin_op(other, new_num(2), BinOpType::Add)?);
+        assert_eq!(emit(stmt), "temp.a[temp.i] = temp.a[0] + 2;");
+
+        // The index would only be evaluated once in the compound form.
+        let call_element = || -> Result<ExprKind, AstNodeError> {
+            let arr = new_member_access(new_id("temp"), new_id("a"))?;
+            Ok(new_array_access(arr, new_fn_call(new_id("f"), vec![])).into())
+        };
+        let stmt = new_assignment(
+            call_element()?,
+            new_bin_op(call_element()?, new_num(1), BinOpType::Add)?,
+        );
+        assert_eq!(emit(stmt), "temp.a[f()] = temp.a[f()] + 1;");
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiplicative_compound_assignment_emit() -> Result<(), AstNodeError> {
+        // obj.x = obj.x * 2;
+        let stmt = new_assignment(
+            new_member_access(new_id("obj"), new_id("x"))?,
+            new_bin_op(
+                new_member_access(new_id("obj"), new_id("x"))?,
+                new_num(2),
+                BinOpType::Mul,
+            )?,
+        );
+        assert_eq!(emit(stmt), "obj.x *= 2;");
+
+        // x = x / 2;
+        let stmt = new_assignment(
+            new_id("x"),
+            new_bin_op(new_id("x"), new_num(2), BinOpType::Div)?,
+        );
+        assert_eq!(emit(stmt), "x /= 2;");
+
+        // x = x % (a + b);
+        let stmt = new_assignment(
+            new_id("x"),
+            new_bin_op(
+                new_id("x"),
+                new_bin_op(new_id("a"), new_id("b"), BinOpType::Add)?,
+                BinOpType::Mod,
+            )?,
+        );
+        assert_eq!(emit(stmt), "x %= a + b;");
+        Ok(())
+    }
+
+    #[test]
+    fn test_compound_assignment_disabled() -> Result<(), AstNodeError> {
+        let context = EmitContextBuilder::default()
+            .use_compound_assignment(false)
+            .build();
+        let stmt: AstKind = new_assignment(
+            new_id("x"),
+            new_bin_op(new_id("x"), new_str("default"), BinOpType::LogicalOr)?,
+        )
+        .into();
+        let mut emitter = Gs2Emitter::new(context);
+        assert_eq!(emitter.visit_node(&stmt).node, "x = x || \"default\";");
+        Ok(())
+    }
 }
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use super::ptr::P;
 use super::visitors::AstVisitor;
 use super::AstKind;
-use super::{expr::ExprKind, AstNodeError};
+use super::{emit, expr::ExprKind, AstNodeError};
 use crate::decompiler::ast::AstVisitable;
 use crate::define_ast_enum_type;
 
@@ -37,6 +37,70 @@ define_ast_enum_type! {
     }
 }
 
+impl BinOpType {
+    /// Gets the precedence of the operator in GS2, where higher binds tighter. Unlike
+    /// C, `^` is the power operator and binds tighter than `*`, while the bitwise
+    /// exclusive or is spelled `xor`, and `&&` binds tighter than `||`.
+    ///
+    /// The relative order of the other operators has not been checked against the GS2
+    /// grammar, so the emitter only relies on this table for the logical operators and
+    /// parenthesizes every other nested operation.
+    ///
+    /// # Returns
+    /// - The precedence of the operator.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinOpType::Power => 12,
+            BinOpType::Mul | BinOpType::Div | BinOpType::Mod => 11,
+            BinOpType::Add | BinOpType::Sub => 10,
+            BinOpType::ShiftLeft | BinOpType::ShiftRight => 9,
+            BinOpType::Join => 8,
+            BinOpType::Greater
+            | BinOpType::Less
+            | BinOpType::GreaterOrEqual
+            | BinOpType::LessOrEqual
+            | BinOpType::In => 7,
+            BinOpType::Equal | BinOpType::NotEqual => 6,
+            BinOpType::And => 5,
+            BinOpType::Xor => 4,
+            BinOpType::Or => 3,
+            BinOpType::LogicalAnd => 2,
+            BinOpType::LogicalOr => 1,
+            BinOpType::Foreach => 0,
+        }
+    }
+
+    /// Gets the precedence of a logical operator. `&&` binds tighter than `||`, so
+    /// `a && b || c` groups as `(a && b) || c`.
+    ///
+    /// # Returns
+    /// - The precedence, where higher binds tighter, or `None` if the operator is not
+    ///   a logical operator.
+    pub fn logical_precedence(&self) -> Option<u8> {
+        match self {
+            BinOpType::LogicalAnd | BinOpType::LogicalOr => Some(self.precedence()),
+            _ => None,
+        }
+    }
+
+    /// Checks if the operator gives the same result with its operands swapped.
+    ///
+    /// # Returns
+    /// - `true` for `+`, `*`, `&`, `|`, `xor`, `==` and `!=`.
+    pub fn is_commutative(&self) -> bool {
+        matches!(
+            self,
+            BinOpType::Add
+                | BinOpType::Mul
+                | BinOpType::And
+                | BinOpType::Or
+                | BinOpType::Xor
+                | BinOpType::Equal
+                | BinOpType::NotEqual
+        )
+    }
+}
+
 /// Represents a binary operation node in the AST, such as `a + b`.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
 #[convert_to(ExprKind::BinOp, AstKind::Expression)]
@@ -65,6 +129,46 @@ impl BinaryOperationNode {
     pub fn new(lhs: ExprKind, rhs: ExprKind, op_type: BinOpType) -> Result<Self, AstNodeError> {
         Ok(Self { lhs, rhs, op_type })
     }
+
+    /// Returns a copy of the operation in a normalized form, so that `a + b` and
+    /// `b + a` compare equal. The operands of commutative operations are ordered by
+    /// their emitted source, and nested operations are normalized as well.
+    ///
+    /// Operands that may have side effects, such as function calls, are never swapped,
+    /// since that would change the order in which they are evaluated.
+    ///
+    /// # Returns
+    /// The normalized `BinaryOperationNode`.
+    pub fn canonicalize(&self) -> BinaryOperationNode {
+        let mut lhs = canonicalize_expr(&self.lhs);
+        let mut rhs = canonicalize_expr(&self.rhs);
+        if self.op_type.is_commutative()
+            && !lhs.has_side_effects()
+            && !rhs.has_side_effects()
+            && emit(lhs.clone()) > emit(rhs.clone())
+        {
+            std::mem::swap(&mut lhs, &mut rhs);
+        }
+        Self {
+            lhs,
+            rhs,
+            op_type: self.op_type.clone(),
+        }
+    }
+}
+
+/// Normalizes the binary operations in an expression.
+fn canonicalize_expr(expr: &ExprKind) -> ExprKind {
+    let mut expr = expr.clone();
+    match &mut expr {
+        ExprKind::BinOp(node) => **node = node.canonicalize(),
+        _ => {
+            for subexpression in expr.subexpressions_mut() {
+                *subexpression = canonicalize_expr(subexpression);
+            }
+        }
+    }
+    expr
 }
 
 // == Other implementations for binary operations ==
@@ -82,7 +186,9 @@ impl AstVisitable for P<BinaryOperationNode> {
 
 #[cfg(test)]
 mod tests {
-    use crate::decompiler::ast::{emit, new_bin_op, new_id};
+    use crate::decompiler::ast::{
+        emit, new_bin_op, new_fn_call, new_id, new_member_access, new_num, new_str,
+    };
 
     use super::*;
 
@@ -106,6 +212,141 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_nested_logical_bin_op_emit() -> Result<(), AstNodeError> {
+        // a && b || c
+        let expr = new_bin_op(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::LogicalAnd)?,
+            new_id("c"),
+            BinOpType::LogicalOr,
+        )?;
+        assert_eq!(emit(expr), "a && b || c");
+
+        // a && (b || c)
+        let expr = new_bin_op(
+            new_id("a"),
+            new_bin_op(new_id("b"), new_id("c"), BinOpType::LogicalOr)?,
+            BinOpType::LogicalAnd,
+        )?;
+        assert_eq!(emit(expr), "a && (b || c)");
+
+        // c || a && b
+        let expr = new_bin_op(
+            new_id("c"),
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::LogicalAnd)?,
+            BinOpType::LogicalOr,
+        )?;
+        assert_eq!(emit(expr), "c || a && b");
+
+        // Both are left-associative, so only a chain on the right keeps its grouping
+        let expr = new_bin_op(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::LogicalOr)?,
+            new_bin_op(new_id("c"), new_id("d"), BinOpType::LogicalOr)?,
+            BinOpType::LogicalOr,
+        )?;
+        assert_eq!(emit(expr), "a || b || (c || d)");
+
+        // Other operations keep their parentheses
+        let expr = new_bin_op(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::Equal)?,
+            new_id("c"),
+            BinOpType::LogicalAnd,
+        )?;
+        assert_eq!(emit(expr), "(a == b) && c");
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_chain_emit() -> Result<(), AstNodeError> {
+        // "Hello, " @ name @ "!" @ count, as the left-leaning chain GS2 compiles it to
+        let expr = new_bin_op(
+            new_bin_op(
+                new_bin_op(new_str("Hello, "), new_id("name"), BinOpType::Join)?,
+                new_str("!"),
+                BinOpType::Join,
+            )?,
+            new_id("count"),
+            BinOpType::Join,
+        )?;
+        assert_eq!(emit(expr.clone()), "\"Hello, \" @ name @ \"!\" @ count");
+
+        // A chain inside another operation is parenthesized as a whole
+        let cmp = new_bin_op(expr, new_str(""), BinOpType::Equal)?;
+        assert_eq!(emit(cmp), "(\"Hello, \" @ name @ \"!\" @ count) == \"\"");
+
+        // A chain on the right keeps its grouping
+        let expr = new_bin_op(
+            new_id("a"),
+            new_bin_op(new_id("b"), new_id("c"), BinOpType::Join)?,
+            BinOpType::Join,
+        )?;
+        assert_eq!(emit(expr), "a @ (b @ c)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bin_op_precedence() {
+        // `^` is the power operator in GS2, so it binds tighter than `*`
+        assert!(BinOpType::Power.precedence() > BinOpType::Mul.precedence());
+        assert!(BinOpType::Mul.precedence() > BinOpType::Add.precedence());
+        assert!(BinOpType::Add.precedence() > BinOpType::ShiftLeft.precedence());
+        assert!(BinOpType::ShiftLeft.precedence() > BinOpType::Less.precedence());
+        assert!(BinOpType::Less.precedence() > BinOpType::Equal.precedence());
+        assert!(BinOpType::Equal.precedence() > BinOpType::And.precedence());
+        assert!(BinOpType::And.precedence() > BinOpType::Xor.precedence());
+        assert!(BinOpType::Xor.precedence() > BinOpType::Or.precedence());
+        assert!(BinOpType::Or.precedence() > BinOpType::LogicalAnd.precedence());
+        assert!(BinOpType::LogicalAnd.precedence() > BinOpType::LogicalOr.precedence());
+        assert_eq!(BinOpType::Add.logical_precedence(), None);
+    }
+
+    #[test]
+    fn test_bitwise_bin_op_emit() -> Result<(), AstNodeError> {
+        for (op_type, expected) in [
+            (BinOpType::And, "a & b"),
+            (BinOpType::Or, "a | b"),
+            (BinOpType::Xor, "a xor b"),
+            (BinOpType::ShiftLeft, "a << b"),
+            (BinOpType::ShiftRight, "a >> b"),
+            (BinOpType::Power, "a ^ b"),
+        ] {
+            assert_eq!(
+                emit(new_bin_op(new_id("a"), new_id("b"), op_type)?),
+                expected
+            );
+        }
+
+        // `a & b == c` groups as `a & (b == c)`, so both groupings keep their parentheses
+        let expr = new_bin_op(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::And)?,
+            new_id("c"),
+            BinOpType::Equal,
+        )?;
+        assert_eq!(emit(expr), "(a & b) == c");
+        let expr = new_bin_op(
+            new_id("a"),
+            new_bin_op(new_id("b"), new_id("c"), BinOpType::Equal)?,
+            BinOpType::And,
+        )?;
+        assert_eq!(emit(expr), "a & (b == c)");
+
+        let expr = new_bin_op(
+            new_bin_op(new_id("a"), new_num(1), BinOpType::ShiftLeft)?,
+            new_bin_op(new_id("b"), new_id("c"), BinOpType::Xor)?,
+            BinOpType::Or,
+        )?;
+        assert_eq!(emit(expr), "(a << 1) | (b xor c)");
+
+        // Bitwise operations are not logical, so they are parenthesized inside `&&`
+        let expr = new_bin_op(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::And)?,
+            new_id("c"),
+            BinOpType::LogicalAnd,
+        )?;
+        assert_eq!(emit(expr), "(a & b) && c");
+        Ok(())
+    }
+
     #[test]
     fn test_bin_op_eq() -> Result<(), AstNodeError> {
         let a = new_bin_op(new_id("a"), new_id("b"), BinOpType::Add)?;
@@ -118,4 +359,67 @@ mod tests {
         assert_ne!(a, d);
         Ok(())
     }
+
+    #[test]
+    fn test_bin_op_canonicalize_commutative() -> Result<(), AstNodeError> {
+        let a = new_bin_op(new_id("a"), new_id("b"), BinOpType::Add)?;
+        let b = new_bin_op(new_id("b"), new_id("a"), BinOpType::Add)?;
+        assert_ne!(a, b);
+        assert_eq!(a.canonicalize(), b.canonicalize());
+        assert_eq!(emit(b.canonicalize()), "a + b");
+
+        // Nested operations are normalized too
+        let a = new_bin_op(
+            new_id("c"),
+            new_bin_op(new_id("b"), new_id("a"), BinOpType::Mul)?,
+            BinOpType::Equal,
+        )?;
+        let b = new_bin_op(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::Mul)?,
+            new_id("c"),
+            BinOpType::Equal,
+        )?;
+        assert_eq!(a.canonicalize(), b.canonicalize());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bin_op_canonicalize_non_commutative() -> Result<(), AstNodeError> {
+        for op_type in [
+            BinOpType::Sub,
+            BinOpType::Div,
+            BinOpType::Less,
+            BinOpType::Power,
+        ] {
+            let expr = new_bin_op(new_id("b"), new_id("a"), op_type)?;
+            assert_eq!(expr.canonicalize(), expr);
+        }
+
+        // The operands of `-` are left in place, but the operation inside is normalized
+        let expr = new_bin_op(
+            new_bin_op(new_num(2), new_id("a"), BinOpType::Add)?,
+            new_id("b"),
+            BinOpType::Sub,
+        )?;
+        assert_eq!(emit(expr.canonicalize()), "(2 + a) - b");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bin_op_canonicalize_side_effects() -> Result<(), AstNodeError> {
+        // Swapping would call `f` after reading `a`
+        let expr = new_bin_op(
+            new_fn_call(new_id("f"), vec![]),
+            new_id("a"),
+            BinOpType::Add,
+        )?;
+        assert_eq!(expr.canonicalize(), expr);
+
+        // Type queries have no side effects
+        let type_query =
+            new_fn_call(new_member_access(new_id("b"), new_id("type"))?, vec![]).with_pure(true);
+        let expr = new_bin_op(type_query, new_id("a"), BinOpType::Equal)?;
+        assert_eq!(emit(expr.canonicalize()), "a == b.type()");
+        Ok(())
+    }
 }
@@ -62,4 +62,14 @@ mod tests {
         let output = emit(block);
         assert_eq!(output, "\n{\n    foo = bar;\n    baz = 3.14;\n}");
     }
+
+    #[test]
+    fn test_block_emit_skips_empty_nodes() {
+        let stmt_1: AstKind = new_assignment(new_id("foo"), new_id("bar")).into();
+        let empty: AstKind = new_id("").into();
+        let stmt_2: AstKind = new_assignment(new_id("baz"), new_float("3.14")).into();
+        let block = BlockNode::new(vec![stmt_1, empty, stmt_2]);
+        let output = emit(block);
+        assert_eq!(output, "\n{\n    foo = bar;\n    baz = 3.14;\n}");
+    }
 }
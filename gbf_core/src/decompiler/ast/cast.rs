@@ -0,0 +1,110 @@
+#![deny(missing_docs)]
+
+use gbf_macros::AstNodeTransform;
+use serde::{Deserialize, Serialize};
+
+use crate::define_ast_enum_type;
+
+use super::{expr::ExprKind, ptr::P, visitors::AstVisitor, AstKind, AstVisitable};
+
+define_ast_enum_type!(
+    CastType {
+        Float => "float",
+        Object => "object",
+        String => "string",
+        Variable => "var",
+    }
+);
+
+/// Represents a cast node in the AST, such as `(float)a`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
+#[convert_to(ExprKind::Cast, AstKind::Expression)]
+pub struct CastNode {
+    /// The expression being cast.
+    pub operand: ExprKind,
+    /// The type the expression is cast to.
+    pub cast_type: CastType,
+}
+
+impl CastNode {
+    /// Creates a new `CastNode`.
+    ///
+    /// # Arguments
+    /// - `operand` - The expression being cast.
+    /// - `cast_type` - The type the expression is cast to.
+    ///
+    /// # Returns
+    /// A new `CastNode`.
+    pub fn new(operand: ExprKind, cast_type: CastType) -> Self {
+        Self { operand, cast_type }
+    }
+}
+
+impl AstVisitable for P<CastNode> {
+    fn accept<V: AstVisitor>(&self, visitor: &mut V) -> V::Output {
+        visitor.visit_cast(self)
+    }
+}
+
+// == Other implementations for casts ==
+impl PartialEq for CastNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.operand == other.operand && self.cast_type == other.cast_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decompiler::ast::{
+        bin_op::BinOpType, emit, new_bin_op, new_cast, new_id, new_member_access, AstNodeError,
+    };
+
+    use super::CastType;
+
+    #[test]
+    fn test_cast_emit() {
+        for cast_type in CastType::all_variants() {
+            let expr = new_cast(new_id("a"), cast_type.clone());
+            assert_eq!(emit(expr), format!("({})a", cast_type.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_cast_binary_operand() -> Result<(), AstNodeError> {
+        let cast = new_cast(
+            new_bin_op(new_id("a"), new_id("b"), BinOpType::Add)?,
+            CastType::Float,
+        );
+        assert_eq!(emit(cast.clone()), "(float)(a + b)");
+
+        let expr = new_bin_op(cast, new_id("c"), BinOpType::Mul)?;
+        assert_eq!(emit(expr), "(float)(a + b) * c");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cast_operand_parentheses() -> Result<(), AstNodeError> {
+        // (float)a + (float)b
+        let expr = new_bin_op(
+            new_cast(new_id("a"), CastType::Float),
+            new_cast(new_id("b"), CastType::Float),
+            BinOpType::Add,
+        )?;
+        assert_eq!(emit(expr), "(float)a + (float)b");
+
+        // Postfix operators bind tighter than a cast
+        let expr = new_member_access(new_cast(new_id("a"), CastType::Object), new_id("b"))?;
+        assert_eq!(emit(expr), "((object)a).b");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cast_equality() {
+        let cast1 = new_cast(new_id("a"), CastType::String);
+        let cast2 = new_cast(new_id("a"), CastType::String);
+        assert_eq!(cast1, cast2);
+
+        let cast3 = new_cast(new_id("a"), CastType::Object);
+        assert_ne!(cast1, cast3);
+    }
+}
@@ -0,0 +1,66 @@
+#![deny(missing_docs)]
+
+use gbf_macros::AstNodeTransform;
+use serde::{Deserialize, Serialize};
+
+use super::{ptr::P, statement::StatementKind, visitors::AstVisitor, AstKind, AstVisitable};
+
+/// Represents a comment node in the AST, such as `// Linear`, which is emitted
+/// on its own line.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
+#[convert_to(StatementKind::Comment, AstKind::Statement)]
+pub struct CommentNode {
+    /// The text of the comment.
+    pub text: String,
+}
+
+impl CommentNode {
+    /// Creates a new `CommentNode` with the provided text.
+    ///
+    /// # Arguments
+    /// - `text`: The text of the comment.
+    ///
+    /// # Returns
+    /// The comment node.
+    pub fn new(text: String) -> Self {
+        Self { text }
+    }
+}
+
+impl AstVisitable for P<CommentNode> {
+    fn accept<V: AstVisitor>(&self, visitor: &mut V) -> V::Output {
+        visitor.visit_comment(self)
+    }
+}
+
+// == Other implementations for comment ==
+impl PartialEq for CommentNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decompiler::ast::{
+        emit, new_comment,
+        visitors::{
+            emit_context::{CommentStyle, EmitContext},
+            emitter::Gs2Emitter,
+        },
+        AstKind, AstVisitable,
+    };
+
+    #[test]
+    fn test_comment_emit() {
+        let comment = new_comment("ControlFlow: Jeq");
+        assert_eq!(emit(comment.clone()), "// ControlFlow: Jeq");
+
+        let context = EmitContext::builder()
+            .comment_style(CommentStyle::Block)
+            .build();
+        let node: AstKind = comment.into();
+        let output = node.accept(&mut Gs2Emitter::new(context));
+        assert_eq!(output.node, "/* ControlFlow: Jeq */");
+    }
+}
@@ -33,6 +33,8 @@ pub struct ControlFlowNode {
     ty: ControlFlowType,
     expr: Option<ExprKind>,
     then_block: P<BlockNode>,
+    init: Option<AstKind>,
+    step: Option<AstKind>,
 }
 
 impl ControlFlowNode {
@@ -54,9 +56,30 @@ impl ControlFlowNode {
             ty,
             expr: condition.map(|e| e.into()),
             then_block: BlockNode::new(body).into(),
+            init: None,
+            step: None,
         }
     }
 
+    /// Sets the initializer and step of a counted `for` loop, such as `i = 0` and `i++`
+    /// in `for (i = 0; i < 10; i++)`.
+    ///
+    /// # Arguments
+    /// - `init`: The statement run once before the loop.
+    /// - `step`: The statement run after each iteration of the loop.
+    ///
+    /// # Returns
+    /// The `ControlFlowNode` with the initializer and step set.
+    pub fn with_for_clauses<I, S>(mut self, init: I, step: S) -> Self
+    where
+        I: Into<AstKind>,
+        S: Into<AstKind>,
+    {
+        self.init = Some(init.into());
+        self.step = Some(step.into());
+        self
+    }
+
     /// Returns the condition of the ControlFlowNode.
     pub fn condition(&self) -> &Option<ExprKind> {
         &self.expr
@@ -71,6 +94,31 @@ impl ControlFlowNode {
     pub fn ty(&self) -> &ControlFlowType {
         &self.ty
     }
+
+    /// Returns the condition of the ControlFlowNode mutably.
+    pub fn condition_mut(&mut self) -> &mut Option<ExprKind> {
+        &mut self.expr
+    }
+
+    /// Returns the body of the ControlFlowNode mutably.
+    pub fn body_mut(&mut self) -> &mut P<BlockNode> {
+        &mut self.then_block
+    }
+
+    /// Returns the initializer of a counted `for` loop.
+    pub fn init(&self) -> &Option<AstKind> {
+        &self.init
+    }
+
+    /// Returns the step of a counted `for` loop.
+    pub fn step(&self) -> &Option<AstKind> {
+        &self.step
+    }
+
+    /// Returns the initializer and step of a counted `for` loop mutably.
+    pub fn for_clauses_mut(&mut self) -> (&mut Option<AstKind>, &mut Option<AstKind>) {
+        (&mut self.init, &mut self.step)
+    }
 }
 
 // == Other implementations for literal ==
@@ -82,7 +130,11 @@ impl AstVisitable for P<ControlFlowNode> {
 
 impl PartialEq for ControlFlowNode {
     fn eq(&self, other: &Self) -> bool {
-        self.ty == other.ty && self.expr == other.expr && self.then_block == other.then_block
+        self.ty == other.ty
+            && self.expr == other.expr
+            && self.then_block == other.then_block
+            && self.init == other.init
+            && self.step == other.step
     }
 }
 
@@ -90,10 +142,23 @@ impl PartialEq for ControlFlowNode {
 mod tests {
     use super::*;
     use crate::decompiler::ast::{
-        bin_op::BinOpType, emit, new_bin_op, new_fn, new_id, new_member_access, new_num,
-        new_return, new_str, AstNodeError,
+        bin_op::BinOpType,
+        emit, new_assignment, new_bin_op, new_counted_for, new_do_while, new_else, new_fn, new_id,
+        new_if, new_member_access, new_num, new_return, new_str, new_while,
+        visitors::{
+            emit_context::{EmitContext, FoldMarkerStyle, IndentStyle},
+            emitter::Gs2Emitter,
+        },
+        AstKind, AstNodeError, AstVisitable,
     };
 
+    fn emit_without_single_statement_braces<N: Into<AstKind>>(node: N) -> String {
+        let context = EmitContext::builder()
+            .braces_for_single_statements(false)
+            .build();
+        node.into().accept(&mut Gs2Emitter::new(context)).node
+    }
+
     #[test]
     fn test_control_flow_node() -> Result<(), AstNodeError> {
         /* if (foo.bar == "baz")  { return 1; } */
@@ -143,4 +208,155 @@ mod tests {
         assert_eq!(output, "function onCreated(test)\n{\n    if (foo.bar == \"baz\") \n    {\n        return 1;\n    }\n    else\n    {\n        return 2;\n    }\n}");
         Ok(())
     }
+
+    #[test]
+    fn test_control_flow_counted_for_emit() -> Result<(), AstNodeError> {
+        /* for (i = 0; i < 10; i++) { echo = i; } */
+        let condition = new_bin_op(new_id("i"), new_num(10), BinOpType::Less)?;
+        let step = new_assignment(
+            new_id("i"),
+            new_bin_op(new_id("i"), new_num(1), BinOpType::Add)?,
+        );
+        let control_flow = new_counted_for(
+            new_assignment(new_id("i"), new_num(0)),
+            condition,
+            step,
+            vec![new_assignment(new_id("echo"), new_id("i"))],
+        );
+        assert!(control_flow.init().is_some());
+        assert!(control_flow.step().is_some());
+        let output = emit(control_flow);
+        assert_eq!(output, "for (i = 0; i < 10; i++) \n{\n    echo = i;\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_flow_braceless_emit() -> Result<(), AstNodeError> {
+        /* if (a) return 1; else return 2; */
+        let function = new_fn(
+            Some("onCreated".to_string()),
+            Vec::<ExprKind>::new(),
+            vec![
+                new_if(new_id("a"), vec![new_return(new_num(1))]),
+                new_else(vec![new_return(new_num(2))]),
+                new_while(new_id("b"), vec![new_assignment(new_id("c"), new_num(3))]),
+            ],
+        );
+        assert_eq!(
+            emit_without_single_statement_braces(function),
+            "function onCreated()\n{\n    if (a) return 1;\n    else return 2;\n    while (b) c = 3;\n}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_flow_braceless_keeps_multiple_statements() -> Result<(), AstNodeError> {
+        let control_flow = new_if(
+            new_id("a"),
+            vec![new_return(new_num(1)), new_return(new_num(2))],
+        );
+        assert_eq!(
+            emit_without_single_statement_braces(control_flow),
+            "if (a) \n{\n    return 1;\n    return 2;\n}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_flow_braceless_dangling_else() -> Result<(), AstNodeError> {
+        /* if (a) { if (b) return 1; } else return 2; */
+        let inner = new_if(new_id("b"), vec![new_return(new_num(1))]);
+        let function = new_fn(
+            Some("onCreated".to_string()),
+            Vec::<ExprKind>::new(),
+            vec![
+                new_if(new_id("a"), vec![inner]),
+                new_else(vec![new_return(new_num(2))]),
+            ],
+        );
+        assert_eq!(
+            emit_without_single_statement_braces(function),
+            "function onCreated()\n{\n    if (a) \n    {\n        if (b) return 1;\n    }\n    else return 2;\n}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_flow_fold_markers() -> Result<(), AstNodeError> {
+        /* if (a) { while (b) { c = 1; } } else { return 2; } */
+        let inner = new_while(new_id("b"), vec![new_assignment(new_id("c"), new_num(1))]);
+        let function = new_fn(
+            Some("onCreated".to_string()),
+            Vec::<ExprKind>::new(),
+            vec![
+                new_if(new_id("a"), vec![inner]),
+                new_else(vec![new_return(new_num(2))]),
+            ],
+        );
+        let emit_with = |style| {
+            let context = EmitContext::builder()
+                .indent_style(IndentStyle::KAndR)
+                .fold_markers(style)
+                .build();
+            AstKind::from(function.clone())
+                .accept(&mut Gs2Emitter::new(context))
+                .node
+        };
+        assert_eq!(
+            emit_with(FoldMarkerStyle::Vim),
+            "function onCreated() {\n    // {{{\n    if (a)  {\n        // {{{\n        while (b)  {\n            c = 1;\n        }\n        // }}}\n    }\n    // }}}\n    // {{{\n    else {\n        return 2;\n    }\n    // }}}\n}"
+        );
+        assert!(emit_with(FoldMarkerStyle::Region).contains("    // region\n    if (a)"));
+
+        // No markers are written by default
+        assert!(!emit(function).contains("//"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_flow_condition_emit() -> Result<(), AstNodeError> {
+        let equal = || new_bin_op(new_id("a"), new_id("b"), BinOpType::Equal);
+        let assignment = || ExprKind::from(new_assignment(new_id("a"), new_id("b")));
+        let body = || vec![new_assignment(new_id("c"), new_num(1))];
+        let emit_braceless = |node: ControlFlowNode| emit_without_single_statement_braces(node);
+
+        // A comparison is emitted as is
+        assert_eq!(
+            emit_braceless(new_if(equal()?, body())),
+            "if (a == b) c = 1;"
+        );
+        assert_eq!(
+            emit_braceless(new_while(equal()?, body())),
+            "while (a == b) c = 1;"
+        );
+        assert_eq!(
+            emit_braceless(new_do_while(equal()?, body())),
+            "do \n{\n    c = 1;\n} while (a == b);"
+        );
+
+        // An assignment is never emitted as a bare `=` in a condition
+        assert_eq!(
+            emit_braceless(new_if(assignment(), body())),
+            "if ((a = b)) c = 1;"
+        );
+        assert_eq!(
+            emit_braceless(new_while(assignment(), body())),
+            "while ((a = b)) c = 1;"
+        );
+        assert_eq!(
+            emit_braceless(new_do_while(assignment(), body())),
+            "do \n{\n    c = 1;\n} while ((a = b));"
+        );
+        let counted_for = new_counted_for(
+            new_assignment(new_id("i"), new_num(0)),
+            assignment(),
+            new_assignment(new_id("i"), new_num(1)),
+            body(),
+        );
+        assert_eq!(
+            emit_braceless(counted_for),
+            "for (i = 0; (a = b); i = 1) c = 1;"
+        );
+        Ok(())
+    }
 }
@@ -4,10 +4,12 @@ use gbf_macros::AstNodeTransform;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    array::ArrayNode, array_access::ArrayAccessNode, bin_op::BinaryOperationNode,
+    array::ArrayNode, array_access::ArrayAccessNode, assignable::AssignableKind,
+    assignment::AssignmentNode, bin_op::BinaryOperationNode, cast::CastNode,
     func_call::FunctionCallNode, identifier::IdentifierNode, literal::LiteralNode,
-    member_access::MemberAccessNode, new::NewNode, new_array::NewArrayNode, phi::PhiNode, ptr::P,
-    range::RangeNode, unary_op::UnaryOperationNode, visitors::AstVisitor, AstKind, AstVisitable,
+    member_access::MemberAccessNode, meta::Metadata, new::NewNode, new_array::NewArrayNode,
+    node_id::NodeId, phi::PhiNode, ptr::P, range::RangeNode, unary_op::UnaryOperationNode,
+    visitors::AstVisitor, AstKind, AstVisitable,
 };
 
 /// Represents an expression node in the AST.
@@ -38,6 +40,200 @@ pub enum ExprKind {
     Phi(P<PhiNode>),
     /// Represents a range node in the AST.
     Range(P<RangeNode>),
+    /// Represents an assignment whose value is used as an expression.
+    Assignment(P<AssignmentNode>),
+    /// Represents a cast node in the AST.
+    Cast(P<CastNode>),
+}
+
+/// A lightweight hint about what kind of value an expression evaluates to. This is
+/// used when emitting code that depends on the kind of value, such as member access.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ExprTypeHint {
+    /// Nothing is known about the value.
+    #[default]
+    Unknown,
+    /// The value is an object.
+    Object,
+    /// The value is an array.
+    Array,
+    /// The value is a builtin namespace, such as `this` or `player`.
+    BuiltinNamespace,
+}
+
+impl ExprKind {
+    /// Returns the recovered type hint for the expression.
+    ///
+    /// # Returns
+    /// - The `ExprTypeHint` of the expression, or `ExprTypeHint::Unknown` if it
+    ///   cannot be determined.
+    pub fn type_hint(&self) -> ExprTypeHint {
+        if let Some(assignable) = AssignableKind::from_expr(self) {
+            return assignable.type_hint();
+        }
+        match self {
+            ExprKind::Array(_) | ExprKind::NewArray(_) => ExprTypeHint::Array,
+            ExprKind::New(_) => ExprTypeHint::Object,
+            _ => ExprTypeHint::Unknown,
+        }
+    }
+
+    /// Returns the expression with the given type hint. Only assignable expressions
+    /// carry a type hint, so other expressions are returned unchanged.
+    ///
+    /// # Arguments
+    /// - `type_hint`: The recovered type hint of the expression.
+    ///
+    /// # Returns
+    /// - The updated `ExprKind`.
+    pub fn with_type_hint(mut self, type_hint: ExprTypeHint) -> Self {
+        let metadata = match &mut self {
+            ExprKind::Identifier(id) => id.metadata_mut(),
+            ExprKind::MemberAccess(member) => member.metadata_mut(),
+            ExprKind::ArrayAccess(array) => array.metadata_mut(),
+            _ => return self,
+        };
+        metadata.set_type_hint(type_hint);
+        self
+    }
+
+    /// Checks if evaluating the expression may have side effects, such as calling a
+    /// function or creating an object.
+    ///
+    /// # Returns
+    /// - `true` if the expression, or any of its subexpressions, may have side effects.
+    pub fn has_side_effects(&self) -> bool {
+        match self {
+            ExprKind::FunctionCall(call) if !call.is_pure() => true,
+            ExprKind::New(_) | ExprKind::NewArray(_) | ExprKind::Assignment(_) => true,
+            _ => self
+                .subexpressions()
+                .into_iter()
+                .any(ExprKind::has_side_effects),
+        }
+    }
+
+    /// Returns the node ID of the expression.
+    pub fn node_id(&self) -> NodeId {
+        match self {
+            ExprKind::Literal(node) => node.node_id(),
+            ExprKind::BinOp(node) => node.node_id(),
+            ExprKind::UnaryOp(node) => node.node_id(),
+            ExprKind::FunctionCall(node) => node.node_id(),
+            ExprKind::Array(node) => node.node_id(),
+            ExprKind::New(node) => node.node_id(),
+            ExprKind::NewArray(node) => node.node_id(),
+            ExprKind::MemberAccess(node) => node.node_id(),
+            ExprKind::Identifier(node) => node.node_id(),
+            ExprKind::ArrayAccess(node) => node.node_id(),
+            ExprKind::Phi(node) => node.node_id(),
+            ExprKind::Range(node) => node.node_id(),
+            ExprKind::Assignment(node) => node.node_id(),
+            ExprKind::Cast(node) => node.node_id(),
+        }
+    }
+
+    /// Returns the metadata of the expression.
+    pub fn metadata(&self) -> &Metadata {
+        match self {
+            ExprKind::Literal(node) => node.metadata(),
+            ExprKind::BinOp(node) => node.metadata(),
+            ExprKind::UnaryOp(node) => node.metadata(),
+            ExprKind::FunctionCall(node) => node.metadata(),
+            ExprKind::Array(node) => node.metadata(),
+            ExprKind::New(node) => node.metadata(),
+            ExprKind::NewArray(node) => node.metadata(),
+            ExprKind::MemberAccess(node) => node.metadata(),
+            ExprKind::Identifier(node) => node.metadata(),
+            ExprKind::ArrayAccess(node) => node.metadata(),
+            ExprKind::Phi(node) => node.metadata(),
+            ExprKind::Range(node) => node.metadata(),
+            ExprKind::Assignment(node) => node.metadata(),
+            ExprKind::Cast(node) => node.metadata(),
+        }
+    }
+
+    /// Returns the metadata of the expression mutably.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        match self {
+            ExprKind::Literal(node) => node.metadata_mut(),
+            ExprKind::BinOp(node) => node.metadata_mut(),
+            ExprKind::UnaryOp(node) => node.metadata_mut(),
+            ExprKind::FunctionCall(node) => node.metadata_mut(),
+            ExprKind::Array(node) => node.metadata_mut(),
+            ExprKind::New(node) => node.metadata_mut(),
+            ExprKind::NewArray(node) => node.metadata_mut(),
+            ExprKind::MemberAccess(node) => node.metadata_mut(),
+            ExprKind::Identifier(node) => node.metadata_mut(),
+            ExprKind::ArrayAccess(node) => node.metadata_mut(),
+            ExprKind::Phi(node) => node.metadata_mut(),
+            ExprKind::Range(node) => node.metadata_mut(),
+            ExprKind::Assignment(node) => node.metadata_mut(),
+            ExprKind::Cast(node) => node.metadata_mut(),
+        }
+    }
+
+    /// Returns the direct subexpressions of the expression, such as both sides of a
+    /// binary operation.
+    pub fn subexpressions(&self) -> Vec<&ExprKind> {
+        match self {
+            ExprKind::BinOp(node) => vec![&node.lhs, &node.rhs],
+            ExprKind::UnaryOp(node) => vec![&node.operand],
+            ExprKind::FunctionCall(node) => std::iter::once(&node.name)
+                .chain(node.arguments.iter())
+                .collect(),
+            ExprKind::Array(node) => node.elements.iter().collect(),
+            ExprKind::New(node) => vec![&node.new_type, &node.arg],
+            ExprKind::NewArray(node) => vec![&node.arg],
+            ExprKind::MemberAccess(node) => vec![&node.lhs, &node.rhs],
+            ExprKind::ArrayAccess(node) => vec![&node.arr, &node.index],
+            ExprKind::Range(node) => vec![&node.start, &node.end],
+            ExprKind::Assignment(node) => vec![&node.lhs, &node.rhs],
+            ExprKind::Cast(node) => vec![&node.operand],
+            ExprKind::Literal(_) | ExprKind::Identifier(_) | ExprKind::Phi(_) => Vec::new(),
+        }
+    }
+
+    /// Returns the direct subexpressions of the expression mutably.
+    pub fn subexpressions_mut(&mut self) -> Vec<&mut ExprKind> {
+        match self {
+            ExprKind::BinOp(node) => {
+                let node = &mut **node;
+                vec![&mut node.lhs, &mut node.rhs]
+            }
+            ExprKind::UnaryOp(node) => vec![&mut node.operand],
+            ExprKind::FunctionCall(node) => {
+                let node = &mut **node;
+                std::iter::once(&mut node.name)
+                    .chain(node.arguments.iter_mut())
+                    .collect()
+            }
+            ExprKind::Array(node) => node.elements.iter_mut().collect(),
+            ExprKind::New(node) => {
+                let node = &mut **node;
+                vec![&mut node.new_type, &mut node.arg]
+            }
+            ExprKind::NewArray(node) => vec![&mut node.arg],
+            ExprKind::MemberAccess(node) => {
+                let node = &mut **node;
+                vec![&mut node.lhs, &mut node.rhs]
+            }
+            ExprKind::ArrayAccess(node) => {
+                let node = &mut **node;
+                vec![&mut node.arr, &mut node.index]
+            }
+            ExprKind::Range(node) => {
+                let node = &mut **node;
+                vec![&mut node.start, &mut node.end]
+            }
+            ExprKind::Assignment(node) => {
+                let node = &mut **node;
+                vec![&mut node.lhs, &mut node.rhs]
+            }
+            ExprKind::Cast(node) => vec![&mut node.operand],
+            ExprKind::Literal(_) | ExprKind::Identifier(_) | ExprKind::Phi(_) => Vec::new(),
+        }
+    }
 }
 
 impl AstVisitable for ExprKind {
@@ -63,6 +259,8 @@ impl PartialEq for ExprKind {
             (ExprKind::ArrayAccess(a1), ExprKind::ArrayAccess(a2)) => a1 == a2,
             (ExprKind::Phi(p1), ExprKind::Phi(p2)) => p1 == p2,
             (ExprKind::Range(r1), ExprKind::Range(r2)) => r1 == r2,
+            (ExprKind::Assignment(a1), ExprKind::Assignment(a2)) => a1 == a2,
+            (ExprKind::Cast(c1), ExprKind::Cast(c2)) => c1 == c2,
             _ => false,
         }
     }
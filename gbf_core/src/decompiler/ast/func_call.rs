@@ -3,7 +3,15 @@
 use gbf_macros::AstNodeTransform;
 use serde::{Deserialize, Serialize};
 
-use super::{expr::ExprKind, ptr::P, visitors::AstVisitor, AstKind, AstVisitable};
+use super::{
+    expr::ExprKind, literal::LiteralNode, ptr::P, visitors::AstVisitor, AstKind, AstVisitable,
+};
+
+/// The name of the builtin function that looks up a localized string.
+pub const TRANSLATION_FUNCTION_NAME: &str = "_";
+
+/// The name of the builtin method that queries the type of an object, such as `x.type()`.
+pub const TYPE_QUERY_METHOD_NAME: &str = "type";
 
 /// Represents a function call
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
@@ -13,6 +21,9 @@ pub struct FunctionCallNode {
     pub name: ExprKind,
     /// The arguments to the function.
     pub arguments: Vec<ExprKind>,
+    /// Whether the call is known to have no side effects.
+    #[serde(default)]
+    pure: bool,
 }
 
 impl FunctionCallNode {
@@ -23,7 +34,75 @@ impl FunctionCallNode {
     /// - `arguments`: The arguments to the function.
     /// - `base`: The base of the function call, if it's a method call.
     pub fn new(name: ExprKind, arguments: Vec<ExprKind>) -> Self {
-        Self { name, arguments }
+        Self {
+            name,
+            arguments,
+            pure: false,
+        }
+    }
+
+    /// Sets whether the call is known to have no side effects. Calls are impure by
+    /// default, since a call such as `x.type()` can be a script function named `type`;
+    /// only the instruction that produced the call can tell.
+    ///
+    /// # Arguments
+    /// - `pure`: Whether the call has no side effects.
+    pub fn with_pure(mut self, pure: bool) -> Self {
+        self.pure = pure;
+        self
+    }
+
+    /// Checks if the call is a translation lookup, such as `_("key")`.
+    pub fn is_translation(&self) -> bool {
+        matches!(&self.name, ExprKind::Identifier(id) if id.as_str() == TRANSLATION_FUNCTION_NAME)
+            && self.arguments.len() == 1
+    }
+
+    /// Gets the key of a translation lookup.
+    ///
+    /// # Returns
+    /// - The key, if the call is a translation lookup with a string literal key.
+    pub fn translation_key(&self) -> Option<&str> {
+        if !self.is_translation() {
+            return None;
+        }
+        match &self.arguments[0] {
+            ExprKind::Literal(literal) => match literal.as_ref() {
+                LiteralNode::String(key) => Some(key.as_str()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Checks if the call is a type query, such as `x.type()`.
+    pub fn is_type_query(&self) -> bool {
+        self.type_query_operand().is_some()
+    }
+
+    /// Gets the object whose type a type query returns.
+    ///
+    /// # Returns
+    /// - The object, such as `x` in `x.type()`, if the call is a type query.
+    pub fn type_query_operand(&self) -> Option<&ExprKind> {
+        if !self.arguments.is_empty() {
+            return None;
+        }
+        match &self.name {
+            ExprKind::MemberAccess(member) => match &member.rhs {
+                ExprKind::Identifier(id) if id.as_str() == TYPE_QUERY_METHOD_NAME => {
+                    Some(&member.lhs)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Checks if the call has no side effects, so it can be reordered or deduplicated
+    /// as long as its operands have none either. See `with_pure`.
+    pub fn is_pure(&self) -> bool {
+        self.pure
     }
 }
 
@@ -42,7 +121,14 @@ impl PartialEq for FunctionCallNode {
 
 #[cfg(test)]
 mod tests {
-    use crate::decompiler::ast::{emit, new_fn_call, new_id, new_member_access, AstNodeError};
+    use crate::decompiler::ast::{
+        bin_op::BinOpType,
+        emit,
+        func_call::{TRANSLATION_FUNCTION_NAME, TYPE_QUERY_METHOD_NAME},
+        new_array_access, new_bin_op, new_fn_call, new_id, new_member_access, new_num, new_str,
+        visitors::{emit_context::EmitContext, emitter::Gs2Emitter, AstVisitor},
+        AstKind, AstNodeError,
+    };
 
     #[test]
     fn test_call_emit() -> Result<(), AstNodeError> {
@@ -56,6 +142,91 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_postfix_chain_emit() -> Result<(), AstNodeError> {
+        // a.b[c + 1].d(e + f).g[0]
+        let index = new_bin_op(new_id("c"), new_num(1), BinOpType::Add)?;
+        let array = new_array_access(new_member_access(new_id("a"), new_id("b"))?, index);
+        let method = new_member_access(array, new_id("d"))?;
+        let argument = new_bin_op(new_id("e"), new_id("f"), BinOpType::Add)?;
+        let call = new_fn_call(method, vec![argument.into()]);
+        let chain = new_array_access(new_member_access(call, new_id("g"))?, new_num(0));
+        assert_eq!(emit(chain.clone()), "a.b[c + 1].d(e + f).g[0]");
+
+        // The chain binds tighter than any operator around it
+        let product = new_bin_op(new_id("x"), chain, BinOpType::Mul)?;
+        assert_eq!(emit(product), "x * a.b[c + 1].d(e + f).g[0]");
+
+        // A target that is not part of the chain keeps its parentheses
+        let sum = new_bin_op(new_id("a"), new_id("b"), BinOpType::Add)?;
+        assert_eq!(emit(new_array_access(sum, new_num(0))), "(a + b)[0]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_dynamic_call_emit() -> Result<(), AstNodeError> {
+        let dynamic: AstKind = new_fn_call(
+            new_array_access(new_id("handlers"), new_id("i")),
+            vec![new_id("x").into()],
+        )
+        .into();
+        let method: AstKind =
+            new_fn_call(new_member_access(new_id("obj"), new_id("foo"))?, vec![]).into();
+
+        // Calls are emitted the same way unless dynamic calls are marked
+        assert_eq!(emit(dynamic.clone()), "handlers[i](x)");
+
+        let context = EmitContext::builder().mark_dynamic_calls(true).build();
+        let mut emitter = Gs2Emitter::new(context);
+        assert_eq!(
+            emitter.visit_node(&dynamic).node,
+            "/* dynamic */ (handlers[i])(x)"
+        );
+        assert_eq!(emitter.visit_node(&method).node, "obj.foo()");
+        Ok(())
+    }
+
+    #[test]
+    fn test_translation_call() {
+        let call = new_fn_call(
+            new_id(TRANSLATION_FUNCTION_NAME),
+            vec![new_str("Welcome").into()],
+        );
+        assert!(call.is_translation());
+        assert_eq!(call.translation_key(), Some("Welcome"));
+        assert_eq!(emit(call), "_(\"Welcome\")");
+
+        // The key is only known when it is a string literal
+        let call = new_fn_call(
+            new_id(TRANSLATION_FUNCTION_NAME),
+            vec![new_id("key").into()],
+        );
+        assert!(call.is_translation());
+        assert_eq!(call.translation_key(), None);
+
+        let call = new_fn_call(new_id("echo"), vec![new_str("Welcome").into()]);
+        assert!(!call.is_translation());
+        assert_eq!(call.translation_key(), None);
+    }
+
+    #[test]
+    fn test_type_query_call() -> Result<(), AstNodeError> {
+        let ma = new_member_access(new_id("x"), new_id(TYPE_QUERY_METHOD_NAME))?;
+        let call = new_fn_call(ma, vec![]);
+        assert!(call.is_type_query());
+        assert_eq!(call.type_query_operand(), Some(&new_id("x").into()));
+        assert_eq!(emit(call.clone()), "x.type()");
+
+        // Purity is not decided by the syntax of the call
+        assert!(!call.is_pure());
+        assert!(call.with_pure(true).is_pure());
+
+        // A call named `type` is not a type query
+        let call = new_fn_call(new_id(TYPE_QUERY_METHOD_NAME), vec![new_id("x").into()]);
+        assert!(!call.is_type_query());
+        Ok(())
+    }
+
     #[test]
     fn test_call_equality() {
         let call1 = new_fn_call(new_id("echo"), vec![new_id("hello").into()]);
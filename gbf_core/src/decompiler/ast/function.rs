@@ -48,6 +48,11 @@ impl FunctionNode {
         &self.body
     }
 
+    /// Returns the body of the function mutably.
+    pub fn body_mut(&mut self) -> &mut P<BlockNode> {
+        &mut self.body
+    }
+
     /// Returns the name of the function.
     pub fn name(&self) -> &Option<String> {
         &self.name
@@ -0,0 +1,51 @@
+#![deny(missing_docs)]
+
+use gbf_macros::AstNodeTransform;
+use serde::{Deserialize, Serialize};
+
+use super::{ptr::P, statement::StatementKind, visitors::AstVisitor, AstKind, AstVisitable};
+
+/// Represents a `goto` node in the AST, such as `goto blk_0x10`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
+#[convert_to(StatementKind::Goto, AstKind::Statement)]
+pub struct GotoNode {
+    /// The label to jump to.
+    pub label: String,
+}
+
+impl GotoNode {
+    /// Creates a new `GotoNode` with the provided label.
+    ///
+    /// # Arguments
+    /// - `label`: The label to jump to.
+    ///
+    /// # Returns
+    /// The goto node.
+    pub fn new(label: String) -> Self {
+        Self { label }
+    }
+}
+
+impl AstVisitable for P<GotoNode> {
+    fn accept<V: AstVisitor>(&self, visitor: &mut V) -> V::Output {
+        visitor.visit_goto(self)
+    }
+}
+
+// == Other implementations for goto ==
+impl PartialEq for GotoNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decompiler::ast::{emit, new_goto};
+
+    #[test]
+    fn test_goto_emit() {
+        let goto = new_goto("blk_0x10");
+        assert_eq!(emit(goto), "goto blk_0x10;");
+    }
+}
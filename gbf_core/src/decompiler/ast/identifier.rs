@@ -50,6 +50,14 @@ impl IdentifierNode {
         &self.id
     }
 
+    /// Replaces the identifier, keeping its SSA version.
+    ///
+    /// # Arguments
+    /// - `id`: The new identifier.
+    pub fn set_id<S: Into<String>>(&mut self, id: S) {
+        self.id = id.into();
+    }
+
     /// Returns the identifier as a `&str`.
     pub fn as_str(&self) -> &str {
         &self.id
@@ -66,7 +74,13 @@ impl AstVisitable for P<IdentifierNode> {
 
 #[cfg(test)]
 mod tests {
-    use crate::decompiler::ast::{emit, new_id};
+    use std::collections::HashMap;
+
+    use crate::decompiler::ast::{
+        emit, new_fn_call, new_id, new_id_with_version, new_member_access,
+        visitors::{emit_context::EmitContext, emitter::Gs2Emitter, AstVisitor},
+        AstKind,
+    };
 
     #[test]
     fn test_identifier_emit() {
@@ -74,6 +88,42 @@ mod tests {
         assert_eq!(emit(id), "test");
     }
 
+    #[test]
+    fn test_identifier_rename_emit() {
+        let context = EmitContext::builder()
+            .include_ssa_versions(true)
+            .rename_map(HashMap::from([(
+                "_a1".to_string(),
+                "playerHealth".to_string(),
+            )]))
+            .build();
+        let mut emitter = Gs2Emitter::new(context);
+
+        let renamed: AstKind = new_id_with_version("_a1", 2.into()).into();
+        assert_eq!(emitter.visit_node(&renamed).node, "playerHealth#2");
+
+        let unmapped: AstKind = new_id("_a2").into();
+        assert_eq!(emitter.visit_node(&unmapped).node, "_a2");
+    }
+
+    #[test]
+    fn test_identifier_rename_skips_names() {
+        let context = EmitContext::builder()
+            .rename_map(HashMap::from([("x".to_string(), "count".to_string())]))
+            .build();
+        let mut emitter = Gs2Emitter::new(context);
+
+        let field: AstKind = new_member_access(new_id("x"), new_id("x")).unwrap().into();
+        assert_eq!(emitter.visit_node(&field).node, "count.x");
+
+        let call: AstKind = new_fn_call(new_id("x"), vec![new_id("x").into()]).into();
+        assert_eq!(emitter.visit_node(&call).node, "x(count)");
+
+        let method: AstKind =
+            new_fn_call(new_member_access(new_id("x"), new_id("x")).unwrap(), vec![]).into();
+        assert_eq!(emitter.visit_node(&method).node, "count.x()");
+    }
+
     #[test]
     fn test_identifier_equality() {
         let id1 = new_id("test");
@@ -0,0 +1,52 @@
+#![deny(missing_docs)]
+
+use gbf_macros::AstNodeTransform;
+use serde::{Deserialize, Serialize};
+
+use super::{ptr::P, statement::StatementKind, visitors::AstVisitor, AstKind, AstVisitable};
+
+/// Represents a label node in the AST, such as `blk_0x10:`, which is the target of
+/// a `goto`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, AstNodeTransform)]
+#[convert_to(StatementKind::Label, AstKind::Statement)]
+pub struct LabelNode {
+    /// The name of the label.
+    pub label: String,
+}
+
+impl LabelNode {
+    /// Creates a new `LabelNode` with the provided name.
+    ///
+    /// # Arguments
+    /// - `label`: The name of the label.
+    ///
+    /// # Returns
+    /// The label node.
+    pub fn new(label: String) -> Self {
+        Self { label }
+    }
+}
+
+impl AstVisitable for P<LabelNode> {
+    fn accept<V: AstVisitor>(&self, visitor: &mut V) -> V::Output {
+        visitor.visit_label(self)
+    }
+}
+
+// == Other implementations for label ==
+impl PartialEq for LabelNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decompiler::ast::{emit, new_label};
+
+    #[test]
+    fn test_label_emit() {
+        let label = new_label("blk_0x10");
+        assert_eq!(emit(label), "blk_0x10:");
+    }
+}
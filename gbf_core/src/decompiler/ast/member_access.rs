@@ -57,7 +57,13 @@ impl PartialEq for MemberAccessNode {
 
 #[cfg(test)]
 mod tests {
-    use crate::decompiler::ast::{emit, new_id, new_member_access, AstNodeError};
+    use crate::decompiler::ast::{
+        emit,
+        expr::{ExprKind, ExprTypeHint},
+        new_id, new_member_access,
+        visitors::{emit_context::EmitContext, emitter::Gs2Emitter, AstVisitor},
+        AstKind, AstNodeError,
+    };
 
     #[test]
     fn test_member_access_emit() -> Result<(), AstNodeError> {
@@ -76,6 +82,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_member_access_builtin_namespace_emit() -> Result<(), AstNodeError> {
+        let builtin =
+            ExprKind::from(new_id("player")).with_type_hint(ExprTypeHint::BuiltinNamespace);
+        let builtin_member: AstKind = new_member_access(builtin, new_id("chat"))?.into();
+        let variable_member: AstKind = new_member_access(new_id("player"), new_id("chat"))?.into();
+
+        // The default accessor is the same for every kind of value
+        assert_eq!(emit(builtin_member.clone()), "player.chat");
+        assert_eq!(emit(variable_member.clone()), "player.chat");
+
+        // A custom accessor is only used for builtin namespaces
+        let context = EmitContext::builder()
+            .builtin_namespace_accessor("::")
+            .build();
+        let mut emitter = Gs2Emitter::new(context);
+        assert_eq!(emitter.visit_node(&builtin_member).node, "player::chat");
+        assert_eq!(emitter.visit_node(&variable_member).node, "player.chat");
+        Ok(())
+    }
+
     #[test]
     fn test_member_access_equality() -> Result<(), AstNodeError> {
         let member1 = new_member_access(new_id("object"), new_id("field"))?;
@@ -3,11 +3,31 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::expr::ExprTypeHint;
+
+/// The metadata property that records the instruction a node was produced by, such
+/// as `PushNumber@0x0a`.
+pub const PROVENANCE_PROPERTY: &str = "provenance";
+
+/// Gets the address of the instruction a provenance property names.
+///
+/// # Arguments
+/// - `source`: The provenance, such as `PushNumber@0x0a`.
+///
+/// # Returns
+/// - The address, or `None` if the provenance does not end in a hexadecimal address.
+pub fn provenance_address(source: &str) -> Option<usize> {
+    let (_, address) = source.rsplit_once('@')?;
+    usize::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
 /// Represents a metadata node in the AST
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, Default)]
 pub struct Metadata {
     comments: Vec<String>,
     properties: HashMap<String, String>,
+    #[serde(default)]
+    type_hint: ExprTypeHint,
 }
 
 impl Metadata {
@@ -35,6 +55,16 @@ impl Metadata {
     pub fn properties(&self) -> &HashMap<String, String> {
         &self.properties
     }
+
+    /// Returns the recovered type hint of the node.
+    pub fn type_hint(&self) -> ExprTypeHint {
+        self.type_hint
+    }
+
+    /// Sets the recovered type hint of the node.
+    pub fn set_type_hint(&mut self, type_hint: ExprTypeHint) {
+        self.type_hint = type_hint;
+    }
 }
 
 impl PartialEq for Metadata {
@@ -5,11 +5,15 @@ use array_access::ArrayAccessNode;
 use assignment::AssignmentNode;
 use bin_op::BinaryOperationNode;
 use block::BlockNode;
+use cast::CastNode;
+use comment::CommentNode;
 use control_flow::{ControlFlowNode, ControlFlowType};
 use expr::ExprKind;
 use func_call::FunctionCallNode;
 use function::FunctionNode;
+use goto::GotoNode;
 use identifier::IdentifierNode;
+use label::LabelNode;
 use literal::LiteralNode;
 use member_access::MemberAccessNode;
 use new_array::NewArrayNode;
@@ -25,12 +29,14 @@ use unary_op::UnaryOperationNode;
 use vbranch::VirtualBranchNode;
 use visitors::{emit_context::EmitContext, emitter::Gs2Emitter};
 
-use super::structure_analysis::region::RegionId;
+use super::structure_analysis::{region::RegionId, ControlFlowEdgeType};
 
 /// Represents an array
 pub mod array;
 /// Represents an array access node.
 pub mod array_access;
+/// Represents expressions that can be the target of an assignment.
+pub mod assignable;
 /// Contains the specifications for any AstNodes that are assignments
 pub mod assignment;
 /// Holds the macro that generates variants for the AST nodes.
@@ -39,6 +45,10 @@ pub mod ast_enum_type;
 pub mod bin_op;
 /// Represents a "block" of code in the AST.
 pub mod block;
+/// Represents a cast node in the AST.
+pub mod cast;
+/// Represents a comment node in the AST.
+pub mod comment;
 /// Represents a control flow node in the AST.
 pub mod control_flow;
 /// Contains the specifications for any AstNodes that are expressions
@@ -47,8 +57,12 @@ pub mod expr;
 pub mod func_call;
 /// Contains the specifications for any AstNodes that are functions.
 pub mod function;
+/// Represents a goto node in the AST.
+pub mod goto;
 /// Contains the specifications for any AstNodes that are identifiers.
 pub mod identifier;
+/// Represents a label node in the AST.
+pub mod label;
 /// Contains the specifications for any AstNodes that are literals.
 pub mod literal;
 /// Contains the specifications for any AstNodes that are member accesses.
@@ -167,6 +181,21 @@ pub fn new_virtual_branch(branch: RegionId) -> VirtualBranchNode {
     VirtualBranchNode::new(branch)
 }
 
+/// Creates a new goto node.
+pub fn new_goto(label: &str) -> GotoNode {
+    GotoNode::new(label.to_string())
+}
+
+/// Creates a new label node.
+pub fn new_label(label: &str) -> LabelNode {
+    LabelNode::new(label.to_string())
+}
+
+/// Creates a new comment node.
+pub fn new_comment(text: &str) -> CommentNode {
+    CommentNode::new(text.to_string())
+}
+
 /// Creates a new member access node.
 pub fn new_member_access<L, R>(lhs: L, rhs: R) -> Result<MemberAccessNode, AstNodeError>
 where
@@ -245,6 +274,14 @@ where
     UnaryOperationNode::new(operand.into(), op_type)
 }
 
+/// Creates a new cast node.
+pub fn new_cast<A>(operand: A, cast_type: cast::CastType) -> CastNode
+where
+    A: Into<ExprKind>,
+{
+    CastNode::new(operand.into(), cast_type)
+}
+
 /// Creates a new range node.
 pub fn new_range<L, R>(lhs: L, rhs: R) -> RangeNode
 where
@@ -398,6 +435,22 @@ where
     )
 }
 
+/// Creates a new counted for loop, such as `for (i = 0; i < 10; i++)`
+pub fn new_counted_for<I, C, S, T>(
+    init: I,
+    condition: C,
+    step: S,
+    then_block: Vec<T>,
+) -> ControlFlowNode
+where
+    I: Into<AstKind>,
+    C: Into<ExprKind>,
+    S: Into<AstKind>,
+    T: Into<AstKind>,
+{
+    new_for(condition, then_block).with_for_clauses(init, step)
+}
+
 /// Creates a new cyclic condition
 pub fn new_cyclic_condition<C, T>(
     condition: C,
@@ -418,32 +471,63 @@ where
     }
 }
 
-/// Creates a new acyclic condition
+/// Creates a new acyclic condition, such as an `if` or a `with`.
+///
+/// The jump expression of a region holds when its fallthrough edge is taken, except
+/// for `ShortCircuitOr`, which jumps when its operand holds. The condition is negated
+/// when `then_block` is on the other edge, so the emitted condition matches the branch.
+///
+/// # Arguments
+/// - `condition`: The jump expression of the region.
+/// - `then_block`: The statements to run when the condition holds.
+/// - `opcode`: The opcode of the branch.
+/// - `then_edge`: The edge of the branch that leads to `then_block`.
+///
+/// # Returns
+/// - The acyclic condition.
+///
+/// # Errors
+/// - `AstNodeError::InvalidOperand` if the opcode is not a conditional branch.
 pub fn new_acylic_condition<C, T>(
     condition: C,
     then_block: Vec<T>,
     opcode: Option<Opcode>,
+    then_edge: ControlFlowEdgeType,
 ) -> Result<ControlFlowNode, AstNodeError>
 where
     C: Into<ExprKind>,
     T: Into<AstKind>,
 {
     match opcode {
-        Some(Opcode::Jne) => Ok(new_if(
-            condition,
-            then_block
-                .into_iter()
-                .map(Into::into)
-                .collect::<Vec<AstKind>>(),
-        )),
-        // TODO: Move condition flipping logic here for Jeq
-        Some(Opcode::Jeq) => Ok(new_if(condition, then_block)),
-        // TODO: We may need to flip the condition on ShortCircuitAnd or ShortCircuitOr
-        Some(Opcode::ShortCircuitAnd) => Ok(new_if(condition, then_block)),
-        Some(Opcode::ShortCircuitOr) => Ok(new_if(condition, then_block)),
-        Some(Opcode::With) => Ok(new_with(condition, then_block)),
-        None => Ok(new_if(condition, then_block)),
-        _ => Err(AstNodeError::InvalidOperand),
+        Some(Opcode::Jne | Opcode::Jeq | Opcode::ShortCircuitAnd | Opcode::ShortCircuitOr)
+        | None => {}
+        // The body of a `with` is always on the fallthrough edge
+        Some(Opcode::With) => return Ok(new_with(condition, then_block)),
+        _ => return Err(AstNodeError::InvalidOperand),
+    };
+
+    let condition = condition.into();
+    if ControlFlowEdgeType::taken_when_condition_holds(opcode) == then_edge {
+        Ok(new_if(condition, then_block))
+    } else {
+        Ok(new_if(new_logical_not(condition)?, then_block))
+    }
+}
+
+/// Creates the logical negation of an expression. A negation is unwrapped rather
+/// than negated again, so `!a` becomes `a`.
+///
+/// # Errors
+/// - `AstNodeError::InvalidOperand` if the expression cannot be negated.
+pub fn new_logical_not<E>(expr: E) -> Result<ExprKind, AstNodeError>
+where
+    E: Into<ExprKind>,
+{
+    match expr.into() {
+        ExprKind::UnaryOp(unary_op) if unary_op.op_type == unary_op::UnaryOpType::LogicalNot => {
+            Ok(unary_op.operand.clone())
+        }
+        expr => Ok(new_unary_op(expr, unary_op::UnaryOpType::LogicalNot)?.into()),
     }
 }
 
@@ -66,6 +66,20 @@ impl PhiNode {
     pub fn regions(&self) -> &[(RegionId, ControlFlowEdgeType)] {
         &self.region_ids
     }
+
+    /// Replaces a predecessor region of this phi node, such as when the region is
+    /// merged into another region during structure analysis.
+    ///
+    /// # Arguments
+    /// * `from` - The region to replace.
+    /// * `to` - The region that replaces it.
+    pub fn replace_region(&mut self, from: RegionId, to: RegionId) {
+        for (region_id, _) in self.region_ids.iter_mut() {
+            if *region_id == from {
+                *region_id = to;
+            }
+        }
+    }
 }
 
 impl AstVisitable for P<PhiNode> {
@@ -113,4 +127,19 @@ mod tests {
 
         assert_ne!(phi1, phi3);
     }
+
+    #[test]
+    fn test_phi_node_replace_region() {
+        let mut phi = PhiNode::new(0);
+        phi.add_region(RegionId::new(1), ControlFlowEdgeType::Branch);
+        phi.add_region(RegionId::new(2), ControlFlowEdgeType::Fallthrough);
+        phi.replace_region(RegionId::new(2), RegionId::new(0));
+        assert_eq!(
+            phi.regions(),
+            &[
+                (RegionId::new(1), ControlFlowEdgeType::Branch),
+                (RegionId::new(0), ControlFlowEdgeType::Fallthrough)
+            ]
+        );
+    }
 }
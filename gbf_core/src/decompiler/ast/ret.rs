@@ -43,7 +43,7 @@ impl PartialEq for ReturnNode {
 
 #[cfg(test)]
 mod tests {
-    use crate::decompiler::ast::{emit, new_num, new_return};
+    use crate::decompiler::ast::{emit, expr::ExprKind, new_array, new_id, new_num, new_return};
 
     #[test]
     fn test_return_node() {
@@ -57,6 +57,17 @@ mod tests {
         assert_eq!(emit(ret), "return 5;");
     }
 
+    #[test]
+    fn test_emit_array() {
+        let inner: Vec<ExprKind> = vec![new_num(1).into(), new_num(2).into()];
+        let elements: Vec<ExprKind> = vec![new_id("a").into(), new_array(inner).into()];
+        assert_eq!(emit(new_return(new_array(elements))), "return {a, {1, 2}};");
+        assert_eq!(
+            emit(new_return(new_array(Vec::<ExprKind>::new()))),
+            "return {};"
+        );
+    }
+
     #[test]
     fn test_equality() {
         let ret = new_return(new_num(5));
@@ -4,7 +4,8 @@ use gbf_macros::AstNodeTransform;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    assignment::AssignmentNode, ptr::P, ret::ReturnNode, vbranch::VirtualBranchNode,
+    assignment::AssignmentNode, comment::CommentNode, expr::ExprKind, goto::GotoNode,
+    label::LabelNode, meta::Metadata, ptr::P, ret::ReturnNode, vbranch::VirtualBranchNode,
     visitors::AstVisitor, AstKind, AstVisitable,
 };
 
@@ -18,6 +19,59 @@ pub enum StatementKind {
     Return(P<ReturnNode>),
     /// Virtual Branch
     VirtualBranch(P<VirtualBranchNode>),
+    /// Goto
+    Goto(P<GotoNode>),
+    /// Label
+    Label(P<LabelNode>),
+    /// Comment
+    Comment(P<CommentNode>),
+}
+
+impl StatementKind {
+    /// Returns the metadata of the statement.
+    pub fn metadata(&self) -> &Metadata {
+        match self {
+            StatementKind::Assignment(node) => node.metadata(),
+            StatementKind::Return(node) => node.metadata(),
+            StatementKind::VirtualBranch(node) => node.metadata(),
+            StatementKind::Goto(node) => node.metadata(),
+            StatementKind::Label(node) => node.metadata(),
+            StatementKind::Comment(node) => node.metadata(),
+        }
+    }
+
+    /// Returns the metadata of the statement mutably.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        match self {
+            StatementKind::Assignment(node) => node.metadata_mut(),
+            StatementKind::Return(node) => node.metadata_mut(),
+            StatementKind::VirtualBranch(node) => node.metadata_mut(),
+            StatementKind::Goto(node) => node.metadata_mut(),
+            StatementKind::Label(node) => node.metadata_mut(),
+            StatementKind::Comment(node) => node.metadata_mut(),
+        }
+    }
+
+    /// Returns the expressions of the statement, such as both sides of an assignment.
+    pub fn expressions(&self) -> Vec<&ExprKind> {
+        match self {
+            StatementKind::Assignment(node) => vec![&node.lhs, &node.rhs],
+            StatementKind::Return(node) => vec![&node.ret],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the expressions of the statement mutably.
+    pub fn expressions_mut(&mut self) -> Vec<&mut ExprKind> {
+        match self {
+            StatementKind::Assignment(node) => {
+                let node = &mut **node;
+                vec![&mut node.lhs, &mut node.rhs]
+            }
+            StatementKind::Return(node) => vec![&mut node.ret],
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl AstVisitable for StatementKind {
@@ -34,6 +88,9 @@ impl PartialEq for StatementKind {
             (StatementKind::Assignment(a1), StatementKind::Assignment(a2)) => a1 == a2,
             (StatementKind::Return(r1), StatementKind::Return(r2)) => r1 == r2,
             (StatementKind::VirtualBranch(v1), StatementKind::VirtualBranch(v2)) => v1 == v2,
+            (StatementKind::Goto(g1), StatementKind::Goto(g2)) => g1 == g2,
+            (StatementKind::Label(l1), StatementKind::Label(l2)) => l1 == l2,
+            (StatementKind::Comment(c1), StatementKind::Comment(c2)) => c1 == c2,
             _ => false,
         }
     }
@@ -1,7 +1,14 @@
 #![deny(missing_docs)]
 
+use std::{collections::HashMap, sync::Arc};
+
 use thiserror::Error;
 
+use crate::{
+    decompiler::temp_naming::{PrefixTempNaming, TempNamingScheme},
+    utils::{Gs2BytecodeAddress, VERSION},
+};
+
 /// Represents an error that occurred while converting an AST node.
 #[derive(Debug, Error)]
 pub enum EmitError {}
@@ -26,8 +33,101 @@ pub enum IndentStyle {
     KAndR,
 }
 
+/// Represents the syntax used for comments in the emitted code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// Line comments, such as `// comment`.
+    Line,
+    /// Block comments, such as `/* comment */`.
+    Block,
+}
+
+/// Represents the editor fold markers written around control flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldMarkerStyle {
+    /// Region markers, such as `// region` and `// endregion`, as used by Visual Studio
+    /// and VS Code.
+    Region,
+    /// Vim fold markers, such as `// {{{` and `// }}}`.
+    Vim,
+}
+
+/// Describes where decompiled output came from, written as a comment header above
+/// each function so that archived output is self-describing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderInfo {
+    /// The version of gbf that decompiled the function.
+    pub gbf_version: String,
+    /// The hash of the module the function is in, if known.
+    pub module_hash: Option<String>,
+    /// A description of the decompile options that were used, if any.
+    pub options: Option<String>,
+}
+
+impl HeaderInfo {
+    /// Renders the header for a function, one comment per line.
+    ///
+    /// # Arguments
+    /// - `function_name`: The name of the function, or `None` for the entry point.
+    /// - `function_address`: The address of the function in the module.
+    /// - `comment_style`: The syntax of the comments.
+    ///
+    /// # Returns
+    /// - The header, ending with a newline.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::decompiler::ast::visitors::emit_context::{CommentStyle, HeaderInfo};
+    ///
+    /// let header = HeaderInfo {
+    ///     module_hash: Some("abc123".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let rendered = header.render(Some("onCreated"), 0x10, CommentStyle::Line);
+    /// assert!(rendered.contains("// Module: abc123\n"));
+    /// assert!(rendered.contains("// Function: onCreated @ 0x10\n"));
+    /// ```
+    pub fn render(
+        &self,
+        function_name: Option<&str>,
+        function_address: Gs2BytecodeAddress,
+        comment_style: CommentStyle,
+    ) -> String {
+        let mut lines = vec![format!("Decompiled by gbf {}", self.gbf_version)];
+        if let Some(module_hash) = &self.module_hash {
+            lines.push(format!("Module: {}", module_hash));
+        }
+        lines.push(format!(
+            "Function: {} @ {:#x}",
+            function_name.unwrap_or("<entry>"),
+            function_address
+        ));
+        if let Some(options) = &self.options {
+            lines.push(format!("Options: {}", options));
+        }
+
+        lines
+            .iter()
+            .map(|line| match comment_style {
+                CommentStyle::Line => format!("// {}\n", line),
+                CommentStyle::Block => format!("/* {} */\n", line),
+            })
+            .collect()
+    }
+}
+
+impl Default for HeaderInfo {
+    fn default() -> Self {
+        Self {
+            gbf_version: VERSION.to_string(),
+            module_hash: None,
+            options: None,
+        }
+    }
+}
+
 /// Contains the emitting context for the AST.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct EmitContext {
     /// The current indentation level.
     pub indent: usize,
@@ -43,6 +143,59 @@ pub struct EmitContext {
     pub expr_root: bool,
     /// If we should include SSA versions in the emitted code.
     pub include_ssa_versions: bool,
+    /// If we should collapse assignments like `x = x + y` into compound assignments like `x += y`.
+    pub use_compound_assignment: bool,
+    /// The accessor used for members of builtin namespaces, such as `player.chat`.
+    pub builtin_namespace_accessor: &'static str,
+    /// Renames applied to variables when they are emitted, keyed by the base
+    /// identifier name. Field and function names are never renamed, and the SSA
+    /// version of a renamed variable is preserved.
+    pub rename_map: Arc<HashMap<String, String>>,
+    /// If structure analysis fails, or leaves branches it could not structure, emit the
+    /// raw control flow with labels and `goto` statements instead.
+    pub allow_goto: bool,
+    /// If we should prefix the content of each region with a comment describing its
+    /// region type, such as `// ControlFlow: Jeq`.
+    pub annotate_regions: bool,
+    /// If we should suffix each statement with a comment listing the instructions it
+    /// was decompiled from, such as `// from PushNumber@0x0a, Add@0x0c`.
+    pub provenance_comments: bool,
+    /// The syntax used for every comment the emitter writes.
+    pub comment_style: CommentStyle,
+    /// If the decompiler encounters an opcode it cannot decompile, emit a placeholder
+    /// comment such as `/* unimplemented: Swap@0x0e */` and continue instead of failing.
+    pub placeholder_unimplemented: bool,
+    /// If we should emit the `ConvertTo*` opcodes as casts, such as `(float)x`, instead
+    /// of dropping them.
+    pub preserve_casts: bool,
+    /// If we should wrap `if`, `else` and `while` bodies that consist of a single
+    /// statement in braces. Bodies that are themselves control flow always keep their
+    /// braces, so that an `else` can never bind to the wrong `if`.
+    pub braces_for_single_statements: bool,
+    /// If we should rewrite comparisons with zero in branch conditions, so that
+    /// `if (x != 0)` is emitted as `if (x)` and `if (x == 0)` as `if (!x)`. Only
+    /// operands that are known to be numbers are rewritten.
+    pub simplify_zero_comparisons: bool,
+    /// If we should emit named constants, such as `pi`, as their numeric value.
+    pub expand_named_constants: bool,
+    /// If we should mark calls whose target is not a name, such as `funcs[i](x)`, so
+    /// they are emitted as `/* dynamic */ (funcs[i])(x)`.
+    pub mark_dynamic_calls: bool,
+    /// If we should move loop-invariant assignments out of loop bodies to just before
+    /// the loop, so the bodies only contain what changes between iterations.
+    pub hoist_invariants: bool,
+    /// The fold markers to write around each `if`, `else`, loop and `with`, so they can
+    /// be collapsed in an editor. No markers are written if this is `None`.
+    pub fold_markers: Option<FoldMarkerStyle>,
+    /// The header written above each decompiled function, recording where the output
+    /// came from. No header is written if this is `None`.
+    pub header: Option<Arc<HeaderInfo>>,
+    /// The scheme used to rename the temporaries the decompiler introduces, such as
+    /// `lit` and `fn_call`. Temporaries keep their generated names if this is `None`.
+    pub temp_naming: Option<Arc<dyn TempNamingScheme>>,
+    /// The attributes written on a line of their own above the signature of a named
+    /// function, separated by spaces. No line is written if this is empty.
+    pub function_attributes: Arc<Vec<String>>,
 }
 
 impl EmitContext {
@@ -81,7 +234,7 @@ impl EmitContext {
     /// let body_context = context.with_indent();
     /// ```
     pub fn with_indent(&self) -> EmitContext {
-        let mut new_context = *self;
+        let mut new_context = self.clone();
         new_context.indent += self.indent_step;
         new_context
     }
@@ -102,7 +255,7 @@ impl EmitContext {
     /// let body_context = context.with_expr_root(true);
     /// ```
     pub fn with_expr_root(&self, expr_root: bool) -> EmitContext {
-        let mut new_context = *self;
+        let mut new_context = self.clone();
         new_context.expr_root = expr_root;
         new_context
     }
@@ -126,6 +279,24 @@ pub struct EmitContextBuilder {
     indent_style: IndentStyle,
     expr_root: bool,
     include_ssa_versions: bool,
+    use_compound_assignment: bool,
+    builtin_namespace_accessor: &'static str,
+    rename_map: HashMap<String, String>,
+    allow_goto: bool,
+    annotate_regions: bool,
+    provenance_comments: bool,
+    comment_style: CommentStyle,
+    placeholder_unimplemented: bool,
+    preserve_casts: bool,
+    braces_for_single_statements: bool,
+    simplify_zero_comparisons: bool,
+    expand_named_constants: bool,
+    mark_dynamic_calls: bool,
+    hoist_invariants: bool,
+    fold_markers: Option<FoldMarkerStyle>,
+    header: Option<HeaderInfo>,
+    temp_naming: Option<Arc<dyn TempNamingScheme>>,
+    function_attributes: Vec<String>,
 }
 
 impl EmitContextBuilder {
@@ -171,6 +342,120 @@ impl EmitContextBuilder {
         self
     }
 
+    /// Sets the `use_compound_assignment` flag.
+    pub fn use_compound_assignment(mut self, use_compound_assignment: bool) -> Self {
+        self.use_compound_assignment = use_compound_assignment;
+        self
+    }
+
+    /// Sets the accessor used for members of builtin namespaces.
+    pub fn builtin_namespace_accessor(mut self, builtin_namespace_accessor: &'static str) -> Self {
+        self.builtin_namespace_accessor = builtin_namespace_accessor;
+        self
+    }
+
+    /// Sets the map of variable renames, keyed by the base identifier name.
+    pub fn rename_map(mut self, rename_map: HashMap<String, String>) -> Self {
+        self.rename_map = rename_map;
+        self
+    }
+
+    /// Sets the `allow_goto` flag.
+    pub fn allow_goto(mut self, allow_goto: bool) -> Self {
+        self.allow_goto = allow_goto;
+        self
+    }
+
+    /// Sets the `annotate_regions` flag.
+    pub fn annotate_regions(mut self, annotate_regions: bool) -> Self {
+        self.annotate_regions = annotate_regions;
+        self
+    }
+
+    /// Sets the `provenance_comments` flag.
+    pub fn provenance_comments(mut self, provenance_comments: bool) -> Self {
+        self.provenance_comments = provenance_comments;
+        self
+    }
+
+    /// Sets the comment style.
+    pub fn comment_style(mut self, comment_style: CommentStyle) -> Self {
+        self.comment_style = comment_style;
+        self
+    }
+
+    /// Sets the `placeholder_unimplemented` flag.
+    pub fn placeholder_unimplemented(mut self, placeholder_unimplemented: bool) -> Self {
+        self.placeholder_unimplemented = placeholder_unimplemented;
+        self
+    }
+
+    /// Sets the `preserve_casts` flag.
+    pub fn preserve_casts(mut self, preserve_casts: bool) -> Self {
+        self.preserve_casts = preserve_casts;
+        self
+    }
+
+    /// Sets the `braces_for_single_statements` flag.
+    pub fn braces_for_single_statements(mut self, braces_for_single_statements: bool) -> Self {
+        self.braces_for_single_statements = braces_for_single_statements;
+        self
+    }
+
+    /// Sets the `simplify_zero_comparisons` flag.
+    pub fn simplify_zero_comparisons(mut self, simplify_zero_comparisons: bool) -> Self {
+        self.simplify_zero_comparisons = simplify_zero_comparisons;
+        self
+    }
+
+    /// Sets the `expand_named_constants` flag.
+    pub fn expand_named_constants(mut self, expand_named_constants: bool) -> Self {
+        self.expand_named_constants = expand_named_constants;
+        self
+    }
+
+    /// Sets the `mark_dynamic_calls` flag.
+    pub fn mark_dynamic_calls(mut self, mark_dynamic_calls: bool) -> Self {
+        self.mark_dynamic_calls = mark_dynamic_calls;
+        self
+    }
+
+    /// Sets the `hoist_invariants` flag.
+    pub fn hoist_invariants(mut self, hoist_invariants: bool) -> Self {
+        self.hoist_invariants = hoist_invariants;
+        self
+    }
+
+    /// Sets the style of the fold markers written around control flow.
+    pub fn fold_markers(mut self, fold_markers: FoldMarkerStyle) -> Self {
+        self.fold_markers = Some(fold_markers);
+        self
+    }
+
+    /// Sets the header written above each decompiled function.
+    pub fn emit_header(mut self, header: HeaderInfo) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Sets the scheme used to rename the temporaries the decompiler introduces.
+    pub fn temp_naming<T: TempNamingScheme + 'static>(mut self, temp_naming: T) -> Self {
+        self.temp_naming = Some(Arc::new(temp_naming));
+        self
+    }
+
+    /// Renames the temporaries the decompiler introduces to a prefix and a counter,
+    /// such as `tmp0`, `tmp1`.
+    pub fn temp_prefix<S: Into<String>>(self, prefix: S) -> Self {
+        self.temp_naming(PrefixTempNaming::new(prefix))
+    }
+
+    /// Sets the attributes written above the signature of a named function.
+    pub fn function_attributes(mut self, function_attributes: Vec<String>) -> Self {
+        self.function_attributes = function_attributes;
+        self
+    }
+
     /// Builds the `EmitContext` with the specified parameters.
     pub fn build(self) -> EmitContext {
         EmitContext {
@@ -181,6 +466,24 @@ impl EmitContextBuilder {
             indent_style: self.indent_style,
             expr_root: self.expr_root,
             include_ssa_versions: self.include_ssa_versions,
+            use_compound_assignment: self.use_compound_assignment,
+            builtin_namespace_accessor: self.builtin_namespace_accessor,
+            rename_map: Arc::new(self.rename_map),
+            allow_goto: self.allow_goto,
+            annotate_regions: self.annotate_regions,
+            provenance_comments: self.provenance_comments,
+            comment_style: self.comment_style,
+            placeholder_unimplemented: self.placeholder_unimplemented,
+            preserve_casts: self.preserve_casts,
+            braces_for_single_statements: self.braces_for_single_statements,
+            simplify_zero_comparisons: self.simplify_zero_comparisons,
+            expand_named_constants: self.expand_named_constants,
+            mark_dynamic_calls: self.mark_dynamic_calls,
+            hoist_invariants: self.hoist_invariants,
+            fold_markers: self.fold_markers,
+            header: self.header.map(Arc::new),
+            temp_naming: self.temp_naming,
+            function_attributes: Arc::new(self.function_attributes),
         }
     }
 }
@@ -197,6 +500,24 @@ impl Default for EmitContextBuilder {
             indent_style: IndentStyle::Allman,
             expr_root: true,
             include_ssa_versions: false,
+            use_compound_assignment: true,
+            builtin_namespace_accessor: ".",
+            rename_map: HashMap::new(),
+            allow_goto: false,
+            annotate_regions: false,
+            provenance_comments: false,
+            comment_style: CommentStyle::Line,
+            placeholder_unimplemented: false,
+            preserve_casts: false,
+            braces_for_single_statements: true,
+            simplify_zero_comparisons: false,
+            expand_named_constants: false,
+            mark_dynamic_calls: false,
+            hoist_invariants: false,
+            fold_markers: None,
+            header: None,
+            temp_naming: None,
+            function_attributes: Vec::new(),
         }
     }
 }
@@ -234,6 +555,24 @@ mod tests {
         assert!(!context.format_number_hex);
         assert_eq!(context.verbosity, EmitVerbosity::Pretty);
         assert_eq!(context.indent_style, IndentStyle::Allman);
+        assert!(context.use_compound_assignment);
+        assert_eq!(context.builtin_namespace_accessor, ".");
+        assert!(context.rename_map.is_empty());
+        assert!(!context.allow_goto);
+        assert!(!context.annotate_regions);
+        assert!(!context.provenance_comments);
+        assert_eq!(context.comment_style, CommentStyle::Line);
+        assert!(!context.placeholder_unimplemented);
+        assert!(!context.preserve_casts);
+        assert!(context.braces_for_single_statements);
+        assert!(!context.simplify_zero_comparisons);
+        assert!(!context.expand_named_constants);
+        assert!(!context.mark_dynamic_calls);
+        assert!(!context.hoist_invariants);
+        assert_eq!(context.fold_markers, None);
+        assert_eq!(context.header, None);
+        assert!(context.temp_naming.is_none());
+        assert!(context.function_attributes.is_empty());
     }
 
     #[test]
@@ -246,6 +585,24 @@ mod tests {
             .indent_style(IndentStyle::KAndR)
             .expr_root(true)
             .include_ssa_versions(true)
+            .use_compound_assignment(false)
+            .builtin_namespace_accessor("::")
+            .rename_map(HashMap::from([("_a1".to_string(), "health".to_string())]))
+            .allow_goto(true)
+            .annotate_regions(true)
+            .provenance_comments(true)
+            .comment_style(CommentStyle::Block)
+            .placeholder_unimplemented(true)
+            .preserve_casts(true)
+            .braces_for_single_statements(false)
+            .simplify_zero_comparisons(true)
+            .expand_named_constants(true)
+            .mark_dynamic_calls(true)
+            .hoist_invariants(true)
+            .fold_markers(FoldMarkerStyle::Vim)
+            .emit_header(HeaderInfo::default())
+            .temp_prefix("tmp")
+            .function_attributes(vec!["public".to_string()])
             .build();
         assert_eq!(context.indent, 2);
         assert_eq!(context.indent_step, 8);
@@ -254,5 +611,26 @@ mod tests {
         assert_eq!(context.indent_style, IndentStyle::KAndR);
         assert!(context.expr_root);
         assert!(context.include_ssa_versions);
+        assert!(!context.use_compound_assignment);
+        assert_eq!(context.builtin_namespace_accessor, "::");
+        assert_eq!(context.rename_map.get("_a1"), Some(&"health".to_string()));
+        assert!(context.allow_goto);
+        assert!(context.annotate_regions);
+        assert!(context.provenance_comments);
+        assert_eq!(context.comment_style, CommentStyle::Block);
+        assert!(context.placeholder_unimplemented);
+        assert!(context.preserve_casts);
+        assert!(!context.braces_for_single_statements);
+        assert!(context.simplify_zero_comparisons);
+        assert!(context.expand_named_constants);
+        assert!(context.mark_dynamic_calls);
+        assert!(context.hoist_invariants);
+        assert_eq!(context.fold_markers, Some(FoldMarkerStyle::Vim));
+        assert_eq!(context.header.as_deref(), Some(&HeaderInfo::default()));
+        assert_eq!(
+            context.temp_naming.map(|scheme| scheme.name("lit", 0)),
+            Some("tmp0".to_string())
+        );
+        assert_eq!(*context.function_attributes, vec!["public".to_string()]);
     }
 }
@@ -1,11 +1,14 @@
 #![deny(missing_docs)]
 
 use super::{
-    emit_context::{EmitContext, IndentStyle},
+    emit_context::{CommentStyle, EmitContext, FoldMarkerStyle, IndentStyle},
     AstVisitor,
 };
 use crate::decompiler::ast::{
-    array::ArrayNode, array_access::ArrayAccessNode, control_flow::ControlFlowType, expr::ExprKind,
+    array::ArrayNode,
+    array_access::ArrayAccessNode,
+    control_flow::ControlFlowType,
+    expr::{ExprKind, ExprTypeHint},
     phi::PhiNode,
 };
 use crate::decompiler::ast::{assignment::AssignmentNode, statement::StatementKind};
@@ -13,11 +16,14 @@ use crate::decompiler::ast::{
     bin_op::{BinOpType, BinaryOperationNode},
     func_call::FunctionCallNode,
 };
-use crate::decompiler::ast::{block::BlockNode, ptr::P};
+use crate::decompiler::ast::{block::BlockNode, cast::CastNode, ptr::P};
 use crate::decompiler::ast::{control_flow::ControlFlowNode, unary_op::UnaryOperationNode};
 use crate::decompiler::ast::{function::FunctionNode, literal::LiteralNode};
 use crate::decompiler::ast::{member_access::MemberAccessNode, ret::ReturnNode};
-use crate::decompiler::ast::{AstKind, AstVisitable};
+use crate::decompiler::ast::{
+    meta::{provenance_address, PROVENANCE_PROPERTY},
+    AstKind, AstVisitable,
+};
 use crate::{decompiler::ast::identifier::IdentifierNode, utils::escape_string};
 
 /// An emitter for the AST.
@@ -43,10 +49,254 @@ impl Gs2Emitter {
         comments.into_iter().flatten().collect()
     }
 
+    /// Formats comment text in the comment style of the context.
+    fn format_comment(&self, text: &str) -> String {
+        match self.context.comment_style {
+            CommentStyle::Line => format!("// {}", text),
+            CommentStyle::Block => format!("/* {} */", text),
+        }
+    }
+
+    /// Surrounds the emitted output of a statement with fold markers, if the context
+    /// asks for them and the statement is control flow, such as an `if` or a loop.
+    /// The first line is indented by the caller, so the statement follows the start
+    /// marker at the same indentation.
+    fn with_fold_markers(&self, stmt: &AstKind, output: String) -> String {
+        let Some(style) = self.context.fold_markers else {
+            return output;
+        };
+        if !matches!(stmt, AstKind::ControlFlow(_)) {
+            return output;
+        }
+        let (start, end) = match style {
+            FoldMarkerStyle::Region => ("region", "endregion"),
+            FoldMarkerStyle::Vim => ("{{{", "}}}"),
+        };
+        format!(
+            "{}\n{}{}\n{}{}",
+            self.format_comment(start),
+            self.emit_indent(),
+            output,
+            self.emit_indent(),
+            self.format_comment(end)
+        )
+    }
+
+    /// Checks if an assignment target is the left operand of the assigned binary
+    /// operation, so `x = x + y` can be emitted as `x += y`. The target must match
+    /// structurally and have no side effects, since the compound form evaluates it once
+    /// instead of twice, so `a[f()] = a[f()] + 1` is kept.
+    fn is_compound_target(target: &ExprKind, operand: &ExprKind) -> bool {
+        matches!(
+            target,
+            ExprKind::Identifier(_) | ExprKind::ArrayAccess(_) | ExprKind::MemberAccess(_)
+        ) && target == operand
+            && !target.has_side_effects()
+    }
+
+    /// Checks if the emitted output of a statement is empty, or only a `;`.
+    fn is_empty_output(output: &str) -> bool {
+        matches!(output.trim(), "" | ";")
+    }
+
+    /// Checks if an operand of a logical operation can be emitted without parentheses.
+    /// That is the case for a logical operation that binds tighter, and for the same
+    /// operation on the left, since both logical operators are left-associative.
+    /// Every other nested operation is parenthesized, such as `(a & b) == c`, so the
+    /// output never depends on the finer points of `BinOpType::precedence`.
+    fn is_unparenthesized_logical_operand(
+        op_type: &BinOpType,
+        operand: &ExprKind,
+        is_lhs: bool,
+    ) -> bool {
+        let ExprKind::BinOp(operand) = operand else {
+            return false;
+        };
+        match (
+            op_type.logical_precedence(),
+            operand.op_type.logical_precedence(),
+        ) {
+            (Some(precedence), Some(operand_precedence)) => {
+                operand_precedence > precedence || (is_lhs && operand_precedence == precedence)
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if the left operand of a `@` is another `@`, so that a concatenation
+    /// chain is emitted flat, as `a @ b @ c`. Concatenation is left-associative, so a
+    /// chain on the right keeps its parentheses.
+    fn is_join_chain(op_type: &BinOpType, lhs: &ExprKind) -> bool {
+        *op_type == BinOpType::Join
+            && matches!(lhs, ExprKind::BinOp(lhs) if lhs.op_type == BinOpType::Join)
+    }
+
+    /// Checks if the target of a call is a name, either a function such as `foo` or a
+    /// method such as `obj.foo`. Other targets are only known at runtime.
+    fn is_static_call_target(name: &ExprKind) -> bool {
+        match name {
+            ExprKind::Identifier(_) => true,
+            ExprKind::MemberAccess(member) => matches!(member.rhs, ExprKind::Identifier(_)),
+            _ => false,
+        }
+    }
+
+    /// Visits the condition of a control flow node. A bare `=` in a condition reads as
+    /// a mistyped `==`, so an assignment used as the condition is parenthesized to make
+    /// it clear the assignment is intended.
+    ///
+    /// # Arguments
+    /// - `condition`: The condition to visit.
+    fn visit_condition(&mut self, condition: &ExprKind) -> AstOutput {
+        let prev_context = self.context.clone();
+        self.context = self.context.with_expr_root(true);
+        let output = condition.accept(self);
+        self.context = prev_context;
+        match condition {
+            ExprKind::Assignment(_) => AstOutput {
+                node: format!("({})", output.node),
+                comments: output.comments,
+            },
+            _ => output,
+        }
+    }
+
+    /// Visits an operand of a postfix operator: the target of a member access, array
+    /// access or call, or the index and arguments between its delimiters. Postfix
+    /// operators share the tightest precedence and associate left to right, so a chain
+    /// such as `a.b[c].d(e)` needs no parentheses, and only a target that is another
+    /// kind of operation is parenthesized. Delimited operands never need parentheses.
+    ///
+    /// # Arguments
+    /// - `operand`: The operand to visit.
+    /// - `delimited`: Whether the operand is between brackets or parentheses.
+    fn visit_postfix_operand(&mut self, operand: &ExprKind, delimited: bool) -> AstOutput {
+        let prev_context = self.context.clone();
+        self.context = self.context.with_expr_root(delimited);
+        let output = operand.accept(self);
+        self.context = prev_context;
+        output
+    }
+
+    /// Visits the name of a field or function, such as `b` in `a.b` or `foo` in `foo()`.
+    /// Names are not variables, so the rename map does not apply to them.
+    ///
+    /// # Arguments
+    /// - `name`: The name to visit.
+    fn visit_name(&mut self, name: &ExprKind) -> AstOutput {
+        if !matches!(name, ExprKind::Identifier(_)) {
+            return self.visit_postfix_operand(name, false);
+        }
+        let rename_map = std::mem::take(&mut self.context.rename_map);
+        let output = self.visit_postfix_operand(name, false);
+        self.context.rename_map = rename_map;
+        output
+    }
+
+    /// Emits the body of a control flow node without braces, such as `x = 1;` in
+    /// `if (a) x = 1;`, when the context allows it and the body is a single plain
+    /// statement. Bodies that are themselves control flow keep their braces, so that
+    /// an `else` can never bind to the wrong `if`.
+    ///
+    /// # Returns
+    /// - The emitted statement, or `None` if the body needs braces.
+    fn emit_braceless_body(&mut self, node: &P<ControlFlowNode>) -> Option<AstOutput> {
+        if self.context.braces_for_single_statements
+            || !matches!(
+                node.ty(),
+                ControlFlowType::If
+                    | ControlFlowType::ElseIf
+                    | ControlFlowType::Else
+                    | ControlFlowType::While
+                    | ControlFlowType::For
+            )
+        {
+            return None;
+        }
+        let [stmt] = node.body().instructions.as_slice() else {
+            return None;
+        };
+        match stmt {
+            AstKind::Expression(_) => {}
+            AstKind::Statement(StatementKind::Label(_) | StatementKind::Comment(_)) => return None,
+            AstKind::Statement(_) => {}
+            AstKind::Function(_) | AstKind::Block(_) | AstKind::ControlFlow(_) => return None,
+        }
+
+        let stmt_out = stmt.accept(self);
+        if !stmt_out.comments.is_empty() || Self::is_empty_output(&stmt_out.node) {
+            return None;
+        }
+        let mut s = stmt_out.node;
+        if matches!(stmt, AstKind::Expression(_)) {
+            s.push(';');
+        }
+        s.push_str(&self.provenance_suffix(stmt));
+        Some(AstOutput {
+            node: s,
+            comments: node.body().metadata().comments().clone(),
+        })
+    }
+
+    /// Emits the body of a control flow node after its header, without braces if
+    /// `emit_braceless_body` allows it.
+    fn push_body(&mut self, s: &mut String, node: &P<ControlFlowNode>) -> AstOutput {
+        if let Some(body_out) = self.emit_braceless_body(node) {
+            if !s.ends_with(' ') {
+                s.push(' ');
+            }
+            s.push_str(&body_out.node);
+            return body_out;
+        }
+        let body_out = node.body().accept(self);
+        s.push_str(&body_out.node);
+        body_out
+    }
+
     /// Returns a string containing spaces corresponding to the current indentation level.
     fn emit_indent(&self) -> String {
         " ".repeat(self.context.indent)
     }
+
+    /// Returns the `from ...` comment listing the instructions a statement was
+    /// decompiled from, ordered by address, or an empty string if provenance comments
+    /// are disabled or the statement has no provenance.
+    fn provenance_suffix(&self, node: &AstKind) -> String {
+        if !self.context.provenance_comments {
+            return String::new();
+        }
+
+        let mut sources = Vec::new();
+        match node {
+            AstKind::Statement(stmt) => {
+                sources.extend(stmt.metadata().get_property(PROVENANCE_PROPERTY).cloned());
+                for expr in stmt.expressions() {
+                    Self::collect_provenance(expr, &mut sources);
+                }
+            }
+            AstKind::Expression(expr) => Self::collect_provenance(expr, &mut sources),
+            // Structured nodes span several statements, which carry their own provenance
+            AstKind::Function(_) | AstKind::Block(_) | AstKind::ControlFlow(_) => {}
+        }
+        if sources.is_empty() {
+            return String::new();
+        }
+
+        sources.sort_by_key(|source| provenance_address(source).unwrap_or(usize::MAX));
+        sources.dedup();
+        format!(
+            " {}",
+            self.format_comment(&format!("from {}", sources.join(", ")))
+        )
+    }
+
+    /// Collects the provenance of an expression and its subexpressions.
+    fn collect_provenance(expr: &ExprKind, sources: &mut Vec<String>) {
+        sources.extend(expr.metadata().get_property(PROVENANCE_PROPERTY).cloned());
+        for subexpression in expr.subexpressions() {
+            Self::collect_provenance(subexpression, sources);
+        }
+    }
 }
 
 /// The output of the emitter.
@@ -78,6 +328,10 @@ impl AstVisitor for Gs2Emitter {
             StatementKind::Assignment(assignment) => assignment.accept(self),
             StatementKind::Return(ret) => ret.accept(self),
             StatementKind::VirtualBranch(vbranch) => vbranch.accept(self),
+            StatementKind::Goto(goto) => goto.accept(self),
+            // Labels and comments are not terminated with a semicolon
+            StatementKind::Label(label) => return label.accept(self),
+            StatementKind::Comment(comment) => return comment.accept(self),
         };
         AstOutput {
             node: format!("{};", stmt_str.node),
@@ -92,59 +346,51 @@ impl AstVisitor for Gs2Emitter {
         let lhs_str = stmt_node.lhs.accept(self);
 
         // Step 2: Check for binary operations that use the LHS.
-        if let ExprKind::BinOp(bin_op_node) = stmt_node.rhs.clone() {
-            let lhs_in_rhs = bin_op_node.lhs == stmt_node.lhs.clone();
-            if lhs_in_rhs {
+        if let (true, ExprKind::BinOp(bin_op_node)) =
+            (self.context.use_compound_assignment, &stmt_node.rhs)
+        {
+            if Self::is_compound_target(&stmt_node.lhs, &bin_op_node.lhs) {
+                let is_one = matches!(
+                    &bin_op_node.rhs,
+                    ExprKind::Literal(lit) if matches!(lit.as_ref(), LiteralNode::Number(1))
+                );
                 match bin_op_node.op_type {
-                    BinOpType::Add => {
-                        if let ExprKind::Literal(lit) = bin_op_node.rhs.clone() {
-                            if let LiteralNode::Number(num) = lit.as_ref() {
-                                if *num == 1 {
-                                    return AstOutput {
-                                        node: format!("{}++", lhs_str.node),
-                                        comments: self.merge_comments(vec![
-                                            base_comments.clone(),
-                                            lhs_str.comments.clone(),
-                                        ]),
-                                    };
-                                } else {
-                                    let rhs_str = bin_op_node.rhs.accept(self);
-                                    return AstOutput {
-                                        node: format!("{} += {}", lhs_str.node, rhs_str.node),
-                                        comments: self.merge_comments(vec![
-                                            base_comments.clone(),
-                                            lhs_str.comments.clone(),
-                                            rhs_str.comments,
-                                        ]),
-                                    };
-                                }
-                            }
-                        }
+                    BinOpType::Add | BinOpType::Sub if is_one => {
+                        let suffix = if bin_op_node.op_type == BinOpType::Add {
+                            "++"
+                        } else {
+                            "--"
+                        };
+                        return AstOutput {
+                            node: format!("{}{}", lhs_str.node, suffix),
+                            comments: self.merge_comments(vec![
+                                base_comments.clone(),
+                                lhs_str.comments.clone(),
+                            ]),
+                        };
                     }
-                    BinOpType::Sub => {
-                        if let ExprKind::Literal(lit) = bin_op_node.rhs.clone() {
-                            if let LiteralNode::Number(num) = lit.as_ref() {
-                                if *num == 1 {
-                                    return AstOutput {
-                                        node: format!("{}--", lhs_str.node),
-                                        comments: self.merge_comments(vec![
-                                            base_comments.clone(),
-                                            lhs_str.comments.clone(),
-                                        ]),
-                                    };
-                                } else {
-                                    let rhs_str = bin_op_node.rhs.accept(self);
-                                    return AstOutput {
-                                        node: format!("{} -= {}", lhs_str.node, rhs_str.node),
-                                        comments: self.merge_comments(vec![
-                                            base_comments.clone(),
-                                            lhs_str.comments.clone(),
-                                            rhs_str.comments,
-                                        ]),
-                                    };
-                                }
-                            }
-                        }
+                    BinOpType::Add
+                    | BinOpType::Sub
+                    | BinOpType::Mul
+                    | BinOpType::Div
+                    | BinOpType::Mod
+                    | BinOpType::LogicalOr
+                    | BinOpType::LogicalAnd => {
+                        let prev_context = self.context.clone();
+                        self.context = self.context.with_expr_root(true);
+                        let rhs_str = bin_op_node.rhs.accept(self);
+                        self.context = prev_context;
+                        return AstOutput {
+                            node: format!(
+                                "{} {}= {}",
+                                lhs_str.node, bin_op_node.op_type, rhs_str.node
+                            ),
+                            comments: self.merge_comments(vec![
+                                base_comments.clone(),
+                                lhs_str.comments.clone(),
+                                rhs_str.comments,
+                            ]),
+                        };
                     }
                     _ => {}
                 }
@@ -152,7 +398,7 @@ impl AstVisitor for Gs2Emitter {
         }
 
         // Step 3: Default assignment.
-        let prev_context = self.context;
+        let prev_context = self.context.clone();
         self.context = self.context.with_expr_root(true);
         let rhs_str = stmt_node.rhs.accept(self);
         self.context = prev_context;
@@ -178,6 +424,33 @@ impl AstVisitor for Gs2Emitter {
         }
     }
 
+    /// Visits a goto node.
+    fn visit_goto(&mut self, node: &P<crate::decompiler::ast::goto::GotoNode>) -> AstOutput {
+        AstOutput {
+            node: format!("goto {}", node.label),
+            comments: node.metadata().comments().clone(),
+        }
+    }
+
+    /// Visits a label node.
+    fn visit_label(&mut self, node: &P<crate::decompiler::ast::label::LabelNode>) -> AstOutput {
+        AstOutput {
+            node: format!("{}:", node.label),
+            comments: node.metadata().comments().clone(),
+        }
+    }
+
+    /// Visits a comment node.
+    fn visit_comment(
+        &mut self,
+        node: &P<crate::decompiler::ast::comment::CommentNode>,
+    ) -> AstOutput {
+        AstOutput {
+            node: self.format_comment(&node.text),
+            comments: node.metadata().comments().clone(),
+        }
+    }
+
     /// Visits an expression node.
     fn visit_expr(&mut self, node: &ExprKind) -> AstOutput {
         match node {
@@ -193,6 +466,24 @@ impl AstVisitor for Gs2Emitter {
             ExprKind::ArrayAccess(array_access) => array_access.accept(self),
             ExprKind::Phi(phi) => phi.accept(self),
             ExprKind::Range(range) => range.accept(self),
+            ExprKind::Assignment(assignment) => {
+                // An assignment binds looser than any operator, so it needs parentheses
+                // unless it is the root of the expression.
+                let expr_root = self.context.expr_root;
+                let prev_context = self.context.clone();
+                self.context = self.context.with_expr_root(true);
+                let output = assignment.accept(self);
+                self.context = prev_context;
+                if expr_root {
+                    output
+                } else {
+                    AstOutput {
+                        node: format!("({})", output.node),
+                        comments: output.comments,
+                    }
+                }
+            }
+            ExprKind::Cast(cast) => cast.accept(self),
         }
     }
 
@@ -215,8 +506,8 @@ impl AstVisitor for Gs2Emitter {
 
     /// Visits an array access node.
     fn visit_array_access(&mut self, node: &P<ArrayAccessNode>) -> AstOutput {
-        let array_str = node.arr.accept(self);
-        let index_str = node.index.accept(self);
+        let array_str = self.visit_postfix_operand(&node.arr, false);
+        let index_str = self.visit_postfix_operand(&node.index, true);
         AstOutput {
             node: format!("{}[{}]", array_str.node, index_str.node),
             comments: self.merge_comments(vec![
@@ -230,9 +521,18 @@ impl AstVisitor for Gs2Emitter {
     /// Visits a binary operation node.
     fn visit_bin_op(&mut self, node: &P<BinaryOperationNode>) -> AstOutput {
         let base_comments = node.metadata().comments().clone();
-        let prev_context = self.context;
-        self.context = self.context.with_expr_root(false);
+        let prev_context = self.context.clone();
+        // A cast binds tighter than any binary operation, so it is never parenthesized
+        self.context = self.context.with_expr_root(
+            Self::is_unparenthesized_logical_operand(&node.op_type, &node.lhs, true)
+                || Self::is_join_chain(&node.op_type, &node.lhs)
+                || matches!(node.lhs, ExprKind::Cast(_)),
+        );
         let lhs_str = node.lhs.accept(self);
+        self.context = self.context.with_expr_root(
+            Self::is_unparenthesized_logical_operand(&node.op_type, &node.rhs, false)
+                || matches!(node.rhs, ExprKind::Cast(_)),
+        );
         let rhs_str = node.rhs.accept(self);
         self.context = prev_context;
         let op_str = node.op_type.to_string();
@@ -260,7 +560,7 @@ impl AstVisitor for Gs2Emitter {
     /// Visits a unary operation node.
     fn visit_unary_op(&mut self, node: &P<UnaryOperationNode>) -> AstOutput {
         let base_comments = node.metadata().comments().clone();
-        let prev_context = self.context;
+        let prev_context = self.context.clone();
         self.context = self.context.with_expr_root(false);
         let operand_str = node.operand.accept(self);
         self.context = prev_context;
@@ -286,9 +586,34 @@ impl AstVisitor for Gs2Emitter {
         }
     }
 
+    /// Visits a cast node.
+    fn visit_cast(&mut self, node: &P<CastNode>) -> AstOutput {
+        let prev_context = self.context.clone();
+        self.context = self.context.with_expr_root(false);
+        let operand_str = node.operand.accept(self);
+        self.context = prev_context;
+        let cast_str = format!("({}){}", node.cast_type, operand_str.node);
+        AstOutput {
+            node: if self.context.expr_root {
+                cast_str
+            } else {
+                format!("({})", cast_str)
+            },
+            comments: self.merge_comments(vec![
+                node.metadata().comments().clone(),
+                operand_str.comments,
+            ]),
+        }
+    }
+
     /// Visits an identifier node.
     fn visit_identifier(&mut self, node: &P<IdentifierNode>) -> AstOutput {
-        let mut s = node.id().clone();
+        let mut s = self
+            .context
+            .rename_map
+            .get(node.id())
+            .unwrap_or(node.id())
+            .clone();
         if self.context.include_ssa_versions {
             if let Some(ssa_version) = node.ssa_version {
                 s.push_str(&format!("#{}", ssa_version));
@@ -340,10 +665,14 @@ impl AstVisitor for Gs2Emitter {
 
     /// Visits a member access node.
     fn visit_member_access(&mut self, node: &P<MemberAccessNode>) -> AstOutput {
-        let lhs_str = node.lhs.accept(self);
-        let rhs_str = node.rhs.accept(self);
+        let lhs_str = self.visit_postfix_operand(&node.lhs, false);
+        let rhs_str = self.visit_name(&node.rhs);
+        let accessor = match node.lhs.type_hint() {
+            ExprTypeHint::BuiltinNamespace => self.context.builtin_namespace_accessor,
+            _ => ".",
+        };
         AstOutput {
-            node: format!("{}.{}", lhs_str.node, rhs_str.node),
+            node: format!("{}{}{}", lhs_str.node, accessor, rhs_str.node),
             comments: self.merge_comments(vec![
                 node.metadata().comments().clone(),
                 lhs_str.comments,
@@ -356,11 +685,16 @@ impl AstVisitor for Gs2Emitter {
     fn visit_function_call(&mut self, node: &P<FunctionCallNode>) -> AstOutput {
         let mut s = String::new();
         let mut arg_comments = Vec::new();
-        let name_out = node.name.accept(self);
-        s.push_str(name_out.node.as_str());
+        if self.context.mark_dynamic_calls && !Self::is_static_call_target(&node.name) {
+            let name_out = self.visit_postfix_operand(&node.name, true);
+            s.push_str(&format!("/* dynamic */ ({})", name_out.node));
+        } else {
+            let name_out = self.visit_name(&node.name);
+            s.push_str(name_out.node.as_str());
+        }
         s.push('(');
         for (i, arg) in node.arguments.iter().enumerate() {
-            let arg_out = arg.accept(self);
+            let arg_out = self.visit_postfix_operand(arg, true);
             s.push_str(&arg_out.node);
             arg_comments.extend(arg_out.comments);
             if i < node.arguments.len() - 1 {
@@ -384,18 +718,30 @@ impl AstVisitor for Gs2Emitter {
                 // First emit any comments.
                 for comment in stmt_out.comments.iter() {
                     s.push_str(&self.emit_indent());
-                    s.push_str("// ");
-                    s.push_str(comment);
+                    s.push_str(&self.format_comment(comment));
                     s.push('\n');
                 }
+                if Self::is_empty_output(&stmt_out.node) {
+                    continue;
+                }
 
-                s.push_str(&stmt_out.node);
+                s.push_str(&self.with_fold_markers(stmt, stmt_out.node));
+                // Expressions in statement position are terminated like statements
+                if matches!(stmt, AstKind::Expression(_)) {
+                    s.push(';');
+                }
+                s.push_str(&self.provenance_suffix(stmt));
                 s.push('\n');
             }
             return AstOutput { node: s, comments };
         }
         let name = node.name().as_ref().unwrap();
         let mut s = String::new();
+        if !self.context.function_attributes.is_empty() {
+            s.push_str(&self.context.function_attributes.join(" "));
+            s.push('\n');
+            s.push_str(&self.emit_indent());
+        }
         s.push_str(&format!("function {}(", name));
         for (i, param) in node.params().iter().enumerate() {
             let param_out = param.accept(self);
@@ -436,7 +782,7 @@ impl AstVisitor for Gs2Emitter {
         } else {
             s.push_str(" {\n");
         }
-        let old_context = self.context;
+        let old_context = self.context.clone();
         self.context = self.context.with_indent();
         if !node.instructions.is_empty() {
             for stmt in node.instructions.iter() {
@@ -444,13 +790,21 @@ impl AstVisitor for Gs2Emitter {
                 // First emit any comments.
                 for comment in stmt_out.comments.iter() {
                     s.push_str(&self.emit_indent());
-                    s.push_str("// ");
-                    s.push_str(comment);
+                    s.push_str(&self.format_comment(comment));
                     s.push('\n');
                 }
+                // Nodes that emit nothing would leave a blank line or a stray `;`.
+                if Self::is_empty_output(&stmt_out.node) {
+                    continue;
+                }
                 // Then emit the statement.
                 s.push_str(&self.emit_indent());
-                s.push_str(&stmt_out.node);
+                s.push_str(&self.with_fold_markers(stmt, stmt_out.node));
+                // Expressions in statement position are terminated like statements
+                if matches!(stmt, AstKind::Expression(_)) {
+                    s.push(';');
+                }
+                s.push_str(&self.provenance_suffix(stmt));
                 s.push('\n');
             }
         }
@@ -483,7 +837,7 @@ impl AstVisitor for Gs2Emitter {
             s.push_str(&body_out.node);
             s.push_str(" while (");
             if let Some(condition) = node.condition() {
-                let condition_out = condition.accept(self);
+                let condition_out = self.visit_condition(condition);
                 s.push_str(&condition_out.node);
                 base_comments.extend(condition_out.comments.clone());
             }
@@ -492,17 +846,45 @@ impl AstVisitor for Gs2Emitter {
                 node: s,
                 comments: self.merge_comments(vec![base_comments, body_out.comments]),
             }
+        } else if let (Some(init), Some(step)) = (node.init(), node.step()) {
+            // Counted for loop. The clauses are statements, so drop their semicolons.
+            let init_out = init.accept(self);
+            let condition_out = node
+                .condition()
+                .as_ref()
+                .map(|condition| self.visit_condition(condition));
+            let step_out = step.accept(self);
+            s.push_str(name);
+            s.push_str(" (");
+            s.push_str(init_out.node.trim_end_matches(';'));
+            s.push_str("; ");
+            if let Some(condition_out) = condition_out.as_ref() {
+                s.push_str(&condition_out.node);
+            }
+            s.push_str("; ");
+            s.push_str(step_out.node.trim_end_matches(';'));
+            s.push_str(") ");
+            let body_out = self.push_body(&mut s, node);
+            AstOutput {
+                node: s,
+                comments: self.merge_comments(vec![
+                    base_comments,
+                    init_out.comments,
+                    condition_out.map(|out| out.comments).unwrap_or_default(),
+                    step_out.comments,
+                    body_out.comments,
+                ]),
+            }
         } else {
             s.push_str(name);
             if let Some(condition) = node.condition() {
-                let condition_out = condition.accept(self);
+                let condition_out = self.visit_condition(condition);
                 s.push_str(" (");
                 s.push_str(&condition_out.node);
                 s.push_str(") ");
                 base_comments.extend(condition_out.comments.clone());
             }
-            let body_out = node.body().accept(self);
-            s.push_str(&body_out.node);
+            let body_out = self.push_body(&mut s, node);
             AstOutput {
                 node: s,
                 comments: self.merge_comments(vec![base_comments, body_out.comments]),
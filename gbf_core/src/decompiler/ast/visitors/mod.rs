@@ -2,10 +2,11 @@
 
 use super::{
     array::ArrayNode, array_access::ArrayAccessNode, assignment::AssignmentNode,
-    bin_op::BinaryOperationNode, block::BlockNode, control_flow::ControlFlowNode, expr::ExprKind,
-    func_call::FunctionCallNode, function::FunctionNode, identifier::IdentifierNode,
-    literal::LiteralNode, member_access::MemberAccessNode, phi::PhiNode, ptr::P, ret::ReturnNode,
-    statement::StatementKind, unary_op::UnaryOperationNode, AstKind,
+    bin_op::BinaryOperationNode, block::BlockNode, cast::CastNode, control_flow::ControlFlowNode,
+    expr::ExprKind, func_call::FunctionCallNode, function::FunctionNode,
+    identifier::IdentifierNode, literal::LiteralNode, member_access::MemberAccessNode,
+    phi::PhiNode, ptr::P, ret::ReturnNode, statement::StatementKind, unary_op::UnaryOperationNode,
+    AstKind,
 };
 
 /// Represents a visitor for the AST.
@@ -30,6 +31,11 @@ pub trait AstVisitor {
     fn visit_bin_op(&mut self, node: &P<BinaryOperationNode>) -> Self::Output;
     /// Visits a unary operation node.
     fn visit_unary_op(&mut self, node: &P<UnaryOperationNode>) -> Self::Output;
+    /// Visits a cast node. By default, the cast is transparent and its operand is
+    /// visited instead.
+    fn visit_cast(&mut self, node: &P<CastNode>) -> Self::Output {
+        self.visit_expr(&node.operand)
+    }
     /// Visits an identifier node.
     fn visit_identifier(&mut self, node: &P<IdentifierNode>) -> Self::Output;
     /// Visits a literal node.
@@ -66,4 +72,13 @@ pub trait AstVisitor {
     ) -> Self::Output;
     /// Visits a range node.
     fn visit_range(&mut self, node: &P<crate::decompiler::ast::range::RangeNode>) -> Self::Output;
+    /// Visits a goto node.
+    fn visit_goto(&mut self, node: &P<crate::decompiler::ast::goto::GotoNode>) -> Self::Output;
+    /// Visits a label node.
+    fn visit_label(&mut self, node: &P<crate::decompiler::ast::label::LabelNode>) -> Self::Output;
+    /// Visits a comment node.
+    fn visit_comment(
+        &mut self,
+        node: &P<crate::decompiler::ast::comment::CommentNode>,
+    ) -> Self::Output;
 }
@@ -0,0 +1,84 @@
+#![deny(missing_docs)]
+
+use crate::opcode::Opcode;
+
+/// A named constant that GS2 pushes with a dedicated opcode, such as `pi`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamedConstant {
+    /// The opcode that pushes the constant.
+    pub opcode: Opcode,
+    /// The canonical name of the constant in GS2 source.
+    pub name: &'static str,
+    /// The numeric value of the constant, if it has one. The value is kept as a
+    /// string so that it is emitted exactly as written.
+    pub value: Option<&'static str>,
+}
+
+/// Every named constant known to the decompiler. Adding a new constant only
+/// requires a new entry here.
+pub const NAMED_CONSTANTS: &[NamedConstant] = &[NamedConstant {
+    opcode: Opcode::Pi,
+    name: "pi",
+    value: Some("3.141592653589793"),
+}];
+
+/// Looks up the named constant pushed by an opcode.
+///
+/// # Arguments
+/// - `opcode`: The opcode to look up.
+///
+/// # Returns
+/// - The named constant, or `None` if the opcode does not push one.
+///
+/// # Example
+/// ```
+/// use gbf_core::decompiler::constants::named_constant;
+/// use gbf_core::opcode::Opcode;
+///
+/// assert_eq!(named_constant(Opcode::Pi).map(|c| c.name), Some("pi"));
+/// assert!(named_constant(Opcode::PushNumber).is_none());
+/// ```
+pub fn named_constant(opcode: Opcode) -> Option<&'static NamedConstant> {
+    NAMED_CONSTANTS
+        .iter()
+        .find(|constant| constant.opcode == opcode)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_named_constants_are_valid() {
+        let mut opcodes = HashSet::new();
+        for constant in NAMED_CONSTANTS {
+            assert!(!constant.name.is_empty(), "{:?} has no name", constant);
+            assert!(
+                opcodes.insert(constant.opcode),
+                "{:?} is listed twice",
+                constant.opcode
+            );
+            if let Some(value) = constant.value {
+                assert!(
+                    value.parse::<f64>().is_ok(),
+                    "{} has an invalid value {}",
+                    constant.name,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_named_constant_lookup() {
+        let pi = named_constant(Opcode::Pi).unwrap();
+        assert_eq!(pi.name, "pi");
+        assert_eq!(
+            pi.value.map(|v| v.parse::<f64>().unwrap()),
+            Some(std::f64::consts::PI)
+        );
+        assert!(named_constant(Opcode::Player).is_none());
+    }
+}
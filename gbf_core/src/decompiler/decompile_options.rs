@@ -0,0 +1,163 @@
+#![deny(missing_docs)]
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::function::{Function, FunctionId};
+use crate::utils::STRUCTURE_ANALYSIS_MAX_ITERATIONS;
+
+use super::function_decompiler::{FunctionDecompiler, FunctionDecompilerBuilder};
+use super::passes::{AstPass, AstPassFactory};
+
+/// The options used to build a decompiler for each function when decompiling a whole
+/// module with `Module::decompile_all`.
+#[derive(Clone)]
+pub struct DecompileOptions {
+    structure_analysis_max_iterations: usize,
+    structure_debug_mode: bool,
+    auto_retry: Option<(usize, usize)>,
+    ast_passes: Vec<AstPassFactory>,
+    parallel: bool,
+    max_phi_nodes: Option<usize>,
+    emit_leftover_stack_values: bool,
+    skip_functions: HashSet<FunctionId>,
+}
+
+impl DecompileOptions {
+    /// Creates the default options, which match the defaults of `FunctionDecompilerBuilder`.
+    pub fn new() -> Self {
+        Self {
+            structure_analysis_max_iterations: STRUCTURE_ANALYSIS_MAX_ITERATIONS,
+            structure_debug_mode: false,
+            auto_retry: None,
+            ast_passes: Vec::new(),
+            parallel: true,
+            max_phi_nodes: None,
+            emit_leftover_stack_values: false,
+            skip_functions: HashSet::new(),
+        }
+    }
+
+    /// Sets the maximum number of iterations for the structure analysis.
+    pub fn structure_analysis_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.structure_analysis_max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets the structure debug mode, which keeps snapshots of the structure analysis.
+    pub fn structure_debug_mode(mut self, structure_debug_mode: bool) -> Self {
+        self.structure_debug_mode = structure_debug_mode;
+        self
+    }
+
+    /// Retries the structure analysis of a function when it reaches the maximum number
+    /// of iterations, as with `FunctionDecompilerBuilder::auto_retry`.
+    pub fn auto_retry(mut self, factor: usize, max_attempts: usize) -> Self {
+        self.auto_retry = Some((factor, max_attempts));
+        self
+    }
+
+    /// Adds a pass to run on the AST of each function before structure analysis. The
+    /// factory is called once per function, and passes run in the order they are added.
+    pub fn ast_pass<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn AstPass> + Send + Sync + 'static,
+    {
+        self.ast_passes.push(Arc::new(factory));
+        self
+    }
+
+    /// Fails the decompilation of a function that needs more than `max_phi_nodes` phi
+    /// nodes, as with `FunctionDecompilerBuilder::max_phi_nodes`.
+    pub fn max_phi_nodes(mut self, max_phi_nodes: usize) -> Self {
+        self.max_phi_nodes = Some(max_phi_nodes);
+        self
+    }
+
+    /// Emits the values left on the stack at the end of each function as expression
+    /// statements, as with `FunctionDecompilerBuilder::emit_leftover_stack_values`.
+    pub fn emit_leftover_stack_values(mut self, emit_leftover_stack_values: bool) -> Self {
+        self.emit_leftover_stack_values = emit_leftover_stack_values;
+        self
+    }
+
+    /// Skips a function that is known to fail or hang. `Module::decompile_all` returns
+    /// `DecompileResult::Skipped` for it instead of decompiling it.
+    pub fn skip_function(mut self, function_id: FunctionId) -> Self {
+        self.skip_functions.insert(function_id);
+        self
+    }
+
+    /// Skips each of the functions, as with `skip_function`.
+    pub fn skip_functions<I>(mut self, function_ids: I) -> Self
+    where
+        I: IntoIterator<Item = FunctionId>,
+    {
+        self.skip_functions.extend(function_ids);
+        self
+    }
+
+    /// Returns whether a function is skipped by `Module::decompile_all`.
+    pub fn is_skipped(&self, function_id: &FunctionId) -> bool {
+        self.skip_functions.contains(function_id)
+    }
+
+    /// Sets whether functions are decompiled in parallel. This only has an effect when
+    /// the `parallel` feature is enabled, and is on by default.
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Returns whether functions are decompiled in parallel, when the `parallel`
+    /// feature is enabled.
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Builds a decompiler for a function with these options.
+    ///
+    /// # Arguments
+    /// - `function`: The function to decompile.
+    ///
+    /// # Returns
+    /// - A `FunctionDecompiler` for the function.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::decompiler::decompile_options::DecompileOptions;
+    /// use gbf_core::decompiler::passes::constant_propagation::ConstantPropagationPass;
+    /// use gbf_core::function::{Function, FunctionId};
+    ///
+    /// let options = DecompileOptions::new()
+    ///     .structure_analysis_max_iterations(100)
+    ///     .ast_pass(|| Box::new(ConstantPropagationPass));
+    /// let function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let decompiler = options.decompiler(function);
+    /// ```
+    pub fn decompiler(&self, function: Function) -> FunctionDecompiler {
+        let mut builder = self
+            .ast_passes
+            .iter()
+            .fold(
+                FunctionDecompilerBuilder::new(function),
+                |builder, factory| builder.ast_pass(factory()),
+            )
+            .structure_analysis_max_iterations(self.structure_analysis_max_iterations)
+            .structure_debug_mode(self.structure_debug_mode)
+            .emit_leftover_stack_values(self.emit_leftover_stack_values);
+        if let Some((factor, max_attempts)) = self.auto_retry {
+            builder = builder.auto_retry(factor, max_attempts);
+        }
+        if let Some(max_phi_nodes) = self.max_phi_nodes {
+            builder = builder.max_phi_nodes(max_phi_nodes);
+        }
+        builder.build()
+    }
+}
+
+impl Default for DecompileOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
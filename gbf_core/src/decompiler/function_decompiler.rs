@@ -1,25 +1,39 @@
 #![deny(missing_docs)]
 
 use crate::basic_block::BasicBlockId;
-use crate::function::{Function, FunctionError};
+use crate::function::{Function, FunctionError, FunctionId, FunctionMetrics};
 use crate::instruction::Instruction;
 use crate::opcode::Opcode;
 use crate::operand::OperandError;
-use crate::utils::STRUCTURE_ANALYSIS_MAX_ITERATIONS;
+use crate::utils::{Gs2BytecodeAddress, STRUCTURE_ANALYSIS_MAX_ITERATIONS};
 use serde::Serialize;
 use std::backtrace::Backtrace;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use thiserror::Error;
 
 use super::ast::expr::ExprKind;
 use super::ast::function::FunctionNode;
+use super::ast::identifier::IdentifierNode;
+use super::ast::meta::PROVENANCE_PROPERTY;
+use super::ast::statement::StatementKind;
 use super::ast::visitors::emit_context::EmitContext;
 use super::ast::visitors::emitter::Gs2Emitter;
-use super::ast::{new_phi, AstKind, AstVisitable};
+use super::ast::{
+    emit, new_comment, new_goto, new_if, new_label, new_logical_not, new_phi, AstKind, AstVisitable,
+};
 use super::execution_frame::ExecutionFrame;
 use super::function_decompiler_context::FunctionDecompilerContext;
+use super::loop_invariants::hoist_loop_invariants;
+use super::passes::zero_comparison::ZeroComparisonPass;
+use super::passes::AstPass;
 use super::structure_analysis::region::{RegionId, RegionType};
-use super::structure_analysis::{ControlFlowEdgeType, StructureAnalysis, StructureAnalysisError};
+use super::structure_analysis::{
+    ControlFlowEdgeType, EntryPredecessorHandling, ReductionEvent, StructureAnalysis,
+    StructureAnalysisError,
+};
+use super::temp_naming::rename_temporaries;
+use super::ProcessedInstruction;
 
 /// An error when decompiling a function
 #[derive(Debug, Error, Serialize)]
@@ -140,6 +154,30 @@ pub enum FunctionDecompilerError {
         backtrace: Backtrace,
     },
 
+    /// The decompiler produced warnings, and warnings are treated as errors
+    #[error("The decompiler produced {} warning(s): {:?}", .warnings.len(), .warnings)]
+    WarningsAsErrors {
+        /// The warnings the decompiler produced
+        warnings: Vec<DecompilerWarning>,
+        /// The context of the error
+        context: Box<FunctionDecompilerErrorContext>,
+        /// The backtrace of the error
+        #[serde(skip)]
+        backtrace: Backtrace,
+    },
+
+    /// The function needs more phi nodes than the limit allows
+    #[error("The function needs more than {limit} phi nodes.")]
+    TooManyPhiNodes {
+        /// The maximum number of phi nodes
+        limit: usize,
+        /// The context of the error
+        context: Box<FunctionDecompilerErrorContext>,
+        /// The backtrace of the error
+        #[serde(skip)]
+        backtrace: Backtrace,
+    },
+
     /// All other errors
     #[error("An error occurred while decompiling the function: {message}")]
     Other {
@@ -174,12 +212,147 @@ pub struct FunctionDecompilerErrorContext {
     pub current_ast_node_stack: Vec<ExecutionFrame>,
 }
 
+/// A run of the structure analysis of a function, as recorded by
+/// `FunctionDecompiler::structure_analysis_attempts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StructureAnalysisAttempt {
+    /// The maximum number of iterations of the run.
+    pub max_iterations: usize,
+    /// The number of iterations the run took.
+    pub iterations: usize,
+    /// Whether the run stopped because it reached the maximum number of iterations.
+    pub reached_max_iterations: bool,
+}
+
+/// A problem the decompiler recovered from, which may make the output less accurate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DecompilerWarning {
+    /// Structure analysis failed, or left branches it could not structure, so the raw
+    /// control flow was emitted with `goto`.
+    GotoFallback {
+        /// Why the structured body could not be used.
+        reason: String,
+    },
+    /// A predecessor block left a different number of values on the stack than the
+    /// other predecessors of a block, so some phi candidates were dropped.
+    InconsistentPhiCandidates {
+        /// The predecessor block.
+        block_id: BasicBlockId,
+        /// The number of values the other predecessors left on the stack.
+        expected: usize,
+        /// The number of values this predecessor left on the stack.
+        found: usize,
+    },
+    /// Values were left on the stack at the end of a tail region, where nothing can
+    /// consume them. This points to malformed bytecode or an opcode handler that pushes
+    /// the wrong number of values. The values are emitted as statements or dropped.
+    StackImbalance {
+        /// The block of the tail region.
+        block_id: BasicBlockId,
+        /// The number of values left on the stack.
+        depth: usize,
+        /// The emitted values, from the bottom of the stack to the top.
+        nodes: Vec<String>,
+    },
+    /// A block that ends in `Ret` has outgoing edges, which were ignored.
+    StrayReturnEdges {
+        /// The block that ends in `Ret`.
+        block_id: BasicBlockId,
+        /// The targets of the ignored edges.
+        targets: Vec<BasicBlockId>,
+    },
+    /// A block cannot be reached from the entry block, such as one that is only
+    /// reachable through stray edges out of a return block. It is dead code, so it was
+    /// not decompiled.
+    UnreachableBlock {
+        /// The unreachable block.
+        block_id: BasicBlockId,
+    },
+    /// A block has instructions after the instruction that ends it, which can never
+    /// execute. They were not decompiled.
+    UnreachableInstructions {
+        /// The block with the unreachable instructions.
+        block_id: BasicBlockId,
+        /// The number of unreachable instructions.
+        count: usize,
+    },
+    /// An opcode could not be decompiled, so a placeholder was emitted in its place.
+    UnimplementedOpcode {
+        /// The opcode that could not be decompiled.
+        opcode: Opcode,
+        /// The address of the instruction.
+        address: Gs2BytecodeAddress,
+    },
+}
+
+/// Measurements of the structure analysis of a function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StructureAnalysisMetrics {
+    /// The number of regions before structure analysis.
+    pub regions_before: usize,
+    /// The number of regions left after structure analysis.
+    pub regions_after: usize,
+    /// Each run of the structure analysis, including retries.
+    pub attempts: Vec<StructureAnalysisAttempt>,
+    /// The number of phi nodes inserted where blocks merge.
+    pub phi_nodes: usize,
+}
+
+/// Maps the lines of the emitted source to the bytecode addresses they came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LineMap {
+    /// The address of each line that came from the bytecode, keyed by the zero-based
+    /// line number.
+    pub addresses: BTreeMap<usize, Gs2BytecodeAddress>,
+}
+
+/// The output of `FunctionDecompiler::decompile_rich`: the emitted source, along with
+/// what the decompiler learned while producing it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecompileOutput {
+    /// The emitted source of the function.
+    pub source: String,
+    /// The problems the decompiler recovered from, in the order they occurred.
+    pub warnings: Vec<DecompilerWarning>,
+    /// The addresses the lines of the source came from. The emitter does not track
+    /// lines yet, so this is always `None`.
+    pub line_map: Option<LineMap>,
+    /// Measurements of the structure analysis.
+    pub metrics: StructureAnalysisMetrics,
+}
+
+/// The report of `FunctionDecompiler::metrics_report`: the measurements of a function,
+/// along with how its last decompilation went.
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionDecompileReport {
+    /// The measurements of the function that do not need decompiling.
+    pub function: FunctionMetrics,
+    /// Whether the last decompilation succeeded.
+    pub decompile_success: bool,
+    /// The number of warnings the decompiler produced.
+    pub warnings: usize,
+    /// The number of phi nodes inserted where blocks merge.
+    pub phi_nodes: usize,
+    /// The number of regions left after structure analysis.
+    pub regions_after: usize,
+    /// Each run of the structure analysis, including retries.
+    pub attempts: Vec<StructureAnalysisAttempt>,
+}
+
 /// The builder for a function decompiler
 pub struct FunctionDecompilerBuilder {
     function: Function,
     emit_context: EmitContext,
     structure_debug_mode: bool,
+    snapshots_on_failure_only: bool,
+    entry_predecessor_handling: EntryPredecessorHandling,
     structure_analysis_max_iterations: usize,
+    auto_retry: Option<(usize, usize)>,
+    ast_passes: Vec<Box<dyn AstPass>>,
+    warnings_as_errors: bool,
+    max_phi_nodes: Option<usize>,
+    function_attributes: HashMap<FunctionId, Vec<String>>,
+    emit_leftover_stack_values: bool,
 }
 
 impl FunctionDecompilerBuilder {
@@ -189,7 +362,15 @@ impl FunctionDecompilerBuilder {
             function,
             emit_context: EmitContext::default(),
             structure_debug_mode: false,
+            snapshots_on_failure_only: false,
+            entry_predecessor_handling: EntryPredecessorHandling::default(),
             structure_analysis_max_iterations: STRUCTURE_ANALYSIS_MAX_ITERATIONS,
+            auto_retry: None,
+            ast_passes: Vec::new(),
+            warnings_as_errors: false,
+            max_phi_nodes: None,
+            function_attributes: HashMap::new(),
+            emit_leftover_stack_values: false,
         }
     }
 
@@ -206,19 +387,84 @@ impl FunctionDecompilerBuilder {
         self
     }
 
+    /// Only keep the structure analysis snapshots when the analysis fails. The most
+    /// recent snapshots are kept in a ring buffer, which is discarded if the analysis
+    /// succeeds, so `structure_debug_mode` is not needed.
+    pub fn snapshots_on_failure_only(mut self, snapshots_on_failure_only: bool) -> Self {
+        self.snapshots_on_failure_only = snapshots_on_failure_only;
+        self
+    }
+
+    /// Sets how the structure analysis handles an entry block with predecessors, such as
+    /// a function that starts with a loop. By default, an empty region is inserted
+    /// before it.
+    pub fn entry_predecessor_handling(
+        mut self,
+        entry_predecessor_handling: EntryPredecessorHandling,
+    ) -> Self {
+        self.entry_predecessor_handling = entry_predecessor_handling;
+        self
+    }
+
     /// Sets the maximum number of iterations for the structure analysis
     pub fn structure_analysis_max_iterations(mut self, max_iterations: usize) -> Self {
         self.structure_analysis_max_iterations = max_iterations;
         self
     }
 
+    /// Retries the structure analysis when it reaches the maximum number of iterations,
+    /// with the maximum multiplied by `factor` on each retry, up to `max_attempts` times.
+    /// Retrying is off by default.
+    pub fn auto_retry(mut self, factor: usize, max_attempts: usize) -> Self {
+        self.auto_retry = Some((factor, max_attempts));
+        self
+    }
+
+    /// Adds a pass to run on the AST before structure analysis. Passes run in the
+    /// order they are added, and none run by default.
+    pub fn ast_pass(mut self, pass: Box<dyn AstPass>) -> Self {
+        self.ast_passes.push(pass);
+        self
+    }
+
+    /// Fails `decompile` with `FunctionDecompilerError::WarningsAsErrors` if the
+    /// decompiler produced any warnings. This is off by default.
+    pub fn warnings_as_errors(mut self, warnings_as_errors: bool) -> Self {
+        self.warnings_as_errors = warnings_as_errors;
+        self
+    }
+
+    /// Fails `decompile` with `FunctionDecompilerError::TooManyPhiNodes` if the function
+    /// needs more than `max_phi_nodes` phi nodes. There is no limit by default.
+    pub fn max_phi_nodes(mut self, max_phi_nodes: usize) -> Self {
+        self.max_phi_nodes = Some(max_phi_nodes);
+        self
+    }
+
+    /// Sets the attributes written above the signature of each function, keyed by
+    /// function. Attributes recovered elsewhere, such as `public`, can be carried into
+    /// the output this way. Only the entry of the decompiled function is used, and no
+    /// attributes are written by default.
+    pub fn function_attributes(
+        mut self,
+        function_attributes: HashMap<FunctionId, Vec<String>>,
+    ) -> Self {
+        self.function_attributes = function_attributes;
+        self
+    }
+
+    /// Emits the values left on the stack at the end of a tail region as expression
+    /// statements before its trailing return, instead of dropping them. This changes
+    /// the output of functions that leave a value unconsumed, so it is off by default.
+    /// The values are reported as `DecompilerWarning::StackImbalance` either way.
+    pub fn emit_leftover_stack_values(mut self, emit_leftover_stack_values: bool) -> Self {
+        self.emit_leftover_stack_values = emit_leftover_stack_values;
+        self
+    }
+
     /// Build the function decompiler
     pub fn build(self) -> FunctionDecompiler {
-        FunctionDecompiler::new(
-            self.function,
-            self.structure_debug_mode,
-            self.structure_analysis_max_iterations,
-        )
+        FunctionDecompiler::new(self)
     }
 }
 
@@ -236,33 +482,62 @@ pub struct FunctionDecompiler {
     struct_analysis: StructureAnalysis,
     /// Whether the analysis has been run
     did_run_analysis: bool,
+    /// The retry factor and maximum number of retries of the structure analysis
+    auto_retry: Option<(usize, usize)>,
+    /// Each run of the structure analysis
+    structure_attempts: Vec<StructureAnalysisAttempt>,
+    /// The problems the decompiler recovered from
+    warnings: Vec<DecompilerWarning>,
+    /// The passes to run on the AST before structure analysis
+    ast_passes: Vec<Box<dyn AstPass>>,
+    /// Whether decompiling fails if the decompiler produced any warnings
+    warnings_as_errors: bool,
+    /// The maximum number of phi nodes, if any
+    max_phi_nodes: Option<usize>,
+    /// The number of phi nodes inserted so far
+    phi_nodes: usize,
+    /// The AST of the last successful decompilation, kept so that it can be re-emitted
+    ast: Option<AstKind>,
+    /// The attributes written above the signature of the function
+    function_attributes: Option<Vec<String>>,
+    /// Whether values left on the stack of a tail region are emitted as statements
+    emit_leftover_stack_values: bool,
 }
 
 impl FunctionDecompiler {
     /// A new method for the FunctionDecompiler struct.
     ///
     /// # Arguments
-    /// - `function`: The function to analyze and decompile.
-    /// - `structure_debug_mode`: Whether to enable debug mode for the structure analysis.
-    /// - `structure_max_iterations`: The maximum number of iterations for the structure analysis.
+    /// - `builder`: The builder holding the options of the decompiler.
     ///
     /// # Returns
     /// - A newly constructed `FunctionDecompiler` instance.
-    ///
-    /// # Errors
-    /// - `FunctionDecompilerError` if there is an error while decompiling the function.
-    fn new(
-        function: Function,
-        structure_debug_mode: bool,
-        structure_max_iterations: usize,
-    ) -> Self {
+    fn new(builder: FunctionDecompilerBuilder) -> Self {
+        let mut struct_analysis = StructureAnalysis::new(
+            builder.structure_debug_mode,
+            builder.structure_analysis_max_iterations,
+        );
+        struct_analysis.set_snapshots_on_failure_only(builder.snapshots_on_failure_only);
+        struct_analysis.set_entry_predecessor_handling(builder.entry_predecessor_handling);
+        let mut function_attributes = builder.function_attributes;
+        let function_attributes = function_attributes.remove(&builder.function.id);
         FunctionDecompiler {
-            function,
+            function: builder.function,
             block_to_region: HashMap::new(),
             context: None,
             function_parameters: Vec::<ExprKind>::new(),
-            struct_analysis: StructureAnalysis::new(structure_debug_mode, structure_max_iterations),
+            struct_analysis,
             did_run_analysis: false,
+            auto_retry: builder.auto_retry,
+            structure_attempts: Vec::new(),
+            warnings: Vec::new(),
+            ast_passes: builder.ast_passes,
+            warnings_as_errors: builder.warnings_as_errors,
+            max_phi_nodes: builder.max_phi_nodes,
+            phi_nodes: 0,
+            ast: None,
+            function_attributes,
+            emit_leftover_stack_values: builder.emit_leftover_stack_values,
         }
     }
 }
@@ -283,41 +558,247 @@ impl FunctionDecompiler {
         &mut self,
         emit_context: EmitContext,
     ) -> Result<String, FunctionDecompilerError> {
-        self.process_regions()?;
+        self.decompile_rich(emit_context)
+            .map(|output| output.source)
+    }
+
+    /// Decompile the function and emit the AST as a string, along with the warnings
+    /// and structure analysis metrics gathered along the way.
+    ///
+    /// # Arguments
+    /// - `context`: The context for AST emission.
+    ///
+    /// # Returns
+    /// - The emitted AST as a string, with its warnings and metrics.
+    ///
+    /// # Errors
+    /// - Returns `FunctionDecompilerError` for any issues encountered during decompilation.
+    /// - Returns `FunctionDecompilerError::WarningsAsErrors` if warnings are treated as
+    ///   errors and the decompiler produced any.
+    pub fn decompile_rich(
+        &mut self,
+        emit_context: EmitContext,
+    ) -> Result<DecompileOutput, FunctionDecompilerError> {
+        self.ast = None;
+        self.process_regions(&emit_context)?;
+
+        for pass in self.ast_passes.iter_mut() {
+            log::debug!("Running AST pass {} on {}", pass.name(), self.function.id);
+            pass.run(&mut self.struct_analysis).map_err(|e| {
+                FunctionDecompilerError::StructureAnalysisError {
+                    source: Box::new(e),
+                    context: self.context.as_ref().unwrap().get_error_context(),
+                    backtrace: Backtrace::capture(),
+                }
+            })?;
+        }
 
-        let entry_block_id = self.function.get_entry_basic_block().id;
-        let entry_region_id = self.block_to_region.get(&entry_block_id).unwrap();
+        // Branch conditions are only the jump expressions of the regions until structure
+        // analysis merges them into control flow nodes.
+        if emit_context.simplify_zero_comparisons {
+            ZeroComparisonPass
+                .run(&mut self.struct_analysis)
+                .map_err(|e| FunctionDecompilerError::StructureAnalysisError {
+                    source: Box::new(e),
+                    context: self.context.as_ref().unwrap().get_error_context(),
+                    backtrace: Backtrace::capture(),
+                })?;
+        }
+
+        if emit_context.annotate_regions {
+            self.annotate_regions()?;
+        }
+
+        // Structure analysis reduces the regions in place, so the goto fallback has to
+        // be built from the regions before it runs.
+        let goto_body = if emit_context.allow_goto {
+            Some(self.build_goto_body()?)
+        } else {
+            None
+        };
 
         self.did_run_analysis = true;
-        self.struct_analysis.execute().map_err(|e| {
-            FunctionDecompilerError::StructureAnalysisError {
-                source: Box::new(e),
-                context: self.context.as_ref().unwrap().get_error_context(),
-                backtrace: Backtrace::capture(),
+        let regions_before = self.struct_analysis.region_ids().len();
+        let body = match self.execute_structure_analysis() {
+            Ok(()) => {
+                // The entry region may have changed if a pre-header was inserted
+                let entry_region = self
+                    .struct_analysis
+                    .get_region(self.struct_analysis.get_entry_region())
+                    .expect("[Bug] The entry region should exist.");
+                let body = entry_region.iter_nodes().cloned().collect::<Vec<_>>();
+                match goto_body {
+                    // Branches that structure analysis could not turn into control flow,
+                    // such as the edges into an irreducible loop, have no labels to go to.
+                    Some(goto_body) if body.iter().any(Self::contains_virtual_branch) => self
+                        .fall_back_to_goto(
+                            goto_body,
+                            "structure analysis left unstructured branches".to_string(),
+                        ),
+                    _ => body,
+                }
             }
-        })?;
-        let entry_region = {
-            let region = self
-                .struct_analysis
-                .get_region(*entry_region_id)
-                .expect("[Bug] The entry region should exist.");
-            region.clone()
+            Err(e) => match goto_body {
+                Some(goto_body) => {
+                    self.fall_back_to_goto(goto_body, format!("structure analysis failed: {}", e))
+                }
+                None => {
+                    return Err(FunctionDecompilerError::StructureAnalysisError {
+                        source: Box::new(e),
+                        context: self.context.as_ref().unwrap().get_error_context(),
+                        backtrace: Backtrace::capture(),
+                    })
+                }
+            },
         };
-        let entry_region_nodes = entry_region.iter_nodes().cloned().collect::<Vec<_>>();
 
         let func = AstKind::Function(
             FunctionNode::new(
                 self.function.id.name.clone(),
                 self.function_parameters.clone(),
-                entry_region_nodes,
+                body,
             )
             .into(),
         );
 
+        if self.warnings_as_errors && !self.warnings.is_empty() {
+            return Err(FunctionDecompilerError::WarningsAsErrors {
+                warnings: self.warnings.clone(),
+                context: self.context.as_ref().unwrap().get_error_context(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        let source = self.emit_function(&func, emit_context);
+        self.ast = Some(func);
+
+        Ok(DecompileOutput {
+            source,
+            warnings: self.warnings.clone(),
+            line_map: None,
+            metrics: StructureAnalysisMetrics {
+                regions_before,
+                regions_after: self.struct_analysis.region_ids().len(),
+                attempts: self.structure_attempts.clone(),
+                phi_nodes: self.phi_nodes,
+            },
+        })
+    }
+
+    /// Gets the AST of the function. This is `None` until `decompile` has succeeded.
+    ///
+    /// # Returns
+    /// - The AST of the function, if it has been decompiled.
+    pub fn ast(&self) -> Option<&AstKind> {
+        self.ast.as_ref()
+    }
+
+    /// Emits the AST of the last decompilation again with a different context, without
+    /// re-running the structure analysis.
+    ///
+    /// Only the formatting options of the context apply. Options that change how the
+    /// AST is built, such as `preserve_casts` or `allow_goto`, take the values that were
+    /// passed to `decompile`.
+    ///
+    /// # Arguments
+    /// - `emit_context`: The context for AST emission.
+    ///
+    /// # Returns
+    /// - The emitted AST as a string, or `None` if `decompile` has not succeeded.
+    pub fn re_emit(&self, emit_context: EmitContext) -> Option<String> {
+        self.ast
+            .as_ref()
+            .map(|ast| self.emit_function(ast, emit_context))
+    }
+
+    /// Emits the AST of the function with its header and attributes. If the context hoists
+    /// loop invariants or has a naming scheme for temporaries, a transformed copy of the
+    /// AST is emitted, so the stored AST is left as it was.
+    fn emit_function(&self, func: &AstKind, mut emit_context: EmitContext) -> String {
+        if let Some(attributes) = &self.function_attributes {
+            emit_context.function_attributes = Arc::new(attributes.clone());
+        }
+        let header = self.render_header(&emit_context);
+        let transformed = (emit_context.hoist_invariants || emit_context.temp_naming.is_some())
+            .then(|| {
+                let mut transformed = func.clone();
+                if emit_context.hoist_invariants {
+                    hoist_loop_invariants(&mut transformed);
+                }
+                if let Some(scheme) = &emit_context.temp_naming {
+                    rename_temporaries(&mut transformed, scheme.as_ref());
+                }
+                transformed
+            });
         let mut emitter = Gs2Emitter::new(emit_context);
-        let output: String = func.accept(&mut emitter).node;
+        header
+            + &transformed
+                .as_ref()
+                .unwrap_or(func)
+                .accept(&mut emitter)
+                .node
+    }
+
+    /// Renders the header of the context for this function, or an empty string if the
+    /// context has no header.
+    fn render_header(&self, emit_context: &EmitContext) -> String {
+        emit_context
+            .header
+            .as_ref()
+            .map(|header| {
+                header.render(
+                    self.function.id.name.as_deref(),
+                    self.function.id.address,
+                    emit_context.comment_style,
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Gets the problems the decompiler recovered from. This is empty until `decompile`
+    /// has run.
+    ///
+    /// # Returns
+    /// - The warnings, in the order they occurred.
+    pub fn warnings(&self) -> &[DecompilerWarning] {
+        &self.warnings
+    }
 
-        Ok(output)
+    /// Gets the number of phi nodes inserted where blocks merge. This is zero until
+    /// `decompile` has run.
+    ///
+    /// # Returns
+    /// - The number of phi nodes.
+    pub fn phi_node_count(&self) -> usize {
+        self.phi_nodes
+    }
+
+    /// Measures the function, along with how the last call to `decompile` went. Before
+    /// `decompile` has run, only the measurements of the function are meaningful.
+    ///
+    /// # Returns
+    /// - The `FunctionDecompileReport` of the function.
+    ///
+    /// # Errors
+    /// - `FunctionError` if the function could not be measured.
+    pub fn metrics_report(&self) -> Result<FunctionDecompileReport, FunctionError> {
+        Ok(FunctionDecompileReport {
+            function: self.function.metrics_report()?,
+            decompile_success: self.ast.is_some(),
+            warnings: self.warnings.len(),
+            phi_nodes: self.phi_nodes,
+            regions_after: self.struct_analysis.region_ids().len(),
+            attempts: self.structure_attempts.clone(),
+        })
+    }
+
+    /// Gets each run of the structure analysis, including retries. This is empty until
+    /// `decompile` has run.
+    ///
+    /// # Returns
+    /// - The runs of the structure analysis, in the order they ran.
+    pub fn structure_analysis_attempts(&self) -> &[StructureAnalysisAttempt] {
+        &self.structure_attempts
     }
 
     /// Get the structure analysis snapshots
@@ -332,8 +813,109 @@ impl FunctionDecompiler {
             .cloned()
     }
 
+    /// Get the reductions the structure analysis made, which are only recorded in
+    /// debug mode.
+    pub fn get_structure_analysis_reduction_history(
+        &self,
+    ) -> Result<Vec<ReductionEvent>, FunctionDecompilerError> {
+        self.struct_analysis
+            .reduction_history()
+            .map_err(|e| FunctionDecompilerError::StructureAnalysisError {
+                source: Box::new(e),
+                context: self.context.as_ref().unwrap().get_error_context(),
+                backtrace: Backtrace::capture(),
+            })
+            .cloned()
+    }
+
+    /// Gets the parameters of the function, as recovered from its `EndParams`
+    /// instruction. This is empty until `decompile` has run.
+    ///
+    /// # Returns
+    /// - The parameters of the function, in declaration order.
+    pub fn parameters(&self) -> &[ExprKind] {
+        &self.function_parameters
+    }
+
+    /// Gets the region each block of the function was placed in before structure
+    /// analysis. This is empty until `decompile` has run.
+    ///
+    /// # Returns
+    /// - A map from each block to its region.
+    pub fn block_region_map(&self) -> &HashMap<BasicBlockId, RegionId> {
+        &self.block_to_region
+    }
+
+    /// Formats the block-to-region mapping, one block per line ordered by address,
+    /// such as `Block at address 0x0 -> RegionId(0)`.
+    ///
+    /// # Returns
+    /// - The formatted mapping, or an empty string until `decompile` has run.
+    pub fn dump_block_region_map(&self) -> String {
+        let mut blocks: Vec<_> = self.block_to_region.iter().collect();
+        blocks.sort_by_key(|(block_id, _)| block_id.address);
+        blocks
+            .into_iter()
+            .map(|(block_id, region_id)| format!("{} -> {}\n", block_id, region_id))
+            .collect()
+    }
+
+    /// Runs the structure analysis. With `auto_retry`, an analysis that reaches the
+    /// maximum number of iterations is run again from the unreduced regions with a
+    /// higher maximum.
+    fn execute_structure_analysis(&mut self) -> Result<(), StructureAnalysisError> {
+        let unreduced = self.auto_retry.map(|_| self.struct_analysis.clone());
+        let mut retries = 0;
+        loop {
+            let result = self.struct_analysis.execute();
+            self.structure_attempts.push(StructureAnalysisAttempt {
+                max_iterations: self.struct_analysis.max_iterations(),
+                iterations: self.struct_analysis.iterations(),
+                reached_max_iterations: matches!(
+                    result,
+                    Err(StructureAnalysisError::MaxIterationsReached { .. })
+                ),
+            });
+
+            match (&result, self.auto_retry, &unreduced) {
+                (
+                    Err(StructureAnalysisError::MaxIterationsReached { max_iterations, .. }),
+                    Some((factor, max_attempts)),
+                    Some(unreduced),
+                ) if retries < max_attempts => {
+                    retries += 1;
+                    let max_iterations = max_iterations.saturating_mul(factor);
+                    log::debug!(
+                        "Retrying structure analysis of {} with {} iterations",
+                        self.function.id,
+                        max_iterations
+                    );
+                    self.struct_analysis = unreduced.clone();
+                    self.struct_analysis.set_max_iterations(max_iterations);
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Creates a region for each block. Blocks that cannot be reached from the entry
+    /// block are skipped with a `DecompilerWarning::UnreachableBlock`, since a region
+    /// without predecessors cannot be reduced by the structure analysis.
     fn generate_regions(&mut self) -> Result<(), FunctionDecompilerError> {
-        for block in self.function.iter() {
+        let reachable = self.reachable_blocks()?;
+        // Short-circuits only jump forward, so sorting by address visits the blocks a
+        // short-circuit starts in before its operands
+        let mut blocks: Vec<_> = self.function.iter().collect();
+        blocks.sort_by_key(|block| block.id.address);
+        for block in blocks {
+            // Blocks that are only reachable through stray edges are dead code
+            if !reachable.contains(&block.id) {
+                log::warn!("Skipping block {:?}, which is unreachable", block.id);
+                self.warnings
+                    .push(DecompilerWarning::UnreachableBlock { block_id: block.id });
+                continue;
+            }
+
             // If the block is the end of the module, it is a tail region
             let successors = self.function.get_successors(block.id).map_err(|e| {
                 FunctionDecompilerError::FunctionError {
@@ -342,33 +924,133 @@ impl FunctionDecompiler {
                     context: self.context.as_ref().unwrap().get_error_context(),
                 }
             })?;
-            let region_type = if successors.is_empty() {
+
+            // A block that returns is always a tail region, even if the CFG has edges
+            // out of it by mistake. Those edges are ignored from here on.
+            let is_return = Self::is_return_block(&self.function, block.id);
+            if is_return && !successors.is_empty() {
+                log::warn!(
+                    "Ignoring stray edges out of return block {:?}: {:?}",
+                    block.id,
+                    successors
+                );
+                self.warnings.push(DecompilerWarning::StrayReturnEdges {
+                    block_id: block.id,
+                    targets: successors.clone(),
+                });
+            }
+
+            let region_type = if is_return || successors.is_empty() {
                 RegionType::Tail
             } else {
                 RegionType::Linear
             };
 
+            // The operands of a short-circuit make up a single expression, so they stay
+            // in the region of the block the short-circuit starts in
+            if let Some(region_id) = self.short_circuit_region(block.id)? {
+                if region_type == RegionType::Tail {
+                    self.struct_analysis
+                        .get_region_mut(region_id)
+                        .expect("[Bug] The region should exist.")
+                        .set_region_type(region_type);
+                }
+                self.block_to_region.insert(block.id, region_id);
+                continue;
+            }
+
             let new_region_id = self.struct_analysis.add_region(region_type);
             self.block_to_region.insert(block.id, new_region_id);
         }
         Ok(())
     }
 
-    fn process_regions(&mut self) -> Result<(), FunctionDecompilerError> {
+    /// Get the region of the short-circuit a block is an operand or the end of. That is
+    /// the case if a predecessor ends in a short-circuit. `ShortCircuitEnd` ends a block
+    /// as well, so the rest of the statement is kept in the region too.
+    fn short_circuit_region(
+        &self,
+        block_id: BasicBlockId,
+    ) -> Result<Option<RegionId>, FunctionDecompilerError> {
+        let predecessors = self.function.get_predecessors(block_id).map_err(|e| {
+            FunctionDecompilerError::FunctionError {
+                source: e,
+                backtrace: Backtrace::capture(),
+                context: self.context.as_ref().unwrap().get_error_context(),
+            }
+        })?;
+        Ok(predecessors
+            .into_iter()
+            .filter(|pred_id| Self::is_short_circuit_block(&self.function, *pred_id))
+            .find_map(|pred_id| self.block_to_region.get(&pred_id).copied()))
+    }
+
+    /// Check if a block ends in a `ShortCircuitAnd`, `ShortCircuitOr` or `ShortCircuitEnd`
+    /// instruction
+    fn is_short_circuit_block(function: &Function, block_id: BasicBlockId) -> bool {
+        matches!(
+            function
+                .get_basic_block_by_id(block_id)
+                .ok()
+                .and_then(|block| block.terminator_opcode()),
+            Some(Opcode::ShortCircuitAnd | Opcode::ShortCircuitOr | Opcode::ShortCircuitEnd)
+        )
+    }
+
+    /// Get the blocks reachable from the entry block, without following the stray
+    /// edges out of return blocks
+    fn reachable_blocks(&self) -> Result<HashSet<BasicBlockId>, FunctionDecompilerError> {
+        let mut reachable = HashSet::new();
+        let mut worklist = vec![self.function.get_entry_basic_block_id()];
+        while let Some(block_id) = worklist.pop() {
+            if !reachable.insert(block_id) || Self::is_return_block(&self.function, block_id) {
+                continue;
+            }
+            let successors = self.function.get_successors(block_id).map_err(|e| {
+                FunctionDecompilerError::FunctionError {
+                    source: e,
+                    backtrace: Backtrace::capture(),
+                    context: self.context.as_ref().unwrap().get_error_context(),
+                }
+            })?;
+            worklist.extend(successors);
+        }
+        Ok(reachable)
+    }
+
+    /// Check if a block ends in a `Ret` instruction
+    fn is_return_block(function: &Function, block_id: BasicBlockId) -> bool {
+        function
+            .get_basic_block_by_id(block_id)
+            .ok()
+            .and_then(|block| block.terminator_opcode())
+            == Some(Opcode::Ret)
+    }
+
+    fn process_regions(
+        &mut self,
+        emit_context: &EmitContext,
+    ) -> Result<(), FunctionDecompilerError> {
         // Generate all the regions before doing anything else
         self.generate_regions()?;
 
         let mut ctx = FunctionDecompilerContext::new(self.function.get_entry_basic_block_id());
+        ctx.preserve_casts = emit_context.preserve_casts;
+        ctx.expand_named_constants = emit_context.expand_named_constants;
 
         // Iterate through all the blocks in reverse post order
-        let reverse_post_order = self
+        // Blocks without a region are dead code, see `generate_regions`
+        let reverse_post_order: Vec<_> = self
             .function
             .get_reverse_post_order(self.function.get_entry_basic_block().id)
             .map_err(|e| FunctionDecompilerError::FunctionError {
                 source: e,
                 backtrace: Backtrace::capture(),
                 context: ctx.get_error_context(),
-            })?;
+            })?
+            .into_iter()
+            .filter(|block_id| self.block_to_region.contains_key(block_id))
+            .collect();
 
         for block_id in &reverse_post_order {
             // Get the region id for the block
@@ -384,15 +1066,27 @@ impl FunctionDecompiler {
 
             // Process instructions in the block
             let instructions: Vec<_> = {
-                let block = self
+                // Guard against malformed blocks that continue past their terminator.
+                // Only a copy of the block is truncated, so the function body is still
+                // shared.
+                let mut block = self
                     .function
                     .get_basic_block_by_id(*block_id)
                     .map_err(|e| FunctionDecompilerError::FunctionError {
                         source: e,
                         backtrace: Backtrace::capture(),
                         context: ctx.get_error_context(),
-                    })?;
-                block.iter().cloned().collect()
+                    })?
+                    .clone();
+                let removed = block.truncate_after_terminator();
+                if removed > 0 {
+                    self.warnings
+                        .push(DecompilerWarning::UnreachableInstructions {
+                            block_id: *block_id,
+                            count: removed,
+                        });
+                }
+                block.instructions
             };
 
             // Create a vector of RegionIds for the predecessors
@@ -402,6 +1096,12 @@ impl FunctionDecompiler {
             // For each predecessor block, see what AST nodes are left on the stack
             // and introduce Phi nodes if necessary
             for pred in self.get_predecessors(*block_id)? {
+                // A short-circuit jump carries its operand in the context instead
+                if pred.2 == ControlFlowEdgeType::Branch
+                    && Self::is_short_circuit_block(&self.function, pred.0)
+                {
+                    continue;
+                }
                 let exec = ctx.block_ast_node_stack.get(&pred.0);
 
                 // There's a chance that we haven't processed the predecessor block yet, especially
@@ -439,32 +1139,60 @@ impl FunctionDecompiler {
 
                 // Validate that this predecessor's execution stack length matches what we expect.
                 if !predecessor_regions.is_empty() && exec.len() != predecessor_regions.len() {
-                    // TODO: This will happen with short-circuit operators. We should handle this case
-                    // more gracefully.
                     log::warn!(
                         "Inconsistent number of phi candidates in predecessor block {:?}: expected {}, got {}",
                         pred.0,
                         predecessor_regions.len(),
                         exec.len()
                     );
+                    self.warnings
+                        .push(DecompilerWarning::InconsistentPhiCandidates {
+                            block_id: pred.0,
+                            expected: predecessor_regions.len(),
+                            found: exec.len(),
+                        });
                 }
             }
 
-            // Inject phi nodes into the AST
-            for (index, raw_phi) in predecessor_regions.iter().enumerate() {
+            // Inject phi nodes into the AST. The index counts from the top of the stack,
+            // so the deepest node is pushed first.
+            for (index, raw_phi) in predecessor_regions.iter().enumerate().rev() {
                 if raw_phi.len() == 1 || raw_phi.iter().all(|(_, _, node)| node == &raw_phi[0].2) {
                     // If there's only one predecessor or all nodes are equal, simply push the node onto the stack.
                     let (_, _, node) = &raw_phi[0];
                     ctx.push_one_node(node.clone())?;
                     continue;
                 }
+                self.phi_nodes += 1;
+                if let Some(limit) = self.max_phi_nodes.filter(|limit| self.phi_nodes > *limit) {
+                    return Err(FunctionDecompilerError::TooManyPhiNodes {
+                        limit,
+                        context: ctx.get_error_context(),
+                        backtrace: Backtrace::capture(),
+                    });
+                }
                 let mut phi = new_phi(index);
                 phi.add_regions(raw_phi.iter().map(|x| (x.0, x.1)).collect());
                 ctx.push_one_node(phi.into())?;
             }
 
             for instr in instructions {
-                let processed = ctx.process_instruction(&instr)?;
+                let mut processed = match ctx.process_instruction(&instr) {
+                    Err(FunctionDecompilerError::UnimplementedOpcode { .. })
+                        if emit_context.placeholder_unimplemented =>
+                    {
+                        self.warnings.push(DecompilerWarning::UnimplementedOpcode {
+                            opcode: instr.opcode,
+                            address: instr.address,
+                        });
+                        ctx.process_unimplemented_instruction(&instr)?
+                    }
+                    result => result?,
+                };
+                if emit_context.provenance_comments {
+                    let source = format!("{}@{:#04x}", instr.opcode, instr.address);
+                    Self::tag_provenance(&mut ctx, *block_id, &mut processed, &source);
+                }
                 if let Some(node) = processed.node_to_push {
                     let current_region_id = self
                         .block_to_region
@@ -474,10 +1202,34 @@ impl FunctionDecompiler {
                         .push_to_region(*current_region_id, node);
                 }
 
+                if let Some(ssa_id) = &processed.discarded_ssa_id {
+                    let current_region_id = self
+                        .block_to_region
+                        .get(block_id)
+                        .expect("[Bug] The region should exist.");
+                    let nodes = self
+                        .struct_analysis
+                        .get_region_mut(*current_region_id)
+                        .expect("[Bug] The region should exist.")
+                        .get_nodes_mut();
+                    Self::discard_temporary(nodes, ssa_id);
+                }
+
                 if let Some(params) = processed.function_parameters {
                     self.function_parameters = params;
                 }
 
+                if processed.increases_loop_counter {
+                    let current_region_id = self
+                        .block_to_region
+                        .get(block_id)
+                        .expect("[Bug] The region should exist.");
+                    self.struct_analysis
+                        .get_region_mut(*current_region_id)
+                        .expect("[Bug] The region should exist.")
+                        .set_loop_body(true);
+                }
+
                 if let Some(jmp) = &processed.jump_condition {
                     let current_region_id = self
                         .block_to_region
@@ -501,16 +1253,24 @@ impl FunctionDecompiler {
                 .get(&blk)
                 .expect("[Bug] We just made the regions, so not sure why it doesn't exist.");
 
+            // The nodes left in a short-circuit operand are passed on to the next operand
+            if self
+                .get_successors(blk)?
+                .iter()
+                .any(|(successor, successor_region, _)| {
+                    *successor != blk && *successor_region == region_id
+                })
+            {
+                continue;
+            }
+
             // for any nodes left in the block push them to the region
             let exec = ctx.block_ast_node_stack.get(&blk);
             if let Some(exec) = exec {
+                let mut leftover = Vec::new();
                 for frame in exec.iter().rev() {
                     match frame {
-                        ExecutionFrame::StandaloneNode(n) => {
-                            // Push the unresolved node into the region
-                            self.struct_analysis
-                                .push_unresolved_to_region(region_id, n.clone());
-                        }
+                        ExecutionFrame::StandaloneNode(n) => leftover.push(n.clone()),
                         // TODO: Bug. BuildingArray is another possible frame type.
                         _ => {
                             return Err(FunctionDecompilerError::Other {
@@ -521,6 +1281,51 @@ impl FunctionDecompiler {
                         }
                     }
                 }
+
+                let region = self
+                    .struct_analysis
+                    .get_region_mut(region_id)
+                    .expect("[Bug] The region should exist.");
+                if region.get_region_type() == RegionType::Tail && !leftover.is_empty() {
+                    log::warn!(
+                        "{} value(s) left on the stack at the end of tail block {:?}",
+                        leftover.len(),
+                        blk
+                    );
+                    self.warnings.push(DecompilerWarning::StackImbalance {
+                        block_id: blk,
+                        depth: leftover.len(),
+                        nodes: leftover.iter().rev().map(|n| emit(n.clone())).collect(),
+                    });
+                }
+
+                if region.get_region_type() == RegionType::Tail && self.emit_leftover_stack_values {
+                    // Nothing after a tail region can consume the nodes, so emit them as
+                    // expression statements in the order they were computed, before a
+                    // trailing return. Variable reads and phi nodes have no effect on
+                    // their own, so they are left out.
+                    let statements = leftover.into_iter().rev().filter(|n| {
+                        !matches!(
+                            n,
+                            AstKind::Expression(
+                                ExprKind::Identifier(_)
+                                    | ExprKind::MemberAccess(_)
+                                    | ExprKind::Phi(_)
+                            )
+                        )
+                    });
+                    let nodes = region.get_nodes_mut();
+                    let at = match nodes.last() {
+                        Some(AstKind::Statement(StatementKind::Return(_))) => nodes.len() - 1,
+                        _ => nodes.len(),
+                    };
+                    nodes.splice(at..at, statements);
+                } else {
+                    // Push the unresolved nodes into the region
+                    for n in leftover {
+                        region.push_unresolved_node(n);
+                    }
+                }
             }
         }
 
@@ -529,6 +1334,252 @@ impl FunctionDecompiler {
         Ok(())
     }
 
+    /// Drops the assignment of a temporary whose value was discarded. If evaluating the
+    /// value has side effects, it is kept as an expression statement instead, so
+    /// `fn_call = foo();` becomes `foo();`. Only the last node of the region is checked,
+    /// since a value is discarded right after it is assigned.
+    ///
+    /// # Arguments
+    /// - `nodes`: The nodes of the region the temporary was discarded in.
+    /// - `ssa_id`: The discarded temporary.
+    fn discard_temporary(nodes: &mut Vec<AstKind>, ssa_id: &IdentifierNode) {
+        let Some(AstKind::Statement(StatementKind::Assignment(assignment))) = nodes.last() else {
+            return;
+        };
+        let ExprKind::Identifier(lhs) = &assignment.lhs else {
+            return;
+        };
+        if lhs.id() != ssa_id.id() || lhs.ssa_version != ssa_id.ssa_version {
+            return;
+        }
+        let rhs = assignment.rhs.clone();
+        nodes.pop();
+        if rhs.has_side_effects() {
+            nodes.push(AstKind::Expression(rhs));
+        }
+    }
+
+    /// Use the goto body in place of the structured body, and record why.
+    ///
+    /// # Arguments
+    /// - `goto_body`: The body built by `build_goto_body`.
+    /// - `reason`: Why the structured body could not be used.
+    ///
+    /// # Returns
+    /// - The goto body.
+    fn fall_back_to_goto(&mut self, goto_body: Vec<AstKind>, reason: String) -> Vec<AstKind> {
+        log::warn!("Falling back to goto for {}: {}", self.function.id, reason);
+        self.warnings
+            .push(DecompilerWarning::GotoFallback { reason });
+        goto_body
+    }
+
+    /// Check if a node is, or contains, a virtual branch that structure analysis did
+    /// not resolve.
+    fn contains_virtual_branch(node: &AstKind) -> bool {
+        match node {
+            AstKind::Statement(StatementKind::VirtualBranch(_)) => true,
+            AstKind::ControlFlow(control_flow) => control_flow
+                .body()
+                .instructions
+                .iter()
+                .any(Self::contains_virtual_branch),
+            AstKind::Block(block) => block.instructions.iter().any(Self::contains_virtual_branch),
+            _ => false,
+        }
+    }
+
+    /// Build the body of the function from the unreduced regions, with `goto` statements
+    /// for the edges between blocks and a label at the start of every block they target.
+    ///
+    /// # Returns
+    /// - The statements of the function body.
+    ///
+    /// # Errors
+    /// - `FunctionDecompilerError` if a block or region could not be found.
+    fn build_goto_body(&self) -> Result<Vec<AstKind>, FunctionDecompilerError> {
+        let mut block_ids: Vec<_> = self.function.iter().map(|block| block.id).collect();
+        block_ids.sort_by_key(|block_id| block_id.address);
+
+        let mut body = Vec::new();
+        for (index, block_id) in block_ids.iter().enumerate() {
+            // Unreachable blocks are skipped when the regions are created
+            let Some(region_id) = self.block_to_region.get(block_id).copied() else {
+                continue;
+            };
+            let region = self.struct_analysis.get_region(region_id).map_err(|e| {
+                FunctionDecompilerError::StructureAnalysisError {
+                    source: Box::new(e),
+                    context: self.context.as_ref().unwrap().get_error_context(),
+                    backtrace: Backtrace::capture(),
+                }
+            })?;
+
+            body.push(new_label(&Self::goto_label(*block_id)).into());
+            body.extend(region.iter_nodes().cloned());
+
+            let next_block_id = block_ids.get(index + 1).copied();
+            let mut fallthrough = None;
+            for (successor, _, edge_type) in self.get_successors(*block_id)? {
+                match (edge_type, region.get_jump_expr()) {
+                    (ControlFlowEdgeType::Branch, Some(jump_expr)) => {
+                        // Jump when the branch is the edge taken when the jump expression holds
+                        let condition = if ControlFlowEdgeType::taken_when_condition_holds(
+                            region.get_branch_opcode(),
+                        ) == ControlFlowEdgeType::Branch
+                        {
+                            jump_expr.clone()
+                        } else {
+                            new_logical_not(jump_expr.clone()).map_err(|e| {
+                                FunctionDecompilerError::AstNodeError {
+                                    source: e,
+                                    context: self.context.as_ref().unwrap().get_error_context(),
+                                    backtrace: Backtrace::capture(),
+                                }
+                            })?
+                        };
+                        body.push(
+                            new_if(condition, vec![new_goto(&Self::goto_label(successor))]).into(),
+                        );
+                    }
+                    _ => fallthrough = Some(successor),
+                }
+            }
+
+            if let Some(fallthrough) = fallthrough {
+                if Some(fallthrough) != next_block_id {
+                    body.push(new_goto(&Self::goto_label(fallthrough)).into());
+                }
+            }
+        }
+
+        // Only keep the labels that a `goto` refers to
+        let mut targets = HashSet::new();
+        for node in body.iter() {
+            Self::collect_goto_targets(node, &mut targets);
+        }
+        body.retain(|node| match node {
+            AstKind::Statement(StatementKind::Label(label)) => targets.contains(&label.label),
+            _ => true,
+        });
+        Ok(body)
+    }
+
+    /// Collect the labels of the `goto` statements in a node.
+    fn collect_goto_targets(node: &AstKind, targets: &mut HashSet<String>) {
+        match node {
+            AstKind::Statement(StatementKind::Goto(goto)) => {
+                targets.insert(goto.label.clone());
+            }
+            AstKind::ControlFlow(control_flow) => {
+                for node in control_flow.body().instructions.iter() {
+                    Self::collect_goto_targets(node, targets);
+                }
+            }
+            AstKind::Block(block) => {
+                for node in block.instructions.iter() {
+                    Self::collect_goto_targets(node, targets);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Prefix the nodes of every region with a comment describing its region type, and
+    /// its branch opcode if it has one. This has to happen before structure analysis,
+    /// which merges the regions together.
+    ///
+    /// # Errors
+    /// - `FunctionDecompilerError` if a region could not be found.
+    fn annotate_regions(&mut self) -> Result<(), FunctionDecompilerError> {
+        let mut region_ids: Vec<_> = self.block_to_region.iter().collect();
+        region_ids.sort_by_key(|(block_id, _)| block_id.address);
+
+        for (_, region_id) in region_ids {
+            let region = self
+                .struct_analysis
+                .get_region_mut(*region_id)
+                .map_err(|e| FunctionDecompilerError::StructureAnalysisError {
+                    source: Box::new(e),
+                    context: self.context.as_ref().unwrap().get_error_context(),
+                    backtrace: Backtrace::capture(),
+                })?;
+
+            let annotation = match region.get_branch_opcode() {
+                Some(opcode) => format!("{}: {}", region.get_region_type(), opcode),
+                None => region.get_region_type().to_string(),
+            };
+            let nodes = region.get_nodes().clone();
+            region.clear_nodes();
+            region.push_node(new_comment(&annotation).into());
+            region.push_nodes(nodes);
+        }
+        Ok(())
+    }
+
+    /// Record `source` as the provenance of every node the last instruction produced,
+    /// both the nodes it emitted and the nodes it left on the block's stack. Nodes that
+    /// were produced by an earlier instruction keep their provenance.
+    fn tag_provenance(
+        ctx: &mut FunctionDecompilerContext,
+        block_id: BasicBlockId,
+        processed: &mut ProcessedInstruction,
+        source: &str,
+    ) {
+        if let Some(node) = processed.node_to_push.as_mut() {
+            tag_node(node, source);
+        }
+        if let Some(jump_condition) = processed.jump_condition.as_mut() {
+            tag_expr(jump_condition, source);
+        }
+        if let Some(stack) = ctx.block_ast_node_stack.get_mut(&block_id) {
+            for frame in stack.iter_mut() {
+                match frame {
+                    ExecutionFrame::StandaloneNode(node) => tag_node(node, source),
+                    ExecutionFrame::BuildingArray(elements) => {
+                        for element in elements.iter_mut() {
+                            tag_expr(element, source);
+                        }
+                    }
+                    ExecutionFrame::None => {}
+                }
+            }
+        }
+    }
+
+    /// Get the label used for a block by the goto fallback.
+    fn goto_label(block_id: BasicBlockId) -> String {
+        format!("blk_0x{:X}", block_id.address)
+    }
+
+    /// Get successors of a block and return the results as a vector of tuples
+    fn get_successors(
+        &self,
+        block_id: BasicBlockId,
+    ) -> Result<Vec<(BasicBlockId, RegionId, ControlFlowEdgeType)>, FunctionDecompilerError> {
+        let successors = self.function.get_successors(block_id).map_err(|e| {
+            FunctionDecompilerError::FunctionError {
+                source: e,
+                backtrace: Backtrace::capture(),
+                context: self.context.as_ref().unwrap().get_error_context(),
+            }
+        })?;
+
+        // Edges out of a return block are stray, see `generate_regions`
+        if Self::is_return_block(&self.function, block_id) {
+            return Ok(Vec::new());
+        }
+
+        successors
+            .into_iter()
+            .map(|successor_id| {
+                let region_id = *self.block_to_region.get(&successor_id).unwrap();
+                let edge_type = self.edge_type(block_id, successor_id)?;
+                Ok((successor_id, region_id, edge_type))
+            })
+            .collect()
+    }
+
     /// Get predecessors of a block and return the results as a vector of tuples
     fn get_predecessors(
         &self,
@@ -544,41 +1595,33 @@ impl FunctionDecompiler {
         })?;
 
         // Step 2: Map each predecessor to its region ID and determine the edge type
-        let predecessor_regions: Vec<(BasicBlockId, RegionId, ControlFlowEdgeType)> = predecessors
+        predecessors
             .iter()
+            .filter(|pred_id| {
+                !Self::is_return_block(&self.function, **pred_id)
+                    && self.block_to_region.contains_key(*pred_id)
+            })
             .map(|pred_id| {
                 let pred_region_id = *self.block_to_region.get(pred_id).unwrap();
-
-                // Get the predecessor block
-                let pred_block = self
-                    .function
-                    .get_basic_block_by_id(*pred_id)
-                    .expect("Predecessor block not found");
-
-                // Get the last instruction of the predecessor block
-                // TODO: This is a bug if the block is empty; maybe in this case we should
-                // just get the address of the block?
-                let pred_last_instruction = pred_block.last().expect("Empty block");
-
-                // Get the target block address
-                let target_block = self
-                    .function
-                    .get_basic_block_by_id(block_id)
-                    .expect("Target block not found");
-                let target_address = target_block.id.address;
-
-                // Determine the edge type based on control flow
-                let edge_type = if pred_last_instruction.address + 1 != target_address {
-                    // The target address is NOT the next address, so it's a branch
-                    ControlFlowEdgeType::Branch
-                } else {
-                    ControlFlowEdgeType::Fallthrough
-                };
-
-                (*pred_id, pred_region_id, edge_type)
+                let edge_type = self.edge_type(*pred_id, block_id)?;
+                Ok((*pred_id, pred_region_id, edge_type))
             })
-            .collect();
-        Ok(predecessor_regions)
+            .collect()
+    }
+
+    /// Get the type of the edge between two blocks
+    fn edge_type(
+        &self,
+        source: BasicBlockId,
+        target: BasicBlockId,
+    ) -> Result<ControlFlowEdgeType, FunctionDecompilerError> {
+        self.function.edge_type(source, target).map_err(|e| {
+            FunctionDecompilerError::FunctionError {
+                source: e,
+                backtrace: Backtrace::capture(),
+                context: self.context.as_ref().unwrap().get_error_context(),
+            }
+        })
     }
 
     fn connect_predecessor_regions(
@@ -589,8 +1632,12 @@ impl FunctionDecompiler {
         // Step 1: Get the predecessors of the current block
         let predecessor_regions = self.get_predecessors(block_id)?;
 
-        // Step 2: Connect the predecessor regions to the target region in the graph
-        for (_, pred_region_id, edge_type) in &predecessor_regions {
+        // Step 2: Connect the predecessor regions to the target region in the graph. Edges
+        // within a short-circuit stay inside its region.
+        for (pred_id, pred_region_id, edge_type) in &predecessor_regions {
+            if *pred_region_id == region_id && *pred_id != block_id {
+                continue;
+            }
             self.struct_analysis
                 .connect_regions(*pred_region_id, region_id, *edge_type)
                 .map_err(|e| FunctionDecompilerError::StructureAnalysisError {
@@ -607,6 +1654,36 @@ impl FunctionDecompiler {
 
 // == Other Implementations ==
 
+/// Tag a node and its expressions with their provenance, if they do not have one yet.
+fn tag_node(node: &mut AstKind, source: &str) {
+    match node {
+        AstKind::Statement(stmt) => {
+            if stmt.metadata().get_property(PROVENANCE_PROPERTY).is_none() {
+                stmt.metadata_mut()
+                    .add_property(PROVENANCE_PROPERTY.to_string(), source.to_string());
+            }
+            for expr in stmt.expressions_mut() {
+                tag_expr(expr, source);
+            }
+        }
+        AstKind::Expression(expr) => tag_expr(expr, source),
+        AstKind::Function(_) | AstKind::Block(_) | AstKind::ControlFlow(_) => {}
+    }
+}
+
+/// Tag an expression with its provenance, if it does not have one yet. Tagged
+/// expressions were produced by an earlier instruction, along with their subexpressions.
+fn tag_expr(expr: &mut ExprKind, source: &str) {
+    if expr.metadata().get_property(PROVENANCE_PROPERTY).is_some() {
+        return;
+    }
+    expr.metadata_mut()
+        .add_property(PROVENANCE_PROPERTY.to_string(), source.to_string());
+    for subexpression in expr.subexpressions_mut() {
+        tag_expr(subexpression, source);
+    }
+}
+
 impl FunctionDecompilerErrorDetails for FunctionDecompilerError {
     fn context(&self) -> &FunctionDecompilerErrorContext {
         match self {
@@ -621,6 +1698,8 @@ impl FunctionDecompilerErrorDetails for FunctionDecompilerError {
             FunctionDecompilerError::Other { context, .. } => context,
             FunctionDecompilerError::StructureAnalysisError { context, .. } => context,
             FunctionDecompilerError::RegisterNotFound { context, .. } => context,
+            FunctionDecompilerError::WarningsAsErrors { context, .. } => context,
+            FunctionDecompilerError::TooManyPhiNodes { context, .. } => context,
         }
     }
 
@@ -637,6 +1716,8 @@ impl FunctionDecompilerErrorDetails for FunctionDecompilerError {
             FunctionDecompilerError::Other { backtrace, .. } => backtrace,
             FunctionDecompilerError::StructureAnalysisError { source, .. } => source.backtrace(),
             FunctionDecompilerError::RegisterNotFound { backtrace, .. } => backtrace,
+            FunctionDecompilerError::WarningsAsErrors { backtrace, .. } => backtrace,
+            FunctionDecompilerError::TooManyPhiNodes { backtrace, .. } => backtrace,
         }
     }
 
@@ -663,6 +1744,33 @@ impl FunctionDecompilerErrorDetails for FunctionDecompilerError {
                 "StructureAnalysisError".to_string()
             }
             FunctionDecompilerError::RegisterNotFound { .. } => "RegisterNotFound".to_string(),
+            FunctionDecompilerError::WarningsAsErrors { .. } => "WarningsAsErrors".to_string(),
+            FunctionDecompilerError::TooManyPhiNodes { .. } => "TooManyPhiNodes".to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::decompiler::ast::{block::BlockNode, new_goto, new_id, new_if, AstKind};
+
+    use super::FunctionDecompiler;
+
+    #[test]
+    fn test_collect_goto_targets_in_blocks() {
+        let node: AstKind = BlockNode::new(vec![
+            AstKind::from(new_goto("blk_0x1")),
+            new_if(new_id("a"), vec![new_goto("blk_0x2")]).into(),
+        ])
+        .into();
+
+        let mut targets = HashSet::new();
+        FunctionDecompiler::collect_goto_targets(&node, &mut targets);
+        assert_eq!(
+            targets,
+            HashSet::from(["blk_0x1".to_string(), "blk_0x2".to_string()])
+        );
+    }
+}
@@ -2,20 +2,34 @@
 
 use crate::basic_block::BasicBlockId;
 use crate::instruction::Instruction;
-use crate::opcode::Opcode;
+use crate::opcode::{Opcode, StackEffect};
 use std::backtrace::Backtrace;
 use std::collections::HashMap;
 
+use super::ast::bin_op::BinOpType;
 use super::ast::expr::ExprKind;
 use super::ast::identifier::IdentifierNode;
+use super::ast::node_id::NodeId;
 use super::ast::ptr::P;
 use super::ast::ssa::SsaContext;
-use super::ast::AstKind;
+use super::ast::{new_bin_op, new_comment, new_id_with_version, AstKind};
 use super::execution_frame::ExecutionFrame;
 use super::function_decompiler::{FunctionDecompilerError, FunctionDecompilerErrorContext};
 use super::handlers::{global_opcode_handlers, OpcodeHandler};
+use super::temp_naming::UNIMPLEMENTED_TEMPORARY;
 use super::{ProcessedInstruction, ProcessedInstructionBuilder};
 
+/// A short-circuit operation whose right-hand side is still being decompiled.
+#[derive(Debug, Clone)]
+pub struct ShortCircuitFrame {
+    /// The logical operation, `&&` or `||`.
+    pub op_type: BinOpType,
+    /// The left-hand side of the operation.
+    pub lhs: ExprKind,
+    /// The address the short-circuit jumps to, where the right-hand side ends.
+    pub target: usize,
+}
+
 /// Manages the state of the decompiler, including per-block AST stacks and current processing context.
 pub struct FunctionDecompilerContext {
     /// AST node stacks for each basic block.
@@ -30,6 +44,16 @@ pub struct FunctionDecompilerContext {
     pub current_instruction: Instruction,
     /// Register mapping for the current function
     pub register_mapping: HashMap<usize, ExprKind>,
+    /// If the `ConvertTo*` opcodes should wrap the top of the stack in a cast.
+    pub preserve_casts: bool,
+    /// If named constants, such as `pi`, should be pushed as their numeric value.
+    pub expand_named_constants: bool,
+    /// The address of the `Copy` instruction that produced each copied value, keyed by
+    /// the node ID of the value, so both copies of a value can be matched up later.
+    pub copied_values: HashMap<NodeId, usize>,
+    /// The short-circuit operations that have not reached their jump target yet, with
+    /// the innermost operation last.
+    pub short_circuits: Vec<ShortCircuitFrame>,
 }
 
 impl FunctionDecompilerContext {
@@ -42,6 +66,10 @@ impl FunctionDecompilerContext {
             ssa_context: SsaContext::new(),
             current_instruction: Instruction::default(),
             register_mapping: HashMap::new(),
+            preserve_casts: false,
+            expand_named_constants: false,
+            copied_values: HashMap::new(),
+            short_circuits: Vec::new(),
         }
     }
 
@@ -175,6 +203,48 @@ impl FunctionDecompilerContext {
         Ok(op)
     }
 
+    /// Processes an instruction that has no handler by emitting a placeholder comment,
+    /// such as `unimplemented: Exp@0x0e`, in its place. The values the opcode pops are
+    /// discarded, and the values it pushes are replaced by placeholder SSA identifiers
+    /// so that the rest of the block can still be decompiled.
+    ///
+    /// # Arguments
+    /// - `instr`: The instruction to process.
+    ///
+    /// # Errors
+    /// - Returns `FunctionDecompilerError` if a placeholder cannot be pushed to the stack.
+    pub fn process_unimplemented_instruction(
+        &mut self,
+        instr: &Instruction,
+    ) -> Result<ProcessedInstruction, FunctionDecompilerError> {
+        self.current_instruction = instr.clone();
+
+        if let StackEffect::Fixed { pops, pushes } = instr.opcode.stack_effect() {
+            for _ in 0..pops {
+                // The stack may already be short if the handler failed partway through
+                if self.peek_expression().is_none() {
+                    break;
+                }
+                self.pop_expression()?;
+            }
+            for _ in 0..pushes {
+                let ver = self
+                    .ssa_context
+                    .new_ssa_version_for(UNIMPLEMENTED_TEMPORARY);
+                let ssa_id = new_id_with_version(UNIMPLEMENTED_TEMPORARY, ver);
+                self.push_one_node(ssa_id.into())?;
+            }
+        }
+
+        let comment = new_comment(&format!(
+            "unimplemented: {}@{:#04x}",
+            instr.opcode, instr.address
+        ));
+        Ok(ProcessedInstructionBuilder::new()
+            .push_to_region(comment.into())
+            .build())
+    }
+
     /// If an error happens, this helper function will return the context of the error.
     pub fn get_error_context(&self) -> Box<FunctionDecompilerErrorContext> {
         Box::new(FunctionDecompilerErrorContext {
@@ -203,45 +273,41 @@ impl FunctionDecompilerContext {
             .expect("Critical error: stack should always be set for each basic block");
 
         // Ensure there's a frame to pop from
-        let mut last_frame = stack
+        let last_frame = stack
             .pop()
             .ok_or(FunctionDecompilerError::ExecutionStackEmpty {
                 backtrace: Backtrace::capture(),
                 context: error_context.clone(),
             })?;
 
-        let result = match &mut last_frame {
-            ExecutionFrame::BuildingArray(array) => {
+        // The node is moved off the stack rather than cloned, so it keeps its node ID.
+        match last_frame {
+            ExecutionFrame::BuildingArray(mut array) => {
                 // Pop the node from the array
-                if let Some(node) = array.pop() {
-                    Ok(AstKind::Expression(node))
-                } else {
-                    Err(FunctionDecompilerError::ExecutionStackEmpty {
+                let node = array.pop();
+
+                // Push the last frame back onto the stack, even if it's empty
+                stack.push(ExecutionFrame::BuildingArray(array));
+
+                node.map(AstKind::Expression)
+                    .ok_or(FunctionDecompilerError::ExecutionStackEmpty {
                         backtrace: Backtrace::capture(),
-                        context: error_context.clone(),
+                        context: error_context,
                     })
-                }
             }
-            ExecutionFrame::StandaloneNode(node) => Ok(node.clone()),
+            ExecutionFrame::StandaloneNode(node) => Ok(node),
             ExecutionFrame::None => Err(FunctionDecompilerError::ExecutionStackEmpty {
                 backtrace: Backtrace::capture(),
-                context: error_context.clone(),
+                context: error_context,
             }),
-        };
-
-        // Push the last frame back onto the stack, even if it's empty
-        if let ExecutionFrame::BuildingArray(_) = last_frame {
-            stack.push(last_frame);
         }
-
-        result
     }
 
     /// Pops an expression from the current basic block's stack.
     pub fn pop_expression(&mut self) -> Result<ExprKind, FunctionDecompilerError> {
         let node = self.pop_one_node()?;
         match node {
-            AstKind::Expression(expr) => Ok(expr.clone()),
+            AstKind::Expression(expr) => Ok(expr),
             _ => Err(FunctionDecompilerError::UnexpectedNodeType {
                 expected: "Expression".to_string(),
                 context: self.get_error_context(),
@@ -250,6 +316,23 @@ impl FunctionDecompilerContext {
         }
     }
 
+    /// Returns the expression on top of the current basic block's stack without popping it.
+    ///
+    /// # Returns
+    /// - The expression on top of the stack, or `None` if the stack is empty or its top
+    ///   is not an expression.
+    pub fn peek_expression(&self) -> Option<&ExprKind> {
+        match self
+            .block_ast_node_stack
+            .get(&self.current_block_id)?
+            .last()?
+        {
+            ExecutionFrame::BuildingArray(array) => array.last(),
+            ExecutionFrame::StandaloneNode(AstKind::Expression(expr)) => Some(expr),
+            _ => None,
+        }
+    }
+
     /// Pops an identifier from the current basic block's stack.
     pub fn pop_identifier(&mut self) -> Result<P<IdentifierNode>, FunctionDecompilerError> {
         let node = self.pop_expression()?;
@@ -279,7 +362,7 @@ impl FunctionDecompilerContext {
                 ExecutionFrame::BuildingArray(array) => {
                     // Ensure the node is an expression before adding to the array
                     if let AstKind::Expression(expr) = node {
-                        array.push(expr.clone());
+                        array.push(expr);
                         return Ok(());
                     } else {
                         return Err(FunctionDecompilerError::UnexpectedNodeType {
@@ -299,4 +382,52 @@ impl FunctionDecompilerContext {
         stack.push(ExecutionFrame::StandaloneNode(node));
         Ok(())
     }
+
+    /// Completes the short-circuit operations that jump to an address, innermost first.
+    /// Every jump to the address carries the left-hand side of its operation, and
+    /// falling through to it carries the right-hand side of the innermost one, so
+    /// `a && (b || c)` ends with `c` and completes `b || c`, then `a && (b || c)`.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the instruction being processed.
+    /// - `value`: The value that falls through to the address.
+    ///
+    /// # Returns
+    /// - The value with the completed operations applied.
+    ///
+    /// # Errors
+    /// - Returns `FunctionDecompilerError::AstNodeError` if an operation cannot be built.
+    pub fn end_short_circuits(
+        &mut self,
+        address: usize,
+        mut value: ExprKind,
+    ) -> Result<ExprKind, FunctionDecompilerError> {
+        while let Some(frame) = self.short_circuits.pop_if(|frame| frame.target == address) {
+            value = Self::new_short_circuit(frame.lhs, value, frame.op_type).map_err(|e| {
+                FunctionDecompilerError::AstNodeError {
+                    source: e,
+                    context: self.get_error_context(),
+                    backtrace: Backtrace::capture(),
+                }
+            })?;
+        }
+        Ok(value)
+    }
+
+    /// Builds a short-circuit operation. Both logical operations are associative, so
+    /// `a || (b || c)` is built as `(a || b) || c`, which is emitted without parentheses.
+    fn new_short_circuit(
+        lhs: ExprKind,
+        rhs: ExprKind,
+        op_type: BinOpType,
+    ) -> Result<ExprKind, super::ast::AstNodeError> {
+        match rhs {
+            ExprKind::BinOp(node) if node.op_type == op_type => {
+                let node = node.into_inner();
+                let lhs = Self::new_short_circuit(lhs, node.lhs, op_type.clone())?;
+                Ok(new_bin_op(lhs, node.rhs, op_type)?.into())
+            }
+            rhs => Ok(new_bin_op(lhs, rhs, op_type)?.into()),
+        }
+    }
 }
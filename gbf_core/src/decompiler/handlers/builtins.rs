@@ -5,12 +5,14 @@ use std::backtrace::Backtrace;
 use crate::{
     decompiler::{
         ast::{
-            expr::ExprKind, new_assignment, new_fn_call, new_id, new_id_with_version,
-            new_member_access,
+            expr::ExprKind,
+            func_call::{TRANSLATION_FUNCTION_NAME, TYPE_QUERY_METHOD_NAME},
+            new_assignment, new_fn_call, new_id, new_id_with_version, new_member_access,
         },
         execution_frame::ExecutionFrame,
         function_decompiler::FunctionDecompilerError,
         function_decompiler_context::FunctionDecompilerContext,
+        temp_naming::BUILTIN_CALL_TEMPORARY,
         ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
@@ -79,7 +81,7 @@ impl OpcodeHandler for BuiltinsHandler {
             }
             Opcode::GetTranslation => {
                 let args: Vec<_> = vec![context.pop_expression()?];
-                (new_id("_").into(), args)
+                (new_id(TRANSLATION_FUNCTION_NAME).into(), args)
             }
             Opcode::Min => {
                 let param2 = context.pop_expression()?;
@@ -370,7 +372,7 @@ impl OpcodeHandler for BuiltinsHandler {
             Opcode::ObjType => {
                 let args: Vec<_> = vec![];
                 (
-                    new_member_access(context.pop_expression()?, new_id("type"))
+                    new_member_access(context.pop_expression()?, new_id(TYPE_QUERY_METHOD_NAME))
                         .map_err(|e| FunctionDecompilerError::AstNodeError {
                             source: e,
                             context: context.get_error_context(),
@@ -414,10 +416,19 @@ impl OpcodeHandler for BuiltinsHandler {
             }
         };
 
-        let fn_call = new_fn_call(fn_id, args);
+        // Of the builtins, only the type query is known to have no side effects
+        let fn_call = new_fn_call(fn_id, args).with_pure(instruction.opcode == Opcode::ObjType);
 
-        let var = context.ssa_context.new_ssa_version_for("builtin_fn_call");
-        let ssa_id = new_id_with_version("builtin_fn_call", var);
+        // A pure call can be used in place, since it does not matter when it runs
+        if fn_call.is_pure() {
+            context.push_one_node(fn_call.into())?;
+            return Ok(ProcessedInstructionBuilder::new().build());
+        }
+
+        let var = context
+            .ssa_context
+            .new_ssa_version_for(BUILTIN_CALL_TEMPORARY);
+        let ssa_id = new_id_with_version(BUILTIN_CALL_TEMPORARY, var);
         let stmt = new_assignment(ssa_id.clone(), fn_call);
 
         Ok(ProcessedInstructionBuilder::new()
@@ -0,0 +1,51 @@
+#![deny(missing_docs)]
+
+use std::backtrace::Backtrace;
+
+use crate::{
+    decompiler::{
+        ast::{cast::CastType, new_cast},
+        function_decompiler::FunctionDecompilerError,
+        function_decompiler_context::FunctionDecompilerContext,
+        ProcessedInstruction, ProcessedInstructionBuilder,
+    },
+    instruction::Instruction,
+    opcode::Opcode,
+};
+
+use super::OpcodeHandler;
+
+/// Handles the `ConvertTo*` instructions. These are ignored unless the context
+/// preserves casts, in which case the top of the stack is wrapped in a cast.
+pub struct CastHandler;
+
+impl OpcodeHandler for CastHandler {
+    fn handle_instruction(
+        &self,
+        context: &mut FunctionDecompilerContext,
+        instruction: &Instruction,
+    ) -> Result<ProcessedInstruction, FunctionDecompilerError> {
+        if !context.preserve_casts {
+            return Ok(ProcessedInstructionBuilder::new().build());
+        }
+
+        let cast_type = match instruction.opcode {
+            Opcode::ConvertToFloat => CastType::Float,
+            Opcode::ConvertToObject => CastType::Object,
+            Opcode::ConvertToString => CastType::String,
+            Opcode::ConvertToVariable => CastType::Variable,
+            _ => {
+                return Err(FunctionDecompilerError::UnimplementedOpcode {
+                    opcode: instruction.opcode,
+                    context: context.get_error_context(),
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        };
+
+        let expr = context.pop_expression()?;
+        context.push_one_node(new_cast(expr, cast_type).into())?;
+
+        Ok(ProcessedInstructionBuilder::new().build())
+    }
+}
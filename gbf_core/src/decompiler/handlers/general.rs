@@ -4,9 +4,11 @@ use std::backtrace::Backtrace;
 
 use crate::{
     decompiler::{
+        ast::{expr::ExprKind, AstKind},
         function_decompiler::FunctionDecompilerError,
-        function_decompiler_context::FunctionDecompilerContext, ProcessedInstruction,
-        ProcessedInstructionBuilder,
+        function_decompiler_context::FunctionDecompilerContext,
+        temp_naming::TEMPORARY_NAMES,
+        ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
     opcode::Opcode,
@@ -25,18 +27,26 @@ impl OpcodeHandler for GeneralHandler {
     ) -> Result<ProcessedInstruction, FunctionDecompilerError> {
         match instruction.opcode {
             Opcode::Pop => {
-                // TODO: Handle popping nodes
-                context.pop_one_node()?;
-            }
-            _ => {
-                return Err(FunctionDecompilerError::UnimplementedOpcode {
-                    opcode: instruction.opcode,
-                    context: context.get_error_context(),
-                    backtrace: Backtrace::capture(),
-                });
+                // A discarded value only matters if evaluating it has side effects
+                let builder = ProcessedInstructionBuilder::new();
+                Ok(match context.pop_one_node()? {
+                    AstKind::Expression(ExprKind::Identifier(id))
+                        if id.ssa_version.is_some()
+                            && TEMPORARY_NAMES.contains(&id.id().as_str()) =>
+                    {
+                        builder.discarded_ssa_id(id).build()
+                    }
+                    AstKind::Expression(expr) if expr.has_side_effects() => {
+                        builder.push_to_region(expr.into()).build()
+                    }
+                    _ => builder.build(),
+                })
             }
+            _ => Err(FunctionDecompilerError::UnimplementedOpcode {
+                opcode: instruction.opcode,
+                context: context.get_error_context(),
+                backtrace: Backtrace::capture(),
+            }),
         }
-
-        Ok(ProcessedInstructionBuilder::new().build())
     }
 }
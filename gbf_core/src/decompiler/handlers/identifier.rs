@@ -4,9 +4,11 @@ use std::backtrace::Backtrace;
 
 use crate::{
     decompiler::{
-        ast::new_id_with_version, function_decompiler::FunctionDecompilerError,
-        function_decompiler_context::FunctionDecompilerContext, ProcessedInstruction,
-        ProcessedInstructionBuilder,
+        ast::{expr::ExprTypeHint, new_float, new_id_with_version, ptr::P},
+        constants::named_constant,
+        function_decompiler::FunctionDecompilerError,
+        function_decompiler_context::FunctionDecompilerContext,
+        ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
     opcode::Opcode,
@@ -24,6 +26,16 @@ impl OpcodeHandler for IdentifierHandler {
         instruction: &Instruction,
     ) -> Result<ProcessedInstruction, FunctionDecompilerError> {
         let opcode = instruction.opcode;
+        let constant = named_constant(opcode);
+
+        // Named constants with a value are pushed as a number when they are expanded.
+        if let Some(value) = constant.and_then(|c| c.value) {
+            if context.expand_named_constants {
+                context.push_one_node(new_float(value).into())?;
+                return Ok(ProcessedInstructionBuilder::new().build());
+            }
+        }
+
         // If we have a variable, we need to use the operand as the identifier name.
         let str_operand = if opcode == Opcode::PushVariable {
             let operand = instruction.operand.as_ref().ok_or(
@@ -34,15 +46,23 @@ impl OpcodeHandler for IdentifierHandler {
                 },
             )?;
             operand.to_string()
+        } else if let Some(constant) = constant {
+            constant.name.to_string()
         } else {
             // Otherwise, we can just use the opcode name (e.g. "player", "level", "this", etc.).
             opcode.to_string().to_lowercase()
         };
 
-        let id = new_id_with_version(
+        let mut id = P(new_id_with_version(
             str_operand.as_str(),
             context.ssa_context.current_version_of_or_new(&str_operand),
-        );
-        Ok(ProcessedInstructionBuilder::new().ssa_id(id.into()).build())
+        ));
+
+        // Mark builtin namespaces so they are not confused with variables of the same name.
+        if matches!(opcode, Opcode::This | Opcode::Player) {
+            id.metadata_mut()
+                .set_type_hint(ExprTypeHint::BuiltinNamespace);
+        }
+        Ok(ProcessedInstructionBuilder::new().ssa_id(id).build())
     }
 }
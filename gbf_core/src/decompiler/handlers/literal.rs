@@ -9,6 +9,7 @@ use crate::{
         },
         function_decompiler::FunctionDecompilerError,
         function_decompiler_context::FunctionDecompilerContext,
+        temp_naming::LITERAL_TEMPORARY,
         ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
@@ -85,8 +86,8 @@ impl OpcodeHandler for LiteralHandler {
             }
         };
 
-        let ver = context.ssa_context.new_ssa_version_for("lit");
-        let ssa_id = new_id_with_version("lit", ver);
+        let ver = context.ssa_context.new_ssa_version_for(LITERAL_TEMPORARY);
+        let ssa_id = new_id_with_version(LITERAL_TEMPORARY, ver);
         let stmt = new_assignment(ssa_id.clone(), literal);
 
         Ok(ProcessedInstructionBuilder::new()
@@ -0,0 +1,28 @@
+#![deny(missing_docs)]
+
+use crate::{
+    decompiler::{
+        function_decompiler::FunctionDecompilerError,
+        function_decompiler_context::FunctionDecompilerContext, ProcessedInstruction,
+        ProcessedInstructionBuilder,
+    },
+    instruction::Instruction,
+};
+
+use super::OpcodeHandler;
+
+/// Handles the loop counter instruction. It does not produce any nodes, but GS2 emits it
+/// at the start of every loop body, so structure analysis uses it to recognize `for` loops.
+pub struct LoopCounterHandler;
+
+impl OpcodeHandler for LoopCounterHandler {
+    fn handle_instruction(
+        &self,
+        _context: &mut FunctionDecompilerContext,
+        _instruction: &Instruction,
+    ) -> Result<ProcessedInstruction, FunctionDecompilerError> {
+        Ok(ProcessedInstructionBuilder::new()
+            .increases_loop_counter(true)
+            .build())
+    }
+}
@@ -11,7 +11,7 @@ use variable_operand::VariableOperandHandler;
 use crate::{instruction::Instruction, opcode::Opcode};
 
 use super::{
-    function_decompiler::FunctionDecompilerError,
+    constants::NAMED_CONSTANTS, function_decompiler::FunctionDecompilerError,
     function_decompiler_context::FunctionDecompilerContext, ProcessedInstruction,
 };
 
@@ -19,6 +19,8 @@ use super::{
 pub mod bin_op;
 /// Contains built-in handlers for instructions.
 pub mod builtins;
+/// Handles cast instructions.
+pub mod cast;
 /// Contains general handlers for instructions.
 pub mod general;
 /// Handles identifier instructions.
@@ -27,6 +29,8 @@ pub mod identifier;
 pub mod jump;
 /// Handles literal instructions.
 pub mod literal;
+/// Handles the loop counter instruction.
+pub mod loop_counter;
 /// Handles instructions that are not useful to our decompiler.
 pub mod nop;
 /// Handles short-circuit instructions.
@@ -70,8 +74,8 @@ pub fn global_opcode_handlers() -> &'static HashMap<Opcode, Box<dyn OpcodeHandle
         handlers.insert(Opcode::Pop, Box::new(general::GeneralHandler));
 
         // These handlers are used to create identifier nodes. All of them, with the
-        // exception of `PushVariable`, use the lowercase opcode name as the identifier
-        // name.
+        // exception of `PushVariable` and the named constants, use the lowercase opcode
+        // name as the identifier name.
         handlers.insert(Opcode::Player, Box::new(IdentifierHandler));
         handlers.insert(Opcode::PlayerO, Box::new(IdentifierHandler));
         handlers.insert(Opcode::Temp, Box::new(IdentifierHandler));
@@ -80,7 +84,9 @@ pub fn global_opcode_handlers() -> &'static HashMap<Opcode, Box<dyn OpcodeHandle
         handlers.insert(Opcode::ThisO, Box::new(IdentifierHandler));
         handlers.insert(Opcode::Params, Box::new(IdentifierHandler));
         handlers.insert(Opcode::PushVariable, Box::new(IdentifierHandler));
-        handlers.insert(Opcode::Pi, Box::new(IdentifierHandler));
+        for constant in NAMED_CONSTANTS {
+            handlers.insert(constant.opcode, Box::new(IdentifierHandler));
+        }
 
         // These handlers are used to create literal nodes.
         handlers.insert(Opcode::PushString, Box::new(LiteralHandler));
@@ -121,17 +127,24 @@ pub fn global_opcode_handlers() -> &'static HashMap<Opcode, Box<dyn OpcodeHandle
         handlers.insert(Opcode::Join, Box::new(BinaryOperationHandler));
         handlers.insert(Opcode::Power, Box::new(BinaryOperationHandler));
 
+        // Casts are only emitted when the emit context preserves them
+        handlers.insert(Opcode::ConvertToFloat, Box::new(cast::CastHandler));
+        handlers.insert(Opcode::ConvertToObject, Box::new(cast::CastHandler));
+        handlers.insert(Opcode::ConvertToString, Box::new(cast::CastHandler));
+        handlers.insert(Opcode::ConvertToVariable, Box::new(cast::CastHandler));
+
         // These opcodes do nothing ATM
-        handlers.insert(Opcode::ConvertToFloat, Box::new(NopHandler));
-        handlers.insert(Opcode::ConvertToObject, Box::new(NopHandler));
-        handlers.insert(Opcode::ConvertToString, Box::new(NopHandler));
-        handlers.insert(Opcode::ConvertToVariable, Box::new(NopHandler));
         handlers.insert(Opcode::FunctionStart, Box::new(NopHandler));
-        handlers.insert(Opcode::IncreaseLoopCounter, Box::new(NopHandler));
         handlers.insert(Opcode::Jmp, Box::new(NopHandler));
+        // The marked value, such as `temp.i`, names the local by itself
         handlers.insert(Opcode::MarkRegisterVariable, Box::new(NopHandler));
         handlers.insert(Opcode::WithEnd, Box::new(NopHandler));
-        handlers.insert(Opcode::ShortCircuitEnd, Box::new(NopHandler));
+
+        // The loop counter marks the start of a loop body
+        handlers.insert(
+            Opcode::IncreaseLoopCounter,
+            Box::new(loop_counter::LoopCounterHandler),
+        );
 
         // Three operand handlers
         handlers.insert(
@@ -263,6 +276,10 @@ pub fn global_opcode_handlers() -> &'static HashMap<Opcode, Box<dyn OpcodeHandle
             Opcode::ShortCircuitOr,
             Box::new(short_circuit::ShortCircuitHandler),
         );
+        handlers.insert(
+            Opcode::ShortCircuitEnd,
+            Box::new(short_circuit::ShortCircuitHandler),
+        );
 
         handlers
     })
@@ -4,9 +4,10 @@ use std::backtrace::Backtrace;
 
 use crate::{
     decompiler::{
+        ast::bin_op::BinOpType,
         function_decompiler::FunctionDecompilerError,
-        function_decompiler_context::FunctionDecompilerContext, ProcessedInstruction,
-        ProcessedInstructionBuilder,
+        function_decompiler_context::{FunctionDecompilerContext, ShortCircuitFrame},
+        ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
     opcode::Opcode,
@@ -14,7 +15,9 @@ use crate::{
 
 use super::OpcodeHandler;
 
-/// Handles unary operations.
+/// Handles short-circuit operations. `ShortCircuitAnd` and `ShortCircuitOr` jump past
+/// their right-hand side, either to the `ShortCircuitEnd` of the expression or to an
+/// enclosing short-circuit, so the operation is completed at the jump target.
 pub struct ShortCircuitHandler;
 
 impl OpcodeHandler for ShortCircuitHandler {
@@ -23,30 +26,47 @@ impl OpcodeHandler for ShortCircuitHandler {
         context: &mut FunctionDecompilerContext,
         instruction: &Instruction,
     ) -> Result<ProcessedInstruction, FunctionDecompilerError> {
-        let condition = context.pop_expression()?;
+        // Complete the operations that end here, such as `a && b` in `a && b || c`
+        let value = context.pop_expression()?;
+        let value = context.end_short_circuits(instruction.address, value)?;
 
-        // Push the condition back to the stack
-        context.push_one_node(condition.clone().into())?;
-
-        match instruction.opcode {
-            Opcode::ShortCircuitAnd => {
-                // If the operand is falsy, ShortCircuitAnd will jump and not evaluate the other operand.
-
-                Ok(ProcessedInstructionBuilder::new()
-                    .jump_condition(condition)
-                    .build())
+        let op_type = match instruction.opcode {
+            // If the operand is falsy, ShortCircuitAnd will jump and not evaluate the other operand.
+            Opcode::ShortCircuitAnd => BinOpType::LogicalAnd,
+            // If the operand is truthy, ShortCircuitOr will jump and not evaluate the other operand.
+            Opcode::ShortCircuitOr => BinOpType::LogicalOr,
+            Opcode::ShortCircuitEnd => {
+                context.push_one_node(value.into())?;
+                return Ok(ProcessedInstructionBuilder::new().build());
             }
-            Opcode::ShortCircuitOr => {
-                // If the operand is truthy, ShortCircuitOr will jump and not evaluate the other operand.
-                Ok(ProcessedInstructionBuilder::new()
-                    .jump_condition(condition)
-                    .build())
+            _ => {
+                return Err(FunctionDecompilerError::UnimplementedOpcode {
+                    opcode: instruction.opcode,
+                    context: context.get_error_context(),
+                    backtrace: Backtrace::capture(),
+                })
             }
-            _ => Err(FunctionDecompilerError::UnimplementedOpcode {
+        };
+
+        let target = instruction
+            .operand
+            .as_ref()
+            .ok_or(FunctionDecompilerError::InstructionMustHaveOperand {
                 opcode: instruction.opcode,
                 context: context.get_error_context(),
                 backtrace: Backtrace::capture(),
-            }),
-        }
+            })?
+            .get_number_value()
+            .map_err(|e| FunctionDecompilerError::OperandError {
+                source: e,
+                context: context.get_error_context(),
+                backtrace: Backtrace::capture(),
+            })?;
+        context.short_circuits.push(ShortCircuitFrame {
+            op_type,
+            lhs: value,
+            target: target as usize,
+        });
+        Ok(ProcessedInstructionBuilder::new().build())
     }
 }
@@ -10,6 +10,7 @@ use crate::{
         },
         function_decompiler::FunctionDecompilerError,
         function_decompiler_context::FunctionDecompilerContext,
+        temp_naming::{NEW_OBJECT_TEMPORARY, REGISTER_TEMPORARY},
         ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
@@ -38,8 +39,16 @@ impl OpcodeHandler for SpecialOneOperandHandler {
             }
             Opcode::Copy => {
                 let operand = context.pop_expression()?;
-                context.push_one_node(operand.clone().into())?;
-                context.push_one_node(operand.clone().into())?;
+                let copy = operand.clone();
+
+                // Record both copies so an `Assign` can tell when its value is used inline
+                for value in [&operand, &copy] {
+                    context
+                        .copied_values
+                        .insert(value.node_id(), instruction.address);
+                }
+                context.push_one_node(operand.into())?;
+                context.push_one_node(copy.into())?;
                 Ok(ProcessedInstructionBuilder::new().build())
             }
             Opcode::GetRegister => {
@@ -87,7 +96,9 @@ impl OpcodeHandler for SpecialOneOperandHandler {
 
                 let register_store = context.pop_expression()?;
 
-                // If register_store is an identifier, we can use it directly
+                // If register_store is an identifier, we can use it directly. A named local
+                // is compiled as `temp.<name>`, marked with `MarkRegisterVariable`, and stored
+                // in a register, so reading the register reads the local by its name.
                 let (register_map_add, processed_instruction): (ExprKind, ProcessedInstruction) =
                     match register_store.clone() {
                         ExprKind::Identifier(assignable) => (
@@ -99,8 +110,8 @@ impl OpcodeHandler for SpecialOneOperandHandler {
                             ProcessedInstructionBuilder::new().build(),
                         ),
                         _ => {
-                            let var = context.ssa_context.new_ssa_version_for("set_register");
-                            let ssa_id = new_id_with_version("set_register", var);
+                            let var = context.ssa_context.new_ssa_version_for(REGISTER_TEMPORARY);
+                            let ssa_id = new_id_with_version(REGISTER_TEMPORARY, var);
                             let stmt = new_assignment(ssa_id.clone(), register_store.clone());
                             (
                                 ssa_id.clone().into(),
@@ -178,8 +189,10 @@ impl OpcodeHandler for SpecialOneOperandHandler {
                 let new_type = context.pop_expression()?;
 
                 // Create assignment for new node
-                let var = context.ssa_context.new_ssa_version_for("gbf_new_obj");
-                let ssa_id = new_id_with_version("gbf_new_obj", var);
+                let var = context
+                    .ssa_context
+                    .new_ssa_version_for(NEW_OBJECT_TEMPORARY);
+                let ssa_id = new_id_with_version(NEW_OBJECT_TEMPORARY, var);
                 let stmt = new_assignment(ssa_id.clone(), new_type.clone());
 
                 context.push_one_node(ssa_id.clone().into())?;
@@ -10,6 +10,7 @@ use crate::{
         },
         function_decompiler::FunctionDecompilerError,
         function_decompiler_context::FunctionDecompilerContext,
+        temp_naming::NEW_NODE_TEMPORARY,
         ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
@@ -55,6 +56,7 @@ impl OpcodeHandler for SpecialTwoOperandHandler {
             Opcode::Assign => {
                 let rhs = context.pop_expression()?;
                 let mut lhs = context.pop_expression()?;
+                let copy = context.copied_values.get(&lhs.node_id()).copied();
 
                 // an assignment bumps the version of the lhs, if it's an identifier
                 if let ExprKind::Identifier(mut id) = lhs {
@@ -64,6 +66,20 @@ impl OpcodeHandler for SpecialTwoOperandHandler {
                 }
                 let stmt = new_assignment(lhs, rhs);
 
+                // If the lhs was copied before the assignment and the other copy is still
+                // on the stack, the assigned value is used inline, such as in
+                // `(x = value) > 0`, so the assignment replaces that copy.
+                let value_used = copy.is_some()
+                    && context.peek_expression().is_some_and(|top| {
+                        context.copied_values.get(&top.node_id()).copied() == copy
+                    });
+                if value_used {
+                    context.pop_expression()?;
+                    let expr: ExprKind = stmt.into();
+                    context.push_one_node(expr.into())?;
+                    return Ok(ProcessedInstructionBuilder::new().build());
+                }
+
                 Ok(ProcessedInstructionBuilder::new()
                     .push_to_region(stmt.into())
                     .build())
@@ -89,8 +105,8 @@ impl OpcodeHandler for SpecialTwoOperandHandler {
                     })?;
 
                 // Create SSA ID for the function call
-                let var = context.ssa_context.new_ssa_version_for("new_node");
-                let ssa_id = new_id_with_version("new_node", var);
+                let var = context.ssa_context.new_ssa_version_for(NEW_NODE_TEMPORARY);
+                let ssa_id = new_id_with_version(NEW_NODE_TEMPORARY, var);
                 let stmt = new_assignment(ssa_id.clone(), new_node);
 
                 Ok(ProcessedInstructionBuilder::new()
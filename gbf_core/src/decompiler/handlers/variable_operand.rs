@@ -8,6 +8,7 @@ use crate::{
         execution_frame::ExecutionFrame,
         function_decompiler::FunctionDecompilerError,
         function_decompiler_context::FunctionDecompilerContext,
+        temp_naming::CALL_TEMPORARY,
         ProcessedInstruction, ProcessedInstructionBuilder,
     },
     instruction::Instruction,
@@ -65,8 +66,8 @@ impl OpcodeHandler for VariableOperandHandler {
                     let function_call_node = new_fn_call(function_name, args);
 
                     // Create SSA ID for the function call
-                    let var = context.ssa_context.new_ssa_version_for("fn_call");
-                    let ssa_id = new_id_with_version("fn_call", var);
+                    let var = context.ssa_context.new_ssa_version_for(CALL_TEMPORARY);
+                    let ssa_id = new_id_with_version(CALL_TEMPORARY, var);
                     let stmt = new_assignment(ssa_id.clone(), function_call_node);
 
                     return Ok(ProcessedInstructionBuilder::new()
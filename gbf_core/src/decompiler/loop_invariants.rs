@@ -0,0 +1,435 @@
+#![deny(missing_docs)]
+
+use super::ast::{
+    control_flow::{ControlFlowNode, ControlFlowType},
+    emit,
+    expr::ExprKind,
+    statement::StatementKind,
+    AstKind,
+};
+
+/// Moves the loop-invariant assignments at the top level of each loop body to just
+/// before the loop, so `while (c) { temp.y = temp.a * 2; ... }` becomes
+/// `temp.y = temp.a * 2; while (c) { ... }`.
+///
+/// This is a readability transform, so it is conservative. An assignment is only hoisted
+/// when all of the following hold:
+/// - It assigns a `temp.` variable that is not used outside the loop, and that the loop
+///   does not assign or use anywhere before it.
+/// - Its value has no side effects, and reads nothing the loop assigns. If the loop has
+///   side effects, such as calling a function, the value may only read `temp.` variables.
+/// - The loop has no `with` blocks, gotos, or phi nodes.
+///
+/// # Arguments
+/// - `node`: The node to hoist the loop invariants in, usually a function.
+///
+/// # Example
+/// ```
+/// use gbf_core::decompiler::ast::{
+///     bin_op::BinOpType, emit, new_assignment, new_bin_op, new_fn, new_id, new_member_access,
+///     new_num, new_while,
+/// };
+/// use gbf_core::decompiler::loop_invariants::hoist_loop_invariants;
+///
+/// let temp = |name: &str| new_member_access(new_id("temp"), new_id(name)).unwrap();
+/// let body = vec![
+///     new_assignment(temp("y"), new_bin_op(temp("a"), new_num(2), BinOpType::Mul).unwrap()),
+///     new_assignment(temp("i"), new_bin_op(temp("i"), temp("y"), BinOpType::Add).unwrap()),
+/// ];
+/// let mut node = new_fn(Some("f".to_string()), vec![new_id("x")], vec![new_while(temp("c"), body)]).into();
+/// hoist_loop_invariants(&mut node);
+/// assert!(emit(node).contains("temp.y = temp.a * 2;\n    while (temp.c)"));
+/// ```
+pub fn hoist_loop_invariants(node: &mut AstKind) {
+    let mut function_accesses = Accesses::default();
+    function_accesses.add_node(node);
+    let places = function_accesses.places();
+    hoist_in_node(node, &places);
+}
+
+/// Hoists the loop invariants within a node that contains a list of nodes.
+fn hoist_in_node(node: &mut AstKind, places: &[String]) {
+    match node {
+        AstKind::Function(function) => {
+            hoist_in_nodes(&mut function.body_mut().instructions, places)
+        }
+        AstKind::Block(block) => hoist_in_nodes(&mut block.instructions, places),
+        AstKind::ControlFlow(control_flow) => {
+            hoist_in_nodes(&mut control_flow.body_mut().instructions, places)
+        }
+        _ => {}
+    }
+}
+
+/// Hoists the loop invariants within a list of nodes. Inner loops are handled first,
+/// so an invariant can be hoisted through several loops.
+fn hoist_in_nodes(nodes: &mut Vec<AstKind>, places: &[String]) {
+    let mut index = 0;
+    while index < nodes.len() {
+        hoist_in_node(&mut nodes[index], places);
+        let hoisted = match &mut nodes[index] {
+            AstKind::ControlFlow(control_flow) => hoist_from_loop(control_flow, places),
+            _ => Vec::new(),
+        };
+        let count = hoisted.len();
+        nodes.splice(index..index, hoisted);
+        index += count + 1;
+    }
+}
+
+/// Removes the loop-invariant assignments from the top level of a loop body, and returns
+/// them in order.
+fn hoist_from_loop(control_flow: &mut ControlFlowNode, places: &[String]) -> Vec<AstKind> {
+    if !matches!(
+        control_flow.ty(),
+        ControlFlowType::While | ControlFlowType::For | ControlFlowType::DoWhile
+    ) {
+        return Vec::new();
+    }
+
+    let mut loop_accesses = Accesses::default();
+    loop_accesses.add_control_flow(control_flow);
+    if loop_accesses.unsupported {
+        return Vec::new();
+    }
+    let loop_places = loop_accesses.places();
+
+    // The clauses are treated as running before the body
+    let mut earlier = Accesses::default();
+    if let Some(condition) = control_flow.condition() {
+        earlier.add_expr(condition);
+    }
+    for clause in [control_flow.init(), control_flow.step()]
+        .into_iter()
+        .flatten()
+    {
+        earlier.add_node(clause);
+    }
+    let mut earlier_places = earlier.places();
+
+    let body = &mut control_flow.body_mut().instructions;
+    let mut hoisted = Vec::new();
+    let mut index = 0;
+    while index < body.len() {
+        if is_invariant(
+            &body[index],
+            &loop_accesses,
+            &loop_places,
+            &earlier_places,
+            places,
+        ) {
+            hoisted.push(body.remove(index));
+            continue;
+        }
+        let mut accesses = Accesses::default();
+        accesses.add_node(&body[index]);
+        earlier_places.extend(accesses.places());
+        index += 1;
+    }
+    hoisted
+}
+
+/// Checks if a statement in a loop body is a loop-invariant assignment that can be
+/// hoisted out of the loop.
+///
+/// # Arguments
+/// - `node`: The statement.
+/// - `loop_accesses`: The accesses of the whole loop.
+/// - `loop_places`: Every place the loop reads or assigns.
+/// - `earlier_places`: Every place used in the loop before the statement.
+/// - `places`: Every place used in the function.
+fn is_invariant(
+    node: &AstKind,
+    loop_accesses: &Accesses,
+    loop_places: &[String],
+    earlier_places: &[String],
+    places: &[String],
+) -> bool {
+    let AstKind::Statement(StatementKind::Assignment(assignment)) = node else {
+        return false;
+    };
+    let ExprKind::MemberAccess(member_access) = &assignment.lhs else {
+        return false;
+    };
+    if !matches!(&member_access.lhs, ExprKind::Identifier(id) if id.id() == "temp") {
+        return false;
+    }
+
+    let key = emit(assignment.lhs.clone());
+    let count = |places: &[String]| places.iter().filter(|p| overlaps(p, &key)).count();
+    if count(&loop_accesses.writes) != 1
+        || count(earlier_places) != 0
+        || count(places) != count(loop_places)
+    {
+        return false;
+    }
+
+    if assignment.rhs.has_side_effects() {
+        return false;
+    }
+    let mut value = Accesses::default();
+    value.add_expr(&assignment.rhs);
+    !value.unsupported
+        && value.reads.iter().all(|read| {
+            !loop_accesses
+                .writes
+                .iter()
+                .any(|write| overlaps(read, write))
+                && (!loop_accesses.side_effects || is_local(read))
+        })
+}
+
+/// Checks if a place is a `temp.` variable, which only the function can change.
+fn is_local(place: &str) -> bool {
+    place.starts_with("temp.")
+}
+
+/// Checks if two places may refer to the same storage, which is when they are equal,
+/// or one is a member or element of the other.
+fn overlaps(a: &str, b: &str) -> bool {
+    let contains = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+    };
+    a == b || contains(a, b) || contains(b, a)
+}
+
+/// The places a node reads and assigns, keyed by their emitted form, such as `temp.a`
+/// or `temp.a[temp.i]`.
+#[derive(Debug, Default)]
+struct Accesses {
+    reads: Vec<String>,
+    writes: Vec<String>,
+
+    /// Whether the node may have side effects.
+    side_effects: bool,
+
+    /// Whether the node has anything the accesses cannot be tracked through, such as a
+    /// `with` block, which changes what plain identifiers refer to.
+    unsupported: bool,
+}
+
+impl Accesses {
+    /// Gets every place that is read or assigned.
+    fn places(&self) -> Vec<String> {
+        self.reads
+            .iter()
+            .chain(self.writes.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn add_node(&mut self, node: &AstKind) {
+        match node {
+            AstKind::Expression(expr) => self.add_expr(expr),
+            AstKind::Statement(StatementKind::Assignment(assignment)) => {
+                self.add_write(&assignment.lhs);
+                self.add_expr(&assignment.rhs);
+            }
+            AstKind::Statement(StatementKind::Return(ret)) => self.add_expr(&ret.ret),
+            AstKind::Statement(StatementKind::Comment(_)) => {}
+            AstKind::Statement(_) => self.unsupported = true,
+            AstKind::Block(block) => block.instructions.iter().for_each(|n| self.add_node(n)),
+            AstKind::ControlFlow(control_flow) => self.add_control_flow(control_flow),
+            AstKind::Function(function) => function
+                .body()
+                .instructions
+                .iter()
+                .for_each(|n| self.add_node(n)),
+        }
+    }
+
+    fn add_control_flow(&mut self, control_flow: &ControlFlowNode) {
+        if *control_flow.ty() == ControlFlowType::With {
+            self.unsupported = true;
+        }
+        for clause in [control_flow.init(), control_flow.step()]
+            .into_iter()
+            .flatten()
+        {
+            self.add_node(clause);
+        }
+        if let Some(condition) = control_flow.condition() {
+            self.add_expr(condition);
+        }
+        for node in control_flow.body().instructions.iter() {
+            self.add_node(node);
+        }
+    }
+
+    /// Adds the place an assignment assigns, and the places read to find it, such as
+    /// the index of an element.
+    fn add_write(&mut self, lhs: &ExprKind) {
+        self.writes.push(emit(lhs.clone()));
+        match lhs {
+            ExprKind::MemberAccess(member_access) => {
+                if !matches!(member_access.lhs, ExprKind::Identifier(_)) {
+                    self.add_expr(&member_access.lhs);
+                }
+            }
+            ExprKind::ArrayAccess(array_access) => {
+                self.add_expr(&array_access.arr);
+                self.add_expr(&array_access.index);
+            }
+            _ => {}
+        }
+    }
+
+    fn add_expr(&mut self, expr: &ExprKind) {
+        match expr {
+            ExprKind::Identifier(_) => self.reads.push(emit(expr.clone())),
+            // The object of a member access is part of its place, unless it has to be
+            // evaluated to find it
+            ExprKind::MemberAccess(member_access) => {
+                self.reads.push(emit(expr.clone()));
+                if !matches!(member_access.lhs, ExprKind::Identifier(_)) {
+                    self.add_expr(&member_access.lhs);
+                }
+            }
+            ExprKind::ArrayAccess(array_access) => {
+                self.reads.push(emit(expr.clone()));
+                self.add_expr(&array_access.arr);
+                self.add_expr(&array_access.index);
+            }
+            ExprKind::Assignment(assignment) => {
+                self.side_effects = true;
+                self.add_write(&assignment.lhs);
+                self.add_expr(&assignment.rhs);
+            }
+            ExprKind::Phi(_) => self.unsupported = true,
+            _ => {
+                if matches!(expr, ExprKind::FunctionCall(call) if !call.is_pure())
+                    || matches!(expr, ExprKind::New(_) | ExprKind::NewArray(_))
+                {
+                    self.side_effects = true;
+                }
+                for subexpression in expr.subexpressions() {
+                    self.add_expr(subexpression);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::ast::{
+        bin_op::BinOpType, new_assignment, new_bin_op, new_do_while, new_fn, new_fn_call, new_id,
+        new_member_access, new_num, new_while, new_with,
+    };
+
+    fn temp(name: &str) -> ExprKind {
+        new_member_access(new_id("temp"), new_id(name))
+            .unwrap()
+            .into()
+    }
+
+    fn add(lhs: ExprKind, rhs: ExprKind) -> ExprKind {
+        new_bin_op(lhs, rhs, BinOpType::Add).unwrap().into()
+    }
+
+    fn assign<L: Into<ExprKind>, R: Into<ExprKind>>(lhs: L, rhs: R) -> AstKind {
+        new_assignment(lhs, rhs).into()
+    }
+
+    fn hoist(body: Vec<AstKind>) -> String {
+        let mut function = new_fn(Some("f".to_string()), Vec::<ExprKind>::new(), body).into();
+        hoist_loop_invariants(&mut function);
+        emit(function)
+    }
+
+    /// Checks that `temp.y` is still assigned in the loop.
+    fn assert_kept(body: Vec<AstKind>) {
+        let output = hoist(body);
+        assert!(!output.contains("{\n    temp.y"), "{}", output);
+    }
+
+    #[test]
+    fn test_invariant_hoisted() {
+        let output = hoist(vec![new_while(
+            temp("c"),
+            vec![
+                assign(temp("y"), add(temp("a"), new_num(1).into())),
+                assign(temp("i"), add(temp("i"), temp("y"))),
+            ],
+        )
+        .into()]);
+        assert!(output.contains("{\n    temp.y = temp.a + 1;\n    while (temp.c)"));
+        assert!(output.contains("        temp.i += temp.y;\n"));
+    }
+
+    #[test]
+    fn test_nested_invariant_hoisted_out_of_both_loops() {
+        let inner = new_do_while(
+            temp("d"),
+            vec![
+                assign(temp("y"), new_num(2)),
+                assign(temp("i"), add(temp("i"), temp("y"))),
+            ],
+        );
+        let output = hoist(vec![new_while(temp("c"), vec![inner]).into()]);
+        assert!(output.contains("{\n    temp.y = 2;\n    while (temp.c)"));
+    }
+
+    #[test]
+    fn test_dependent_assignments_kept() {
+        // The value reads a variable the loop assigns
+        assert_kept(vec![new_while(
+            temp("c"),
+            vec![
+                assign(temp("y"), temp("i")),
+                assign(temp("i"), add(temp("i"), temp("y"))),
+            ],
+        )
+        .into()]);
+
+        // The variable is assigned twice
+        assert_kept(vec![new_while(
+            temp("c"),
+            vec![assign(temp("y"), new_num(1)), assign(temp("y"), new_num(2))],
+        )
+        .into()]);
+
+        // The variable is read by the condition before it is assigned
+        assert_kept(vec![new_while(
+            temp("y"),
+            vec![assign(temp("y"), new_num(1))],
+        )
+        .into()]);
+
+        // The variable is used after the loop
+        assert_kept(vec![
+            new_while(temp("c"), vec![assign(temp("y"), new_num(1))]).into(),
+            assign(new_id("x"), temp("y")),
+        ]);
+
+        // The value may be changed by the call in the loop
+        assert_kept(vec![new_while(
+            temp("c"),
+            vec![
+                assign(temp("y"), new_id("x")),
+                AstKind::Expression(new_fn_call(new_id("foo"), vec![]).into()),
+            ],
+        )
+        .into()]);
+
+        // The value has side effects
+        assert_kept(vec![new_while(
+            temp("c"),
+            vec![assign(temp("y"), new_fn_call(new_id("foo"), vec![]))],
+        )
+        .into()]);
+
+        // `with` changes what plain identifiers refer to
+        assert_kept(vec![new_while(
+            temp("c"),
+            vec![
+                assign(temp("y"), new_id("x")),
+                new_with(new_id("obj"), vec![assign(new_id("x"), new_num(1))]).into(),
+            ],
+        )
+        .into()]);
+    }
+}
@@ -4,6 +4,10 @@ use ast::{expr::ExprKind, identifier::IdentifierNode, ptr::P, AstKind};
 
 /// This provides the AST for the decompiler.
 pub mod ast;
+/// The table of named constants, such as `pi`
+pub mod constants;
+/// The options for decompiling every function in a module
+pub mod decompile_options;
 /// The state of execution for the decompiler
 pub mod execution_frame;
 /// This assists in decompiling one function
@@ -12,8 +16,16 @@ pub mod function_decompiler;
 pub mod function_decompiler_context;
 /// This provides the handlers for the decompiler
 pub mod handlers;
+/// Hoists loop-invariant assignments out of structured loops
+pub mod loop_invariants;
+/// This provides the passes that rewrite the AST before structure analysis
+pub mod passes;
+/// Checks that decompiled output is lexically well-formed GS2
+pub mod roundtrip;
 /// This is responsible for control flow analysis
 pub mod structure_analysis;
+/// The naming schemes for the temporaries the decompiler introduces
+pub mod temp_naming;
 
 #[derive(Debug, Clone, Default)]
 /// Builder for constructing a `ProcessedInstruction`.
@@ -22,6 +34,8 @@ pub struct ProcessedInstructionBuilder {
     node_to_push: Option<AstKind>,
     function_parameters: Option<Vec<ExprKind>>,
     jump_condition: Option<ExprKind>,
+    increases_loop_counter: bool,
+    discarded_ssa_id: Option<P<IdentifierNode>>,
 }
 
 impl ProcessedInstructionBuilder {
@@ -32,6 +46,8 @@ impl ProcessedInstructionBuilder {
             node_to_push: None,
             function_parameters: None,
             jump_condition: None,
+            increases_loop_counter: false,
+            discarded_ssa_id: None,
         }
     }
 
@@ -83,6 +99,30 @@ impl ProcessedInstructionBuilder {
         self
     }
 
+    /// Marks the processed instruction as increasing the loop counter.
+    ///
+    /// # Arguments
+    /// - `increases_loop_counter`: Whether the instruction increases the loop counter.
+    ///
+    /// # Returns
+    /// A mutable reference to the builder for chaining.
+    pub fn increases_loop_counter(mut self, increases_loop_counter: bool) -> Self {
+        self.increases_loop_counter = increases_loop_counter;
+        self
+    }
+
+    /// Sets the temporary whose value was discarded by the processed instruction.
+    ///
+    /// # Arguments
+    /// - `discarded_ssa_id`: The SSA ID of the discarded temporary.
+    ///
+    /// # Returns
+    /// A mutable reference to the builder for chaining.
+    pub fn discarded_ssa_id(mut self, discarded_ssa_id: P<IdentifierNode>) -> Self {
+        self.discarded_ssa_id = Some(discarded_ssa_id);
+        self
+    }
+
     /// Builds the `ProcessedInstruction` instance.
     ///
     /// # Returns
@@ -93,6 +133,8 @@ impl ProcessedInstructionBuilder {
             node_to_push: self.node_to_push,
             function_parameters: self.function_parameters,
             jump_condition: self.jump_condition,
+            increases_loop_counter: self.increases_loop_counter,
+            discarded_ssa_id: self.discarded_ssa_id,
         }
     }
 }
@@ -108,4 +150,10 @@ pub struct ProcessedInstruction {
     pub function_parameters: Option<Vec<ExprKind>>,
     /// The jump condition
     pub jump_condition: Option<ExprKind>,
+    /// If the instruction increases the loop counter, which GS2 does at the start of
+    /// every loop body. Returned with Opcode::IncreaseLoopCounter.
+    pub increases_loop_counter: bool,
+    /// A temporary whose value was discarded, so the statement that assigned it only
+    /// needs its side effects. Returned with Opcode::Pop.
+    pub discarded_ssa_id: Option<P<IdentifierNode>>,
 }
@@ -0,0 +1,358 @@
+#![deny(missing_docs)]
+
+use std::collections::HashMap;
+
+use crate::decompiler::{
+    ast::{
+        bin_op::BinOpType, expr::ExprKind, literal::LiteralNode, ssa::SsaVersion,
+        statement::StatementKind, unary_op::UnaryOpType, AstKind,
+    },
+    structure_analysis::{region::RegionId, StructureAnalysis, StructureAnalysisError},
+};
+
+use super::AstPass;
+
+/// An SSA value, identified by its name and version.
+type SsaKey = (String, SsaVersion);
+
+/// Substitutes literals assigned to SSA values into the later uses of those values
+/// within the same block, and folds the operations whose operands are then all
+/// literals, so `lit = 5; y = lit + 1;` becomes `y = 6;`.
+///
+/// The pass is conservative across blocks: values that are used by another block are
+/// still substituted within their own block, but their assignment is kept so the other
+/// block, and any phi node, can still refer to them.
+#[derive(Debug, Default)]
+pub struct ConstantPropagationPass;
+
+impl AstPass for ConstantPropagationPass {
+    fn name(&self) -> &'static str {
+        "constant_propagation"
+    }
+
+    fn run(&mut self, analysis: &mut StructureAnalysis) -> Result<(), StructureAnalysisError> {
+        let region_ids = analysis.region_ids();
+
+        // Count the assignments to each SSA value, and which regions use it
+        let mut definitions: HashMap<SsaKey, usize> = HashMap::new();
+        let mut uses: HashMap<SsaKey, Vec<RegionId>> = HashMap::new();
+        for region_id in region_ids.iter() {
+            let region = analysis.get_region_mut(*region_id)?;
+            let mut record_use = |expr: &mut ExprKind| {
+                if let Some(key) = ssa_key(expr) {
+                    uses.entry(key).or_default().push(*region_id);
+                }
+            };
+
+            for node in region.get_nodes_mut().iter_mut() {
+                count_definitions(node, &mut definitions);
+                walk_node(node, &mut record_use);
+            }
+            for mut node in region.get_unresolved_nodes().clone() {
+                walk_node(&mut node, &mut record_use);
+            }
+            if let Some(mut jump_expr) = region.get_jump_expr().cloned() {
+                walk_expr(&mut jump_expr, &mut record_use);
+            }
+        }
+
+        for region_id in region_ids {
+            let region = analysis.get_region_mut(region_id)?;
+            let mut literals: HashMap<SsaKey, ExprKind> = HashMap::new();
+            let mut nodes = Vec::new();
+
+            for mut node in region.get_nodes().clone() {
+                walk_node(&mut node, &mut |expr| substitute_and_fold(expr, &literals));
+
+                if let Some((key, literal)) = literal_assignment(&node) {
+                    // SSA values should only be assigned once, but we can't propagate
+                    // a value that is not.
+                    if definitions.get(&key) == Some(&1) {
+                        let used_elsewhere = uses
+                            .get(&key)
+                            .is_some_and(|regions| regions.iter().any(|r| *r != region_id));
+                        literals.insert(key, literal);
+                        if !used_elsewhere {
+                            continue;
+                        }
+                    }
+                }
+                nodes.push(node);
+            }
+
+            region.clear_nodes();
+            region.push_nodes(nodes);
+
+            let mut unresolved = region.get_unresolved_nodes().clone();
+            for node in unresolved.iter_mut() {
+                walk_node(node, &mut |expr| substitute_and_fold(expr, &literals));
+            }
+            region.clear_unresolved_nodes();
+            region.push_unresolved_nodes(unresolved);
+
+            if let Some(mut jump_expr) = region.get_jump_expr().cloned() {
+                walk_expr(&mut jump_expr, &mut |expr| {
+                    substitute_and_fold(expr, &literals)
+                });
+                region.set_jump_expr(Some(jump_expr));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the SSA value an expression refers to, if it is an SSA identifier.
+fn ssa_key(expr: &ExprKind) -> Option<SsaKey> {
+    match expr {
+        ExprKind::Identifier(id) => id.ssa_version.map(|version| (id.id().clone(), version)),
+        _ => None,
+    }
+}
+
+/// Counts the assignments to each SSA value within a node, including those nested in
+/// structured nodes.
+fn count_definitions(node: &AstKind, definitions: &mut HashMap<SsaKey, usize>) {
+    let nested = match node {
+        AstKind::Statement(StatementKind::Assignment(assignment)) => {
+            if let Some(key) = ssa_key(&assignment.lhs) {
+                *definitions.entry(key).or_default() += 1;
+            }
+            return;
+        }
+        AstKind::Block(block) => block.instructions.iter().collect(),
+        AstKind::ControlFlow(control_flow) => {
+            let mut nested: Vec<_> = control_flow.body().instructions.iter().collect();
+            nested.extend(control_flow.init().iter().chain(control_flow.step()));
+            nested
+        }
+        AstKind::Function(function) => function.body().instructions.iter().collect(),
+        _ => Vec::new(),
+    };
+    for node in nested {
+        count_definitions(node, definitions);
+    }
+}
+
+/// Gets the SSA value and literal of a node that assigns a literal to an SSA identifier.
+fn literal_assignment(node: &AstKind) -> Option<(SsaKey, ExprKind)> {
+    match node {
+        AstKind::Statement(StatementKind::Assignment(assignment))
+            if matches!(assignment.rhs, ExprKind::Literal(_)) =>
+        {
+            ssa_key(&assignment.lhs).map(|key| (key, assignment.rhs.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Replaces an SSA identifier with its literal, if one is known, and folds an
+/// operation whose operands are all literals.
+fn substitute_and_fold(expr: &mut ExprKind, literals: &HashMap<SsaKey, ExprKind>) {
+    if let Some(literal) = ssa_key(expr).and_then(|key| literals.get(&key)) {
+        *expr = literal.clone();
+    }
+
+    let folded = match expr {
+        ExprKind::BinOp(bin_op) => match (&bin_op.lhs, &bin_op.rhs) {
+            (ExprKind::Literal(lhs), ExprKind::Literal(rhs)) => {
+                fold_bin_op(lhs, rhs, &bin_op.op_type)
+            }
+            _ => None,
+        },
+        ExprKind::UnaryOp(unary_op) => match &unary_op.operand {
+            ExprKind::Literal(operand) => fold_unary_op(operand, &unary_op.op_type),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(literal) = folded {
+        *expr = literal.into();
+    }
+}
+
+/// Folds a binary operation on two literals. GS2 numbers are floating point, so only
+/// the integer arithmetic whose result is exact is folded; `5 / 2` is left as it is.
+fn fold_bin_op(lhs: &LiteralNode, rhs: &LiteralNode, op_type: &BinOpType) -> Option<LiteralNode> {
+    match (lhs, rhs) {
+        (LiteralNode::Number(a), LiteralNode::Number(b)) => match op_type {
+            BinOpType::Add => a.checked_add(*b).map(LiteralNode::Number),
+            BinOpType::Sub => a.checked_sub(*b).map(LiteralNode::Number),
+            BinOpType::Mul => a.checked_mul(*b).map(LiteralNode::Number),
+            BinOpType::Equal => Some(LiteralNode::Boolean(a == b)),
+            BinOpType::NotEqual => Some(LiteralNode::Boolean(a != b)),
+            BinOpType::Less => Some(LiteralNode::Boolean(a < b)),
+            BinOpType::LessOrEqual => Some(LiteralNode::Boolean(a <= b)),
+            BinOpType::Greater => Some(LiteralNode::Boolean(a > b)),
+            BinOpType::GreaterOrEqual => Some(LiteralNode::Boolean(a >= b)),
+            _ => None,
+        },
+        (LiteralNode::Boolean(a), LiteralNode::Boolean(b)) => match op_type {
+            BinOpType::LogicalAnd => Some(LiteralNode::Boolean(*a && *b)),
+            BinOpType::LogicalOr => Some(LiteralNode::Boolean(*a || *b)),
+            BinOpType::Equal => Some(LiteralNode::Boolean(a == b)),
+            BinOpType::NotEqual => Some(LiteralNode::Boolean(a != b)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Folds a unary operation on a literal.
+fn fold_unary_op(operand: &LiteralNode, op_type: &UnaryOpType) -> Option<LiteralNode> {
+    match (operand, op_type) {
+        (LiteralNode::Number(n), UnaryOpType::Negate) => n.checked_neg().map(LiteralNode::Number),
+        (LiteralNode::Boolean(b), UnaryOpType::LogicalNot) => Some(LiteralNode::Boolean(!b)),
+        _ => None,
+    }
+}
+
+/// Calls `f` on every expression a node reads, including those nested in structured
+/// nodes. The identifier an assignment writes to is not visited.
+fn walk_node<F: FnMut(&mut ExprKind)>(node: &mut AstKind, f: &mut F) {
+    match node {
+        AstKind::Expression(expr) => walk_expr(expr, f),
+        AstKind::Statement(StatementKind::Assignment(assignment)) => {
+            if !matches!(assignment.lhs, ExprKind::Identifier(_)) {
+                walk_expr(&mut assignment.lhs, f);
+            }
+            walk_expr(&mut assignment.rhs, f);
+        }
+        AstKind::Statement(StatementKind::Return(ret)) => walk_expr(&mut ret.ret, f),
+        AstKind::Statement(_) => {}
+        AstKind::Block(block) => {
+            for node in block.instructions.iter_mut() {
+                walk_node(node, f);
+            }
+        }
+        AstKind::ControlFlow(control_flow) => {
+            if let Some(condition) = control_flow.condition_mut() {
+                walk_expr(condition, f);
+            }
+            for node in control_flow.body_mut().instructions.iter_mut() {
+                walk_node(node, f);
+            }
+            let (init, step) = control_flow.for_clauses_mut();
+            for node in init.iter_mut().chain(step.iter_mut()) {
+                walk_node(node, f);
+            }
+        }
+        AstKind::Function(function) => {
+            for node in function.body_mut().instructions.iter_mut() {
+                walk_node(node, f);
+            }
+        }
+    }
+}
+
+/// Calls `f` on each subexpression of an expression, and then on the expression, so an
+/// operation is only folded after its operands are.
+fn walk_expr<F: FnMut(&mut ExprKind)>(expr: &mut ExprKind, f: &mut F) {
+    for subexpression in expr.subexpressions_mut() {
+        walk_expr(subexpression, f);
+    }
+    f(expr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::{
+        ast::{
+            bin_op::BinOpType, emit, new_assignment, new_bin_op, new_id, new_id_with_version,
+            new_if, new_num, new_return, new_unary_op, unary_op::UnaryOpType,
+        },
+        structure_analysis::{region::RegionType, ControlFlowEdgeType},
+    };
+
+    #[test]
+    fn test_constant_propagation() -> Result<(), StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let entry = analysis.add_region(RegionType::Linear);
+        let exit = analysis.add_region(RegionType::Tail);
+        analysis.connect_regions(entry, exit, ControlFlowEdgeType::Fallthrough)?;
+
+        let five = new_id_with_version("lit", 0.into());
+        let six = new_id_with_version("lit", 1.into());
+        analysis.push_to_region(entry, new_assignment(five.clone(), new_num(5)));
+        analysis.push_to_region(entry, new_assignment(six.clone(), new_num(6)));
+        analysis.push_to_region(
+            entry,
+            new_assignment(
+                new_id("y"),
+                new_bin_op(five.clone(), six.clone(), BinOpType::Add).unwrap(),
+            ),
+        );
+        // `six` flows into the next block, so its assignment has to stay
+        analysis.push_to_region(exit, new_return(six.clone()));
+
+        ConstantPropagationPass.run(&mut analysis)?;
+
+        let entry_nodes: Vec<_> = analysis
+            .get_region(entry)?
+            .iter_nodes()
+            .map(|node| emit(node.clone()))
+            .collect();
+        assert_eq!(entry_nodes, vec!["lit = 6;", "y = 11;"]);
+
+        let exit_nodes: Vec<_> = analysis
+            .get_region(exit)?
+            .iter_nodes()
+            .map(|node| emit(node.clone()))
+            .collect();
+        assert_eq!(exit_nodes, vec!["return lit;"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_constant_propagation_structured() -> Result<(), StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let entry = analysis.add_region(RegionType::Tail);
+
+        let five = new_id_with_version("lit", 0.into());
+        analysis.push_to_region(entry, new_assignment(five.clone(), new_num(5)));
+        analysis.push_to_region(
+            entry,
+            new_if(
+                new_bin_op(five.clone(), new_num(3), BinOpType::Greater).unwrap(),
+                vec![new_assignment(
+                    new_id("y"),
+                    new_unary_op(five.clone(), UnaryOpType::Negate).unwrap(),
+                )],
+            ),
+        );
+        analysis.push_to_region(
+            entry,
+            new_return(new_bin_op(five.clone(), new_num(2), BinOpType::Div).unwrap()),
+        );
+
+        ConstantPropagationPass.run(&mut analysis)?;
+
+        let nodes: Vec<_> = analysis
+            .get_region(entry)?
+            .iter_nodes()
+            .map(|node| emit(node.clone()))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec!["if (true) \n{\n    y = -5;\n}", "return 5 / 2;"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_constant_propagation_reassigned() -> Result<(), StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let entry = analysis.add_region(RegionType::Tail);
+
+        let x = new_id_with_version("x", 0.into());
+        analysis.push_to_region(entry, new_assignment(x.clone(), new_num(1)));
+        analysis.push_to_region(entry, new_assignment(x.clone(), new_num(2)));
+        analysis.push_to_region(entry, new_return(x.clone()));
+
+        ConstantPropagationPass.run(&mut analysis)?;
+
+        assert_eq!(analysis.get_region(entry)?.get_nodes().len(), 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+#![deny(missing_docs)]
+
+use std::sync::Arc;
+
+use super::structure_analysis::{StructureAnalysis, StructureAnalysisError};
+
+/// Propagates literal values into later uses within a block.
+pub mod constant_propagation;
+/// Removes assignments of a value to itself.
+pub mod self_assignment;
+/// Concatenates adjacent string literals in `@` chains.
+pub mod string_concat;
+/// Hoists the statements shared by the end of every branch below the merge point.
+pub mod tail_merge;
+/// Rewrites comparisons with zero in branch conditions to truthiness tests.
+pub mod zero_comparison;
+
+/// A pass that rewrites the AST of a function. Passes run after the regions are built
+/// and before structure analysis, so each region still holds the statements of a
+/// single block.
+pub trait AstPass {
+    /// The name of the pass.
+    fn name(&self) -> &'static str;
+
+    /// Runs the pass over the regions.
+    ///
+    /// # Arguments
+    /// - `analysis`: The structure analysis holding the regions to rewrite.
+    ///
+    /// # Errors
+    /// - `StructureAnalysisError` if a region could not be found.
+    fn run(&mut self, analysis: &mut StructureAnalysis) -> Result<(), StructureAnalysisError>;
+}
+
+/// Creates a new instance of a pass for each function that is decompiled, since passes
+/// may keep state while they run.
+pub type AstPassFactory = Arc<dyn Fn() -> Box<dyn AstPass> + Send + Sync>;
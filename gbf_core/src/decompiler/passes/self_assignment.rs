@@ -0,0 +1,127 @@
+#![deny(missing_docs)]
+
+use crate::decompiler::{
+    ast::{expr::ExprKind, statement::StatementKind, AstKind},
+    structure_analysis::{StructureAnalysis, StructureAnalysisError},
+};
+
+use super::AstPass;
+
+/// Removes assignments of a value to itself, such as `x = x;`, `temp.a[temp.i] =
+/// temp.a[temp.i];` and `this.x = this.x;`.
+///
+/// An array index or member access is only dropped if it has no side effects, so
+/// `temp.a[f()] = temp.a[f()];` is kept, since removing it would skip both calls.
+#[derive(Debug, Default)]
+pub struct SelfAssignmentPass;
+
+impl AstPass for SelfAssignmentPass {
+    fn name(&self) -> &'static str {
+        "self_assignment"
+    }
+
+    fn run(&mut self, analysis: &mut StructureAnalysis) -> Result<(), StructureAnalysisError> {
+        for region_id in analysis.region_ids() {
+            let region = analysis.get_region_mut(region_id)?;
+            if !region.iter_nodes().any(is_self_assignment) {
+                continue;
+            }
+
+            let nodes: Vec<_> = region
+                .get_nodes()
+                .iter()
+                .filter(|node| !is_self_assignment(node))
+                .cloned()
+                .collect();
+            region.clear_nodes();
+            region.push_nodes(nodes);
+        }
+        Ok(())
+    }
+}
+
+/// Checks if a node assigns a value to itself without side effects.
+fn is_self_assignment(node: &AstKind) -> bool {
+    let AstKind::Statement(StatementKind::Assignment(assignment)) = node else {
+        return false;
+    };
+    match (&assignment.lhs, &assignment.rhs) {
+        // The assignment bumps the SSA version of the lhs, so only the names can match
+        (ExprKind::Identifier(lhs), ExprKind::Identifier(rhs)) => lhs.id() == rhs.id(),
+        (lhs @ (ExprKind::ArrayAccess(_) | ExprKind::MemberAccess(_)), rhs) => {
+            lhs == rhs && !lhs.has_side_effects()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::{
+        ast::{
+            emit, new_array_access, new_assignment, new_fn_call, new_id, new_id_with_version,
+            new_member_access, new_num,
+        },
+        structure_analysis::region::RegionType,
+    };
+
+    fn run_pass(nodes: Vec<AstKind>) -> Result<Vec<String>, StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let region = analysis.add_region(RegionType::Linear);
+        for node in nodes {
+            analysis.push_to_region(region, node);
+        }
+
+        SelfAssignmentPass.run(&mut analysis)?;
+
+        Ok(analysis
+            .get_region(region)?
+            .iter_nodes()
+            .map(|node| emit(node.clone()))
+            .collect())
+    }
+
+    fn element(index: ExprKind) -> ExprKind {
+        let arr = new_member_access(new_id("temp"), new_id("a")).unwrap();
+        new_array_access(arr, index).into()
+    }
+
+    #[test]
+    fn test_self_assignment_removed() -> Result<(), StructureAnalysisError> {
+        let index = new_member_access(new_id("temp"), new_id("i")).unwrap();
+        let member = new_member_access(new_id("this"), new_id("x")).unwrap();
+        let nodes = run_pass(vec![
+            new_assignment(
+                new_id_with_version("x", 1.into()),
+                new_id_with_version("x", 0.into()),
+            )
+            .into(),
+            new_assignment(element(index.clone().into()), element(index.into())).into(),
+            new_assignment(member.clone(), member).into(),
+            new_assignment(new_id("y"), new_num(1)).into(),
+        ])?;
+        assert_eq!(nodes, vec!["y = 1;"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_assignment_kept() -> Result<(), StructureAnalysisError> {
+        let call = new_fn_call(new_id("f"), vec![]);
+        let nodes = run_pass(vec![
+            // The index is evaluated twice, so removing the statement skips both calls
+            new_assignment(element(call.clone().into()), element(call.into())).into(),
+            new_assignment(element(new_num(1).into()), element(new_num(2).into())).into(),
+            new_assignment(new_id("x"), new_id("y")).into(),
+        ])?;
+        assert_eq!(
+            nodes,
+            vec![
+                "temp.a[f()] = temp.a[f()];",
+                "temp.a[1] = temp.a[2];",
+                "x = y;"
+            ]
+        );
+        Ok(())
+    }
+}
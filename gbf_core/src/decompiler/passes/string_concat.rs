@@ -0,0 +1,188 @@
+#![deny(missing_docs)]
+
+use crate::decompiler::{
+    ast::{
+        bin_op::{BinOpType, BinaryOperationNode},
+        expr::ExprKind,
+        literal::LiteralNode,
+        AstKind,
+    },
+    structure_analysis::{StructureAnalysis, StructureAnalysisError},
+};
+
+use super::AstPass;
+
+/// Concatenates adjacent string literals in `@` chains, so `x @ "a" @ "b"` becomes
+/// `x @ "ab"`.
+///
+/// Concatenation is associative, so a chain is flattened into its operands before
+/// the literals are merged, and rebuilt as the left-leaning chain GS2 compiles it to.
+/// Only string literals are merged, since other literals, such as floats, may not be
+/// converted to the same text they were written as.
+///
+/// The decompiler pushes each literal through a `lit` temporary, so this pass only
+/// sees the literals of a chain if `ConstantPropagationPass` runs before it.
+#[derive(Debug, Default)]
+pub struct StringConcatPass;
+
+impl AstPass for StringConcatPass {
+    fn name(&self) -> &'static str {
+        "string_concat"
+    }
+
+    fn run(&mut self, analysis: &mut StructureAnalysis) -> Result<(), StructureAnalysisError> {
+        for region_id in analysis.region_ids() {
+            let region = analysis.get_region_mut(region_id)?;
+            for node in region.get_nodes_mut().iter_mut() {
+                fold_node(node);
+            }
+            for node in region.get_unresolved_nodes_mut().iter_mut() {
+                fold_node(node);
+            }
+            if let Some(mut jump_expr) = region.get_jump_expr().cloned() {
+                fold_expr(&mut jump_expr);
+                region.set_jump_expr(Some(jump_expr));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Folds the `@` chains in the expressions of a statement.
+fn fold_node(node: &mut AstKind) {
+    match node {
+        AstKind::Expression(expr) => fold_expr(expr),
+        AstKind::Statement(stmt) => {
+            for expr in stmt.expressions_mut() {
+                fold_expr(expr);
+            }
+        }
+        // Structured nodes only exist after structure analysis, which runs after the passes
+        _ => {}
+    }
+}
+
+/// Folds the `@` chains in an expression and its subexpressions.
+fn fold_expr(expr: &mut ExprKind) {
+    for subexpression in expr.subexpressions_mut() {
+        fold_expr(subexpression);
+    }
+    if !is_join(expr) {
+        return;
+    }
+
+    let mut operands = Vec::new();
+    flatten_join(expr.clone(), &mut operands);
+    let operand_count = operands.len();
+    let mut merged: Vec<ExprKind> = Vec::with_capacity(operand_count);
+    for operand in operands {
+        match (merged.last_mut(), &operand) {
+            (Some(ExprKind::Literal(last)), ExprKind::Literal(next)) => {
+                match (&mut **last, next.as_ref()) {
+                    (LiteralNode::String(last), LiteralNode::String(next)) => {
+                        last.push_str(next);
+                    }
+                    _ => merged.push(operand),
+                }
+            }
+            _ => merged.push(operand),
+        }
+    }
+
+    // Rebuilding the chain drops the metadata of its operations, so only do so if
+    // literals were merged
+    if merged.len() == operand_count {
+        return;
+    }
+
+    let mut merged = merged.into_iter();
+    let first = merged.next().expect("A chain has at least two operands");
+    *expr = merged.fold(first, |lhs, rhs| {
+        BinaryOperationNode {
+            lhs,
+            rhs,
+            op_type: BinOpType::Join,
+        }
+        .into()
+    });
+}
+
+/// Collects the operands of an `@` chain, from left to right.
+fn flatten_join(expr: ExprKind, operands: &mut Vec<ExprKind>) {
+    match expr {
+        ExprKind::BinOp(bin_op) if bin_op.op_type == BinOpType::Join => {
+            flatten_join(bin_op.lhs.clone(), operands);
+            flatten_join(bin_op.rhs.clone(), operands);
+        }
+        _ => operands.push(expr),
+    }
+}
+
+/// Checks if an expression is an `@` operation.
+fn is_join(expr: &ExprKind) -> bool {
+    matches!(expr, ExprKind::BinOp(bin_op) if bin_op.op_type == BinOpType::Join)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::{
+        ast::{emit, new_assignment, new_bin_op, new_id, new_num, new_str},
+        structure_analysis::region::RegionType,
+    };
+
+    fn run_pass(node: AstKind) -> Result<String, StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let region = analysis.add_region(RegionType::Linear);
+        analysis.push_to_region(region, node);
+
+        StringConcatPass.run(&mut analysis)?;
+
+        Ok(emit(analysis.get_region(region)?.get_nodes()[0].clone()))
+    }
+
+    fn join(lhs: impl Into<ExprKind>, rhs: impl Into<ExprKind>) -> ExprKind {
+        new_bin_op(lhs, rhs, BinOpType::Join).unwrap().into()
+    }
+
+    #[test]
+    fn test_adjacent_literals_merged() -> Result<(), StructureAnalysisError> {
+        // x = "a" @ "b" @ name @ "!";
+        let chain = join(
+            join(join(new_str("a"), new_str("b")), new_id("name")),
+            new_str("!"),
+        );
+        assert_eq!(
+            run_pass(new_assignment(new_id("x"), chain).into())?,
+            "x = \"ab\" @ name @ \"!\";"
+        );
+
+        // Literals are merged across a chain that is grouped on the right
+        let chain = join(
+            join(new_id("name"), new_str("a")),
+            join(new_str("b"), new_id("id")),
+        );
+        assert_eq!(
+            run_pass(new_assignment(new_id("x"), chain).into())?,
+            "x = name @ \"ab\" @ id;"
+        );
+
+        // A chain of only literals becomes a single literal
+        let chain = join(new_str("Hello, "), new_str("World!"));
+        assert_eq!(
+            run_pass(new_assignment(new_id("x"), chain).into())?,
+            "x = \"Hello, World!\";"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_string_literals_kept() -> Result<(), StructureAnalysisError> {
+        let chain = join(join(new_str("a"), new_num(1)), new_str("b"));
+        assert_eq!(
+            run_pass(new_assignment(new_id("x"), chain).into())?,
+            "x = \"a\" @ 1 @ \"b\";"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,333 @@
+#![deny(missing_docs)]
+
+use crate::decompiler::{
+    ast::{expr::ExprKind, identifier::IdentifierNode, statement::StatementKind, AstKind},
+    structure_analysis::{region::RegionId, StructureAnalysis, StructureAnalysisError},
+};
+
+use super::AstPass;
+
+/// Hoists the statements that every branch ends with below the point where the
+/// branches merge, so `if (c) { a = 1; x = 2; } else { b = 1; x = 2; }` becomes
+/// `if (c) { a = 1; } else { b = 1; } x = 2;`.
+///
+/// Statements are only hoisted into a region when all of its predecessors are linear
+/// regions that fall through to it, so the hoisted statements still run on every path
+/// that reaches it. Statements are compared structurally, including their SSA versions,
+/// so two statements that read different temporaries are not shared. A statement that
+/// reads a phi node, or a variable assigned by a statement that stays in a branch,
+/// depends on the branch it is in, so it ends the shared tail.
+#[derive(Debug, Default)]
+pub struct TailMergePass;
+
+impl AstPass for TailMergePass {
+    fn name(&self) -> &'static str {
+        "tail_merge"
+    }
+
+    fn run(&mut self, analysis: &mut StructureAnalysis) -> Result<(), StructureAnalysisError> {
+        for region_id in analysis.region_ids() {
+            let Some(preds) = mergeable_predecessors(analysis, region_id)? else {
+                continue;
+            };
+
+            let tails = preds
+                .iter()
+                .map(|pred| {
+                    analysis.get_region(*pred).map(|region| {
+                        region
+                            .iter_nodes()
+                            .rev()
+                            .take_while(|node| is_hoistable(node))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let shared = (0..tails[0].len())
+                .take_while(|&i| tails[1..].iter().all(|tail| tail.get(i) == tails[0].get(i)))
+                .count();
+            let shared = independent_tail_len(analysis, &preds, shared)?;
+            if shared == 0 {
+                continue;
+            }
+
+            // The tails are equal, so the nodes of any one of them can be hoisted
+            let mut hoisted = Vec::new();
+            for pred in preds.iter() {
+                let nodes = analysis.get_region_mut(*pred)?.get_nodes_mut();
+                hoisted = nodes.split_off(nodes.len() - shared);
+            }
+            analysis
+                .get_region_mut(region_id)?
+                .get_nodes_mut()
+                .splice(0..0, hoisted);
+        }
+        Ok(())
+    }
+}
+
+/// Gets the predecessors of a region if there are at least two, and all of them are
+/// linear regions without unresolved nodes that only fall through to the region.
+fn mergeable_predecessors(
+    analysis: &StructureAnalysis,
+    region_id: RegionId,
+) -> Result<Option<Vec<RegionId>>, StructureAnalysisError> {
+    let preds = analysis.get_predecessors(region_id)?;
+    if preds.len() < 2 {
+        return Ok(None);
+    }
+    for pred in preds.iter() {
+        if *pred == region_id
+            || analysis.get_single_linear_successor(*pred)? != Some(region_id)
+            || !analysis
+                .get_region(*pred)?
+                .get_unresolved_nodes()
+                .is_empty()
+        {
+            return Ok(None);
+        }
+    }
+    Ok(Some(preds))
+}
+
+/// Shortens a shared tail until none of its statements read a variable assigned by the
+/// statements that stay in the branches.
+///
+/// # Arguments
+/// - `analysis`: The structure analysis.
+/// - `preds`: The branches that end with the shared tail.
+/// - `shared`: The number of statements the branches end with in common.
+///
+/// # Returns
+/// - The number of statements that can be hoisted.
+fn independent_tail_len(
+    analysis: &StructureAnalysis,
+    preds: &[RegionId],
+    mut shared: usize,
+) -> Result<usize, StructureAnalysisError> {
+    while shared > 0 {
+        let mut assigned = Vec::new();
+        for pred in preds {
+            let nodes = analysis.get_region(*pred)?.get_nodes();
+            for node in &nodes[..nodes.len() - shared] {
+                collect_assigned(node, &mut assigned);
+            }
+        }
+
+        // Every branch ends with the same tail, so any branch can be checked
+        let nodes = analysis.get_region(preds[0])?.get_nodes();
+        let tail = &nodes[nodes.len() - shared..];
+        match tail.iter().rposition(|node| reads_any(node, &assigned)) {
+            // The dependent statement, and everything before it, stays in the branches
+            Some(index) => shared -= index + 1,
+            None => break,
+        }
+    }
+    Ok(shared)
+}
+
+/// Collects the variables a node assigns to.
+fn collect_assigned<'a>(node: &'a AstKind, assigned: &mut Vec<&'a IdentifierNode>) {
+    let AstKind::Statement(stmt) = node else {
+        return;
+    };
+    for expr in stmt.expressions() {
+        collect_assigned_in_expr(expr, assigned);
+    }
+    if let StatementKind::Assignment(assignment) = stmt {
+        if let ExprKind::Identifier(id) = &assignment.lhs {
+            assigned.push(id);
+        }
+    }
+}
+
+/// Collects the variables assigned by assignments used as expressions.
+fn collect_assigned_in_expr<'a>(expr: &'a ExprKind, assigned: &mut Vec<&'a IdentifierNode>) {
+    if let ExprKind::Assignment(assignment) = expr {
+        if let ExprKind::Identifier(id) = &assignment.lhs {
+            assigned.push(id);
+        }
+    }
+    for subexpression in expr.subexpressions() {
+        collect_assigned_in_expr(subexpression, assigned);
+    }
+}
+
+/// Checks if a node reads any of the given variables.
+fn reads_any(node: &AstKind, variables: &[&IdentifierNode]) -> bool {
+    let exprs = match node {
+        AstKind::Statement(StatementKind::Assignment(assignment)) => match &assignment.lhs {
+            // Assigning to a variable does not read it
+            ExprKind::Identifier(_) => vec![&assignment.rhs],
+            lhs => vec![lhs, &assignment.rhs],
+        },
+        AstKind::Statement(stmt) => stmt.expressions(),
+        AstKind::Expression(expr) => vec![expr],
+        _ => Vec::new(),
+    };
+    exprs
+        .into_iter()
+        .any(|expr| expr_reads_any(expr, variables))
+}
+
+/// Checks if an expression reads any of the given variables.
+fn expr_reads_any(expr: &ExprKind, variables: &[&IdentifierNode]) -> bool {
+    match expr {
+        ExprKind::Identifier(id) => variables.contains(&&**id),
+        _ => expr
+            .subexpressions()
+            .into_iter()
+            .any(|subexpression| expr_reads_any(subexpression, variables)),
+    }
+}
+
+/// Checks if a node is a plain statement that does not read a phi node.
+fn is_hoistable(node: &AstKind) -> bool {
+    match node {
+        AstKind::Statement(stmt @ StatementKind::Assignment(_)) => {
+            !stmt.expressions().into_iter().any(contains_phi)
+        }
+        AstKind::Expression(expr) => !contains_phi(expr),
+        _ => false,
+    }
+}
+
+/// Checks if an expression contains a phi node.
+fn contains_phi(expr: &ExprKind) -> bool {
+    matches!(expr, ExprKind::Phi(_)) || expr.subexpressions().into_iter().any(contains_phi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::{
+        ast::{emit, new_assignment, new_fn_call, new_id, new_id_with_version, new_num, new_phi},
+        structure_analysis::{region::RegionType, ControlFlowEdgeType},
+    };
+
+    /// Builds a diamond where the entry branches to two linear regions that both fall
+    /// through to a tail region.
+    fn diamond(
+        then_nodes: Vec<AstKind>,
+        else_nodes: Vec<AstKind>,
+    ) -> Result<(StructureAnalysis, [RegionId; 3]), StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let entry = analysis.add_region(RegionType::ControlFlow);
+        let then_region = analysis.add_region(RegionType::Linear);
+        let else_region = analysis.add_region(RegionType::Linear);
+        let merge = analysis.add_region(RegionType::Tail);
+        analysis
+            .get_region_mut(entry)?
+            .set_jump_expr(Some(new_id("c").into()));
+        for node in then_nodes {
+            analysis.push_to_region(then_region, node);
+        }
+        for node in else_nodes {
+            analysis.push_to_region(else_region, node);
+        }
+        analysis.push_to_region(merge, new_assignment(new_id("y"), new_num(3)));
+        analysis.connect_regions(entry, then_region, ControlFlowEdgeType::Fallthrough)?;
+        analysis.connect_regions(entry, else_region, ControlFlowEdgeType::Branch)?;
+        analysis.connect_regions(then_region, merge, ControlFlowEdgeType::Fallthrough)?;
+        analysis.connect_regions(else_region, merge, ControlFlowEdgeType::Fallthrough)?;
+        Ok((analysis, [then_region, else_region, merge]))
+    }
+
+    fn emit_region(
+        analysis: &StructureAnalysis,
+        region_id: RegionId,
+    ) -> Result<Vec<String>, StructureAnalysisError> {
+        Ok(analysis
+            .get_region(region_id)?
+            .iter_nodes()
+            .map(|node| emit(node.clone()))
+            .collect())
+    }
+
+    #[test]
+    fn test_shared_tail_hoisted() -> Result<(), StructureAnalysisError> {
+        let (mut analysis, [then_region, else_region, merge]) = diamond(
+            vec![
+                new_assignment(new_id("a"), new_num(1)).into(),
+                new_assignment(new_id("x"), new_num(2)).into(),
+            ],
+            vec![
+                new_assignment(new_id("b"), new_num(1)).into(),
+                new_assignment(new_id("x"), new_num(2)).into(),
+            ],
+        )?;
+
+        TailMergePass.run(&mut analysis)?;
+
+        assert_eq!(emit_region(&analysis, then_region)?, vec!["a = 1;"]);
+        assert_eq!(emit_region(&analysis, else_region)?, vec!["b = 1;"]);
+        assert_eq!(emit_region(&analysis, merge)?, vec!["x = 2;", "y = 3;"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_branch_dependent_tail_kept() -> Result<(), StructureAnalysisError> {
+        let with_phi = || {
+            let mut phi = new_phi(0);
+            phi.add_region(RegionId::new(0), ControlFlowEdgeType::Fallthrough);
+            new_assignment(new_id("x"), phi).into()
+        };
+        let (mut analysis, [then_region, else_region, merge]) = diamond(
+            vec![new_assignment(new_id("a"), new_num(1)).into(), with_phi()],
+            vec![new_assignment(new_id("a"), new_num(1)).into(), with_phi()],
+        )?;
+
+        TailMergePass.run(&mut analysis)?;
+
+        assert_eq!(emit_region(&analysis, then_region)?.len(), 2);
+        assert_eq!(emit_region(&analysis, else_region)?.len(), 2);
+        assert_eq!(emit_region(&analysis, merge)?, vec!["y = 3;"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tail_reading_branch_temporary_kept() -> Result<(), StructureAnalysisError> {
+        // if (c) { tmp#1 = foo(); x = tmp#1; } else { tmp#2 = bar(); x = tmp#2; }
+        let (mut analysis, [then_region, else_region, merge]) = diamond(
+            vec![
+                new_assignment(
+                    new_id_with_version("tmp", 1.into()),
+                    new_fn_call(new_id("foo"), vec![]),
+                )
+                .into(),
+                new_assignment(new_id("x"), new_id_with_version("tmp", 1.into())).into(),
+            ],
+            vec![
+                new_assignment(
+                    new_id_with_version("tmp", 2.into()),
+                    new_fn_call(new_id("bar"), vec![]),
+                )
+                .into(),
+                new_assignment(new_id("x"), new_id_with_version("tmp", 2.into())).into(),
+            ],
+        )?;
+        TailMergePass.run(&mut analysis)?;
+        assert_eq!(emit_region(&analysis, then_region)?.len(), 2);
+        assert_eq!(emit_region(&analysis, else_region)?.len(), 2);
+        assert_eq!(emit_region(&analysis, merge)?, vec!["y = 3;"]);
+
+        // Both branches read the same temporary, but it is assigned in each of them
+        let read_tmp = || new_assignment(new_id("x"), new_id_with_version("tmp", 3.into()));
+        let (mut analysis, [then_region, _, merge]) = diamond(
+            vec![
+                new_assignment(new_id_with_version("tmp", 3.into()), new_num(1)).into(),
+                read_tmp().into(),
+                new_assignment(new_id("z"), new_num(4)).into(),
+            ],
+            vec![
+                new_assignment(new_id_with_version("tmp", 3.into()), new_num(2)).into(),
+                read_tmp().into(),
+                new_assignment(new_id("z"), new_num(4)).into(),
+            ],
+        )?;
+        TailMergePass.run(&mut analysis)?;
+        assert_eq!(emit_region(&analysis, then_region)?.len(), 2);
+        assert_eq!(emit_region(&analysis, merge)?, vec!["z = 4;", "y = 3;"]);
+        Ok(())
+    }
+}
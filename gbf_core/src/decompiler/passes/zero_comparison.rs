@@ -0,0 +1,175 @@
+#![deny(missing_docs)]
+
+use crate::decompiler::{
+    ast::{
+        bin_op::BinOpType, cast::CastType, expr::ExprKind, literal::LiteralNode, new_logical_not,
+        unary_op::UnaryOpType,
+    },
+    structure_analysis::{StructureAnalysis, StructureAnalysisError},
+};
+
+use super::AstPass;
+
+/// Rewrites comparisons with zero in branch conditions back to the truthiness tests
+/// they were compiled from, so `if (x != 0)` becomes `if (x)` and `if (x == 0)` becomes
+/// `if (!x)`.
+///
+/// Only the jump expression of each region is rewritten, since that is the only place
+/// where the result of the comparison is used as a condition. A comparison that is
+/// assigned or passed as a value keeps its `0` or `1` result.
+///
+/// The rewrite is also limited to operands that are known to be numbers, such as
+/// `temp.a % 2`. A string is compared with `0` by its numeric value, but tested by
+/// whether it is empty, so `if (temp.s == 0)` and `if (!temp.s)` are not the same test.
+#[derive(Debug, Default)]
+pub struct ZeroComparisonPass;
+
+impl AstPass for ZeroComparisonPass {
+    fn name(&self) -> &'static str {
+        "zero_comparison"
+    }
+
+    fn run(&mut self, analysis: &mut StructureAnalysis) -> Result<(), StructureAnalysisError> {
+        for region_id in analysis.region_ids() {
+            let region = analysis.get_region_mut(region_id)?;
+            if let Some(jump_expr) = region.get_jump_expr().cloned() {
+                region.set_jump_expr(Some(simplify_condition(jump_expr)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Simplifies an expression that is used as a condition. The operands of the logical
+/// operators are conditions as well, so they are simplified recursively.
+fn simplify_condition(expr: ExprKind) -> ExprKind {
+    match expr {
+        ExprKind::BinOp(mut bin_op) => match bin_op.op_type {
+            BinOpType::LogicalAnd | BinOpType::LogicalOr => {
+                bin_op.lhs = simplify_condition(bin_op.lhs.clone());
+                bin_op.rhs = simplify_condition(bin_op.rhs.clone());
+                ExprKind::BinOp(bin_op)
+            }
+            BinOpType::Equal | BinOpType::NotEqual => {
+                let operand = if is_zero(&bin_op.rhs) && is_numeric(&bin_op.lhs) {
+                    bin_op.lhs.clone()
+                } else if is_zero(&bin_op.lhs) && is_numeric(&bin_op.rhs) {
+                    bin_op.rhs.clone()
+                } else {
+                    return ExprKind::BinOp(bin_op);
+                };
+                let operand = simplify_condition(operand);
+                if bin_op.op_type == BinOpType::NotEqual {
+                    operand
+                } else {
+                    negate(operand)
+                }
+            }
+            _ => ExprKind::BinOp(bin_op),
+        },
+        ExprKind::UnaryOp(unary_op) if unary_op.op_type == UnaryOpType::LogicalNot => {
+            let operand = simplify_condition(unary_op.operand.clone());
+            if operand == unary_op.operand {
+                ExprKind::UnaryOp(unary_op)
+            } else {
+                negate(operand)
+            }
+        }
+        expr => expr,
+    }
+}
+
+/// Negates a condition, or keeps it as is if it cannot be negated.
+fn negate(expr: ExprKind) -> ExprKind {
+    new_logical_not(expr.clone()).unwrap_or(expr)
+}
+
+/// Checks if an expression is the literal `0`.
+fn is_zero(expr: &ExprKind) -> bool {
+    matches!(expr, ExprKind::Literal(literal) if **literal == LiteralNode::Number(0))
+}
+
+/// Checks if an expression is known to evaluate to a number. Variables and calls can
+/// hold any kind of value, so only literals and operators with a numeric result count.
+fn is_numeric(expr: &ExprKind) -> bool {
+    match expr {
+        ExprKind::Literal(literal) => matches!(
+            **literal,
+            LiteralNode::Number(_) | LiteralNode::Float(_) | LiteralNode::Boolean(_)
+        ),
+        ExprKind::BinOp(bin_op) => !matches!(bin_op.op_type, BinOpType::Join | BinOpType::Foreach),
+        ExprKind::UnaryOp(_) => true,
+        ExprKind::Cast(cast) => cast.cast_type == CastType::Float,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::{
+        ast::{bin_op::BinOpType, emit, new_bin_op, new_id, new_num, new_unary_op},
+        structure_analysis::region::RegionType,
+    };
+
+    fn simplify(expr: ExprKind) -> Result<String, StructureAnalysisError> {
+        let mut analysis = StructureAnalysis::new(false, 100);
+        let region = analysis.add_region(RegionType::ControlFlow);
+        analysis.get_region_mut(region)?.set_jump_expr(Some(expr));
+
+        ZeroComparisonPass.run(&mut analysis)?;
+
+        let jump_expr = analysis.get_region(region)?.get_jump_expr().cloned();
+        Ok(emit(jump_expr.unwrap()))
+    }
+
+    /// Builds `name % 2`, which is known to be a number.
+    fn remainder(name: &str) -> ExprKind {
+        new_bin_op(new_id(name), new_num(2), BinOpType::Mod)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_zero_comparison() -> Result<(), StructureAnalysisError> {
+        let not_equal = new_bin_op(remainder("x"), new_num(0), BinOpType::NotEqual).unwrap();
+        assert_eq!(simplify(not_equal.into())?, "x % 2");
+
+        let equal = new_bin_op(new_num(0), remainder("x"), BinOpType::Equal).unwrap();
+        assert_eq!(simplify(equal.into())?, "!(x % 2)");
+
+        // `!(x % 2 == 0)` is the same test as `x % 2 != 0`
+        let equal = new_bin_op(remainder("x"), new_num(0), BinOpType::Equal).unwrap();
+        let negated = new_unary_op(equal, UnaryOpType::LogicalNot).unwrap();
+        assert_eq!(simplify(negated.into())?, "x % 2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_comparison_logical_operands() -> Result<(), StructureAnalysisError> {
+        let lhs = new_bin_op(remainder("a"), new_num(0), BinOpType::NotEqual).unwrap();
+        let rhs = new_bin_op(remainder("b"), new_num(0), BinOpType::Equal).unwrap();
+        let condition = new_bin_op(lhs, rhs, BinOpType::LogicalAnd).unwrap();
+        assert_eq!(simplify(condition.into())?, "(a % 2) && (!(b % 2))");
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_comparison_other_operands() -> Result<(), StructureAnalysisError> {
+        let condition = new_bin_op(remainder("x"), new_num(1), BinOpType::NotEqual).unwrap();
+        assert_eq!(simplify(condition.into())?, "(x % 2) != 1");
+
+        // A variable may hold a string, which is not tested the same way
+        let condition = new_bin_op(new_id("x"), new_num(0), BinOpType::Equal).unwrap();
+        assert_eq!(simplify(condition.into())?, "x == 0");
+        let condition = new_bin_op(new_id("x"), new_num(0), BinOpType::NotEqual).unwrap();
+        assert_eq!(simplify(condition.into())?, "x != 0");
+
+        // Only the comparison itself is a condition, not its operands
+        let sum = new_bin_op(new_id("a"), new_id("b"), BinOpType::Add).unwrap();
+        let inner = new_bin_op(remainder("c"), new_num(0), BinOpType::Equal).unwrap();
+        let condition = new_bin_op(sum, inner, BinOpType::Less).unwrap();
+        assert_eq!(simplify(condition.into())?, "(a + b) < ((c % 2) == 0)");
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+#![deny(missing_docs)]
+
+use thiserror::Error;
+
+use crate::function::Function;
+
+use super::{
+    ast::visitors::emit_context::EmitContext,
+    function_decompiler::{FunctionDecompilerBuilder, FunctionDecompilerError},
+};
+
+/// The characters that make up the operators and punctuation of GS2.
+const OPERATOR_CHARS: &str = "+-*/%^<>=!&|~?:;,.@";
+
+/// An error found while checking that decompiled output is well-formed.
+#[derive(Debug, Error)]
+pub enum RoundtripError {
+    /// The function could not be decompiled.
+    #[error("The function could not be decompiled: {0}")]
+    Decompile(#[from] FunctionDecompilerError),
+
+    /// A character that cannot start a GS2 token.
+    #[error("Unexpected character {character:?} at {line}:{column}")]
+    UnexpectedCharacter {
+        /// The unexpected character.
+        character: char,
+        /// The line of the character, starting at 1.
+        line: usize,
+        /// The column of the character, starting at 1.
+        column: usize,
+    },
+
+    /// A string literal or block comment that is never closed.
+    #[error("Unterminated {kind} starting at {line}:{column}")]
+    Unterminated {
+        /// What was left open, such as `string` or `comment`.
+        kind: &'static str,
+        /// The line where it starts, starting at 1.
+        line: usize,
+        /// The column where it starts, starting at 1.
+        column: usize,
+    },
+
+    /// A closing delimiter that does not match the last open delimiter.
+    #[error("Unexpected {found:?} at {line}:{column}, expected {expected:?}")]
+    MismatchedDelimiter {
+        /// The closing delimiter that was found.
+        found: char,
+        /// The closing delimiter that was expected, or `None` if none was open.
+        expected: Option<char>,
+        /// The line of the delimiter, starting at 1.
+        line: usize,
+        /// The column of the delimiter, starting at 1.
+        column: usize,
+    },
+
+    /// A delimiter that is still open at the end of the source.
+    #[error("Unclosed {delimiter:?} opened at {line}:{column}")]
+    UnclosedDelimiter {
+        /// The open delimiter.
+        delimiter: char,
+        /// The line of the delimiter, starting at 1.
+        line: usize,
+        /// The column of the delimiter, starting at 1.
+        column: usize,
+    },
+}
+
+/// Decompiles a function with the default options, and checks that the output is
+/// lexically well-formed GS2 with `check_lexical`. This does not parse the output, but
+/// it is cheap enough to run across a whole corpus and catches emitter bugs such as
+/// unbalanced parentheses or braces.
+///
+/// # Arguments
+/// - `function`: The function to decompile.
+///
+/// # Returns
+/// - `Ok(())` if the function decompiles to well-formed output.
+///
+/// # Errors
+/// - `RoundtripError::Decompile` if the function could not be decompiled.
+/// - Any error of `check_lexical` if the output is not well-formed.
+///
+/// # Example
+/// ```
+/// use gbf_core::decompiler::roundtrip::roundtrip_check;
+/// use gbf_core::function::{Function, FunctionId};
+/// use gbf_core::instruction::Instruction;
+/// use gbf_core::opcode::Opcode;
+///
+/// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+/// let entry = function.get_entry_basic_block_mut();
+/// entry.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+/// entry.add_instruction(Instruction::new(Opcode::Ret, 1));
+///
+/// assert!(roundtrip_check(&function).is_ok());
+/// ```
+pub fn roundtrip_check(function: &Function) -> Result<(), RoundtripError> {
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let source = decompiler.decompile(EmitContext::default())?;
+    check_lexical(&source)
+}
+
+/// Checks that source is made of valid GS2 tokens, and that its parentheses, brackets
+/// and braces are balanced. Delimiters within strings and comments are ignored.
+///
+/// # Arguments
+/// - `source`: The source to check.
+///
+/// # Returns
+/// - `Ok(())` if the source is lexically well-formed.
+///
+/// # Errors
+/// - `RoundtripError::UnexpectedCharacter` if a character cannot start a token.
+/// - `RoundtripError::Unterminated` if a string or block comment is never closed.
+/// - `RoundtripError::MismatchedDelimiter` if a delimiter is closed out of order.
+/// - `RoundtripError::UnclosedDelimiter` if a delimiter is never closed.
+///
+/// # Example
+/// ```
+/// use gbf_core::decompiler::roundtrip::{check_lexical, RoundtripError};
+///
+/// assert!(check_lexical("if (a[0] == \"}\") { foo(); }").is_ok());
+/// assert!(matches!(
+///     check_lexical("foo(a];"),
+///     Err(RoundtripError::MismatchedDelimiter { found: ']', expected: Some(')'), .. })
+/// ));
+/// ```
+pub fn check_lexical(source: &str) -> Result<(), RoundtripError> {
+    let mut chars = source.chars().peekable();
+    let (mut line, mut column) = (1, 0);
+    // The open delimiters, with where they were opened
+    let mut open: Vec<(char, usize, usize)> = Vec::new();
+
+    // Advances by one character, keeping track of the position
+    macro_rules! next {
+        () => {
+            chars.next().inspect(|c| {
+                if *c == '\n' {
+                    line += 1;
+                    column = 0;
+                } else {
+                    column += 1;
+                }
+            })
+        };
+    }
+
+    while let Some(c) = next!() {
+        let (start_line, start_column) = (line, column);
+        match c {
+            _ if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    next!();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                next!();
+                let mut closed = false;
+                while let Some(c) = next!() {
+                    if c == '*' && chars.peek() == Some(&'/') {
+                        next!();
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    return Err(RoundtripError::Unterminated {
+                        kind: "comment",
+                        line: start_line,
+                        column: start_column,
+                    });
+                }
+            }
+            '"' => {
+                let mut closed = false;
+                while let Some(c) = next!() {
+                    match c {
+                        '\\' => {
+                            next!();
+                        }
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\n' => break,
+                        _ => {}
+                    }
+                }
+                if !closed {
+                    return Err(RoundtripError::Unterminated {
+                        kind: "string",
+                        line: start_line,
+                        column: start_column,
+                    });
+                }
+            }
+            '(' | '[' | '{' => open.push((c, line, column)),
+            ')' | ']' | '}' => {
+                let expected = open.pop().map(|(delimiter, _, _)| closing(delimiter));
+                if expected != Some(c) {
+                    return Err(RoundtripError::MismatchedDelimiter {
+                        found: c,
+                        expected,
+                        line,
+                        column,
+                    });
+                }
+            }
+            // Identifiers, keywords and numbers, including hex and float literals
+            _ if c.is_ascii_alphanumeric() || c == '_' => {
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    next!();
+                }
+            }
+            _ if OPERATOR_CHARS.contains(c) => {}
+            _ => {
+                return Err(RoundtripError::UnexpectedCharacter {
+                    character: c,
+                    line,
+                    column,
+                });
+            }
+        }
+    }
+
+    match open.pop() {
+        Some((delimiter, line, column)) => Err(RoundtripError::UnclosedDelimiter {
+            delimiter,
+            line,
+            column,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Gets the closing delimiter of an open delimiter.
+fn closing(delimiter: char) -> char {
+    match delimiter {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_well_formed_source() -> Result<(), RoundtripError> {
+        check_lexical(
+            "function onCreated(a)\n{\n    // a comment with ( and \"\n    /* { */\n    temp.x = \"\\\"}\" @ a[0x1f] * 1.5;\n    player::chat = -temp.x;\n}\n",
+        )
+    }
+
+    #[test]
+    fn test_malformed_source() {
+        assert!(matches!(
+            check_lexical("foo(a"),
+            Err(RoundtripError::UnclosedDelimiter {
+                delimiter: '(',
+                line: 1,
+                column: 4
+            })
+        ));
+        assert!(matches!(
+            check_lexical("{\n}}"),
+            Err(RoundtripError::MismatchedDelimiter {
+                found: '}',
+                expected: None,
+                line: 2,
+                column: 2
+            })
+        ));
+        assert!(matches!(
+            check_lexical("x = \"abc;\n"),
+            Err(RoundtripError::Unterminated { kind: "string", .. })
+        ));
+        assert!(matches!(
+            check_lexical("x = 1; /* abc"),
+            Err(RoundtripError::Unterminated {
+                kind: "comment",
+                ..
+            })
+        ));
+        assert!(matches!(
+            check_lexical("x#3 = 1;"),
+            Err(RoundtripError::UnexpectedCharacter { character: '#', .. })
+        ));
+    }
+}
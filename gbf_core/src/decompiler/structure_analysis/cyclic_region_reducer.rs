@@ -3,16 +3,17 @@
 use std::backtrace::Backtrace;
 
 use crate::decompiler::ast::{
-    control_flow::ControlFlowNode, expr::ExprKind, new_cyclic_condition, new_do_while, ptr::P,
-    AstKind,
+    control_flow::ControlFlowNode, expr::ExprKind, new_bool, new_counted_for, new_cyclic_condition,
+    new_do_while, new_while, ptr::P, statement::StatementKind, AstKind,
 };
+use crate::opcode::Opcode;
 
 use super::{
     region::{RegionId, RegionType},
     RegionReducer, StructureAnalysis, StructureAnalysisError,
 };
 
-/// Reduces a linear region.
+/// Reduces a cyclic region into a loop.
 pub struct CyclicRegionReducer;
 
 impl CyclicRegionReducer {
@@ -44,7 +45,7 @@ impl CyclicRegionReducer {
     ) -> Result<(), StructureAnalysisError> {
         analysis.remove_edge(start_node, remove_node)?;
         analysis.remove_edge(remove_node, final_node)?;
-        analysis.remove_node(remove_node)?;
+        analysis.remove_merged_region(remove_node, start_node)?;
         Ok(())
     }
 
@@ -101,6 +102,87 @@ impl CyclicRegionReducer {
         )?;
         Ok(region.get_nodes().to_vec())
     }
+
+    /// Gets the variable a node assigns to, if it is an assignment.
+    fn assigned_expr(node: &AstKind) -> Option<&ExprKind> {
+        match node {
+            AstKind::Statement(StatementKind::Assignment(assignment)) => Some(&assignment.lhs),
+            _ => None,
+        }
+    }
+
+    /// Checks if an expression is, or contains, the given expression.
+    fn contains_expr(expr: &ExprKind, target: &ExprKind) -> bool {
+        expr == target
+            || expr
+                .subexpressions()
+                .into_iter()
+                .any(|subexpression| Self::contains_expr(subexpression, target))
+    }
+
+    /// Extracts the initializer and step of a counted `for` loop from a while loop.
+    ///
+    /// The loop is counted if its body starts by increasing the loop counter and ends
+    /// by assigning to a variable used in the condition, and the region before the loop
+    /// ends by assigning to the same variable. The initializer can only be moved into
+    /// the loop if the header has no nodes of its own, which would otherwise run between
+    /// the initializer and the loop.
+    ///
+    /// # Arguments
+    /// - `analysis`: The structure analysis.
+    /// - `header_id`: The region that evaluates the condition of the loop.
+    /// - `body_id`: The region of the loop body.
+    /// - `condition`: The condition of the loop.
+    ///
+    /// # Returns
+    /// - The initializer and step, which are removed from their regions, or `None` if
+    ///   the loop is not counted.
+    fn extract_for_clauses(
+        analysis: &mut StructureAnalysis,
+        header_id: RegionId,
+        body_id: RegionId,
+        condition: &ExprKind,
+    ) -> Result<Option<(AstKind, AstKind)>, StructureAnalysisError> {
+        let header = analysis.get_region(header_id)?;
+        let body = analysis.get_region(body_id)?;
+        if !body.is_loop_body() || !header.get_nodes().is_empty() {
+            return Ok(None);
+        }
+
+        let variable = match body.get_nodes().last().and_then(Self::assigned_expr) {
+            Some(variable) if Self::contains_expr(condition, variable) => variable.clone(),
+            _ => return Ok(None),
+        };
+
+        let pre_headers: Vec<_> = analysis
+            .get_predecessors(header_id)?
+            .into_iter()
+            .filter(|predecessor| *predecessor != body_id)
+            .collect();
+        let pre_header_id = match pre_headers.as_slice() {
+            [pre_header_id] => *pre_header_id,
+            _ => return Ok(None),
+        };
+        if analysis.get_single_linear_successor(pre_header_id)? != Some(header_id) {
+            return Ok(None);
+        }
+        let pre_header = analysis.get_region(pre_header_id)?;
+        if pre_header.get_nodes().last().and_then(Self::assigned_expr) != Some(&variable) {
+            return Ok(None);
+        }
+
+        let init = analysis
+            .get_region_mut(pre_header_id)?
+            .get_nodes_mut()
+            .pop()
+            .expect("[Bug] The pre-header should end with the initializer.");
+        let step = analysis
+            .get_region_mut(body_id)?
+            .get_nodes_mut()
+            .pop()
+            .expect("[Bug] The body should end with the step.");
+        Ok(Some((init, step)))
+    }
 }
 
 impl RegionReducer for CyclicRegionReducer {
@@ -113,24 +195,29 @@ impl RegionReducer for CyclicRegionReducer {
         let len = successors.len();
 
         // Case 1: doWhile, if the region has an expression AND the successor is the same region.
+        // If the region is its own only successor, it is an infinite loop.
         for successor in &successors {
             if successor.0 == region_id {
                 // Call the before_reduce hook
                 analysis.before_reduce(region_id);
 
-                if len == 1 {
-                    // TODO: Infinite loop: not implemented yet
-                    return Err(StructureAnalysisError::Other {
-                        message: "Infinite loop not implemented yet".to_string(),
-                        backtrace: Backtrace::capture(),
-                    });
-                }
-
-                let jump_expr = Self::extract_jump_expr(analysis, region_id)?;
                 let region_nodes = Self::get_region_nodes(analysis, region_id)?;
-                let cond: P<ControlFlowNode> = new_do_while(jump_expr, region_nodes).into();
+
+                // Without an exit edge, the region loops forever
+                let cond: P<ControlFlowNode> = if len == 1 {
+                    new_while(new_bool(true), region_nodes).into()
+                } else {
+                    let jump_expr = Self::extract_jump_expr(analysis, region_id)?;
+                    new_do_while(jump_expr, region_nodes).into()
+                };
 
                 Self::replace_region_nodes(analysis, region_id, cond)?;
+                if len == 1 {
+                    // Nothing runs after an infinite loop
+                    analysis
+                        .get_region_mut(region_id)?
+                        .set_region_type(RegionType::Tail);
+                }
                 return Ok(true);
             }
         }
@@ -145,17 +232,28 @@ impl RegionReducer for CyclicRegionReducer {
 
                 // We have a while loop! Merge the regions.
                 let jump_expr = Self::extract_jump_expr(analysis, region_id)?;
+                let branch_opcode = analysis.get_branch_opcode(region_id)?;
+
+                // A while loop may really be a counted for loop, but foreach loops
+                // already have their own form.
+                let for_clauses = if branch_opcode == Some(Opcode::ForEach) {
+                    None
+                } else {
+                    Self::extract_for_clauses(analysis, region_id, successor.0, &jump_expr)?
+                };
+
                 let region_nodes = Self::get_region_nodes(analysis, successor.0)?;
-                let cond: P<ControlFlowNode> = new_cyclic_condition(
-                    jump_expr,
-                    region_nodes,
-                    analysis.get_branch_opcode(region_id)?,
-                )
-                .map_err(|e| StructureAnalysisError::AstNodeError {
-                    source: Box::new(e),
-                    backtrace: Backtrace::capture(),
-                })?
-                .into();
+                let cond: P<ControlFlowNode> = match for_clauses {
+                    Some((init, step)) => {
+                        new_counted_for(init, jump_expr, step, region_nodes).into()
+                    }
+                    None => new_cyclic_condition(jump_expr, region_nodes, branch_opcode)
+                        .map_err(|e| StructureAnalysisError::AstNodeError {
+                            source: Box::new(e),
+                            backtrace: Backtrace::capture(),
+                        })?
+                        .into(),
+                };
 
                 Self::merge_conditional(analysis, region_id, cond)?;
                 Self::cleanup_region(analysis, successor.0, region_id, region_id)?;
@@ -4,8 +4,9 @@ use std::backtrace::Backtrace;
 
 use crate::decompiler::ast::{
     control_flow::ControlFlowNode, emit, expr::ExprKind, new_acylic_condition, new_else, new_if,
-    ptr::P, AstKind,
+    new_with, ptr::P, AstKind,
 };
+use crate::opcode::Opcode;
 
 use super::{
     region::{RegionId, RegionType},
@@ -44,7 +45,7 @@ impl IfRegionReducer {
     ) -> Result<(), StructureAnalysisError> {
         analysis.remove_edge(start_node, remove_node)?;
         analysis.remove_edge(remove_node, final_node)?;
-        analysis.remove_node(remove_node)?;
+        analysis.remove_merged_region(remove_node, start_node)?;
         Ok(())
     }
 
@@ -94,6 +95,45 @@ impl IfRegionReducer {
         Ok(region.get_unresolved_nodes().to_vec())
     }
 
+    /// Reduces a `with` region. The body is the fallthrough region, and the branch region
+    /// is where execution continues once the `with` scope ends. A body that leaves the
+    /// scope early, such as with a `return`, is a tail region instead of falling through
+    /// to the end of the scope.
+    fn reduce_with(
+        analysis: &mut StructureAnalysis,
+        region_id: RegionId,
+        jump_expr: ExprKind,
+        body_region_id: RegionId,
+        end_region_id: RegionId,
+    ) -> Result<bool, StructureAnalysisError> {
+        if !analysis.has_single_predecessor(body_region_id)? {
+            return Ok(false);
+        }
+        let body_is_tail = analysis.get_region_type(body_region_id)? == RegionType::Tail;
+        if !body_is_tail
+            && analysis.get_single_linear_successor(body_region_id)? != Some(end_region_id)
+        {
+            return Ok(false);
+        }
+
+        // Call the before_reduce hook
+        analysis.before_reduce(region_id);
+
+        let body_statements = IfRegionReducer::get_region_nodes(analysis, body_region_id)?;
+        let mut with_stmt: P<ControlFlowNode> = new_with(jump_expr, body_statements).into();
+        IfRegionReducer::add_region_comments(analysis, &mut with_stmt, region_id);
+        IfRegionReducer::add_region_comments(analysis, &mut with_stmt, body_region_id);
+
+        // The region now falls through to the end of the scope
+        Self::merge_conditional(analysis, region_id, vec![with_stmt])?;
+        analysis.remove_edge(region_id, body_region_id)?;
+        if !body_is_tail {
+            analysis.remove_edge(body_region_id, end_region_id)?;
+        }
+        analysis.remove_merged_region(body_region_id, region_id)?;
+        Ok(true)
+    }
+
     /// Add region comments to P<ControlFlowNode>
     fn add_region_comments(
         analysis: &StructureAnalysis,
@@ -153,6 +193,18 @@ impl RegionReducer for IfRegionReducer {
                 backtrace: Backtrace::capture(),
             })?;
 
+        // A `with` skips its body rather than branching to an `else`, so the branch region
+        // always follows the `with` scope.
+        if analysis.get_branch_opcode(region_id)? == Some(Opcode::With) {
+            return Self::reduce_with(
+                analysis,
+                region_id,
+                jump_expr,
+                fallthrough_region_id,
+                branch_region_id,
+            );
+        }
+
         // Step 3: Determine linear successors
         let branch_linear_successor = analysis.get_single_linear_successor(branch_region_id)?;
         let fallthrough_linear_successor =
@@ -175,6 +227,7 @@ impl RegionReducer for IfRegionReducer {
                     jump_expr,
                     branch_statements,
                     analysis.get_branch_opcode(region_id)?,
+                    ControlFlowEdgeType::Branch,
                 )
                 .map_err(|e| StructureAnalysisError::AstNodeError {
                     source: Box::new(e),
@@ -207,6 +260,7 @@ impl RegionReducer for IfRegionReducer {
                     jump_expr,
                     fallthrough_statements,
                     analysis.get_branch_opcode(region_id)?,
+                    ControlFlowEdgeType::Fallthrough,
                 )
                 .map_err(|e| StructureAnalysisError::AstNodeError {
                     source: Box::new(e),
@@ -237,22 +291,18 @@ impl RegionReducer for IfRegionReducer {
                 // Call the before_reduce hook
                 analysis.before_reduce(region_id);
 
-                // Both linear successors are the same
-                let branch_statements =
-                    IfRegionReducer::get_region_nodes(analysis, branch_region_id)?;
-                let fallthrough_statements =
-                    IfRegionReducer::get_region_nodes(analysis, fallthrough_region_id)?;
-                let mut if_stmnt: P<ControlFlowNode> =
-                    new_if(jump_expr, fallthrough_statements).into();
-                let mut else_stmt: P<ControlFlowNode> = new_else(branch_statements).into();
+                // Both linear successors are the same. The branch opcode decides which
+                // successor runs when the jump expression holds.
+                let (then_region_id, else_region_id) =
+                    analysis.get_condition_successors(region_id)?;
+                let then_statements = IfRegionReducer::get_region_nodes(analysis, then_region_id)?;
+                let else_statements = IfRegionReducer::get_region_nodes(analysis, else_region_id)?;
+                let mut if_stmnt: P<ControlFlowNode> = new_if(jump_expr, then_statements).into();
+                let mut else_stmt: P<ControlFlowNode> = new_else(else_statements).into();
 
                 IfRegionReducer::add_region_comments(analysis, &mut if_stmnt, region_id);
-                IfRegionReducer::add_region_comments(
-                    analysis,
-                    &mut if_stmnt,
-                    fallthrough_region_id,
-                );
-                IfRegionReducer::add_region_comments(analysis, &mut else_stmt, branch_region_id);
+                IfRegionReducer::add_region_comments(analysis, &mut if_stmnt, then_region_id);
+                IfRegionReducer::add_region_comments(analysis, &mut else_stmt, else_region_id);
 
                 Self::merge_conditional(analysis, region_id, vec![if_stmnt, else_stmt])?;
                 Self::cleanup_region(analysis, branch_region_id, region_id, branch_successor)?;
@@ -388,4 +438,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_if_else_follows_branch_opcode() -> Result<(), StructureAnalysisError> {
+        for (opcode, then_value, else_value) in [
+            (Opcode::Jne, "fallthrough", "branch"),
+            // The jump expression of `Jeq` is already negated, so it reads the same way
+            (Opcode::Jeq, "fallthrough", "branch"),
+            (Opcode::ShortCircuitOr, "branch", "fallthrough"),
+        ] {
+            let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+            let entry_region = structure_analysis.add_region(RegionType::ControlFlow);
+            let branch_region = structure_analysis.add_region(RegionType::Linear);
+            let fallthrough_region = structure_analysis.add_region(RegionType::Linear);
+            let exit_region = structure_analysis.add_region(RegionType::Tail);
+
+            let region = structure_analysis.get_region_mut(entry_region)?;
+            region.set_jump_expr(Some(new_id("foo").into()));
+            region.set_branch_opcode(opcode);
+            structure_analysis
+                .push_to_region(branch_region, new_assignment(new_id("x"), new_id("branch")));
+            structure_analysis.push_to_region(
+                fallthrough_region,
+                new_assignment(new_id("x"), new_id("fallthrough")),
+            );
+
+            structure_analysis.connect_regions(
+                entry_region,
+                branch_region,
+                ControlFlowEdgeType::Branch,
+            )?;
+            structure_analysis.connect_regions(
+                entry_region,
+                fallthrough_region,
+                ControlFlowEdgeType::Fallthrough,
+            )?;
+            structure_analysis.connect_regions(
+                branch_region,
+                exit_region,
+                ControlFlowEdgeType::Fallthrough,
+            )?;
+            structure_analysis.connect_regions(
+                fallthrough_region,
+                exit_region,
+                ControlFlowEdgeType::Branch,
+            )?;
+            structure_analysis.execute()?;
+
+            let region = structure_analysis.get_region(entry_region)?;
+            let emitted: String = region.iter_nodes().map(|node| emit(node.clone())).collect();
+            let then_index = emitted.find(&format!("x = {};", then_value)).unwrap();
+            let else_index = emitted.find("else").unwrap();
+            let else_value_index = emitted.find(&format!("x = {};", else_value)).unwrap();
+            assert!(then_index < else_index && else_index < else_value_index);
+        }
+        Ok(())
+    }
 }
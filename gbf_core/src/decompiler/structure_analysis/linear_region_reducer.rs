@@ -67,7 +67,8 @@ impl RegionReducer for LinearRegionReducer {
             }
         })?;
 
-        if !analysis.has_single_predecessor(succ)? {
+        // A region that loops to itself is left to the cyclic reducer
+        if succ == region_id || !analysis.has_single_predecessor(succ)? {
             return Ok(false);
         }
 
@@ -83,7 +84,7 @@ impl RegionReducer for LinearRegionReducer {
         }
 
         // Remove the successor region
-        analysis.remove_node(succ)?;
+        analysis.remove_merged_region(succ, region_id)?;
         Ok(true)
     }
 }
@@ -1,12 +1,12 @@
 #![deny(missing_docs)]
 
-use std::backtrace::Backtrace;
+use std::{backtrace::Backtrace, fmt::Display};
 
 use cyclic_region_reducer::CyclicRegionReducer;
 use if_region_reducer::IfRegionReducer;
 use linear_region_reducer::LinearRegionReducer;
 use petgraph::{
-    algo::dominators::simple_fast,
+    algo::{dominators::simple_fast, toposort},
     graph::{DiGraph, NodeIndex},
     visit::{DfsPostOrder, Walker},
 };
@@ -17,11 +17,12 @@ use vbranch::VirtualBranchReducer;
 
 use crate::{
     cfg_dot::{CfgDot, CfgDotConfig, DotRenderableGraph, NodeResolver},
+    cfg_mermaid::{CfgMermaid, CfgMermaidConfig, MermaidRenderableGraph},
     opcode::Opcode,
-    utils::{GBF_GREEN, GBF_RED, GBF_YELLOW},
+    utils::{GBF_GREEN, GBF_RED, GBF_YELLOW, STRUCTURE_ANALYSIS_FAILURE_SNAPSHOTS},
 };
 
-use super::ast::{AstKind, AstNodeError};
+use super::ast::{expr::ExprKind, phi::PhiNode, AstKind, AstNodeError};
 
 use thiserror::Error;
 
@@ -99,6 +100,39 @@ pub enum StructureAnalysisError {
         backtrace: Backtrace,
     },
 
+    /// When the region graph has a cycle where an acyclic graph is expected.
+    #[error("Region graph has a cycle through {:?}", region_id)]
+    CyclicRegionGraph {
+        /// A region on the cycle.
+        region_id: RegionId,
+
+        /// The error backtrace.
+        #[serde(skip)]
+        backtrace: Backtrace,
+    },
+
+    /// When the entry region has predecessors, and `EntryPredecessorHandling::Error` is set.
+    #[error("Entry region {:?} has predecessors", region_id)]
+    EntryRegionHasPredecessors {
+        /// The entry region.
+        region_id: RegionId,
+
+        /// The error backtrace.
+        #[serde(skip)]
+        backtrace: Backtrace,
+    },
+
+    /// When a phi node refers to a region that is no longer part of the region graph.
+    #[error("Phi node refers to inactive region {:?}", region_id)]
+    DanglingPhiRegion {
+        /// The region the phi node refers to.
+        region_id: RegionId,
+
+        /// The error backtrace.
+        #[serde(skip)]
+        backtrace: Backtrace,
+    },
+
     /// Other errors.
     #[error("A structure analysis error occurred: {message}")]
     Other {
@@ -120,6 +154,9 @@ impl StructureAnalysisError {
             StructureAnalysisError::MaxIterationsReached { backtrace, .. } => backtrace,
             StructureAnalysisError::ExpectedConditionNotFound { backtrace } => backtrace,
             StructureAnalysisError::AstNodeError { backtrace, .. } => backtrace,
+            StructureAnalysisError::CyclicRegionGraph { backtrace, .. } => backtrace,
+            StructureAnalysisError::EntryRegionHasPredecessors { backtrace, .. } => backtrace,
+            StructureAnalysisError::DanglingPhiRegion { backtrace, .. } => backtrace,
             StructureAnalysisError::Other { backtrace, .. } => backtrace,
         }
     }
@@ -134,8 +171,105 @@ pub enum ControlFlowEdgeType {
     Fallthrough,
 }
 
+impl ControlFlowEdgeType {
+    /// Gets the edge of a conditional branch that is taken when the region's jump
+    /// expression holds. This is the fallthrough edge, except for `ShortCircuitOr`,
+    /// which jumps when its operand holds.
+    ///
+    /// # Arguments
+    /// - `branch_opcode`: The opcode of the branch.
+    ///
+    /// # Returns
+    /// - The edge that is taken when the jump expression holds.
+    pub fn taken_when_condition_holds(branch_opcode: Option<Opcode>) -> Self {
+        match branch_opcode {
+            Some(Opcode::ShortCircuitOr) => ControlFlowEdgeType::Branch,
+            _ => ControlFlowEdgeType::Fallthrough,
+        }
+    }
+}
+
+/// How structure analysis handles an entry region with predecessors, such as when the
+/// function starts with a loop header. The reducers assume nothing flows into the entry
+/// region, so it would otherwise be merged into its predecessors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Copy, Default)]
+pub enum EntryPredecessorHandling {
+    /// Inserts an empty region before the entry region, which becomes the new entry.
+    #[default]
+    InsertPreHeader,
+    /// Fails with `StructureAnalysisError::EntryRegionHasPredecessors`.
+    Error,
+}
+
+/// The reducer that made a reduction during structure analysis.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Copy)]
+pub enum ReducerKind {
+    /// The linear region reducer
+    Linear,
+    /// The if region reducer
+    If,
+    /// The cyclic region reducer
+    Cyclic,
+    /// The tail region reducer
+    Tail,
+    /// The virtual branch reducer
+    VirtualBranch,
+}
+
+impl Display for ReducerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ReducerKind::Linear => "linear",
+            ReducerKind::If => "if",
+            ReducerKind::Cyclic => "cyclic",
+            ReducerKind::Tail => "tail",
+            ReducerKind::VirtualBranch => "virtual branch",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A reduction made during structure analysis, recorded in debug mode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReductionEvent {
+    /// The iteration of `execute` the reduction was made in.
+    pub iteration: usize,
+    /// The reducer that made the reduction.
+    pub reducer: ReducerKind,
+    /// The region the reducer ran on, which holds the result of the reduction.
+    pub region_id: RegionId,
+    /// The regions that were merged into `region_id` and removed from the graph.
+    pub merged_regions: Vec<RegionId>,
+}
+
+impl Display for ReductionEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let merged = self
+            .merged_regions
+            .iter()
+            .map(|region_id| region_id.index.to_string())
+            .collect::<Vec<_>>();
+        if merged.is_empty() {
+            write!(
+                f,
+                "iteration {}: the {} reducer reduced region {}",
+                self.iteration, self.reducer, self.region_id.index
+            )
+        } else {
+            write!(
+                f,
+                "iteration {}: the {} reducer merged region {} into {}",
+                self.iteration,
+                self.reducer,
+                merged.join(", "),
+                self.region_id.index
+            )
+        }
+    }
+}
+
 /// This module is responsible for control flow analysis.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct StructureAnalysis {
     /// Regions vector
     regions: Vec<Region>,
@@ -145,8 +279,20 @@ pub struct StructureAnalysis {
     debug_mode: bool,
     /// The debug snapshots, if debug mode is enabled
     snapshots: Vec<String>,
+    /// If we should only keep the most recent snapshots, and only when `execute` fails
+    snapshots_on_failure_only: bool,
+    /// The entry region, which is the first region added unless a pre-header was inserted
+    entry_region: RegionId,
+    /// How to handle an entry region with predecessors
+    entry_predecessor_handling: EntryPredecessorHandling,
+    /// The reductions made by the last call to `execute`, if debug mode is enabled
+    reduction_history: Vec<ReductionEvent>,
+    /// The regions removed by the reduction currently being made
+    removed_regions: Vec<RegionId>,
     /// The maximum number of iterations for the structure analysis
     max_iterations: usize,
+    /// The number of iterations the last call to `execute` ran
+    iterations: usize,
     /// The region to highlight, if any, for the snapshot
     region_to_highlight: Option<RegionId>,
     /// If we marked a region to reduce
@@ -161,7 +307,13 @@ impl StructureAnalysis {
             region_graph: DiGraph::new(),
             debug_mode,
             snapshots: Vec::new(),
+            snapshots_on_failure_only: false,
+            entry_region: RegionId::new(0),
+            entry_predecessor_handling: EntryPredecessorHandling::default(),
+            reduction_history: Vec::new(),
+            removed_regions: Vec::new(),
             max_iterations: structure_max_iterations,
+            iterations: 0,
             region_to_highlight: None,
             is_marked: false,
         }
@@ -198,6 +350,19 @@ impl StructureAnalysis {
             })
     }
 
+    /// Gets the IDs of all regions that have not been removed from the graph.
+    ///
+    /// # Returns
+    /// - The region IDs, in the order the regions were added.
+    pub fn region_ids(&self) -> Vec<RegionId> {
+        self.regions
+            .iter()
+            .enumerate()
+            .filter(|(_, region)| region.get_region_type() != RegionType::Inactive)
+            .map(|(index, _)| RegionId::new(index))
+            .collect()
+    }
+
     /// Gets a mutable region by its ID.
     pub fn get_region_mut(
         &mut self,
@@ -211,11 +376,10 @@ impl StructureAnalysis {
             })
     }
 
-    /// Gets the entry region id
+    /// Gets the entry region id. This is the first region added, unless `execute` inserted
+    /// a pre-header before it.
     pub fn get_entry_region(&self) -> RegionId {
-        // TODO: Find a more robust method for finding the entry region. Our assumption
-        // is that the entry region is the region with index 0.
-        RegionId::new(0)
+        self.entry_region
     }
 
     /// Gets the node index of a region.
@@ -258,17 +422,55 @@ impl StructureAnalysis {
             })
     }
 
+    /// Gets the maximum number of iterations for the structure analysis.
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Sets the maximum number of iterations for the structure analysis.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Gets the number of iterations the last call to `execute` ran.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Sets whether snapshots are only kept when `execute` fails. In this mode, the
+    /// most recent snapshots are captured even if debug mode is off, and they are
+    /// discarded when `execute` succeeds.
+    pub fn set_snapshots_on_failure_only(&mut self, snapshots_on_failure_only: bool) {
+        self.snapshots_on_failure_only = snapshots_on_failure_only;
+    }
+
+    /// Sets how `execute` handles an entry region with predecessors.
+    pub fn set_entry_predecessor_handling(
+        &mut self,
+        entry_predecessor_handling: EntryPredecessorHandling,
+    ) {
+        self.entry_predecessor_handling = entry_predecessor_handling;
+    }
+
     /// Executes the control flow analysis.
     pub fn execute(&mut self) -> Result<(), StructureAnalysisError> {
+        // Only the snapshots of a failed run are of interest
+        if self.snapshots_on_failure_only {
+            self.snapshots.clear();
+        }
+
         // Before we start, capture a snapshot of the CFG
         self.capture_snapshot(None);
 
-        let mut iterations = 0;
+        self.iterations = 0;
+        self.reduction_history.clear();
+
+        self.handle_entry_predecessors()?;
 
         // while the region count is still above 1
         while self.region_graph.node_count() > 1 {
             // if we have reached the maximum number of iterations
-            if iterations > self.max_iterations {
+            if self.iterations > self.max_iterations {
                 return Err(StructureAnalysisError::MaxIterationsReached {
                     max_iterations: self.max_iterations,
                     backtrace: Backtrace::capture(),
@@ -304,7 +506,8 @@ impl StructureAnalysis {
                     let mut did_reduce = self.reduce_acyclic_region(region_id)?;
 
                     if !did_reduce && self.is_cyclic(region_id)? {
-                        did_reduce = CyclicRegionReducer.reduce_region(self, region_id)?;
+                        did_reduce =
+                            self.run_reducer(CyclicRegionReducer, ReducerKind::Cyclic, region_id)?;
                     }
 
                     if !did_reduce {
@@ -324,10 +527,14 @@ impl StructureAnalysis {
                 self.post_reduce()?;
             }
 
-            iterations += 1;
+            self.iterations += 1;
         }
 
-        Ok(())
+        let result = self.validate_phi_regions();
+        if self.snapshots_on_failure_only && result.is_ok() {
+            self.snapshots.clear();
+        }
+        result
     }
 
     /// Push a node to a region.
@@ -391,6 +598,44 @@ impl StructureAnalysis {
         Ok(Some(preds[0]))
     }
 
+    /// Gets the successors of a control flow region, ordered by the branch opcode of
+    /// the region so the first is taken when the jump expression holds, and the
+    /// second when it does not.
+    ///
+    /// # Arguments
+    /// - `region_id`: The region ID of the control flow region.
+    ///
+    /// # Returns
+    /// - The successor taken when the jump expression holds, and the other successor.
+    ///
+    /// # Errors
+    /// - `StructureAnalysisError::Other` if the region does not have both a branch and
+    ///   a fallthrough successor.
+    pub fn get_condition_successors(
+        &self,
+        region_id: RegionId,
+    ) -> Result<(RegionId, RegionId), StructureAnalysisError> {
+        let successors = self.get_successors(region_id)?;
+        let find = |edge_type: ControlFlowEdgeType| {
+            successors
+                .iter()
+                .find(|(_, successor_edge_type)| *successor_edge_type == edge_type)
+                .map(|(id, _)| *id)
+                .ok_or(StructureAnalysisError::Other {
+                    message: format!("Control flow region must have a {:?} successor", edge_type),
+                    backtrace: Backtrace::capture(),
+                })
+        };
+
+        let holds =
+            ControlFlowEdgeType::taken_when_condition_holds(self.get_branch_opcode(region_id)?);
+        let fails = match holds {
+            ControlFlowEdgeType::Branch => ControlFlowEdgeType::Fallthrough,
+            ControlFlowEdgeType::Fallthrough => ControlFlowEdgeType::Branch,
+        };
+        Ok((find(holds)?, find(fails)?))
+    }
+
     /// If a region is cyclic
     ///
     /// # Arguments
@@ -411,6 +656,32 @@ impl StructureAnalysis {
         Ok(false)
     }
 
+    /// Gets the active regions in topological order, so that every region comes before
+    /// its successors.
+    ///
+    /// # Returns
+    /// - The region IDs in topological order, without `Inactive` regions.
+    /// - `Err(StructureAnalysisError)` if an error occurred.
+    ///
+    /// # Errors
+    /// - `StructureAnalysisError::CyclicRegionGraph` if the region graph has a cycle.
+    pub fn regions_in_topo_order(&self) -> Result<Vec<RegionId>, StructureAnalysisError> {
+        let nodes = toposort(&self.region_graph, None).map_err(|cycle| {
+            StructureAnalysisError::CyclicRegionGraph {
+                region_id: self.region_graph[cycle.node_id()],
+                backtrace: Backtrace::capture(),
+            }
+        })?;
+
+        Ok(nodes
+            .into_iter()
+            .map(|node| self.region_graph[node])
+            .filter(|region_id| {
+                self.regions[region_id.index].get_region_type() != RegionType::Inactive
+            })
+            .collect())
+    }
+
     /// If a node is a back edge. Basically calls `dominates_strictly` with the arguments reversed.
     ///
     /// # Arguments
@@ -577,23 +848,96 @@ impl StructureAnalysis {
             .collect()
     }
 
-    /// Removes a node from the region graph.
+    /// Removes a node from the region graph. Phi nodes that refer to the region are
+    /// left as they are, so reducers use `remove_merged_region` instead.
     ///
     /// # Arguments
     /// - `region_id`: The region ID of the region to remove.
-    pub fn remove_node(&mut self, region_id: RegionId) -> Result<(), StructureAnalysisError> {
+    fn remove_node(&mut self, region_id: RegionId) -> Result<(), StructureAnalysisError> {
         let node_index = self.get_node_index(region_id)?;
         debug_assert!(self.region_graph.remove_node(node_index).is_some());
 
         // set the region to inactive
         self.regions[region_id.index].set_region_type(RegionType::Inactive);
+        if self.debug_mode {
+            self.removed_regions.push(region_id);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a region that has been merged into another region from the region graph,
+    /// and updates any phi node that refers to it to refer to the surviving region.
+    ///
+    /// # Arguments
+    /// - `region_id`: The region ID of the region that was merged.
+    /// - `into`: The region ID of the region it was merged into.
+    pub fn remove_merged_region(
+        &mut self,
+        region_id: RegionId,
+        into: RegionId,
+    ) -> Result<(), StructureAnalysisError> {
+        for region in self.regions.iter_mut() {
+            if region.get_region_type() == RegionType::Inactive {
+                continue;
+            }
+            let mut replace = |phi: &mut PhiNode| phi.replace_region(region_id, into);
+            for node in region.get_nodes_mut().iter_mut() {
+                walk_phis_in_node(node, &mut replace);
+            }
+            for node in region.get_unresolved_nodes_mut().iter_mut() {
+                walk_phis_in_node(node, &mut replace);
+            }
+            if let Some(mut jump_expr) = region.get_jump_expr().cloned() {
+                walk_phis_in_expr(&mut jump_expr, &mut replace);
+                region.set_jump_expr(Some(jump_expr));
+            }
+        }
+
+        self.remove_node(region_id)
+    }
+
+    /// Checks that every region referenced by a phi node is still part of the region graph.
+    ///
+    /// # Returns
+    /// - `Ok(())` if every phi node refers to active regions.
+    /// - `Err(StructureAnalysisError::DanglingPhiRegion)` for the first reference to an
+    ///   inactive or unknown region.
+    pub fn validate_phi_regions(&self) -> Result<(), StructureAnalysisError> {
+        let mut referenced = Vec::new();
+        for region in self.regions.iter() {
+            if region.get_region_type() == RegionType::Inactive {
+                continue;
+            }
+            let mut collect = |phi: &PhiNode| {
+                referenced.extend(phi.regions().iter().map(|(region_id, _)| *region_id))
+            };
+            for node in region.iter_nodes().chain(region.get_unresolved_nodes()) {
+                visit_phis_in_node(node, &mut collect);
+            }
+            if let Some(jump_expr) = region.get_jump_expr() {
+                visit_phis_in_expr(jump_expr, &mut collect);
+            }
+        }
 
+        for region_id in referenced {
+            let is_live = self
+                .regions
+                .get(region_id.index)
+                .is_some_and(|region| region.get_region_type() != RegionType::Inactive);
+            if !is_live {
+                return Err(StructureAnalysisError::DanglingPhiRegion {
+                    region_id,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        }
         Ok(())
     }
 
     /// Gets the debug snapshots, where each snapshot is a Graphviz representation of the CFG.
     pub fn get_snapshots(&self) -> Result<&Vec<String>, StructureAnalysisError> {
-        if !self.debug_mode {
+        if !self.debug_mode && !self.snapshots_on_failure_only {
             return Err(StructureAnalysisError::Other {
                 message: "Debug mode is not enabled".to_string(),
                 backtrace: Backtrace::capture(),
@@ -603,6 +947,24 @@ impl StructureAnalysis {
         Ok(&self.snapshots)
     }
 
+    /// Gets the reductions made by the last call to `execute`, in the order they were made.
+    ///
+    /// # Returns
+    /// - The reduction history.
+    ///
+    /// # Errors
+    /// - `StructureAnalysisError::Other` if debug mode is not enabled.
+    pub fn reduction_history(&self) -> Result<&Vec<ReductionEvent>, StructureAnalysisError> {
+        if !self.debug_mode {
+            return Err(StructureAnalysisError::Other {
+                message: "Debug mode is not enabled".to_string(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        Ok(&self.reduction_history)
+    }
+
     /// This function should always be called before reducing a region.
     pub fn before_reduce(&mut self, region_id: RegionId) {
         self.capture_region_snapshot(region_id);
@@ -612,15 +974,21 @@ impl StructureAnalysis {
     /// This function should always be called after reducing a region.
     pub fn after_reduce(&mut self, region_id: RegionId) {
         self.capture_region_snapshot(region_id);
+        debug_assert!(self.validate_phi_regions().is_ok());
     }
 
     /// Capture a snapshot of the CFG.
     pub fn capture_snapshot(&mut self, region_to_highlight: Option<RegionId>) {
-        if !self.debug_mode {
+        if !self.debug_mode && !self.snapshots_on_failure_only {
             return;
         }
         self.region_to_highlight = region_to_highlight;
         let dot = self.render_dot(CfgDotConfig::default());
+        if self.snapshots_on_failure_only
+            && self.snapshots.len() >= STRUCTURE_ANALYSIS_FAILURE_SNAPSHOTS
+        {
+            self.snapshots.remove(0);
+        }
         self.snapshots.push(dot);
         self.region_to_highlight = None;
     }
@@ -650,16 +1018,76 @@ impl StructureAnalysis {
                     backtrace: Backtrace::capture(),
                 })?;
         Ok(match region.get_region_type() {
-            RegionType::Linear => LinearRegionReducer.reduce_region(self, region_id)?,
+            RegionType::Linear => {
+                self.run_reducer(LinearRegionReducer, ReducerKind::Linear, region_id)?
+            }
             RegionType::Tail => false,
             RegionType::Inactive => Err(StructureAnalysisError::Other {
                 message: "Inactive region".to_string(),
                 backtrace: Backtrace::capture(),
             })?,
-            RegionType::ControlFlow => IfRegionReducer.reduce_region(self, region_id)?,
+            RegionType::ControlFlow => {
+                self.run_reducer(IfRegionReducer, ReducerKind::If, region_id)?
+            }
         })
     }
 
+    /// Runs a reducer on a region, and records the reduction in debug mode.
+    ///
+    /// # Arguments
+    /// - `reducer`: The reducer to run.
+    /// - `kind`: The kind of the reducer, for the reduction history.
+    /// - `region_id`: The region to reduce.
+    ///
+    /// # Returns
+    /// - `true` if the reducer reduced the region.
+    fn run_reducer<R: RegionReducer>(
+        &mut self,
+        mut reducer: R,
+        kind: ReducerKind,
+        region_id: RegionId,
+    ) -> Result<bool, StructureAnalysisError> {
+        self.removed_regions.clear();
+        let did_reduce = reducer.reduce_region(self, region_id)?;
+        if did_reduce && self.debug_mode {
+            self.reduction_history.push(ReductionEvent {
+                iteration: self.iterations,
+                reducer: kind,
+                region_id,
+                merged_regions: std::mem::take(&mut self.removed_regions),
+            });
+        }
+        Ok(did_reduce)
+    }
+
+    /// Makes sure nothing flows into the entry region, according to
+    /// `entry_predecessor_handling`.
+    ///
+    /// # Errors
+    /// - `StructureAnalysisError::EntryRegionHasPredecessors` if the entry region has
+    ///   predecessors, and `EntryPredecessorHandling::Error` is set.
+    fn handle_entry_predecessors(&mut self) -> Result<(), StructureAnalysisError> {
+        let entry_region = self.get_entry_region();
+        if self.regions.is_empty() || self.get_predecessors(entry_region)?.is_empty() {
+            return Ok(());
+        }
+
+        match self.entry_predecessor_handling {
+            EntryPredecessorHandling::InsertPreHeader => {
+                let pre_header = self.add_region(RegionType::Linear);
+                self.connect_regions(pre_header, entry_region, ControlFlowEdgeType::Fallthrough)?;
+                self.entry_region = pre_header;
+                Ok(())
+            }
+            EntryPredecessorHandling::Error => {
+                Err(StructureAnalysisError::EntryRegionHasPredecessors {
+                    region_id: entry_region,
+                    backtrace: Backtrace::capture(),
+                })
+            }
+        }
+    }
+
     /// Post reduction step
     fn post_reduce(&mut self) -> Result<bool, StructureAnalysisError> {
         let entry_region_id = self.get_entry_region();
@@ -673,18 +1101,22 @@ impl StructureAnalysis {
             .map(|node| self.get_region_id(*node))
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Iterate through the nodes in post order
-        for region_id in nodes {
-            // If the region is inactive, skip it
-            if self.regions[region_id.index].get_region_type() == RegionType::Inactive {
-                continue;
-            }
-
-            if TailRegionReducer.reduce_region(self, region_id)? {
+        // Iterate through the nodes in post order. A virtual branch is only inserted
+        // once no tail region can be reduced, since a region that is about to be merged
+        // into its predecessor does not need one.
+        let active: Vec<RegionId> = nodes
+            .into_iter()
+            .filter(|region_id| {
+                self.regions[region_id.index].get_region_type() != RegionType::Inactive
+            })
+            .collect();
+        for region_id in active.iter() {
+            if self.run_reducer(TailRegionReducer, ReducerKind::Tail, *region_id)? {
                 return Ok(true);
             }
-
-            if VirtualBranchReducer.reduce_region(self, region_id)? {
+        }
+        for region_id in active.iter() {
+            if self.run_reducer(VirtualBranchReducer, ReducerKind::VirtualBranch, *region_id)? {
                 return Ok(true);
             }
         }
@@ -694,6 +1126,85 @@ impl StructureAnalysis {
 }
 
 // == Other impls ==
+/// Calls `f` on every phi node within a node, including those nested in structured nodes.
+fn walk_phis_in_node<F: FnMut(&mut PhiNode)>(node: &mut AstKind, f: &mut F) {
+    match node {
+        AstKind::Expression(expr) => walk_phis_in_expr(expr, f),
+        AstKind::Statement(stmt) => {
+            for expr in stmt.expressions_mut() {
+                walk_phis_in_expr(expr, f);
+            }
+        }
+        AstKind::Block(block) => {
+            for node in block.instructions.iter_mut() {
+                walk_phis_in_node(node, f);
+            }
+        }
+        AstKind::ControlFlow(control_flow) => {
+            if let Some(condition) = control_flow.condition_mut() {
+                walk_phis_in_expr(condition, f);
+            }
+            for node in control_flow.body_mut().instructions.iter_mut() {
+                walk_phis_in_node(node, f);
+            }
+            let (init, step) = control_flow.for_clauses_mut();
+            for node in init.iter_mut().chain(step.iter_mut()) {
+                walk_phis_in_node(node, f);
+            }
+        }
+        AstKind::Function(_) => {}
+    }
+}
+
+/// Calls `f` on every phi node within an expression.
+fn walk_phis_in_expr<F: FnMut(&mut PhiNode)>(expr: &mut ExprKind, f: &mut F) {
+    if let ExprKind::Phi(phi) = expr {
+        f(phi);
+    }
+    for subexpression in expr.subexpressions_mut() {
+        walk_phis_in_expr(subexpression, f);
+    }
+}
+
+/// Calls `f` on every phi node within a node without modifying it. See `walk_phis_in_node`.
+fn visit_phis_in_node<F: FnMut(&PhiNode)>(node: &AstKind, f: &mut F) {
+    match node {
+        AstKind::Expression(expr) => visit_phis_in_expr(expr, f),
+        AstKind::Statement(stmt) => {
+            for expr in stmt.expressions() {
+                visit_phis_in_expr(expr, f);
+            }
+        }
+        AstKind::Block(block) => {
+            for node in block.instructions.iter() {
+                visit_phis_in_node(node, f);
+            }
+        }
+        AstKind::ControlFlow(control_flow) => {
+            if let Some(condition) = control_flow.condition() {
+                visit_phis_in_expr(condition, f);
+            }
+            for node in control_flow.body().instructions.iter() {
+                visit_phis_in_node(node, f);
+            }
+            for node in control_flow.init().iter().chain(control_flow.step()) {
+                visit_phis_in_node(node, f);
+            }
+        }
+        AstKind::Function(_) => {}
+    }
+}
+
+/// Calls `f` on every phi node within an expression without modifying it.
+fn visit_phis_in_expr<F: FnMut(&PhiNode)>(expr: &ExprKind, f: &mut F) {
+    if let ExprKind::Phi(phi) = expr {
+        f(phi);
+    }
+    for subexpression in expr.subexpressions() {
+        visit_phis_in_expr(subexpression, f);
+    }
+}
+
 impl DotRenderableGraph for StructureAnalysis {
     /// Convert the Graph to `dot` format.
     ///
@@ -705,6 +1216,17 @@ impl DotRenderableGraph for StructureAnalysis {
     }
 }
 
+impl MermaidRenderableGraph for StructureAnalysis {
+    /// Convert the Graph to a Mermaid flowchart.
+    ///
+    /// # Returns
+    /// - A `String` containing the Mermaid representation of the graph.
+    fn render_mermaid(&self, config: CfgMermaidConfig) -> String {
+        let mermaid = CfgMermaid { config };
+        mermaid.render(&self.region_graph, self)
+    }
+}
+
 impl NodeResolver for StructureAnalysis {
     type NodeData = Region;
 
@@ -741,7 +1263,108 @@ impl NodeResolver for StructureAnalysis {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::decompiler::ast::{new_assignment, new_id};
+    use crate::decompiler::ast::{
+        control_flow::ControlFlowType, new_assignment, new_bool, new_id, new_phi,
+        statement::StatementKind,
+    };
+
+    /// Gets the type of each control flow node in a region.
+    fn control_flow_types(
+        structure_analysis: &StructureAnalysis,
+        region_id: RegionId,
+    ) -> Result<Vec<ControlFlowType>, StructureAnalysisError> {
+        Ok(structure_analysis
+            .get_region(region_id)?
+            .get_nodes()
+            .iter()
+            .filter_map(|node| match node {
+                AstKind::ControlFlow(control_flow) => Some(control_flow.ty().clone()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_regions_in_topo_order() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+        let entry_region = structure_analysis.add_region(RegionType::Linear);
+        let region_1 = structure_analysis.add_region(RegionType::Linear);
+        let region_2 = structure_analysis.add_region(RegionType::Linear);
+        let region_3 = structure_analysis.add_region(RegionType::Tail);
+        structure_analysis.connect_regions(entry_region, region_2, ControlFlowEdgeType::Branch)?;
+        structure_analysis.connect_regions(
+            entry_region,
+            region_1,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(region_1, region_2, ControlFlowEdgeType::Fallthrough)?;
+        structure_analysis.connect_regions(region_2, region_3, ControlFlowEdgeType::Fallthrough)?;
+
+        assert_eq!(
+            structure_analysis.regions_in_topo_order()?,
+            vec![entry_region, region_1, region_2, region_3]
+        );
+
+        // Inactive regions are not part of the result
+        structure_analysis.remove_node(region_1)?;
+        assert_eq!(
+            structure_analysis.regions_in_topo_order()?,
+            vec![entry_region, region_2, region_3]
+        );
+
+        // A back edge makes the order impossible
+        structure_analysis.connect_regions(region_3, region_2, ControlFlowEdgeType::Branch)?;
+        assert!(matches!(
+            structure_analysis.regions_in_topo_order(),
+            Err(StructureAnalysisError::CyclicRegionGraph { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduction_history() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(true, 100);
+
+        let entry_region = structure_analysis.add_region(RegionType::Linear);
+        let region_1 = structure_analysis.add_region(RegionType::Linear);
+        let region_2 = structure_analysis.add_region(RegionType::Tail);
+        structure_analysis.connect_regions(
+            entry_region,
+            region_1,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(region_1, region_2, ControlFlowEdgeType::Fallthrough)?;
+        structure_analysis.execute()?;
+
+        let history = structure_analysis.reduction_history()?;
+        assert_eq!(
+            history,
+            &vec![
+                ReductionEvent {
+                    iteration: 0,
+                    reducer: ReducerKind::Linear,
+                    region_id: region_1,
+                    merged_regions: vec![region_2],
+                },
+                ReductionEvent {
+                    iteration: 0,
+                    reducer: ReducerKind::Linear,
+                    region_id: entry_region,
+                    merged_regions: vec![region_1],
+                },
+            ]
+        );
+        assert_eq!(
+            history[0].to_string(),
+            "iteration 0: the linear reducer merged region 2 into 1"
+        );
+
+        // The history is only recorded in debug mode
+        let structure_analysis = StructureAnalysis::new(false, 100);
+        assert!(structure_analysis.reduction_history().is_err());
+        Ok(())
+    }
 
     #[test]
     fn test_remove_edge() -> Result<(), StructureAnalysisError> {
@@ -791,4 +1414,257 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_phi_regions_follow_merged_regions() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+        let entry_region = structure_analysis.add_region(RegionType::Linear);
+        let region_1 = structure_analysis.add_region(RegionType::Linear);
+        let region_2 = structure_analysis.add_region(RegionType::Tail);
+        structure_analysis.connect_regions(
+            entry_region,
+            region_1,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(region_1, region_2, ControlFlowEdgeType::Fallthrough)?;
+
+        let mut phi = new_phi(0);
+        phi.add_region(region_1, ControlFlowEdgeType::Fallthrough);
+        structure_analysis.push_to_region(region_2, new_assignment(new_id("x"), phi));
+
+        // Merging the regions updates the phi node to refer to the surviving region
+        structure_analysis.execute()?;
+        let nodes = structure_analysis.get_region(entry_region)?.get_nodes();
+        let AstKind::Statement(StatementKind::Assignment(assignment)) = &nodes[0] else {
+            panic!("Expected an assignment");
+        };
+        let ExprKind::Phi(phi) = &assignment.rhs else {
+            panic!("Expected a phi node");
+        };
+        assert_eq!(
+            phi.regions(),
+            &[(entry_region, ControlFlowEdgeType::Fallthrough)]
+        );
+        structure_analysis.validate_phi_regions()?;
+
+        // Removing a region without merging it leaves the phi node dangling
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+        let region_1 = structure_analysis.add_region(RegionType::Linear);
+        let region_2 = structure_analysis.add_region(RegionType::Tail);
+        let mut phi = new_phi(0);
+        phi.add_region(region_1, ControlFlowEdgeType::Fallthrough);
+        structure_analysis.push_to_region(region_2, new_assignment(new_id("x"), phi));
+        structure_analysis.remove_node(region_1)?;
+        assert!(matches!(
+            structure_analysis.validate_phi_regions(),
+            Err(StructureAnalysisError::DanglingPhiRegion { region_id, .. }) if region_id == region_1
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduce_while_loop() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+        let entry_region = structure_analysis.add_region(RegionType::Linear);
+        let header_region = structure_analysis.add_region(RegionType::ControlFlow);
+        let body_region = structure_analysis.add_region(RegionType::Linear);
+        let exit_region = structure_analysis.add_region(RegionType::Tail);
+
+        structure_analysis
+            .push_to_region(entry_region, new_assignment(new_id("foo"), new_id("bar")));
+        structure_analysis
+            .get_region_mut(header_region)?
+            .set_jump_expr(Some(new_id("foo").into()));
+        structure_analysis
+            .push_to_region(body_region, new_assignment(new_id("foo2"), new_id("bar2")));
+        structure_analysis
+            .push_to_region(exit_region, new_assignment(new_id("foo3"), new_id("bar3")));
+        structure_analysis.connect_regions(
+            entry_region,
+            header_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(
+            header_region,
+            body_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(
+            header_region,
+            exit_region,
+            ControlFlowEdgeType::Branch,
+        )?;
+
+        // the back edge from the body to the header
+        structure_analysis.connect_regions(
+            body_region,
+            header_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        assert!(structure_analysis.is_cyclic(header_region)?);
+
+        structure_analysis.execute()?;
+        assert_eq!(structure_analysis.region_graph.node_count(), 1);
+        assert_eq!(
+            control_flow_types(&structure_analysis, entry_region)?,
+            vec![ControlFlowType::While]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduce_loop_with_condition_in_latch() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+        let entry_region = structure_analysis.add_region(RegionType::Linear);
+        let body_region = structure_analysis.add_region(RegionType::Linear);
+        let latch_region = structure_analysis.add_region(RegionType::ControlFlow);
+        let exit_region = structure_analysis.add_region(RegionType::Tail);
+
+        structure_analysis
+            .push_to_region(body_region, new_assignment(new_id("foo"), new_id("bar")));
+        structure_analysis
+            .push_to_region(latch_region, new_assignment(new_id("foo2"), new_id("bar2")));
+        structure_analysis
+            .get_region_mut(latch_region)?
+            .set_jump_expr(Some(new_id("foo").into()));
+        structure_analysis
+            .push_to_region(exit_region, new_assignment(new_id("foo3"), new_id("bar3")));
+        structure_analysis.connect_regions(
+            entry_region,
+            body_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(
+            body_region,
+            latch_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(
+            latch_region,
+            body_region,
+            ControlFlowEdgeType::Branch,
+        )?;
+        structure_analysis.connect_regions(
+            latch_region,
+            exit_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+
+        structure_analysis.execute()?;
+        assert_eq!(structure_analysis.region_graph.node_count(), 1);
+        assert_eq!(
+            control_flow_types(&structure_analysis, entry_region)?,
+            vec![ControlFlowType::DoWhile]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduce_infinite_loop() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+        let entry_region = structure_analysis.add_region(RegionType::Linear);
+        let loop_region = structure_analysis.add_region(RegionType::Linear);
+
+        structure_analysis
+            .push_to_region(entry_region, new_assignment(new_id("foo"), new_id("bar")));
+        structure_analysis
+            .push_to_region(loop_region, new_assignment(new_id("foo2"), new_id("bar2")));
+        structure_analysis.connect_regions(
+            entry_region,
+            loop_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+
+        // the loop branches to itself and has no exit
+        structure_analysis.connect_regions(
+            loop_region,
+            loop_region,
+            ControlFlowEdgeType::Branch,
+        )?;
+
+        structure_analysis.execute()?;
+        assert_eq!(structure_analysis.region_graph.node_count(), 1);
+        let nodes = structure_analysis.get_region(entry_region)?.get_nodes();
+        let AstKind::ControlFlow(control_flow) = nodes.last().unwrap() else {
+            panic!("Expected a control flow node");
+        };
+        assert_eq!(control_flow.ty(), &ControlFlowType::While);
+        assert_eq!(control_flow.condition(), &Some(new_bool(true).into()));
+
+        // a function that is only an infinite loop
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+        let loop_region = structure_analysis.add_region(RegionType::Linear);
+        structure_analysis
+            .push_to_region(loop_region, new_assignment(new_id("foo"), new_id("bar")));
+        structure_analysis.connect_regions(
+            loop_region,
+            loop_region,
+            ControlFlowEdgeType::Branch,
+        )?;
+        structure_analysis.execute()?;
+        assert_eq!(
+            control_flow_types(&structure_analysis, structure_analysis.get_entry_region())?,
+            vec![ControlFlowType::While]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_region_loop_header() -> Result<(), StructureAnalysisError> {
+        let mut structure_analysis = StructureAnalysis::new(false, 100);
+
+        let header_region = structure_analysis.add_region(RegionType::ControlFlow);
+        let body_region = structure_analysis.add_region(RegionType::Linear);
+        let exit_region = structure_analysis.add_region(RegionType::Tail);
+
+        structure_analysis
+            .get_region_mut(header_region)?
+            .set_jump_expr(Some(new_id("foo").into()));
+        structure_analysis
+            .push_to_region(body_region, new_assignment(new_id("foo"), new_id("bar")));
+        structure_analysis
+            .push_to_region(exit_region, new_assignment(new_id("foo2"), new_id("bar2")));
+        structure_analysis.connect_regions(
+            header_region,
+            body_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+        structure_analysis.connect_regions(
+            header_region,
+            exit_region,
+            ControlFlowEdgeType::Branch,
+        )?;
+        structure_analysis.connect_regions(
+            body_region,
+            header_region,
+            ControlFlowEdgeType::Fallthrough,
+        )?;
+
+        // Failing is opt-in
+        let mut strict = structure_analysis.clone();
+        strict.set_entry_predecessor_handling(EntryPredecessorHandling::Error);
+        assert!(matches!(
+            strict.execute(),
+            Err(StructureAnalysisError::EntryRegionHasPredecessors { region_id, .. })
+                if region_id == header_region
+        ));
+
+        // By default, a pre-header becomes the entry, and the loop is reduced into it
+        structure_analysis.execute()?;
+        let entry_region = structure_analysis.get_entry_region();
+        assert_ne!(entry_region, header_region);
+        assert!(structure_analysis
+            .get_predecessors(entry_region)?
+            .is_empty());
+        assert_eq!(structure_analysis.region_graph.node_count(), 1);
+        assert_eq!(
+            control_flow_types(&structure_analysis, entry_region)?,
+            vec![ControlFlowType::While]
+        );
+        Ok(())
+    }
 }
@@ -25,6 +25,18 @@ pub enum RegionType {
     Inactive,
 }
 
+impl Display for RegionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RegionType::Linear => "Linear",
+            RegionType::ControlFlow => "ControlFlow",
+            RegionType::Tail => "Tail",
+            RegionType::Inactive => "Inactive",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Describes a region
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Copy)]
 pub struct RegionId {
@@ -59,6 +71,7 @@ pub struct Region {
     jump_expr: Option<ExprKind>,
     region_type: RegionType,
     branch_opcode: Option<Opcode>,
+    loop_body: bool,
     region_id: RegionId,
 }
 
@@ -74,6 +87,7 @@ impl Region {
             jump_expr: None,
             region_type,
             branch_opcode: None,
+            loop_body: false,
             region_id,
         }
     }
@@ -133,6 +147,14 @@ impl Region {
         &self.nodes
     }
 
+    /// Gets the nodes in the region mutably.
+    ///
+    /// # Return
+    /// The nodes in the region.
+    pub fn get_nodes_mut(&mut self) -> &mut Vec<AstKind> {
+        &mut self.nodes
+    }
+
     /// Gets the unresolved nodes in the region.
     ///
     /// # Return
@@ -141,6 +163,14 @@ impl Region {
         &self.unresolved_nodes
     }
 
+    /// Gets the unresolved nodes in the region mutably.
+    ///
+    /// # Return
+    /// The unresolved nodes in the region.
+    pub fn get_unresolved_nodes_mut(&mut self) -> &mut Vec<AstKind> {
+        &mut self.unresolved_nodes
+    }
+
     /// Gets the region type.
     ///
     /// # Return
@@ -194,6 +224,23 @@ impl Region {
         self.branch_opcode = Some(opcode);
     }
 
+    /// Gets whether the region is the body of a loop, which GS2 marks by increasing
+    /// the loop counter at the start of the body.
+    ///
+    /// # Return
+    /// If the region is the body of a loop.
+    pub fn is_loop_body(&self) -> bool {
+        self.loop_body
+    }
+
+    /// Sets whether the region is the body of a loop.
+    ///
+    /// # Arguments
+    /// * `loop_body` - If the region is the body of a loop.
+    pub fn set_loop_body(&mut self, loop_body: bool) {
+        self.loop_body = loop_body;
+    }
+
     /// Returns an iterator over the statements in the region.
     ///
     /// # Return
@@ -255,16 +302,10 @@ impl RenderableNode for Region {
         ).unwrap();
 
         // Write the region type as the label.
-        let region_type_str = match self.region_type {
-            RegionType::Linear => "Linear",
-            RegionType::ControlFlow => "ControlFlow",
-            RegionType::Tail => "Tail",
-            RegionType::Inactive => "Inactive",
-        };
         writeln!(
             &mut label,
             r#"{indent}<TR><TD ALIGN="LEFT"><FONT COLOR="{GBF_GREEN}">RegionType: {}</FONT></TD></TR><TR><TD> </TD></TR>"#,
-            region_type_str,
+            self.region_type,
             GBF_GREEN = GBF_GREEN,
             indent = indent
         )
@@ -322,6 +363,35 @@ impl RenderableNode for Region {
 
         label
     }
+
+    /// Render the region as plain text lines: its id, type, statements and jump expression.
+    fn render_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            self.region_id.to_string(),
+            format!("RegionType: {}", self.region_type),
+        ];
+
+        for node in &self.nodes {
+            let context = EmitContextBuilder::default()
+                .verbosity(EmitVerbosity::Debug)
+                .include_ssa_versions(true)
+                .build();
+            let mut emitter = Gs2Emitter::new(context);
+            let result = emitter.visit_node(node).node;
+            lines.extend(result.split('\n').map(str::to_string));
+        }
+
+        if let Some(jump_expr) = &self.jump_expr {
+            let context = EmitContextBuilder::default()
+                .verbosity(EmitVerbosity::Pretty)
+                .include_ssa_versions(true)
+                .build();
+            let mut emitter = Gs2Emitter::new(context);
+            lines.push(format!("JumpExpr: {}", emitter.visit_expr(jump_expr).node));
+        }
+
+        lines
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,7 @@ use crate::decompiler::ast::{
     control_flow::ControlFlowNode, expr::ExprKind, new_acylic_condition, new_else, new_if, ptr::P,
     AstKind,
 };
+use crate::opcode::Opcode;
 
 use super::{
     region::{RegionId, RegionType},
@@ -42,7 +43,7 @@ impl TailRegionReducer {
         start_node: RegionId,
     ) -> Result<(), StructureAnalysisError> {
         analysis.remove_edge(start_node, remove_node)?;
-        analysis.remove_node(remove_node)?;
+        analysis.remove_merged_region(remove_node, start_node)?;
         Ok(())
     }
 
@@ -95,6 +96,11 @@ impl RegionReducer for TailRegionReducer {
             return Ok(false);
         }
 
+        // `with` regions never have an `else`, and are reduced by the `IfRegionReducer`
+        if analysis.get_branch_opcode(region_id)? == Some(Opcode::With) {
+            return Ok(false);
+        }
+
         // Step 1: Extract the jump expression
         let jump_expr = Self::extract_jump_expr(analysis, region_id)?;
 
@@ -143,18 +149,20 @@ impl RegionReducer for TailRegionReducer {
 
             analysis.before_reduce(region_id);
 
-            let branch_statements = Self::get_region_nodes(analysis, branch_region_id)?;
-            let fallthrough_statements = Self::get_region_nodes(analysis, fallthrough_region_id)?;
+            // The branch opcode decides which successor runs when the jump expression holds
+            let (then_region_id, else_region_id) = analysis.get_condition_successors(region_id)?;
+            let then_statements = Self::get_region_nodes(analysis, then_region_id)?;
+            let else_statements = Self::get_region_nodes(analysis, else_region_id)?;
 
-            let mut if_else: P<ControlFlowNode> = new_if(jump_expr, fallthrough_statements).into();
-            let mut else_stmt: P<ControlFlowNode> = new_else(branch_statements).into();
+            let mut if_else: P<ControlFlowNode> = new_if(jump_expr, then_statements).into();
+            let mut else_stmt: P<ControlFlowNode> = new_else(else_statements).into();
 
             if_else
                 .metadata_mut()
-                .add_comment(fallthrough_region_id.to_string());
+                .add_comment(then_region_id.to_string());
             else_stmt
                 .metadata_mut()
-                .add_comment(branch_region_id.to_string());
+                .add_comment(else_region_id.to_string());
 
             Self::merge_tail(analysis, region_id, vec![if_else, else_stmt], true)?;
             Self::cleanup_region(analysis, branch_region_id, region_id)?;
@@ -175,6 +183,7 @@ impl RegionReducer for TailRegionReducer {
                 jump_expr,
                 branch_statements,
                 analysis.get_branch_opcode(region_id)?,
+                ControlFlowEdgeType::Branch,
             )
             .map_err(|e| StructureAnalysisError::AstNodeError {
                 source: Box::new(e),
@@ -206,6 +215,7 @@ impl RegionReducer for TailRegionReducer {
                 jump_expr,
                 fallthrough_statements,
                 analysis.get_branch_opcode(region_id)?,
+                ControlFlowEdgeType::Fallthrough,
             )
             .map_err(|e| StructureAnalysisError::AstNodeError {
                 source: Box::new(e),
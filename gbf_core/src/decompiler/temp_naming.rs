@@ -0,0 +1,238 @@
+#![deny(missing_docs)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+use super::ast::{expr::ExprKind, ssa::SsaVersion, AstKind};
+
+/// The name of the temporary that holds a literal pushed to the stack.
+pub const LITERAL_TEMPORARY: &str = "lit";
+
+/// The name of the temporary that holds the result of a function call.
+pub const CALL_TEMPORARY: &str = "fn_call";
+
+/// The name of the temporary that holds the result of a builtin function call.
+pub const BUILTIN_CALL_TEMPORARY: &str = "builtin_fn_call";
+
+/// The name of the temporary that holds the type of an object created with `new`.
+pub const NEW_OBJECT_TEMPORARY: &str = "gbf_new_obj";
+
+/// The name of the temporary that holds an object created with `new`.
+pub const NEW_NODE_TEMPORARY: &str = "new_node";
+
+/// The name of the temporary that holds a value stored in a register.
+pub const REGISTER_TEMPORARY: &str = "set_register";
+
+/// The name of the temporary that stands in for a value pushed by an opcode the
+/// decompiler does not implement.
+pub const UNIMPLEMENTED_TEMPORARY: &str = "unimplemented";
+
+/// The names the decompiler gives the temporaries it introduces.
+pub const TEMPORARY_NAMES: &[&str] = &[
+    LITERAL_TEMPORARY,
+    CALL_TEMPORARY,
+    BUILTIN_CALL_TEMPORARY,
+    NEW_OBJECT_TEMPORARY,
+    NEW_NODE_TEMPORARY,
+    REGISTER_TEMPORARY,
+    UNIMPLEMENTED_TEMPORARY,
+];
+
+/// Names the temporaries the decompiler introduces.
+pub trait TempNamingScheme: Debug + Send + Sync {
+    /// Gets the name of a temporary.
+    ///
+    /// # Arguments
+    /// - `kind`: The name the decompiler gave the temporary, one of `TEMPORARY_NAMES`.
+    /// - `index`: A counter that is unique for each name that was asked for. It starts
+    ///   at zero, and is increased until the name does not collide with an identifier
+    ///   that is already in the function.
+    ///
+    /// # Returns
+    /// - The name of the temporary.
+    fn name(&self, kind: &str, index: usize) -> String;
+}
+
+/// Names each temporary with a prefix and a counter, such as `tmp0`, `tmp1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixTempNaming {
+    /// The prefix of every temporary.
+    pub prefix: String,
+}
+
+impl PrefixTempNaming {
+    /// Creates a new `PrefixTempNaming`.
+    ///
+    /// # Arguments
+    /// - `prefix`: The prefix of every temporary.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl TempNamingScheme for PrefixTempNaming {
+    fn name(&self, _kind: &str, index: usize) -> String {
+        format!("{}{}", self.prefix, index)
+    }
+}
+
+/// Renames every temporary in a node with a naming scheme. Each SSA value gets its own
+/// name, numbered in the order the values first appear, and names that collide with
+/// an identifier that is not a temporary are skipped.
+///
+/// # Arguments
+/// - `node`: The node to rename the temporaries in, usually a function.
+/// - `scheme`: The naming scheme.
+///
+/// # Example
+/// ```
+/// use gbf_core::decompiler::ast::{emit, new_assignment, new_id, new_id_with_version, new_num};
+/// use gbf_core::decompiler::temp_naming::{rename_temporaries, PrefixTempNaming};
+///
+/// let mut node = new_assignment(new_id_with_version("lit", 3.into()), new_num(1)).into();
+/// rename_temporaries(&mut node, &PrefixTempNaming::new("_t"));
+/// assert_eq!(emit(node), "_t0 = 1;");
+/// ```
+pub fn rename_temporaries(node: &mut AstKind, scheme: &dyn TempNamingScheme) {
+    let mut taken = HashSet::new();
+    walk_node(node, &mut |expr| {
+        if let ExprKind::Identifier(id) = expr {
+            if temporary_key(id.id(), id.ssa_version).is_none() {
+                taken.insert(id.id().clone());
+            }
+        }
+    });
+
+    let mut names: HashMap<(String, SsaVersion), String> = HashMap::new();
+    let mut index = 0;
+    walk_node(node, &mut |expr| {
+        let ExprKind::Identifier(id) = expr else {
+            return;
+        };
+        let Some(key) = temporary_key(id.id(), id.ssa_version) else {
+            return;
+        };
+        let name = names.entry(key).or_insert_with_key(|(kind, _)| loop {
+            let name = scheme.name(kind, index);
+            index += 1;
+            if !taken.contains(&name) {
+                break name;
+            }
+        });
+        id.set_id(name.clone());
+    });
+}
+
+/// Gets the key of an identifier that is a temporary, or `None` if it is not one.
+fn temporary_key(id: &str, ssa_version: Option<SsaVersion>) -> Option<(String, SsaVersion)> {
+    match ssa_version {
+        Some(version) if TEMPORARY_NAMES.contains(&id) => Some((id.to_string(), version)),
+        _ => None,
+    }
+}
+
+/// Calls `f` on every expression within a node, including those nested in structured
+/// nodes, in the order they are emitted.
+fn walk_node<F: FnMut(&mut ExprKind)>(node: &mut AstKind, f: &mut F) {
+    match node {
+        AstKind::Expression(expr) => walk_expr(expr, f),
+        AstKind::Statement(stmt) => {
+            for expr in stmt.expressions_mut() {
+                walk_expr(expr, f);
+            }
+        }
+        AstKind::Block(block) => {
+            for node in block.instructions.iter_mut() {
+                walk_node(node, f);
+            }
+        }
+        AstKind::ControlFlow(control_flow) => {
+            let (init, step) = control_flow.for_clauses_mut();
+            for node in init.iter_mut().chain(step.iter_mut()) {
+                walk_node(node, f);
+            }
+            if let Some(condition) = control_flow.condition_mut() {
+                walk_expr(condition, f);
+            }
+            for node in control_flow.body_mut().instructions.iter_mut() {
+                walk_node(node, f);
+            }
+        }
+        AstKind::Function(function) => {
+            for node in function.body_mut().instructions.iter_mut() {
+                walk_node(node, f);
+            }
+        }
+    }
+}
+
+/// Calls `f` on an expression, and then on each of its subexpressions.
+fn walk_expr<F: FnMut(&mut ExprKind)>(expr: &mut ExprKind, f: &mut F) {
+    f(expr);
+    for subexpression in expr.subexpressions_mut() {
+        walk_expr(subexpression, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::ast::{
+        bin_op::BinOpType, emit, function::FunctionNode, new_assignment, new_bin_op, new_id,
+        new_id_with_version, new_num,
+    };
+
+    #[test]
+    fn test_rename_temporaries() {
+        let lit0 = new_id_with_version("lit", 0.into());
+        let lit1 = new_id_with_version("lit", 1.into());
+        let call = new_id_with_version("fn_call", 2.into());
+        let body: Vec<AstKind> = vec![
+            new_assignment(lit0.clone(), new_num(1)).into(),
+            new_assignment(lit1.clone(), new_num(2)).into(),
+            // `tmp1` is a real variable, so no temporary may be named after it
+            new_assignment(new_id("tmp1"), new_num(3)).into(),
+            new_assignment(
+                call.clone(),
+                new_bin_op(lit0, lit1, BinOpType::Add).unwrap(),
+            )
+            .into(),
+            new_assignment(new_id("x"), call).into(),
+        ];
+        let mut function: AstKind = FunctionNode::new(Some("f".to_string()), vec![], body).into();
+
+        rename_temporaries(&mut function, &PrefixTempNaming::new("tmp"));
+
+        let AstKind::Function(function) = function else {
+            unreachable!();
+        };
+        let statements: Vec<_> = function
+            .body()
+            .instructions
+            .iter()
+            .map(|node| emit(node.clone()))
+            .collect();
+        assert_eq!(
+            statements,
+            vec![
+                "tmp0 = 1;",
+                "tmp2 = 2;",
+                "tmp1 = 3;",
+                "tmp3 = tmp0 + tmp2;",
+                "x = tmp3;"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unversioned_names_kept() {
+        // Only identifiers with an SSA version were introduced by the decompiler
+        let mut node: AstKind = new_assignment(new_id("lit"), new_num(1)).into();
+        rename_temporaries(&mut node, &PrefixTempNaming::new("tmp"));
+        assert_eq!(emit(node), "lit = 1;");
+    }
+}
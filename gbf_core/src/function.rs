@@ -1,17 +1,23 @@
 #![deny(missing_docs)]
 
+use petgraph::algo::dominators::simple_fast;
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::{DfsPostOrder, Walker};
 use petgraph::Direction;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
 use std::ops::{Deref, Index};
+use std::sync::Arc;
 use thiserror::Error;
 
 use crate::basic_block::{BasicBlock, BasicBlockId, BasicBlockType};
 use crate::cfg_dot::{CfgDot, CfgDotConfig, DotRenderableGraph, NodeResolver};
+use crate::cfg_mermaid::{CfgMermaid, CfgMermaidConfig, MermaidRenderableGraph};
+use crate::decompiler::structure_analysis::ControlFlowEdgeType;
+use crate::instruction::Instruction;
+use crate::opcode::{Opcode, StackEffect};
 use crate::utils::{Gs2BytecodeAddress, GBF_BLUE, GBF_GREEN, GBF_RED};
 
 /// Represents an error that can occur when working with functions.
@@ -32,6 +38,26 @@ pub enum FunctionError {
     /// The function already has an entry block.
     #[error("Function already has an entry block")]
     EntryBlockAlreadyExists,
+
+    /// An instruction popped more values than were on the stack.
+    #[error("Stack underflow at address {0}")]
+    StackUnderflow(Gs2BytecodeAddress),
+
+    /// Two paths reached a `BasicBlock` with different stack depths.
+    #[error("Stack depth mismatch at {0}: expected {1}, got {2}")]
+    StackDepthMismatch(BasicBlockId, usize, usize),
+
+    /// There is no edge between the two `BasicBlock`s.
+    #[error("No edge from {0} to {1}")]
+    EdgeNotFound(BasicBlockId, BasicBlockId),
+
+    /// A traced path returned to a `BasicBlock` it already visited.
+    #[error("Path returns to {0} after visiting {1:?}")]
+    PathCycle(BasicBlockId, Vec<BasicBlockId>),
+
+    /// The `BasicBlock` has no outgoing edge of the requested type.
+    #[error("{0} has no {1:?} edge")]
+    EdgeTypeNotFound(BasicBlockId, ControlFlowEdgeType),
 }
 
 /// Represents the identifier of a function.
@@ -97,11 +123,21 @@ impl FunctionId {
     }
 }
 
-/// Represents a function in a module.
-#[derive(Debug, Serialize, Deserialize)]
+/// Represents a function in a module. The blocks and control-flow graph are shared
+/// between clones of a function until one of them is modified, so cloning a function
+/// for each decompiler is cheap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Function {
     /// The identifier of the function.
     pub id: FunctionId,
+    /// The blocks and control-flow graph of the function.
+    #[serde(flatten)]
+    body: Arc<FunctionBody>,
+}
+
+/// The blocks and control-flow graph of a `Function`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FunctionBody {
     /// A vector of all the `BasicBlock`s in the function.
     blocks: Vec<BasicBlock>,
     /// Maps `BasicBlockId` to their index in the `blocks` vector.
@@ -116,6 +152,29 @@ pub struct Function {
     address_to_id: HashMap<Gs2BytecodeAddress, FunctionId>,
 }
 
+/// Measurements of a `Function` that can be computed from its bytecode alone, as
+/// returned by `Function::metrics_report`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionMetrics {
+    /// The identifier of the function.
+    pub id: FunctionId,
+    /// The number of blocks, including unreachable blocks.
+    pub blocks: usize,
+    /// The number of edges between blocks.
+    pub edges: usize,
+    /// The number of instructions.
+    pub instructions: usize,
+    /// The cyclomatic complexity of the blocks reachable from the entry block, which is
+    /// the number of edges minus the number of blocks plus two.
+    pub cyclomatic_complexity: usize,
+    /// The number of natural loops.
+    pub loops: usize,
+    /// The maximum depth of the value stack, or `None` if the stack is not balanced.
+    pub max_stack_depth: Option<usize>,
+    /// The number of instructions with each opcode, keyed by opcode name.
+    pub opcode_histogram: BTreeMap<String, usize>,
+}
+
 impl Function {
     /// Create a new `Function`. Automatically creates an entry block.
     ///
@@ -144,12 +203,14 @@ impl Function {
 
         Self {
             id,
-            blocks,
-            block_map,
-            cfg,
-            graph_node_to_block,
-            block_to_graph_node,
-            address_to_id,
+            body: Arc::new(FunctionBody {
+                blocks,
+                block_map,
+                cfg,
+                graph_node_to_block,
+                block_to_graph_node,
+                address_to_id,
+            }),
         }
     }
 
@@ -179,14 +240,15 @@ impl Function {
             return Err(FunctionError::EntryBlockAlreadyExists);
         }
 
-        let id = BasicBlockId::new(self.blocks.len(), block_type, address);
-        self.blocks.push(BasicBlock::new(id));
-        self.block_map.insert(id, self.blocks.len() - 1);
+        let body = Arc::make_mut(&mut self.body);
+        let id = BasicBlockId::new(body.blocks.len(), block_type, address);
+        body.blocks.push(BasicBlock::new(id));
+        body.block_map.insert(id, body.blocks.len() - 1);
 
         // Insert a node in the petgraph to represent this BasicBlock
-        let node_id = self.cfg.add_node(());
-        self.block_to_graph_node.insert(id, node_id);
-        self.graph_node_to_block.insert(node_id, id);
+        let node_id = body.cfg.add_node(());
+        body.block_to_graph_node.insert(id, node_id);
+        body.graph_node_to_block.insert(node_id, id);
 
         Ok(id)
     }
@@ -213,10 +275,11 @@ impl Function {
     /// ```
     pub fn get_basic_block_by_id(&self, id: BasicBlockId) -> Result<&BasicBlock, FunctionError> {
         let index = self
+            .body
             .block_map
             .get(&id)
             .ok_or(FunctionError::BasicBlockNotFoundById(id))?;
-        Ok(&self.blocks[*index])
+        Ok(&self.body.blocks[*index])
     }
 
     /// Get a reference to a `BasicBlock` by its address. The block address
@@ -244,11 +307,12 @@ impl Function {
         &mut self,
         id: BasicBlockId,
     ) -> Result<&mut BasicBlock, FunctionError> {
-        let index = self
+        let body = Arc::make_mut(&mut self.body);
+        let index = body
             .block_map
             .get(&id)
             .ok_or(FunctionError::BasicBlockNotFoundById(id))?;
-        Ok(&mut self.blocks[*index])
+        Ok(&mut body.blocks[*index])
     }
 
     /// Get a reference to a `BasicBlock` by its address.
@@ -327,7 +391,44 @@ impl Function {
     /// assert!(function.basic_block_exists_by_address(0x100));
     /// ```
     pub fn basic_block_exists_by_address(&self, address: Gs2BytecodeAddress) -> bool {
-        self.blocks.iter().any(|block| block.id.address == address)
+        self.body
+            .blocks
+            .iter()
+            .any(|block| block.id.address == address)
+    }
+
+    /// Gets the start address of every block in the function. Since the blocks do not
+    /// overlap, the block containing an address can be found by binary searching the
+    /// result for the last start address that is not after it.
+    ///
+    /// # Returns
+    /// - The start addresses of the blocks, sorted ascending.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// function.create_block(BasicBlockType::Normal, 0x200).unwrap();
+    /// function.create_block(BasicBlockType::Normal, 0x100).unwrap();
+    ///
+    /// let addresses = function.block_addresses();
+    /// assert_eq!(addresses, vec![0, 0x100, 0x200]);
+    ///
+    /// // The block containing 0x150 starts at 0x100
+    /// let index = addresses.partition_point(|&address| address <= 0x150) - 1;
+    /// assert_eq!(addresses[index], 0x100);
+    /// ```
+    pub fn block_addresses(&self) -> Vec<Gs2BytecodeAddress> {
+        let mut addresses: Vec<Gs2BytecodeAddress> = self
+            .body
+            .blocks
+            .iter()
+            .map(|block| block.id.address)
+            .collect();
+        addresses.sort_unstable();
+        addresses
     }
 
     /// Gets the entry basic block id of the function.
@@ -343,7 +444,7 @@ impl Function {
     /// let entry = function.get_entry_basic_block_id();
     /// ```
     pub fn get_entry_basic_block_id(&self) -> BasicBlockId {
-        self.blocks[0].id
+        self.body.blocks[0].id
     }
 
     /// Get the entry basic block of the function.
@@ -359,7 +460,7 @@ impl Function {
     /// let entry = function.get_entry_basic_block();
     /// ```
     pub fn get_entry_basic_block(&self) -> &BasicBlock {
-        self.blocks.first().unwrap()
+        self.body.blocks.first().unwrap()
     }
 
     /// Get the entry block of the function.
@@ -375,7 +476,7 @@ impl Function {
     /// let entry = function.get_entry_basic_block_mut();
     /// ```
     pub fn get_entry_basic_block_mut(&mut self) -> &mut BasicBlock {
-        self.blocks.first_mut().unwrap()
+        Arc::make_mut(&mut self.body).blocks.first_mut().unwrap()
     }
 
     /// Add an edge between two `BasicBlock`s.
@@ -412,10 +513,69 @@ impl Function {
 
         // With petgraph, this does not fail, so we simply do it:
         // It can panic if the node does not exist, but we have already checked that.
-        self.cfg.add_edge(source_node_id, target_node_id, ());
+        Arc::make_mut(&mut self.body)
+            .cfg
+            .add_edge(source_node_id, target_node_id, ());
         Ok(())
     }
 
+    /// Get the type of the edge between two `BasicBlock`s. The edge is a fallthrough
+    /// if the target block starts right after the last instruction of the source block,
+    /// and a branch otherwise.
+    ///
+    /// # Arguments
+    /// - `source`: The `BasicBlockId` of the source block.
+    /// - `target`: The `BasicBlockId` of the target block.
+    ///
+    /// # Returns
+    /// - The `ControlFlowEdgeType` of the edge.
+    ///
+    /// # Errors
+    /// - `FunctionError::BasicBlockNodeIndexNotFound` if either block does not have a `NodeIndex`.
+    /// - `FunctionError::BasicBlockNotFoundById` if the source block does not exist.
+    /// - `FunctionError::EdgeNotFound` if there is no edge from `source` to `target`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    /// use gbf_core::decompiler::structure_analysis::ControlFlowEdgeType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_id();
+    /// let block = function.create_block(BasicBlockType::Normal, 0).unwrap();
+    /// function.add_edge(entry, block).unwrap();
+    ///
+    /// assert_eq!(function.edge_type(entry, block).unwrap(), ControlFlowEdgeType::Fallthrough);
+    /// ```
+    pub fn edge_type(
+        &self,
+        source: BasicBlockId,
+        target: BasicBlockId,
+    ) -> Result<ControlFlowEdgeType, FunctionError> {
+        let source_node_id = self
+            .block_id_to_node_id(source)
+            .ok_or(FunctionError::BasicBlockNodeIndexNotFound(source))?;
+        let target_node_id = self
+            .block_id_to_node_id(target)
+            .ok_or(FunctionError::BasicBlockNodeIndexNotFound(target))?;
+        if !self.body.cfg.contains_edge(source_node_id, target_node_id) {
+            return Err(FunctionError::EdgeNotFound(source, target));
+        }
+
+        // An empty block falls through to the block at its own address
+        let source_block = self.get_basic_block_by_id(source)?;
+        let fallthrough_address = source_block
+            .terminator()
+            .map_or(source.address, |instruction| instruction.address + 1);
+
+        if target.address != fallthrough_address {
+            Ok(ControlFlowEdgeType::Branch)
+        } else {
+            Ok(ControlFlowEdgeType::Fallthrough)
+        }
+    }
+
     /// Get the number of `BasicBlock`s in the function.
     ///
     /// # Returns
@@ -434,7 +594,7 @@ impl Function {
     /// assert_eq!(function.len(), 4);
     /// ```
     pub fn len(&self) -> usize {
-        self.blocks.len()
+        self.body.blocks.len()
     }
 
     /// Check if the function is empty.
@@ -451,7 +611,7 @@ impl Function {
     /// ```
     pub fn is_empty(&self) -> bool {
         // This will always be false since we always create an entry block
-        self.blocks.is_empty()
+        self.body.blocks.is_empty()
     }
 
     /// Get the predecessors of a `BasicBlock`.
@@ -485,6 +645,7 @@ impl Function {
 
         // Collect all incoming neighbors
         let preds = self
+            .body
             .cfg
             .neighbors_directed(node_id, Direction::Incoming)
             .collect::<Vec<_>>();
@@ -526,6 +687,7 @@ impl Function {
 
         // Collect all outgoing neighbors
         let succs = self
+            .body
             .cfg
             .neighbors_directed(node_id, Direction::Outgoing)
             .collect::<Vec<_>>();
@@ -555,8 +717,8 @@ impl Function {
             .block_id_to_node_id(id)
             .ok_or(FunctionError::BasicBlockNodeIndexNotFound(id))?;
 
-        let dfs = DfsPostOrder::new(&self.cfg, node_id)
-            .iter(&self.cfg)
+        let dfs = DfsPostOrder::new(&self.body.cfg, node_id)
+            .iter(&self.body.cfg)
             .collect::<Vec<_>>();
 
         Ok(dfs
@@ -565,6 +727,548 @@ impl Function {
             .filter_map(|node_id| self.node_id_to_block_id(node_id))
             .collect())
     }
+
+    /// Get every block dominated by a `BasicBlock`, including the block itself. The
+    /// entry block dominates every block reachable from it. Dominance is not defined for
+    /// unreachable blocks, so an unreachable block only dominates itself.
+    ///
+    /// # Arguments
+    /// - `id`: The `BasicBlockId` of the dominating block.
+    ///
+    /// # Returns
+    /// - A vector of the dominated `BasicBlockId`s, in reverse post order.
+    ///
+    /// # Errors
+    /// - `FunctionError::BasicBlockNodeIndexNotFound` if the block does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_id();
+    /// let block1 = function.create_block(BasicBlockType::Normal, 1).unwrap();
+    /// let block2 = function.create_block(BasicBlockType::Normal, 2).unwrap();
+    ///
+    /// function.add_edge(entry, block1).unwrap();
+    /// function.add_edge(block1, block2).unwrap();
+    /// assert_eq!(function.dominated_by(block1).unwrap(), vec![block1, block2]);
+    /// ```
+    pub fn dominated_by(&self, id: BasicBlockId) -> Result<Vec<BasicBlockId>, FunctionError> {
+        let node_id = self
+            .block_id_to_node_id(id)
+            .ok_or(FunctionError::BasicBlockNodeIndexNotFound(id))?;
+        let entry_id = self.get_entry_basic_block_id();
+        let entry_node_id = self
+            .block_id_to_node_id(entry_id)
+            .ok_or(FunctionError::BasicBlockNodeIndexNotFound(entry_id))?;
+
+        let dominators = simple_fast(&self.body.cfg, entry_node_id);
+        let mut dominated: Vec<_> = self
+            .get_reverse_post_order(entry_id)?
+            .into_iter()
+            .filter(|block_id| {
+                self.block_id_to_node_id(*block_id)
+                    .and_then(|node| dominators.dominators(node))
+                    .is_some_and(|mut doms| doms.any(|dom| dom == node_id))
+            })
+            .collect();
+
+        // Unreachable blocks have no dominators, but still dominate themselves
+        if dominated.is_empty() {
+            dominated.push(id);
+        }
+        Ok(dominated)
+    }
+
+    /// Compute the maximum depth the value stack reaches while executing the function.
+    /// The stack starts empty at the entry block, and every path into a block must
+    /// agree on the stack depth at the start of that block.
+    ///
+    /// # Returns
+    /// - The maximum stack depth.
+    ///
+    /// # Errors
+    /// - `FunctionError::StackUnderflow` if an instruction pops more values than are on
+    ///   the stack.
+    /// - `FunctionError::StackDepthMismatch` if two paths reach a block with different
+    ///   stack depths.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_mut();
+    /// entry.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+    /// entry.add_instruction(Instruction::new(Opcode::PushFalse, 1));
+    /// entry.add_instruction(Instruction::new(Opcode::Add, 2));
+    /// entry.add_instruction(Instruction::new(Opcode::Ret, 3));
+    ///
+    /// assert_eq!(function.max_stack_depth().unwrap(), 2);
+    /// ```
+    pub fn max_stack_depth(&self) -> Result<usize, FunctionError> {
+        let entry_id = self.get_entry_basic_block_id();
+        let mut entry_states: HashMap<BasicBlockId, StackState> = HashMap::new();
+        entry_states.insert(entry_id, StackState::default());
+        let mut worklist = vec![entry_id];
+        let mut max_depth = 0;
+
+        while let Some(block_id) = worklist.pop() {
+            let block = self.get_basic_block_by_id(block_id)?;
+            let mut state = entry_states[&block_id].clone();
+            for instruction in block.iter() {
+                state.apply(instruction)?;
+                max_depth = max_depth.max(state.depth);
+            }
+
+            for successor in self.get_successors(block_id)? {
+                let mut successor_state = state.clone();
+
+                // Short circuits only keep the condition on the stack when they jump,
+                // so the fall-through path pops it.
+                if let Some(last) = block.terminator() {
+                    if matches!(
+                        last.opcode,
+                        Opcode::ShortCircuitAnd | Opcode::ShortCircuitOr
+                    ) && successor.address == last.address + 1
+                    {
+                        successor_state.pop(1, last.address)?;
+                    }
+                }
+
+                match entry_states.get(&successor) {
+                    Some(existing) if *existing != successor_state => {
+                        return Err(FunctionError::StackDepthMismatch(
+                            successor,
+                            existing.depth,
+                            successor_state.depth,
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        entry_states.insert(successor, successor_state);
+                        worklist.push(successor);
+                    }
+                }
+            }
+        }
+
+        Ok(max_depth)
+    }
+
+    /// Check whether the control-flow graph of this function has the same structure as
+    /// another, ignoring addresses. Blocks are compared by their type and number of
+    /// instructions, and edges by whether they fall through or branch. Only blocks
+    /// reachable from the entry block are compared.
+    ///
+    /// # Arguments
+    /// - `other`: The function to compare against.
+    ///
+    /// # Returns
+    /// - `true` if the control-flow graphs are isomorphic.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut a = Function::new(FunctionId::new_without_name(0, 0));
+    /// a.get_entry_basic_block_mut().add_instruction(Instruction::new(Opcode::Ret, 0));
+    /// let mut b = Function::new(FunctionId::new_without_name(0, 100));
+    /// b.get_entry_basic_block_mut().add_instruction(Instruction::new(Opcode::Ret, 100));
+    ///
+    /// assert!(a.cfg_isomorphic_to(&b));
+    /// ```
+    pub fn cfg_isomorphic_to(&self, other: &Function) -> bool {
+        self.canonical_cfg(false) == other.canonical_cfg(false)
+    }
+
+    /// Check whether the control-flow graph of this function has the same structure as
+    /// another, ignoring addresses, and every pair of matching blocks has the same
+    /// sequence of opcodes. Operands are not compared.
+    ///
+    /// # Arguments
+    /// - `other`: The function to compare against.
+    ///
+    /// # Returns
+    /// - `true` if the control-flow graphs are isomorphic with matching opcodes.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut a = Function::new(FunctionId::new_without_name(0, 0));
+    /// a.get_entry_basic_block_mut().add_instruction(Instruction::new(Opcode::PushTrue, 0));
+    /// let mut b = Function::new(FunctionId::new_without_name(0, 100));
+    /// b.get_entry_basic_block_mut().add_instruction(Instruction::new(Opcode::PushFalse, 100));
+    ///
+    /// assert!(a.cfg_isomorphic_to(&b));
+    /// assert!(!a.cfg_equivalent_with_opcodes(&b));
+    /// ```
+    pub fn cfg_equivalent_with_opcodes(&self, other: &Function) -> bool {
+        self.canonical_cfg(true) == other.canonical_cfg(true)
+    }
+
+    /// Find every block that contains at least one instruction with the given opcode.
+    ///
+    /// # Arguments
+    /// - `opcode`: The opcode to look for.
+    ///
+    /// # Returns
+    /// - The ids of the matching blocks, ordered by address.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_mut();
+    /// entry.add_instruction(Instruction::new(Opcode::Ret, 0));
+    ///
+    /// assert_eq!(function.blocks_with_opcode(Opcode::Ret), vec![function.get_entry_basic_block_id()]);
+    /// assert!(function.blocks_with_opcode(Opcode::Call).is_empty());
+    /// ```
+    pub fn blocks_with_opcode(&self, opcode: Opcode) -> Vec<BasicBlockId> {
+        let mut blocks: Vec<BasicBlockId> = self
+            .body
+            .blocks
+            .iter()
+            .filter(|block| block.iter().any(|inst| inst.opcode == opcode))
+            .map(|block| block.id)
+            .collect();
+        blocks.sort_by_key(|id| id.address);
+        blocks
+    }
+
+    /// Find every instruction with the given opcode, along with the block it is in.
+    ///
+    /// # Arguments
+    /// - `opcode`: The opcode to look for.
+    ///
+    /// # Returns
+    /// - The block id and address of each matching instruction, ordered by address.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_mut();
+    /// entry.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+    /// entry.add_instruction(Instruction::new(Opcode::Ret, 1));
+    ///
+    /// let entry_id = function.get_entry_basic_block_id();
+    /// assert_eq!(function.instructions_with_opcode(Opcode::Ret), vec![(entry_id, 1)]);
+    /// ```
+    pub fn instructions_with_opcode(
+        &self,
+        opcode: Opcode,
+    ) -> Vec<(BasicBlockId, Gs2BytecodeAddress)> {
+        let mut instructions: Vec<(BasicBlockId, Gs2BytecodeAddress)> = self
+            .body
+            .blocks
+            .iter()
+            .flat_map(|block| {
+                block
+                    .iter()
+                    .filter(move |inst| inst.opcode == opcode)
+                    .map(move |inst| (block.id, inst.address))
+            })
+            .collect();
+        instructions.sort_by_key(|(_, address)| *address);
+        instructions
+    }
+
+    /// Find every address that a jump instruction in the function jumps to. Each target
+    /// should be the start of a block, since the loader splits blocks at jump targets.
+    ///
+    /// # Returns
+    /// - The target addresses, sorted.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    /// use gbf_core::operand::Operand;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_mut();
+    /// entry.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+    /// entry.add_instruction(Instruction::new_with_operand(Opcode::Jne, 1, Operand::new_number(3)));
+    /// entry.add_instruction(Instruction::new_with_operand(Opcode::Jmp, 2, Operand::new_number(0)));
+    ///
+    /// assert_eq!(function.jump_targets().into_iter().collect::<Vec<_>>(), vec![0, 3]);
+    /// ```
+    pub fn jump_targets(&self) -> BTreeSet<Gs2BytecodeAddress> {
+        self.body
+            .blocks
+            .iter()
+            .flat_map(|block| block.iter().filter_map(Instruction::jump_target))
+            .collect()
+    }
+
+    /// Get the control flow graph of the function as an adjacency list, for exporting
+    /// it to tools that do not read dot or Mermaid.
+    ///
+    /// # Returns
+    /// - Each block with the targets and types of its outgoing edges. The blocks and
+    ///   the targets of each block are ordered by address.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    /// use gbf_core::decompiler::structure_analysis::ControlFlowEdgeType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_id();
+    /// let block = function.create_block(BasicBlockType::Normal, 0).unwrap();
+    /// function.add_edge(entry, block).unwrap();
+    ///
+    /// let adjacency = function.cfg_adjacency();
+    /// assert_eq!(adjacency[0], (entry, vec![(block, ControlFlowEdgeType::Fallthrough)]));
+    /// ```
+    pub fn cfg_adjacency(&self) -> Vec<(BasicBlockId, Vec<(BasicBlockId, ControlFlowEdgeType)>)> {
+        let mut adjacency: Vec<_> = self
+            .body
+            .blocks
+            .iter()
+            .map(|block| {
+                let mut edges: Vec<_> = self
+                    .get_successors(block.id)
+                    .expect("[Bug] Every block should have a node in the graph.")
+                    .into_iter()
+                    .map(|target| {
+                        let edge_type = self
+                            .edge_type(block.id, target)
+                            .expect("[Bug] The edge to a successor should exist.");
+                        (target, edge_type)
+                    })
+                    .collect();
+                edges.sort_by_key(|(target, _)| target.address);
+                (block.id, edges)
+            })
+            .collect();
+        adjacency.sort_by_key(|(id, _)| id.address);
+        adjacency
+    }
+
+    /// Trace the blocks one execution of the function visits, starting at the entry
+    /// block. At each conditional block, the decision for that block picks the branch
+    /// edge if it is `true` and the fallthrough edge if it is `false`. Blocks without a
+    /// decision fall through.
+    ///
+    /// # Arguments
+    /// - `decisions`: The branch decision for each conditional block.
+    ///
+    /// # Returns
+    /// - The blocks visited, in order, ending with a block that has no successors.
+    ///
+    /// # Errors
+    /// - `FunctionError::PathCycle` if the path returns to a block it already visited,
+    ///   since it would then repeat forever.
+    /// - `FunctionError::EdgeTypeNotFound` if a conditional block has no edge of the
+    ///   type its decision picks.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_id();
+    /// let block = function.create_block(BasicBlockType::Normal, 0).unwrap();
+    /// function.add_edge(entry, block).unwrap();
+    ///
+    /// assert_eq!(function.trace_path(&[]).unwrap(), vec![entry, block]);
+    /// ```
+    pub fn trace_path(
+        &self,
+        decisions: &[(BasicBlockId, bool)],
+    ) -> Result<Vec<BasicBlockId>, FunctionError> {
+        let decisions: HashMap<BasicBlockId, bool> = decisions.iter().cloned().collect();
+        let mut path = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = self.get_entry_basic_block_id();
+
+        loop {
+            if !visited.insert(current) {
+                return Err(FunctionError::PathCycle(current, path));
+            }
+            path.push(current);
+
+            let mut successors = self.get_successors(current)?;
+            let next = match successors.len() {
+                0 => return Ok(path),
+                1 => successors[0],
+                _ => {
+                    let edge_type = if decisions.get(&current).copied().unwrap_or(false) {
+                        ControlFlowEdgeType::Branch
+                    } else {
+                        ControlFlowEdgeType::Fallthrough
+                    };
+                    successors.sort_by_key(|successor| successor.address);
+                    let mut next = None;
+                    for successor in successors {
+                        if self.edge_type(current, successor)? == edge_type {
+                            next = Some(successor);
+                            break;
+                        }
+                    }
+                    next.ok_or(FunctionError::EdgeTypeNotFound(current, edge_type))?
+                }
+            };
+            current = next;
+        }
+    }
+
+    /// Get the number of loops a `BasicBlock` is nested in. Loops are the natural loops
+    /// of the back edges of the graph, where a back edge is an edge to a block that
+    /// dominates its source. Back edges to the same header make up a single loop.
+    ///
+    /// # Arguments
+    /// - `id`: The `BasicBlockId` of the block.
+    ///
+    /// # Returns
+    /// - The number of loops that contain the block, or `0` if it is not in a loop.
+    ///
+    /// # Errors
+    /// - `FunctionError::BasicBlockNodeIndexNotFound` if the block does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_id();
+    /// let header = function.create_block(BasicBlockType::Normal, 1).unwrap();
+    /// let exit = function.create_block(BasicBlockType::Normal, 2).unwrap();
+    ///
+    /// function.add_edge(entry, header).unwrap();
+    /// function.add_edge(header, header).unwrap();
+    /// function.add_edge(header, exit).unwrap();
+    /// assert_eq!(function.loop_depth(header).unwrap(), 1);
+    /// assert_eq!(function.loop_depth(exit).unwrap(), 0);
+    /// ```
+    pub fn loop_depth(&self, id: BasicBlockId) -> Result<usize, FunctionError> {
+        self.block_id_to_node_id(id)
+            .ok_or(FunctionError::BasicBlockNodeIndexNotFound(id))?;
+        Ok(self
+            .natural_loops()?
+            .values()
+            .filter(|body| body.contains(&id))
+            .count())
+    }
+
+    /// Check if two `BasicBlock`s are directly in the same loop, that is, if the
+    /// innermost loop of both blocks is the same. Blocks that are not in a loop are
+    /// not in the same loop as any block. See `loop_depth` for how loops are found.
+    ///
+    /// # Arguments
+    /// - `a`: The `BasicBlockId` of the first block.
+    /// - `b`: The `BasicBlockId` of the second block.
+    ///
+    /// # Returns
+    /// - `true` if both blocks have the same innermost loop.
+    ///
+    /// # Errors
+    /// - `FunctionError::BasicBlockNodeIndexNotFound` if either block does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::basic_block::BasicBlockType;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_id();
+    /// let header = function.create_block(BasicBlockType::Normal, 1).unwrap();
+    /// let body = function.create_block(BasicBlockType::Normal, 2).unwrap();
+    ///
+    /// function.add_edge(entry, header).unwrap();
+    /// function.add_edge(header, body).unwrap();
+    /// function.add_edge(body, header).unwrap();
+    /// assert!(function.same_loop(header, body).unwrap());
+    /// assert!(!function.same_loop(entry, body).unwrap());
+    /// ```
+    pub fn same_loop(&self, a: BasicBlockId, b: BasicBlockId) -> Result<bool, FunctionError> {
+        for id in [a, b] {
+            self.block_id_to_node_id(id)
+                .ok_or(FunctionError::BasicBlockNodeIndexNotFound(id))?;
+        }
+        let loops = self.natural_loops()?;
+        let innermost = |id: BasicBlockId| {
+            loops
+                .iter()
+                .filter(|(_, body)| body.contains(&id))
+                .min_by_key(|(_, body)| body.len())
+                .map(|(header, _)| *header)
+        };
+        Ok(innermost(a).is_some_and(|header| innermost(b) == Some(header)))
+    }
+
+    /// Measure the function without decompiling it. The report can be serialized, so
+    /// it can be collected across a corpus.
+    ///
+    /// # Returns
+    /// - The `FunctionMetrics` of the function.
+    ///
+    /// # Errors
+    /// - `FunctionError::BasicBlockNodeIndexNotFound` if a block is missing from the graph.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::function::{Function, FunctionId};
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    /// let entry = function.get_entry_basic_block_mut();
+    /// entry.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+    /// entry.add_instruction(Instruction::new(Opcode::Ret, 1));
+    ///
+    /// let metrics = function.metrics_report().unwrap();
+    /// assert_eq!(metrics.instructions, 2);
+    /// assert_eq!(metrics.cyclomatic_complexity, 1);
+    /// assert_eq!(metrics.opcode_histogram["Ret"], 1);
+    /// ```
+    pub fn metrics_report(&self) -> Result<FunctionMetrics, FunctionError> {
+        let reachable = self.get_reverse_post_order(self.get_entry_basic_block_id())?;
+        let mut reachable_edges = 0;
+        for block_id in reachable.iter() {
+            reachable_edges += self.get_successors(*block_id)?.len();
+        }
+
+        let mut opcode_histogram = BTreeMap::new();
+        for instruction in self.body.blocks.iter().flat_map(|block| block.iter()) {
+            *opcode_histogram
+                .entry(instruction.opcode.to_string())
+                .or_insert(0) += 1;
+        }
+
+        Ok(FunctionMetrics {
+            id: self.id.clone(),
+            blocks: self.len(),
+            edges: self.body.cfg.edge_count(),
+            instructions: opcode_histogram.values().sum(),
+            cyclomatic_complexity: (reachable_edges + 2).saturating_sub(reachable.len()),
+            loops: self.natural_loops()?.len(),
+            max_stack_depth: self.max_stack_depth().ok(),
+            opcode_histogram,
+        })
+    }
 }
 
 /// Internal API for `Function`.
@@ -583,13 +1287,58 @@ impl Function {
         &self,
         address: Gs2BytecodeAddress,
     ) -> Result<BasicBlockId, FunctionError> {
-        self.blocks
+        self.body
+            .blocks
             .iter()
             .find(|block| block.id.address == address)
             .map(|block| block.id)
             .ok_or(FunctionError::BasicBlockNotFoundByAddress(address))
     }
 
+    /// Find the natural loops of the blocks reachable from the entry block. The body of
+    /// a loop is its header, along with every block that can reach one of the back
+    /// edges to the header without passing through the header.
+    ///
+    /// # Returns
+    /// - The body of each loop, keyed by the header of the loop.
+    ///
+    /// # Errors
+    /// - `FunctionError::BasicBlockNodeIndexNotFound` if a block is missing from the graph.
+    fn natural_loops(&self) -> Result<HashMap<BasicBlockId, HashSet<BasicBlockId>>, FunctionError> {
+        let entry_id = self.get_entry_basic_block_id();
+        let entry_node_id = self
+            .block_id_to_node_id(entry_id)
+            .ok_or(FunctionError::BasicBlockNodeIndexNotFound(entry_id))?;
+        let dominators = simple_fast(&self.body.cfg, entry_node_id);
+        let dominates = |header: BasicBlockId, id: BasicBlockId| {
+            let header = self.block_id_to_node_id(header);
+            self.block_id_to_node_id(id)
+                .and_then(|node| dominators.dominators(node))
+                .is_some_and(|mut doms| doms.any(|dom| Some(dom) == header))
+        };
+
+        let mut loops: HashMap<BasicBlockId, HashSet<BasicBlockId>> = HashMap::new();
+        for source in self.get_reverse_post_order(entry_id)? {
+            for header in self.get_successors(source)? {
+                if !dominates(header, source) {
+                    continue;
+                }
+                let body = loops
+                    .entry(header)
+                    .or_insert_with(|| HashSet::from([header]));
+                let mut worklist = vec![source];
+                while let Some(id) = worklist.pop() {
+                    if body.insert(id) {
+                        let preds = self.get_predecessors(id)?;
+                        // Unreachable predecessors are not part of the loop
+                        worklist.extend(preds.into_iter().filter(|pred| dominates(header, *pred)));
+                    }
+                }
+            }
+        }
+        Ok(loops)
+    }
+
     /// Convert a `NodeIndex` to a `BasicBlockId`.
     ///
     /// # Arguments
@@ -598,7 +1347,7 @@ impl Function {
     /// # Returns
     /// - The `BasicBlockId` of the block with the corresponding `NodeIndex`.
     fn node_id_to_block_id(&self, node_id: NodeIndex) -> Option<BasicBlockId> {
-        self.graph_node_to_block.get(&node_id).cloned()
+        self.body.graph_node_to_block.get(&node_id).cloned()
     }
 
     /// Convert a `BasicBlockId` to a `NodeIndex`.
@@ -609,7 +1358,143 @@ impl Function {
     /// # Returns
     /// - The `NodeIndex` of the block with the corresponding `BasicBlockId`.
     fn block_id_to_node_id(&self, block_id: BasicBlockId) -> Option<NodeIndex> {
-        self.block_to_graph_node.get(&block_id).cloned()
+        self.body.block_to_graph_node.get(&block_id).cloned()
+    }
+
+    /// Label the blocks reachable from the entry block in depth-first order, visiting
+    /// the fallthrough successor of a block before its branch successors. The labeling
+    /// only depends on the shape of the graph, so two isomorphic graphs produce the
+    /// same result.
+    ///
+    /// # Arguments
+    /// - `include_opcodes`: Whether to include the opcodes of each block.
+    ///
+    /// # Returns
+    /// - The canonical form of each block, in depth-first order.
+    fn canonical_cfg(&self, include_opcodes: bool) -> Vec<CanonicalBlock> {
+        let successors_of = |block: &BasicBlock| {
+            let fallthrough_address = block
+                .terminator()
+                .map_or(block.id.address, |instruction| instruction.address + 1);
+            let mut successors = self.get_successors(block.id).unwrap_or_default();
+            successors.sort_by_key(|successor| {
+                (successor.address != fallthrough_address, successor.address)
+            });
+            successors
+        };
+
+        let mut order = Vec::new();
+        let mut labels = HashMap::new();
+        let mut stack = vec![self.get_entry_basic_block_id()];
+        while let Some(block_id) = stack.pop() {
+            if labels.contains_key(&block_id) {
+                continue;
+            }
+            let Ok(block) = self.get_basic_block_by_id(block_id) else {
+                continue;
+            };
+            labels.insert(block_id, order.len());
+            order.push(block);
+            stack.extend(successors_of(block).into_iter().rev());
+        }
+
+        order
+            .into_iter()
+            .map(|block| CanonicalBlock {
+                block_type: block.id.block_type,
+                len: block.len(),
+                opcodes: if include_opcodes {
+                    block.iter().map(|instruction| instruction.opcode).collect()
+                } else {
+                    Vec::new()
+                },
+                successors: successors_of(block)
+                    .iter()
+                    .map(|successor| labels[successor])
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// The address-independent form of a `BasicBlock`, used to compare control-flow graphs.
+#[derive(Debug, PartialEq, Eq)]
+struct CanonicalBlock {
+    /// The type of the block.
+    block_type: BasicBlockType,
+    /// The number of instructions in the block.
+    len: usize,
+    /// The opcodes of the block, if they are being compared.
+    opcodes: Vec<Opcode>,
+    /// The canonical labels of the successors of the block.
+    successors: Vec<usize>,
+}
+
+/// The state of the value stack at a point in a function.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct StackState {
+    /// The number of values on the stack.
+    depth: usize,
+    /// The stack positions of the open `PushArray` markers, innermost last.
+    markers: Vec<usize>,
+}
+
+impl StackState {
+    /// Pop values from the stack.
+    ///
+    /// # Arguments
+    /// - `count`: The number of values to pop.
+    /// - `address`: The address of the popping instruction.
+    ///
+    /// # Errors
+    /// - `FunctionError::StackUnderflow` if there are fewer than `count` values.
+    fn pop(&mut self, count: usize, address: Gs2BytecodeAddress) -> Result<(), FunctionError> {
+        self.depth = self
+            .depth
+            .checked_sub(count)
+            .ok_or(FunctionError::StackUnderflow(address))?;
+        Ok(())
+    }
+
+    /// Apply the stack effect of an instruction.
+    ///
+    /// # Arguments
+    /// - `instruction`: The instruction to apply.
+    ///
+    /// # Errors
+    /// - `FunctionError::StackUnderflow` if the instruction pops more values than are
+    ///   on the stack, or pops down to a `PushArray` marker that does not exist.
+    fn apply(&mut self, instruction: &Instruction) -> Result<(), FunctionError> {
+        let address = instruction.address;
+        match instruction.opcode.stack_effect() {
+            StackEffect::Fixed { pops, pushes } => {
+                if instruction.opcode == Opcode::PushArray {
+                    self.markers.push(self.depth);
+                }
+                self.pop(pops, address)?;
+                self.depth += pushes;
+
+                // Swapping moves the marker if it is one of the top two values
+                if instruction.opcode == Opcode::Swap {
+                    if let Some(marker) = self.markers.last_mut() {
+                        if *marker + 1 == self.depth {
+                            *marker -= 1;
+                        } else if *marker + 2 == self.depth {
+                            *marker += 1;
+                        }
+                    }
+                }
+            }
+            StackEffect::Variable { pushes } => {
+                let marker = self
+                    .markers
+                    .pop()
+                    .filter(|marker| *marker < self.depth)
+                    .ok_or(FunctionError::StackUnderflow(address))?;
+                self.depth = marker + pushes;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -627,56 +1512,12 @@ impl Display for FunctionId {
     }
 }
 
-/// Clone implementation for Function
-impl Clone for Function {
-    fn clone(&self) -> Self {
-        let mut blocks = Vec::new();
-        let mut block_map = HashMap::new();
-        let mut graph_node_to_block = HashMap::new();
-        let mut block_to_graph_node = HashMap::new();
-        let address_to_id = HashMap::new();
-        let mut cfg = DiGraph::new();
-
-        // Clone blocks
-        for block in &self.blocks {
-            let new_block = block.clone();
-            let new_block_id = new_block.id;
-            blocks.push(new_block);
-            block_map.insert(new_block_id, blocks.len() - 1);
-
-            // Insert a node in the petgraph to represent this BasicBlock
-            let node_id = cfg.add_node(());
-            block_to_graph_node.insert(new_block_id, node_id);
-            graph_node_to_block.insert(node_id, new_block_id);
-        }
-
-        // Clone edges
-        for edge in self.cfg.raw_edges() {
-            let source = self.graph_node_to_block[&edge.source()];
-            let target = self.graph_node_to_block[&edge.target()];
-            let source_node_id = block_to_graph_node[&source];
-            let target_node_id = block_to_graph_node[&target];
-            cfg.add_edge(source_node_id, target_node_id, ());
-        }
-
-        Self {
-            id: self.id.clone(),
-            blocks,
-            block_map,
-            cfg,
-            graph_node_to_block,
-            block_to_graph_node,
-            address_to_id,
-        }
-    }
-}
-
 /// Deref implementation for Function
 impl Deref for Function {
     type Target = [BasicBlock];
 
     fn deref(&self) -> &Self::Target {
-        &self.blocks
+        &self.body.blocks
     }
 }
 
@@ -685,7 +1526,7 @@ impl Index<usize> for Function {
     type Output = BasicBlock;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.blocks[index]
+        &self.body.blocks[index]
     }
 }
 
@@ -695,7 +1536,7 @@ impl<'a> IntoIterator for &'a Function {
     type IntoIter = std::slice::Iter<'a, BasicBlock>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.blocks.iter()
+        self.body.blocks.iter()
     }
 }
 
@@ -705,7 +1546,7 @@ impl<'a> IntoIterator for &'a mut Function {
     type IntoIter = std::slice::IterMut<'a, BasicBlock>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.blocks.iter_mut()
+        Arc::make_mut(&mut self.body).blocks.iter_mut()
     }
 }
 
@@ -713,46 +1554,46 @@ impl NodeResolver for Function {
     type NodeData = BasicBlock;
 
     fn resolve(&self, node_index: NodeIndex) -> Option<&Self::NodeData> {
-        self.graph_node_to_block
+        self.body
+            .graph_node_to_block
             .get(&node_index)
             .and_then(|block_id| {
-                self.block_map
+                self.body
+                    .block_map
                     .get(block_id)
-                    .and_then(|index| self.blocks.get(*index))
+                    .and_then(|index| self.body.blocks.get(*index))
             })
     }
 
     fn resolve_edge_color(&self, source: NodeIndex, target: NodeIndex) -> String {
-        // Get the last instruction of the source block
         let source_block_id = self
+            .body
             .graph_node_to_block
             .get(&source)
             .expect("Source block not found");
-        let source_block = self
-            .get_basic_block_by_id(*source_block_id)
-            .expect("Source block not found");
-        let source_last_instruction = source_block.last().unwrap();
-
         let target_block_id = self
+            .body
             .graph_node_to_block
             .get(&target)
             .expect("Target block not found");
-        let target_block = self
-            .get_basic_block_by_id(*target_block_id)
-            .expect("Target block not found");
 
-        // Figure out if the edge represents a branch by seeing if the target
-        // block address is NOT the next address after the source instruction.
-        let source_last_address = source_last_instruction.address;
-        let target_address = target_block.id.address;
-        if source_last_address + 1 != target_address {
+        let edge_type = self
+            .edge_type(*source_block_id, *target_block_id)
+            .expect("Edge not found");
+        if edge_type == ControlFlowEdgeType::Branch {
             // This represents a branch. Color the edge green.
             return GBF_GREEN.to_string();
         }
 
         // If the opcode of the last instruction is a fall through, color the edge red since
         // the target block's address is the next address
-        if source_last_instruction.opcode.has_fall_through() {
+        let source_block = self
+            .get_basic_block_by_id(*source_block_id)
+            .expect("Source block not found");
+        if source_block
+            .terminator_opcode()
+            .is_some_and(|opcode| opcode.has_fall_through())
+        {
             return GBF_RED.to_string();
         }
 
@@ -768,7 +1609,18 @@ impl DotRenderableGraph for Function {
     /// - A `String` containing the `dot` representation of the graph.
     fn render_dot(&self, config: CfgDotConfig) -> String {
         let cfg = CfgDot { config };
-        cfg.render(&self.cfg, self)
+        cfg.render(&self.body.cfg, self)
+    }
+}
+
+impl MermaidRenderableGraph for Function {
+    /// Convert the Graph to a Mermaid flowchart.
+    ///
+    /// # Returns
+    /// - A `String` containing the Mermaid representation of the graph.
+    fn render_mermaid(&self, config: CfgMermaidConfig) -> String {
+        let cfg = CfgMermaid { config };
+        cfg.render(&self.body.cfg, self)
     }
 }
 
@@ -782,7 +1634,7 @@ mod tests {
         let function = Function::new(id.clone());
 
         assert_eq!(function.id, id);
-        assert_eq!(function.blocks.len(), 1);
+        assert_eq!(function.body.blocks.len(), 1);
     }
 
     #[test]
@@ -794,8 +1646,8 @@ mod tests {
         assert_eq!(function.len(), 2);
 
         // check block id & node id mappings
-        let node_id = function.block_to_graph_node.get(&block_id).unwrap();
-        let new_block_id = function.graph_node_to_block.get(node_id).unwrap();
+        let node_id = function.body.block_to_graph_node.get(&block_id).unwrap();
+        let new_block_id = function.body.graph_node_to_block.get(node_id).unwrap();
         assert_eq!(*new_block_id, block_id);
 
         // test EntryBlockAlreadyExists error
@@ -992,6 +1844,445 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_dominated_by() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let cond = function.create_block(BasicBlockType::Normal, 1).unwrap();
+        let then = function.create_block(BasicBlockType::Normal, 2).unwrap();
+        let merge = function.create_block(BasicBlockType::Normal, 3).unwrap();
+        let unreachable = function.create_block(BasicBlockType::Normal, 4).unwrap();
+
+        // entry -> cond -> then -> merge, with cond -> merge
+        function.add_edge(entry, cond).unwrap();
+        function.add_edge(cond, then).unwrap();
+        function.add_edge(cond, merge).unwrap();
+        function.add_edge(then, merge).unwrap();
+        function.add_edge(unreachable, merge).unwrap();
+
+        let mut dominated = function.dominated_by(entry).unwrap();
+        dominated.sort();
+        let mut expected = vec![entry, cond, then, merge];
+        expected.sort();
+        assert_eq!(dominated, expected);
+
+        let mut dominated = function.dominated_by(cond).unwrap();
+        dominated.sort();
+        let mut expected = vec![cond, then, merge];
+        expected.sort();
+        assert_eq!(dominated, expected);
+
+        // `then` does not dominate `merge`, since `cond` can branch to it directly
+        assert_eq!(function.dominated_by(then).unwrap(), vec![then]);
+        assert_eq!(
+            function.dominated_by(unreachable).unwrap(),
+            vec![unreachable]
+        );
+
+        let result = function.dominated_by(BasicBlockId::new(1234, BasicBlockType::Normal, 0));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_nested_loops() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let outer = function.create_block(BasicBlockType::Normal, 1).unwrap();
+        let inner = function.create_block(BasicBlockType::Normal, 2).unwrap();
+        let inner_body = function.create_block(BasicBlockType::Normal, 3).unwrap();
+        let latch = function.create_block(BasicBlockType::Normal, 4).unwrap();
+        let exit = function.create_block(BasicBlockType::Normal, 5).unwrap();
+        let unreachable = function.create_block(BasicBlockType::Normal, 6).unwrap();
+
+        // while (outer) { while (inner) { inner_body } latch }
+        function.add_edge(entry, outer).unwrap();
+        function.add_edge(outer, inner).unwrap();
+        function.add_edge(outer, exit).unwrap();
+        function.add_edge(inner, inner_body).unwrap();
+        function.add_edge(inner, latch).unwrap();
+        function.add_edge(inner_body, inner).unwrap();
+        function.add_edge(latch, outer).unwrap();
+        function.add_edge(unreachable, inner_body).unwrap();
+
+        assert_eq!(function.loop_depth(entry).unwrap(), 0);
+        assert_eq!(function.loop_depth(outer).unwrap(), 1);
+        assert_eq!(function.loop_depth(inner).unwrap(), 2);
+        assert_eq!(function.loop_depth(inner_body).unwrap(), 2);
+        assert_eq!(function.loop_depth(latch).unwrap(), 1);
+        assert_eq!(function.loop_depth(exit).unwrap(), 0);
+        assert_eq!(function.loop_depth(unreachable).unwrap(), 0);
+
+        assert!(function.same_loop(inner, inner_body).unwrap());
+        assert!(function.same_loop(outer, latch).unwrap());
+        // A break from the inner loop to the latch only leaves one loop
+        assert!(!function.same_loop(inner_body, latch).unwrap());
+        assert!(!function.same_loop(entry, exit).unwrap());
+
+        let missing = BasicBlockId::new(1234, BasicBlockType::Normal, 0);
+        assert!(function.loop_depth(missing).is_err());
+        assert!(function.same_loop(entry, missing).is_err());
+    }
+
+    #[test]
+    fn test_metrics_report() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id.clone());
+        let entry = function.get_entry_basic_block_id();
+        let then = function.create_block(BasicBlockType::Normal, 2).unwrap();
+        let merge = function.create_block(BasicBlockType::Normal, 3).unwrap();
+        let unreachable = function.create_block(BasicBlockType::Normal, 5).unwrap();
+
+        // if (true) { pop(true) } return true;
+        let entry_block = function.get_entry_basic_block_mut();
+        entry_block.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+        entry_block.add_instruction(Instruction::new(Opcode::Jne, 1));
+        let then_block = function.get_basic_block_by_id_mut(then).unwrap();
+        then_block.add_instruction(Instruction::new(Opcode::PushTrue, 2));
+        then_block.add_instruction(Instruction::new(Opcode::Pop, 3));
+        let merge_block = function.get_basic_block_by_id_mut(merge).unwrap();
+        merge_block.add_instruction(Instruction::new(Opcode::PushTrue, 4));
+        merge_block.add_instruction(Instruction::new(Opcode::Ret, 5));
+        function.add_edge(entry, then).unwrap();
+        function.add_edge(entry, merge).unwrap();
+        function.add_edge(then, merge).unwrap();
+        function.add_edge(unreachable, merge).unwrap();
+
+        let metrics = function.metrics_report().unwrap();
+        assert_eq!(metrics.id, id);
+        assert_eq!(metrics.blocks, 4);
+        assert_eq!(metrics.edges, 4);
+        assert_eq!(metrics.instructions, 6);
+        // The unreachable block does not add to the complexity
+        assert_eq!(metrics.cyclomatic_complexity, 2);
+        assert_eq!(metrics.loops, 0);
+        assert_eq!(metrics.max_stack_depth, Some(1));
+        assert_eq!(
+            metrics.opcode_histogram,
+            BTreeMap::from([
+                ("Jne".to_string(), 1),
+                ("Pop".to_string(), 1),
+                ("PushTrue".to_string(), 3),
+                ("Ret".to_string(), 1),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_max_stack_depth() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+
+        // foo(1, 2); with the result popped
+        let block = function.get_basic_block_by_id_mut(entry).unwrap();
+        block.add_instruction(Instruction::new(Opcode::PushArray, 0));
+        block.add_instruction(Instruction::new(Opcode::PushNumber, 1));
+        block.add_instruction(Instruction::new(Opcode::PushNumber, 2));
+        block.add_instruction(Instruction::new(Opcode::PushVariable, 3));
+        block.add_instruction(Instruction::new(Opcode::Call, 4));
+        block.add_instruction(Instruction::new(Opcode::Pop, 5));
+        block.add_instruction(Instruction::new(Opcode::PushTrue, 6));
+        block.add_instruction(Instruction::new(Opcode::Jne, 7));
+
+        // Both branches push one value before joining
+        let then = function.create_block(BasicBlockType::Normal, 8).unwrap();
+        let otherwise = function.create_block(BasicBlockType::Normal, 10).unwrap();
+        let merge = function.create_block(BasicBlockType::Normal, 11).unwrap();
+        let block = function.get_basic_block_by_id_mut(then).unwrap();
+        block.add_instruction(Instruction::new(Opcode::PushTrue, 8));
+        block.add_instruction(Instruction::new(Opcode::Jmp, 9));
+        let block = function.get_basic_block_by_id_mut(otherwise).unwrap();
+        block.add_instruction(Instruction::new(Opcode::PushFalse, 10));
+        let block = function.get_basic_block_by_id_mut(merge).unwrap();
+        block.add_instruction(Instruction::new(Opcode::Ret, 11));
+
+        function.add_edge(entry, then).unwrap();
+        function.add_edge(entry, otherwise).unwrap();
+        function.add_edge(then, merge).unwrap();
+        function.add_edge(otherwise, merge).unwrap();
+        assert_eq!(function.max_stack_depth().unwrap(), 4);
+
+        // One branch leaves an extra value on the stack
+        let block = function.get_basic_block_by_id_mut(otherwise).unwrap();
+        block.add_instruction(Instruction::new(Opcode::PushNull, 11));
+        assert!(matches!(
+            function.max_stack_depth(),
+            Err(FunctionError::StackDepthMismatch(block, ..)) if block == merge
+        ));
+    }
+
+    #[test]
+    fn test_max_stack_depth_underflow() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_mut();
+        entry.add_instruction(Instruction::new(Opcode::PushTrue, 0));
+        entry.add_instruction(Instruction::new(Opcode::Add, 1));
+        assert!(matches!(
+            function.max_stack_depth(),
+            Err(FunctionError::StackUnderflow(1))
+        ));
+
+        // A call without a `PushArray` marker
+        let mut function = Function::new(FunctionId::new_without_name(0, 0));
+        let entry = function.get_entry_basic_block_mut();
+        entry.add_instruction(Instruction::new(Opcode::PushVariable, 0));
+        entry.add_instruction(Instruction::new(Opcode::Call, 1));
+        assert!(matches!(
+            function.max_stack_depth(),
+            Err(FunctionError::StackUnderflow(1))
+        ));
+    }
+
+    /// Build a function with an if/else at the given address.
+    fn build_if_else(address: Gs2BytecodeAddress, then_opcode: Opcode) -> Function {
+        let mut function = Function::new(FunctionId::new_without_name(0, address));
+        let entry = function.get_entry_basic_block_id();
+        let then = function
+            .create_block(BasicBlockType::Normal, address + 2)
+            .unwrap();
+        let otherwise = function
+            .create_block(BasicBlockType::Normal, address + 4)
+            .unwrap();
+        let merge = function
+            .create_block(BasicBlockType::Exit, address + 5)
+            .unwrap();
+
+        let block = function.get_basic_block_by_id_mut(entry).unwrap();
+        block.add_instruction(Instruction::new(Opcode::PushTrue, address));
+        block.add_instruction(Instruction::new(Opcode::Jne, address + 1));
+        let block = function.get_basic_block_by_id_mut(then).unwrap();
+        block.add_instruction(Instruction::new(then_opcode, address + 2));
+        block.add_instruction(Instruction::new(Opcode::Jmp, address + 3));
+        let block = function.get_basic_block_by_id_mut(otherwise).unwrap();
+        block.add_instruction(Instruction::new(Opcode::PushFalse, address + 4));
+        let block = function.get_basic_block_by_id_mut(merge).unwrap();
+        block.add_instruction(Instruction::new(Opcode::Ret, address + 5));
+
+        function.add_edge(entry, then).unwrap();
+        function.add_edge(entry, otherwise).unwrap();
+        function.add_edge(then, merge).unwrap();
+        function.add_edge(otherwise, merge).unwrap();
+        function
+    }
+
+    #[test]
+    fn test_cfg_isomorphic_to() {
+        let function = build_if_else(0, Opcode::PushTrue);
+        let shifted = build_if_else(100, Opcode::PushTrue);
+        assert!(function.cfg_isomorphic_to(&shifted));
+        assert!(function.cfg_equivalent_with_opcodes(&shifted));
+
+        // Same shape, different opcodes
+        let changed = build_if_else(100, Opcode::PushNull);
+        assert!(function.cfg_isomorphic_to(&changed));
+        assert!(!function.cfg_equivalent_with_opcodes(&changed));
+
+        // The else branch no longer joins the then branch
+        let mut changed = build_if_else(100, Opcode::PushTrue);
+        let merge = changed.get_basic_block_id_by_start_address(105).unwrap();
+        let exit = changed.create_block(BasicBlockType::Exit, 106).unwrap();
+        changed
+            .get_basic_block_by_id_mut(exit)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 106));
+        changed.add_edge(merge, exit).unwrap();
+        assert!(!function.cfg_isomorphic_to(&changed));
+    }
+
+    #[test]
+    fn test_find_opcode() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let exit = function.create_block(BasicBlockType::Exit, 3).unwrap();
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::PushArray, 0));
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::Call, 1));
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::Call, 2));
+        function
+            .get_basic_block_by_id_mut(exit)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Call, 3));
+        function
+            .get_basic_block_by_id_mut(exit)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 4));
+        function.add_edge(entry, exit).unwrap();
+
+        assert_eq!(function.blocks_with_opcode(Opcode::Call), vec![entry, exit]);
+        assert_eq!(function.blocks_with_opcode(Opcode::Ret), vec![exit]);
+        assert!(function.blocks_with_opcode(Opcode::Jmp).is_empty());
+        assert_eq!(
+            function.instructions_with_opcode(Opcode::Call),
+            vec![(entry, 1), (entry, 2), (exit, 3)]
+        );
+        assert!(function.instructions_with_opcode(Opcode::Jmp).is_empty());
+    }
+
+    #[test]
+    fn test_edge_type() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let fallthrough = function.create_block(BasicBlockType::Normal, 2).unwrap();
+        let branch = function.create_block(BasicBlockType::Exit, 4).unwrap();
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::PushTrue, 0));
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::Jne, 1));
+        function
+            .get_basic_block_by_id_mut(fallthrough)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 2));
+        function
+            .get_basic_block_by_id_mut(branch)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 4));
+        function.add_edge(entry, fallthrough).unwrap();
+        function.add_edge(entry, branch).unwrap();
+
+        assert_eq!(
+            function.edge_type(entry, fallthrough).unwrap(),
+            ControlFlowEdgeType::Fallthrough
+        );
+        assert_eq!(
+            function.edge_type(entry, branch).unwrap(),
+            ControlFlowEdgeType::Branch
+        );
+        assert!(matches!(
+            function.edge_type(fallthrough, branch),
+            Err(FunctionError::EdgeNotFound(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_cfg_adjacency() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let fallthrough = function.create_block(BasicBlockType::Normal, 2).unwrap();
+        let branch = function.create_block(BasicBlockType::Exit, 4).unwrap();
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::PushTrue, 0));
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::Jne, 1));
+        function
+            .get_basic_block_by_id_mut(fallthrough)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 2));
+        function
+            .get_basic_block_by_id_mut(branch)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 4));
+        function.add_edge(entry, branch).unwrap();
+        function.add_edge(entry, fallthrough).unwrap();
+
+        assert_eq!(
+            function.cfg_adjacency(),
+            vec![
+                (
+                    entry,
+                    vec![
+                        (fallthrough, ControlFlowEdgeType::Fallthrough),
+                        (branch, ControlFlowEdgeType::Branch)
+                    ]
+                ),
+                (fallthrough, vec![]),
+                (branch, vec![]),
+            ]
+        );
+
+        // The adjacency list can be serialized without petgraph
+        let json = serde_json::to_string(&function.cfg_adjacency()).unwrap();
+        assert!(json.contains("Fallthrough"));
+    }
+
+    #[test]
+    fn test_trace_path() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let fallthrough = function.create_block(BasicBlockType::Normal, 2).unwrap();
+        let branch = function.create_block(BasicBlockType::Normal, 4).unwrap();
+        let exit = function.create_block(BasicBlockType::Exit, 5).unwrap();
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::PushTrue, 0));
+        function
+            .get_entry_basic_block_mut()
+            .add_instruction(Instruction::new(Opcode::Jne, 1));
+        function
+            .get_basic_block_by_id_mut(fallthrough)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Jmp, 2));
+        function
+            .get_basic_block_by_id_mut(branch)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Pop, 4));
+        function
+            .get_basic_block_by_id_mut(exit)
+            .unwrap()
+            .add_instruction(Instruction::new(Opcode::Ret, 5));
+        function.add_edge(entry, fallthrough).unwrap();
+        function.add_edge(entry, branch).unwrap();
+        function.add_edge(fallthrough, exit).unwrap();
+        function.add_edge(branch, exit).unwrap();
+
+        // Blocks without a decision fall through
+        assert_eq!(
+            function.trace_path(&[]).unwrap(),
+            vec![entry, fallthrough, exit]
+        );
+        assert_eq!(
+            function.trace_path(&[(entry, true)]).unwrap(),
+            vec![entry, branch, exit]
+        );
+
+        // A loop back to the entry block is reported instead of traced forever
+        function.add_edge(exit, entry).unwrap();
+        match function.trace_path(&[(entry, true)]) {
+            Err(FunctionError::PathCycle(block, path)) => {
+                assert_eq!(block, entry);
+                assert_eq!(path, vec![entry, branch, exit]);
+            }
+            result => panic!("Expected a cycle, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_clone_shares_body() {
+        let id = FunctionId::new_without_name(0, 0);
+        let mut function = Function::new(id);
+        let entry = function.get_entry_basic_block_id();
+        let block = function.create_block(BasicBlockType::Normal, 1).unwrap();
+        function.add_edge(entry, block).unwrap();
+
+        // Cloning only bumps a reference count
+        let mut clone = function.clone();
+        assert!(Arc::ptr_eq(&function.body, &clone.body));
+
+        // Modifying a clone copies the body first, leaving the original as it was
+        let other = clone.create_block(BasicBlockType::Exit, 2).unwrap();
+        clone.add_edge(block, other).unwrap();
+        assert!(!Arc::ptr_eq(&function.body, &clone.body));
+        assert_eq!(function.len(), 2);
+        assert_eq!(clone.len(), 3);
+        assert!(function.get_successors(block).unwrap().is_empty());
+        assert_eq!(clone.get_successors(block).unwrap(), vec![other]);
+    }
+
     #[test]
     fn test_get_entry_basic_block_id() {
         let id = FunctionId::new_without_name(0, 0);
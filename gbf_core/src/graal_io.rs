@@ -24,6 +24,16 @@ pub const GUINT40_MAX: u64 = 0xFFFFFFFF;
 /// A reader that reads Graal-encoded data.
 pub struct GraalReader<R: Read> {
     inner: R,
+    lenient_strings: bool,
+    unterminated_strings: usize,
+}
+
+/// A reader adapter that counts the bytes read through it and, optionally, computes
+/// their CRC-32 checksum.
+pub struct ChecksumReader<R: Read> {
+    inner: R,
+    bytes_read: u64,
+    crc: Option<u32>,
 }
 
 /// A writer that writes Graal-encoded data.
@@ -115,7 +125,52 @@ impl<R: Read> GraalReader<R> {
     /// let reader = GraalReader::new(Cursor::new(vec![1, 2, 3, 4]));
     /// ```
     pub fn new(inner: R) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            lenient_strings: false,
+            unterminated_strings: 0,
+        }
+    }
+
+    /// Sets whether `read_string` accepts a string that ends at EOF without a null
+    /// terminator, returning the bytes read so far instead of failing. This recovers
+    /// modules whose final string was truncated when they were packed.
+    ///
+    /// # Arguments
+    /// - `lenient_strings`: Whether to accept unterminated strings.
+    ///
+    /// # Returns
+    /// - The updated GraalReader.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::graal_io::GraalReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = GraalReader::new(Cursor::new(vec![104, 105])).lenient_strings(true);
+    /// assert_eq!(reader.read_string().unwrap(), "hi");
+    /// assert_eq!(reader.unterminated_strings(), 1);
+    /// ```
+    pub fn lenient_strings(mut self, lenient_strings: bool) -> Self {
+        self.lenient_strings = lenient_strings;
+        self
+    }
+
+    /// Gets the number of strings that ended at EOF without a null terminator. This is
+    /// only ever non-zero if `lenient_strings` is set.
+    ///
+    /// # Returns
+    /// - The number of unterminated strings read so far.
+    pub fn unterminated_strings(&self) -> usize {
+        self.unterminated_strings
+    }
+
+    /// Gets a reference to the wrapped reader.
+    ///
+    /// # Returns
+    /// - The reader that was passed to `GraalReader::new`.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
     }
 
     /// Decodes a sequence of bytes using the Graal encoding.
@@ -152,7 +207,8 @@ impl<R: Read> GraalReader<R> {
     /// - The string read from the reader.
     ///
     /// # Errors
-    /// - `GraalIoError::NoNullTerminator`: If the null terminator (`0x00`) is not found.
+    /// - `GraalIoError::NoNullTerminator`: If the null terminator (`0x00`) is not found,
+    ///   unless `lenient_strings` is set.
     /// - `GraalIoError::Utf8ConversionFailed`: If the bytes cannot be converted to a UTF-8 string.
     /// - `GraalIoError::Io`: If there is an underlying I/O error.
     ///
@@ -172,7 +228,15 @@ impl<R: Read> GraalReader<R> {
         loop {
             let bytes_read = self.inner.read(&mut byte)?;
             if bytes_read == 0 {
-                return Err(GraalIoError::NoNullTerminator()); // EOF before finding null terminator
+                if !self.lenient_strings {
+                    return Err(GraalIoError::NoNullTerminator()); // EOF before finding null terminator
+                }
+                log::warn!(
+                    "Reached EOF after {} bytes without finding a null terminator, using the bytes read as the string",
+                    buffer.len()
+                );
+                self.unterminated_strings += 1;
+                break;
             }
             if byte[0] == 0x00 {
                 break; // Null terminator found
@@ -284,6 +348,34 @@ impl<R: Read> GraalReader<R> {
         Ok(u32::from_be_bytes(buffer))
     }
 
+    /// Skips over a number of bytes in the reader without decoding them.
+    ///
+    /// # Arguments
+    /// - `count`: The number of bytes to skip.
+    ///
+    /// # Errors
+    /// - `GraalIoError::Io`: If there is an underlying I/O error, or if the reader
+    ///   ends before `count` bytes were skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::graal_io::GraalReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = GraalReader::new(Cursor::new(vec![1, 2, 3, 4]));
+    /// reader.skip(3).unwrap();
+    /// assert_eq!(reader.read_u8().unwrap(), 4);
+    /// ```
+    pub fn skip(&mut self, count: u64) -> Result<(), GraalIoError> {
+        let skipped = io::copy(&mut (&mut self.inner).take(count), &mut io::sink())?;
+        if skipped != count {
+            return Err(GraalIoError::Io(io::Error::from(
+                io::ErrorKind::UnexpectedEof,
+            )));
+        }
+        Ok(())
+    }
+
     /// Reads a Graal encoded unsigned 8-bit integer from the reader.
     ///
     /// # Returns
@@ -414,6 +506,82 @@ impl<R: Read> GraalReader<R> {
     }
 }
 
+/// The CRC-32 (IEEE) lookup table, one entry per byte value.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+impl<R: Read> ChecksumReader<R> {
+    /// Creates a new ChecksumReader
+    ///
+    /// # Arguments
+    /// - `inner`: The reader to wrap.
+    /// - `compute_checksum`: Whether to compute the CRC-32 checksum of the bytes read.
+    ///
+    /// # Returns
+    /// - A new ChecksumReader wrapping the given reader.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::graal_io::ChecksumReader;
+    /// use std::io::{Cursor, Read};
+    ///
+    /// let mut reader = ChecksumReader::new(Cursor::new(b"123456789".to_vec()), true);
+    /// reader.read_to_end(&mut Vec::new()).unwrap();
+    /// assert_eq!(reader.bytes_read(), 9);
+    /// assert_eq!(reader.checksum(), Some(0xCBF43926));
+    /// ```
+    pub fn new(inner: R, compute_checksum: bool) -> Self {
+        Self {
+            inner,
+            bytes_read: 0,
+            crc: compute_checksum.then_some(0xFFFF_FFFF),
+        }
+    }
+
+    /// Gets the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Gets the CRC-32 checksum of the bytes read so far.
+    ///
+    /// # Returns
+    /// - The checksum, or `None` if the reader was not asked to compute one.
+    pub fn checksum(&self) -> Option<u32> {
+        self.crc.map(|crc| !crc)
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.bytes_read += count as u64;
+        if let Some(crc) = self.crc.as_mut() {
+            for &byte in &buf[..count] {
+                *crc = CRC32_TABLE[((*crc ^ byte as u32) & 0xFF) as usize] ^ (*crc >> 8);
+            }
+        }
+        Ok(count)
+    }
+}
+
 impl<W: Write> GraalWriter<W> {
     /// Creates a new GraalWriter
     ///
@@ -435,6 +603,23 @@ impl<W: Write> GraalWriter<W> {
         Self { inner }
     }
 
+    /// Unwraps the writer.
+    ///
+    /// # Returns
+    /// - The writer that was passed to `GraalWriter::new`.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::graal_io::GraalWriter;
+    ///
+    /// let mut writer = GraalWriter::new(Vec::new());
+    /// writer.write_u8(1).unwrap();
+    /// assert_eq!(writer.into_inner(), vec![1]);
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
     /// Encodes a value as a sequence of bytes using the Graal encoding.
     ///
     /// # Arguments
@@ -755,6 +940,27 @@ mod tests {
     const MIN_ENCODED: u8 = 0x20;
     const MIN_DECODED: u64 = 0;
 
+    #[test]
+    fn test_checksum_reader() {
+        let data = b"123456789".to_vec();
+
+        let mut reader = GraalReader::new(ChecksumReader::new(Cursor::new(data.clone()), true));
+        assert_eq!(reader.read_u32().unwrap(), 0x31323334);
+        assert_eq!(reader.get_ref().bytes_read(), 4);
+        reader.skip(5).unwrap();
+        assert_eq!(reader.get_ref().bytes_read(), 9);
+        assert_eq!(reader.get_ref().checksum(), Some(0xCBF43926));
+
+        // The checksum is opt-in, but bytes are always counted
+        let mut reader = ChecksumReader::new(Cursor::new(data), false);
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(reader.bytes_read(), 9);
+        assert_eq!(reader.checksum(), None);
+
+        let reader = ChecksumReader::new(Cursor::new(Vec::new()), true);
+        assert_eq!(reader.checksum(), Some(0));
+    }
+
     #[test]
     fn test_constants() {
         // Test for GUINT8
@@ -837,6 +1043,20 @@ mod tests {
         // do it on a buffer with two bytes, then read a u32
         let mut reader = GraalReader::new(Cursor::new(vec![1, 2]));
         assert!(reader.read_u32().is_err());
+
+        // skip past the end of the buffer
+        let mut reader = GraalReader::new(Cursor::new(vec![1, 2]));
+        assert!(reader.skip(3).is_err());
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut reader = GraalReader::new(Cursor::new(vec![1, 2, 3, 4, 5]));
+        reader.skip(0).unwrap();
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        reader.skip(2).unwrap();
+        assert_eq!(reader.read_u16().unwrap(), 0x0405);
+        assert!(reader.read_u8().is_err());
     }
 
     #[test]
@@ -902,6 +1122,16 @@ mod tests {
         ]));
         assert!(reader.read_string().is_err());
 
+        // in lenient mode, the bytes before EOF are the string
+        let mut reader = GraalReader::new(Cursor::new(vec![
+            104, 101, 108, 108, 111, 0, 119, 111, 114, 108, 100,
+        ]))
+        .lenient_strings(true);
+        assert_eq!(reader.read_string().unwrap(), "hello");
+        assert_eq!(reader.unterminated_strings(), 0);
+        assert_eq!(reader.read_string().unwrap(), "world");
+        assert_eq!(reader.unterminated_strings(), 1);
+
         // read a string that is empty
         let mut reader = GraalReader::new(Cursor::new(vec![0]));
         assert_eq!(reader.read_string().unwrap(), "");
@@ -17,6 +17,16 @@ pub struct Instruction {
 
     /// The operand of the instruction, if any.
     pub operand: Option<Operand>,
+
+    /// The immediate opcode the operand was encoded with, if the instruction was loaded
+    /// from bytecode.
+    #[serde(default)]
+    immediate_kind: Option<Opcode>,
+
+    /// The index into the strings section a string operand was encoded with, if the
+    /// instruction was loaded from bytecode.
+    #[serde(default)]
+    string_index: Option<usize>,
 }
 
 impl Instruction {
@@ -33,6 +43,8 @@ impl Instruction {
             opcode,
             address,
             operand: None,
+            immediate_kind: None,
+            string_index: None,
         }
     }
 
@@ -50,6 +62,8 @@ impl Instruction {
             opcode,
             address,
             operand: Some(operand),
+            immediate_kind: None,
+            string_index: None,
         }
     }
 
@@ -70,6 +84,197 @@ impl Instruction {
     pub fn set_operand(&mut self, operand: Operand) {
         self.operand = Some(operand);
     }
+
+    /// Get the operand as a number.
+    ///
+    /// # Returns
+    /// - The number, or `None` if there is no operand or it is not a number.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::operand::Operand;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let instruction = Instruction::new_with_operand(Opcode::PushNumber, 0, Operand::new_number(42));
+    /// assert_eq!(instruction.number_operand(), Some(42));
+    /// assert_eq!(instruction.string_operand(), None);
+    /// ```
+    pub fn number_operand(&self) -> Option<i32> {
+        match self.operand {
+            Some(Operand::Number(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the operand as a string.
+    ///
+    /// # Returns
+    /// - The string, or `None` if there is no operand or it is not a string.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::operand::Operand;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let instruction =
+    ///     Instruction::new_with_operand(Opcode::PushString, 0, Operand::new_string("abc"));
+    /// assert_eq!(instruction.string_operand(), Some("abc"));
+    /// assert_eq!(instruction.float_operand(), None);
+    /// ```
+    pub fn string_operand(&self) -> Option<&str> {
+        match &self.operand {
+            Some(Operand::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the operand as a float, which is stored as a string.
+    ///
+    /// # Returns
+    /// - The float, or `None` if there is no operand or it is not a float.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::operand::Operand;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let instruction =
+    ///     Instruction::new_with_operand(Opcode::PushNumber, 0, Operand::new_float("3.14"));
+    /// assert_eq!(instruction.float_operand(), Some("3.14"));
+    /// assert_eq!(instruction.number_operand(), None);
+    /// ```
+    pub fn float_operand(&self) -> Option<&str> {
+        match &self.operand {
+            Some(Operand::Float(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the address a jump instruction jumps to.
+    ///
+    /// # Returns
+    /// - The target address, or `None` if the opcode has no jump target, or the operand
+    ///   is absent, not a number, or negative.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::operand::Operand;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let instruction = Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(5));
+    /// assert_eq!(instruction.jump_target(), Some(5));
+    ///
+    /// let instruction = Instruction::new_with_operand(Opcode::PushNumber, 0, Operand::new_number(5));
+    /// assert_eq!(instruction.jump_target(), None);
+    /// ```
+    pub fn jump_target(&self) -> Option<Gs2BytecodeAddress> {
+        if !self.opcode.has_jump_target() {
+            return None;
+        }
+        self.number_operand()
+            .and_then(|target| Gs2BytecodeAddress::try_from(target).ok())
+    }
+
+    /// Get the immediate opcode the operand was originally encoded with, such as
+    /// `Opcode::ImmShort` for a number that would also fit in an `Opcode::ImmByte`.
+    /// Re-encoding with this opcode reproduces the original bytes.
+    ///
+    /// # Returns
+    /// - The immediate opcode, or `None` if the instruction was not loaded from bytecode.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let instruction = Instruction::new(Opcode::PushNumber, 0);
+    /// assert_eq!(instruction.original_immediate_kind(), None);
+    /// ```
+    pub fn original_immediate_kind(&self) -> Option<Opcode> {
+        self.immediate_kind
+    }
+
+    /// Set the immediate opcode the operand was originally encoded with.
+    ///
+    /// # Arguments
+    /// - `immediate_kind`: The immediate opcode, such as `Opcode::ImmByte`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut instruction = Instruction::new(Opcode::PushNumber, 0);
+    /// instruction.set_original_immediate_kind(Opcode::ImmShort);
+    /// assert_eq!(instruction.original_immediate_kind(), Some(Opcode::ImmShort));
+    /// ```
+    pub fn set_original_immediate_kind(&mut self, immediate_kind: Opcode) {
+        self.immediate_kind = Some(immediate_kind);
+    }
+
+    /// Get the index into the strings section the string operand was originally
+    /// encoded with.
+    ///
+    /// # Returns
+    /// - The string index, or `None` if the operand is not a string loaded from bytecode.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut instruction = Instruction::new(Opcode::PushString, 0);
+    /// assert_eq!(instruction.original_string_index(), None);
+    /// instruction.set_original_string_index(3);
+    /// assert_eq!(instruction.original_string_index(), Some(3));
+    /// ```
+    pub fn original_string_index(&self) -> Option<usize> {
+        self.string_index
+    }
+
+    /// Set the index into the strings section the string operand was originally
+    /// encoded with.
+    ///
+    /// # Arguments
+    /// - `string_index`: The index into the strings section.
+    pub fn set_original_string_index(&mut self, string_index: usize) {
+        self.string_index = Some(string_index);
+    }
+
+    /// Describe the immediate the operand was originally encoded with, such as `imm16`
+    /// for a number read as an `Opcode::ImmShort`, or `imm8=0` for a string read as an
+    /// `Opcode::ImmStringByte` from index 0 of the strings section.
+    ///
+    /// # Returns
+    /// - The description, or `None` if the original immediate is not known.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::opcode::Opcode;
+    ///
+    /// let mut instruction = Instruction::new(Opcode::PushString, 0);
+    /// instruction.set_original_immediate_kind(Opcode::ImmStringByte);
+    /// instruction.set_original_string_index(0);
+    /// assert_eq!(instruction.immediate_encoding(), Some("imm8=0".to_string()));
+    /// ```
+    pub fn immediate_encoding(&self) -> Option<String> {
+        let width = match self.immediate_kind? {
+            Opcode::ImmByte | Opcode::ImmStringByte => "imm8",
+            Opcode::ImmShort | Opcode::ImmStringShort => "imm16",
+            Opcode::ImmInt | Opcode::ImmStringInt => "imm32",
+            Opcode::ImmFloat => "immfloat",
+            _ => return None,
+        };
+        Some(match self.string_index {
+            Some(string_index) => format!("{}={}", width, string_index),
+            None => width.to_string(),
+        })
+    }
 }
 
 /// Implement the `Display` trait for `Instruction`.
@@ -104,6 +309,8 @@ impl Default for Instruction {
             opcode: Opcode::ConvertToFloat,
             address: 0,
             operand: None,
+            immediate_kind: None,
+            string_index: None,
         }
     }
 }
@@ -133,4 +340,22 @@ mod tests {
             Instruction::new_with_operand(Opcode::PushNumber, 0, Operand::new_float("3.14"));
         assert_eq!(instruction.to_string(), "PushNumber 3.14");
     }
+
+    #[test]
+    fn instruction_typed_operands() {
+        let instruction = Instruction::new(Opcode::Jmp, 0);
+        assert_eq!(instruction.number_operand(), None);
+        assert_eq!(instruction.string_operand(), None);
+        assert_eq!(instruction.float_operand(), None);
+        assert_eq!(instruction.jump_target(), None);
+
+        let instruction = Instruction::new_with_operand(Opcode::Jne, 0, Operand::new_number(-1));
+        assert_eq!(instruction.number_operand(), Some(-1));
+        assert_eq!(instruction.jump_target(), None);
+
+        let instruction =
+            Instruction::new_with_operand(Opcode::Jeq, 0, Operand::new_string("label"));
+        assert_eq!(instruction.string_operand(), Some("label"));
+        assert_eq!(instruction.jump_target(), None);
+    }
 }
@@ -11,8 +11,12 @@ use bytecode_loader::{BytecodeLoaderBuilder, BytecodeLoaderError};
 pub mod basic_block;
 /// This module reads bytecode from a reader and disassembles it.
 pub mod bytecode_loader;
+/// This module writes a module back out as bytecode.
+pub mod bytecode_writer;
 /// This module contains the logic to visualize the control flow graph of a module.
 pub mod cfg_dot;
+/// This module contains the logic to render the control flow graph of a module as Mermaid.
+pub mod cfg_mermaid;
 /// Decompiler module
 pub mod decompiler;
 /// This module contains the definition of a function.
@@ -27,6 +31,8 @@ pub mod module;
 pub mod opcode;
 /// This module contains the definition of an operand.
 pub mod operand;
+/// This module contains the decoders for the operands of operand opcodes.
+pub mod operand_decoder;
 /// This module contains utility functions and types.
 pub mod utils;
 
@@ -50,13 +56,105 @@ pub mod utils;
 /// let result = disassemble_bytecode(reader).unwrap();
 /// ```
 pub fn disassemble_bytecode<R: std::io::Read>(reader: R) -> Result<String, BytecodeLoaderError> {
+    disassemble_bytecode_with_options(reader, &DisasmOptions::default())
+}
+
+/// How the target of a jump instruction is shown in disassembly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BranchDisplay {
+    /// The absolute address of the target, such as `Jmp 0x5`.
+    #[default]
+    Absolute,
+
+    /// The offset of the target from the jump instruction, such as `Jmp +5`. A negative
+    /// offset is a backward jump, which usually closes a loop.
+    Relative,
+
+    /// The absolute address followed by the offset, such as `Jmp 0x5 (+5)`.
+    Both,
+}
+
+/// Options that control the output of `disassemble_bytecode_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct DisasmOptions {
+    /// Annotate each operand with the immediate it was encoded with, such as
+    /// `PushString abc ; imm8=0`.
+    pub show_immediate_encoding: bool,
+
+    /// How the targets of jump instructions are shown.
+    pub branch_display: BranchDisplay,
+}
+
+impl DisasmOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether each operand is annotated with the immediate it was encoded with.
+    pub fn show_immediate_encoding(mut self, show_immediate_encoding: bool) -> Self {
+        self.show_immediate_encoding = show_immediate_encoding;
+        self
+    }
+
+    /// Sets how the targets of jump instructions are shown.
+    pub fn branch_display(mut self, branch_display: BranchDisplay) -> Self {
+        self.branch_display = branch_display;
+        self
+    }
+}
+
+/// Disassemble bytecode using a reader, with options that control the output.
+///
+/// # Arguments
+/// - `reader`: The reader to read the bytecode from.
+/// - `options`: The options that control the output.
+///
+/// # Returns
+/// - The string representation of the disassembled bytecode.
+///
+/// # Errors
+/// - `BytecodeLoaderError`: An error occurred while loading the bytecode.
+///
+/// # Examples
+/// ```
+/// use gbf_core::{disassemble_bytecode_with_options, DisasmOptions};
+///
+/// let reader = std::fs::File::open("tests/gs2bc/simple.gs2bc").unwrap();
+/// let options = DisasmOptions::new().show_immediate_encoding(true);
+/// let result = disassemble_bytecode_with_options(reader, &options).unwrap();
+/// ```
+pub fn disassemble_bytecode_with_options<R: std::io::Read>(
+    reader: R,
+    options: &DisasmOptions,
+) -> Result<String, BytecodeLoaderError> {
     // create a new bytecode loader builder
     let loader = BytecodeLoaderBuilder::new(reader).build()?;
 
     // write a string representation of the bytecode using each instruction in the instructions vec
     let mut result = String::new();
     for (index, instruction) in loader.instructions.iter().enumerate() {
-        result.push_str(&format!("{:08x}: {}\n", index, instruction));
+        let target = instruction
+            .number_operand()
+            .filter(|_| instruction.opcode.has_jump_target());
+        match (target, options.branch_display) {
+            (Some(target), BranchDisplay::Relative | BranchDisplay::Both) => {
+                let offset = target as i64 - instruction.address as i64;
+                result.push_str(&format!("{:08x}: {}", index, instruction.opcode));
+                if options.branch_display == BranchDisplay::Both {
+                    result.push_str(&format!(" {:#x} ({:+})", target, offset));
+                } else {
+                    result.push_str(&format!(" {:+}", offset));
+                }
+            }
+            _ => result.push_str(&format!("{:08x}: {}", index, instruction)),
+        }
+        if options.show_immediate_encoding {
+            if let Some(encoding) = instruction.immediate_encoding() {
+                result.push_str(&format!(" ; {}", encoding));
+            }
+        }
+        result.push('\n');
     }
     Ok(result)
 }
@@ -67,7 +165,7 @@ mod tests {
 
     #[test]
     fn test_disassemble() {
-        let reader = std::io::Cursor::new(vec![
+        let bytecode = vec![
             0x00, 0x00, 0x00, 0x01, // Section type: Gs1Flags
             0x00, 0x00, 0x00, 0x04, // Length: 4
             0x00, 0x00, 0x00, 0x00, // Flags: 0
@@ -92,9 +190,9 @@ mod tests {
             0x00, // Operand: 0
             0x1b, // Opcode: PushPi
             0x07, // Opcode: Ret
-        ]);
+        ];
 
-        let result = disassemble_bytecode(reader).unwrap();
+        let result = disassemble_bytecode(std::io::Cursor::new(bytecode.clone())).unwrap();
 
         assert_eq!(
             result,
@@ -105,6 +203,37 @@ mod tests {
             00000004: Ret\n"
         );
 
+        let options = DisasmOptions::new().show_immediate_encoding(true);
+        let result =
+            disassemble_bytecode_with_options(std::io::Cursor::new(bytecode.clone()), &options)
+                .unwrap();
+
+        assert_eq!(
+            result,
+            "00000000: Jmp 0x1 ; imm8\n\
+            00000001: PushNumber 0x1 ; imm16\n\
+            00000002: PushString abc ; imm8=0\n\
+            00000003: Pi\n\
+            00000004: Ret\n"
+        );
+
+        let options = DisasmOptions::new().branch_display(BranchDisplay::Relative);
+        let result =
+            disassemble_bytecode_with_options(std::io::Cursor::new(bytecode.clone()), &options)
+                .unwrap();
+        assert!(result.starts_with("00000000: Jmp +1\n00000001: PushNumber 0x1\n"));
+
+        let options = DisasmOptions::new().branch_display(BranchDisplay::Both);
+        let result =
+            disassemble_bytecode_with_options(std::io::Cursor::new(bytecode), &options).unwrap();
+        assert!(result.starts_with("00000000: Jmp 0x1 (+1)\n"));
+
+        // A loop jumps backward
+        let reader = std::fs::File::open("tests/gs2bc/loops.gs2bc").unwrap();
+        let options = DisasmOptions::new().branch_display(BranchDisplay::Relative);
+        let result = disassemble_bytecode_with_options(reader, &options).unwrap();
+        assert!(result.lines().any(|line| line.contains(": Jmp -")));
+
         // test failure case
         let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x01]);
         let result = disassemble_bytecode(reader);
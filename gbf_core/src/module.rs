@@ -2,16 +2,23 @@
 
 use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Display, Formatter},
 };
 use thiserror::Error;
 
 use crate::{
     basic_block::{BasicBlockId, BasicBlockType},
-    bytecode_loader::{self, BytecodeLoaderError},
+    bytecode_loader::{self, BytecodeLoaderError, SectionMask, SectionType},
+    decompiler::{
+        ast::{literal::LiteralNode, visitors::emit_context::EmitContext},
+        decompile_options::DecompileOptions,
+        function_decompiler::{FunctionDecompilerError, FunctionDecompilerErrorDetails},
+    },
     function::{Function, FunctionId},
     instruction::Instruction,
+    opcode::{Opcode, StackEffect},
+    operand::Operand,
     utils::Gs2BytecodeAddress,
 };
 
@@ -39,10 +46,148 @@ pub enum ModuleError {
     BytecodeLoaderError(#[from] BytecodeLoaderError),
 }
 
+/// The kinds of literals that `Module::collect_literals` can collect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum LiteralFilter {
+    /// Collect every kind of literal.
+    All,
+    /// Collect string literals.
+    Strings,
+    /// Collect integer literals.
+    Numbers,
+    /// Collect floating point literals.
+    Floats,
+}
+
+impl LiteralFilter {
+    /// Checks if the filter matches the given literal.
+    ///
+    /// # Arguments
+    /// - `literal`: The literal to check.
+    ///
+    /// # Returns
+    /// - `true` if the literal matches the filter, `false` otherwise.
+    pub fn matches(&self, literal: &LiteralNode) -> bool {
+        matches!(
+            (self, literal),
+            (LiteralFilter::All, _)
+                | (LiteralFilter::Strings, LiteralNode::String(_))
+                | (LiteralFilter::Numbers, LiteralNode::Number(_))
+                | (LiteralFilter::Floats, LiteralNode::Float(_))
+        )
+    }
+}
+
+/// The target of a call instruction, as found by `Module::call_targets`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum CallTarget {
+    /// A call to a function of the module.
+    Function(FunctionId),
+    /// A call by name to a function that is not part of the module, such as a builtin.
+    External(String),
+    /// A call whose target is not a name pushed directly before the call, such as a
+    /// method call, so it cannot be resolved without decompiling.
+    Dynamic,
+}
+
+/// The differences between two versions of a module, as found by `Module::diff`.
+/// Functions are matched by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModuleDiff {
+    /// The functions of the other module that are not in this module.
+    pub added: Vec<FunctionId>,
+    /// The functions of this module that are not in the other module.
+    pub removed: Vec<FunctionId>,
+    /// The functions in both modules whose instructions differ, as pairs of the
+    /// function in this module and the function in the other module.
+    pub changed: Vec<(FunctionId, FunctionId)>,
+}
+
+impl ModuleDiff {
+    /// Checks if the modules have no differences.
+    ///
+    /// # Returns
+    /// - `true` if no function was added, removed, or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// How many functions of a module decompile, as found by `Module::decompile_coverage`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    /// The name of the module.
+    pub module: Option<String>,
+    /// The number of functions in the module.
+    pub functions: usize,
+    /// The number of functions that decompiled successfully.
+    pub successful_functions: usize,
+    /// The number of functions that failed with each kind of error, keyed by
+    /// `FunctionDecompilerErrorDetails::error_type`.
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl CoverageReport {
+    /// Gets the fraction of the functions that decompiled successfully. A module
+    /// without functions is fully covered.
+    ///
+    /// # Returns
+    /// - The coverage, between `0.0` and `1.0`.
+    pub fn coverage(&self) -> f64 {
+        if self.functions == 0 {
+            return 1.0;
+        }
+        self.successful_functions as f64 / self.functions as f64
+    }
+}
+
+/// The result of decompiling one function with `Module::decompile_all`.
+#[derive(Debug)]
+pub enum DecompileResult {
+    /// The function was decompiled, or failed to decompile.
+    Decompiled(Result<String, FunctionDecompilerError>),
+    /// The function was skipped by `DecompileOptions::skip_function`.
+    Skipped,
+}
+
+impl DecompileResult {
+    /// Gets the result of decompiling the function.
+    ///
+    /// # Returns
+    /// - The result, or `None` if the function was skipped.
+    pub fn decompiled(&self) -> Option<&Result<String, FunctionDecompilerError>> {
+        match self {
+            DecompileResult::Decompiled(result) => Some(result),
+            DecompileResult::Skipped => None,
+        }
+    }
+
+    /// Returns whether the function was skipped.
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, DecompileResult::Skipped)
+    }
+}
+
+/// A value on the stack that `Module::translation_keys` simulates.
+#[derive(Debug, Clone)]
+enum SimulatedValue {
+    /// The marker pushed by `PushArray`.
+    ArrayMarker,
+    /// A string literal, along with the address of the instruction that pushed it.
+    String(Gs2BytecodeAddress, String),
+    /// Any other value.
+    Other,
+}
+
 /// Represents a builder for a `Module`.
 pub struct ModuleBuilder {
     name: Option<String>,
     reader: Option<Box<dyn std::io::Read>>,
+    sections: SectionMask,
+    strict_function_names: bool,
+    expect_header: bool,
+    max_instructions: usize,
+    max_blocks: usize,
 }
 
 /// Public API for `ModuleBuilder`.
@@ -65,6 +210,11 @@ impl ModuleBuilder {
         Self {
             name: None,
             reader: None,
+            sections: SectionMask::ALL,
+            strict_function_names: false,
+            expect_header: false,
+            max_instructions: bytecode_loader::DEFAULT_MAX_INSTRUCTIONS,
+            max_blocks: bytecode_loader::DEFAULT_MAX_BLOCKS,
         }
     }
     /// Set the name of the module.
@@ -105,6 +255,110 @@ impl ModuleBuilder {
         self
     }
 
+    /// Set which sections of the bytecode to load. When the instructions section is
+    /// skipped, functions are declared from the functions section, but only contain an
+    /// empty entry block.
+    ///
+    /// # Arguments
+    /// - `sections`: The sections to load.
+    ///
+    /// # Returns
+    /// - A reference to the builder.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::{SectionMask, SectionType};
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let builder = ModuleBuilder::new().sections(SectionMask::NONE.with(SectionType::Functions));
+    /// ```
+    pub fn sections(mut self, sections: SectionMask) -> Self {
+        self.sections = sections;
+        self
+    }
+
+    /// Set whether a function name that is defined more than once is an error. By
+    /// default, the duplicate is renamed by appending its address, e.g. `onCreated@12`.
+    ///
+    /// # Arguments
+    /// - `strict_function_names`: Whether duplicate function names are an error.
+    ///
+    /// # Returns
+    /// - A reference to the builder.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let builder = ModuleBuilder::new().strict_function_names(true);
+    /// ```
+    pub fn strict_function_names(mut self, strict_function_names: bool) -> Self {
+        self.strict_function_names = strict_function_names;
+        self
+    }
+
+    /// Set whether the bytecode starts with a magic and version header, which
+    /// `Module::version` returns after the module is built.
+    ///
+    /// # Arguments
+    /// - `expect_header`: Whether the bytecode starts with a header.
+    ///
+    /// # Returns
+    /// - A reference to the builder.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let builder = ModuleBuilder::new().expect_header(true);
+    /// ```
+    pub fn expect_header(mut self, expect_header: bool) -> Self {
+        self.expect_header = expect_header;
+        self
+    }
+
+    /// Set the maximum number of instructions to load, as with
+    /// `BytecodeLoaderBuilder::max_instructions`. Building a module with more
+    /// instructions fails with `BytecodeLoaderError::TooManyInstructions`.
+    ///
+    /// # Arguments
+    /// - `max_instructions`: The maximum number of instructions.
+    ///
+    /// # Returns
+    /// - A reference to the builder.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let builder = ModuleBuilder::new().max_instructions(100_000);
+    /// ```
+    pub fn max_instructions(mut self, max_instructions: usize) -> Self {
+        self.max_instructions = max_instructions;
+        self
+    }
+
+    /// Set the maximum number of blocks to load, as with
+    /// `BytecodeLoaderBuilder::max_blocks`. Building a module with more blocks fails
+    /// with `BytecodeLoaderError::TooManyBlocks`.
+    ///
+    /// # Arguments
+    /// - `max_blocks`: The maximum number of blocks.
+    ///
+    /// # Returns
+    /// - A reference to the builder.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let builder = ModuleBuilder::new().max_blocks(10_000);
+    /// ```
+    pub fn max_blocks(mut self, max_blocks: usize) -> Self {
+        self.max_blocks = max_blocks;
+        self
+    }
+
     /// Build the `Module` from the builder.
     ///
     /// # Returns
@@ -117,29 +371,72 @@ impl ModuleBuilder {
     /// let module = ModuleBuilder::new().name("test").build().unwrap();
     /// ```
     pub fn build(self) -> Result<Module, ModuleError> {
-        let mut module = Module {
-            name: self.name,
-            functions: Vec::new(),
-            id_to_index: HashMap::new(),
-            name_to_id: HashMap::new(),
-            address_to_id: HashMap::new(),
-        };
-
-        // Create entry function
-        let fun_id = FunctionId::new_without_name(module.functions.len(), 0);
-
-        // Create new function struct
-        module.functions.push(Function::new(fun_id.clone()));
-        module.id_to_index.insert(fun_id.clone(), 0);
-        module.name_to_id.insert(None, fun_id.clone());
-        module.address_to_id.insert(0, fun_id.clone());
+        let mut module = Module::with_entry_function(self.name);
 
         if let Some(reader) = self.reader {
-            module.load(reader)?;
+            let loader = bytecode_loader::BytecodeLoaderBuilder::new(reader)
+                .sections(self.sections)
+                .strict_function_names(self.strict_function_names)
+                .expect_header(self.expect_header)
+                .max_instructions(self.max_instructions)
+                .max_blocks(self.max_blocks);
+            module.load(loader)?;
         }
 
         Ok(module)
     }
+
+    /// Build a `Module` from instructions that are already decoded, without reading
+    /// bytecode. The instructions are split into blocks and attributed to functions the
+    /// same way they are when loading bytecode.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the module.
+    /// - `instructions`: The instructions, where each instruction's address is its index.
+    /// - `function_map`: A map of function names to their addresses. The entry function
+    ///   is added at address 0 if it is missing.
+    /// - `strings`: The strings used by the instructions.
+    ///
+    /// # Returns
+    /// - A new `Module` instance.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` if the instructions cannot be split into blocks.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use gbf_core::instruction::Instruction;
+    /// use gbf_core::module::ModuleBuilder;
+    /// use gbf_core::opcode::Opcode;
+    /// use gbf_core::operand::Operand;
+    ///
+    /// let instructions = vec![
+    ///     Instruction::new_with_operand(Opcode::PushNumber, 0, Operand::new_number(1)),
+    ///     Instruction::new(Opcode::Ret, 1),
+    ///     Instruction::new(Opcode::Ret, 2),
+    /// ];
+    /// let function_map = HashMap::from([(Some("onCreated".to_string()), 2)]);
+    /// let module =
+    ///     ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+    ///         .unwrap();
+    /// assert_eq!(module.len(), 2);
+    /// ```
+    pub fn from_instructions<N: Into<String>>(
+        name: N,
+        instructions: Vec<Instruction>,
+        function_map: HashMap<Option<String>, Gs2BytecodeAddress>,
+        strings: Vec<String>,
+    ) -> Result<Module, ModuleError> {
+        let loaded_bytecode = bytecode_loader::BytecodeLoader::from_instructions(
+            instructions,
+            function_map,
+            strings,
+        )?;
+        let mut module = Module::with_entry_function(Some(name.into()));
+        module.populate(loaded_bytecode)?;
+        Ok(module)
+    }
 }
 
 /// Represents a GS2 module in a bytecode system. A module contains
@@ -155,6 +452,14 @@ pub struct Module {
     name_to_id: HashMap<Option<String>, FunctionId>,
     /// A map of function addresses to their IDs.
     address_to_id: HashMap<Gs2BytecodeAddress, FunctionId>,
+    /// The sections of the bytecode that were loaded.
+    sections: SectionMask,
+    /// Whether the module was loaded from bytecode, rather than built by hand.
+    loaded: bool,
+    /// The strings section of the bytecode, in the order the loader read it.
+    strings: Vec<String>,
+    /// The version from the bytecode header, if one was read.
+    version: Option<u32>,
 }
 
 /// Public API for `Module`.
@@ -383,75 +688,766 @@ impl Module {
         // The module will always have an entry function, so this is always false
         self.functions.is_empty()
     }
-}
 
-/// Internal API for `Module`.
-impl Module {
-    /// Load bytecode into the module using a reader.
+    /// Collect the literals pushed by the instructions of the module. This scans the raw
+    /// operands, so it does not require decompiling any function.
     ///
     /// # Arguments
-    /// - `reader`: The reader to use to load the bytecode.
+    /// - `filter`: The kinds of literals to collect.
+    ///
+    /// # Returns
+    /// - The matching literals, along with the function and address of the instruction
+    ///   that pushes them, ordered by address.
     ///
     /// # Errors
-    /// - `ModuleError::BytecodeLoaderError` if the bytecode loader fails to load the bytecode.
-    /// - `ModuleError::EntryModuleDefinedMoreThanOnce` if the entry function is already set.
-    fn load<R: std::io::Read>(&mut self, reader: R) -> Result<(), ModuleError> {
-        let loaded_bytecode = bytecode_loader::BytecodeLoaderBuilder::new(reader).build()?;
-
-        // Iterate through each instruction in the bytecode
-        for (offset, instruction) in loaded_bytecode.instructions.iter().enumerate() {
-            // Check if instruction is even reachable. If it's not, we can skip it
-            if !loaded_bytecode.is_instruction_reachable(offset) {
-                continue;
-            }
-            let function_name = loaded_bytecode.get_function_name_for_address(offset);
-            // The precondition above guarantees that this will always be true
-            debug_assert!(function_name.is_ok());
-            let function_name = function_name.unwrap().clone();
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::{LiteralFilter, ModuleBuilder};
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// assert!(module.collect_literals(LiteralFilter::Strings).unwrap().is_empty());
+    /// ```
+    pub fn collect_literals(
+        &self,
+        filter: LiteralFilter,
+    ) -> Result<Vec<(FunctionId, Gs2BytecodeAddress, LiteralNode)>, ModuleError> {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        let mut literals = Vec::new();
+        for function in &self.functions {
+            for instruction in function.iter().flat_map(|block| block.iter()) {
+                let literal = match (instruction.opcode, &instruction.operand) {
+                    (Opcode::PushString, Some(Operand::String(value))) => {
+                        LiteralNode::String(value.clone())
+                    }
+                    (Opcode::PushNumber, Some(Operand::Number(value))) => {
+                        LiteralNode::Number(*value)
+                    }
+                    (Opcode::PushNumber, Some(Operand::Float(value))) => {
+                        LiteralNode::Float(value.clone())
+                    }
+                    _ => continue,
+                };
 
-            if let Some(function_name) = function_name.clone() {
-                if !self.has_function(function_name.clone()) {
-                    let function_name_clone = function_name.clone();
-                    let offset = loaded_bytecode
-                        .function_map
-                        .get(&Some(function_name_clone))
-                        .expect("Function must exist in the function map");
-                    self.create_function(function_name, *offset)?;
+                if filter.matches(&literal) {
+                    literals.push((function.id.clone(), instruction.address, literal));
                 }
             }
+        }
+        literals.sort_by_key(|(_, address, _)| *address);
+        Ok(literals)
+    }
 
-            let is_entry = function_name.is_none();
+    /// Collect the keys of the translation lookups in the module, such as `_("key")`.
+    /// This simulates the value stack of each function in address order, so it does
+    /// not require decompiling any function. A key is found when the value under a
+    /// `GetTranslation` instruction was pushed by a `PushString`.
+    ///
+    /// # Returns
+    /// - The translation keys, along with the function and address of the instruction
+    ///   that pushes them, ordered by address.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// assert!(module.translation_keys().unwrap().is_empty());
+    /// ```
+    pub fn translation_keys(
+        &self,
+    ) -> Result<Vec<(FunctionId, Gs2BytecodeAddress, String)>, ModuleError> {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        let mut keys = Vec::new();
+        for function in &self.functions {
+            keys.extend(
+                Self::function_translation_keys(function)
+                    .into_iter()
+                    .map(|(address, key)| (function.id.clone(), address, key)),
+            );
+        }
+        keys.sort_by_key(|(_, address, _)| *address);
+        Ok(keys)
+    }
 
-            // Get the function reference. If the function is an entry function, we will use the entry function, otherwise we will use the function name
-            let function = if is_entry {
-                self.get_entry_function_mut()
-            } else {
-                self.get_function_by_name_mut(function_name.unwrap())?
-            };
+    /// Get the strings section of the module. The index of each string is the index
+    /// the bytecode loader assigned to it, which is the index encoded in the operands
+    /// of the instructions that load it.
+    ///
+    /// # Returns
+    /// - The strings, or an empty slice if the module was not loaded from bytecode.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the strings section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// assert!(module.strings().unwrap().is_empty());
+    /// ```
+    pub fn strings(&self) -> Result<&[String], ModuleError> {
+        self.expect_section_not_skipped(SectionType::Strings)?;
+        Ok(&self.strings)
+    }
 
-            // Get the start address for the basic block
-            let start_address = loaded_bytecode.find_block_start_address(offset);
+    /// Get the version from the bytecode header.
+    ///
+    /// # Returns
+    /// - The version, or `None` if the module was not loaded with `expect_header`.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// assert!(module.version().is_none());
+    /// ```
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
 
-            // Create new basic block if it doesn't exist
-            if !function.basic_block_exists_by_address(start_address) {
-                // We won't run into this error because we are not making an entry block here
+    /// Find every instruction that loads a string from the strings section.
+    ///
+    /// # Arguments
+    /// - `index`: The index of the string, as returned by `Module::strings`.
+    ///
+    /// # Returns
+    /// - The function and address of each instruction that loads the string, ordered
+    ///   by address. The result is empty if the index is out of range.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// assert!(module.string_xrefs(0).unwrap().is_empty());
+    /// ```
+    pub fn string_xrefs(
+        &self,
+        index: usize,
+    ) -> Result<Vec<(FunctionId, Gs2BytecodeAddress)>, ModuleError> {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        let mut xrefs: Vec<_> = self
+            .functions
+            .iter()
+            .flat_map(|function| {
                 function
-                    .create_block(BasicBlockType::Normal, start_address)
-                    .expect("Block collisions are not possible");
-            }
-
-            // Get the basic block reference
-            let block = function
-                .get_basic_block_by_start_address_mut(start_address)
-                .unwrap();
-
-            // Add the instruction to the basic block
-            block.add_instruction(instruction.clone());
-        }
+                    .iter()
+                    .flat_map(|block| block.iter())
+                    .filter(|instruction| instruction.original_string_index() == Some(index))
+                    .map(|instruction| (function.id.clone(), instruction.address))
+            })
+            .collect();
+        xrefs.sort_by_key(|(_, address)| *address);
+        Ok(xrefs)
+    }
 
-        // To the entry block, let's create a new basic block with address set to the length of the bytecode
-        // This is the block that will be used to represent the end of the module
-        let entry = self.get_entry_function_mut();
+    /// Find the target of each call in a function. A call is static when the name of
+    /// the function is pushed directly before the `Call` instruction.
+    ///
+    /// # Arguments
+    /// - `id`: The `FunctionId` of the function to scan.
+    ///
+    /// # Returns
+    /// - The address and target of each call in the function, ordered by address.
+    ///
+    /// # Errors
+    /// - `ModuleError::FunctionNotFoundById` if the function does not exist.
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// let entry = module.get_entry_function().id.clone();
+    /// assert!(module.call_targets(&entry).unwrap().is_empty());
+    /// ```
+    pub fn call_targets(
+        &self,
+        id: &FunctionId,
+    ) -> Result<Vec<(Gs2BytecodeAddress, CallTarget)>, ModuleError> {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        let function = self.get_function_by_id(id)?;
+        let mut targets = Vec::new();
+        for block in function.iter() {
+            let mut previous: Option<&Instruction> = None;
+            for instruction in block.iter() {
+                if instruction.opcode == Opcode::Call {
+                    let target = match previous.map(|i| (i.opcode, i.string_operand())) {
+                        Some((Opcode::PushVariable | Opcode::PushString, Some(name))) => {
+                            match self.name_to_id.get(&Some(name.to_string())) {
+                                Some(function_id) => CallTarget::Function(function_id.clone()),
+                                None => CallTarget::External(name.to_string()),
+                            }
+                        }
+                        _ => CallTarget::Dynamic,
+                    };
+                    targets.push((instruction.address, target));
+                }
+                previous = Some(instruction);
+            }
+        }
+        targets.sort_by_key(|(address, _)| *address);
+        Ok(targets)
+    }
+
+    /// Find the functions that are reachable from a function through static calls,
+    /// including the function itself. Dynamic calls are not followed; use
+    /// `Module::call_targets` to find them.
+    ///
+    /// # Arguments
+    /// - `from`: The `FunctionId` of the function to start from.
+    ///
+    /// # Returns
+    /// - The reachable functions, or an empty set if `from` is not part of the module.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// let entry = module.get_entry_function().id.clone();
+    /// assert_eq!(module.reachable_functions(entry.clone()).unwrap().len(), 1);
+    /// ```
+    pub fn reachable_functions(
+        &self,
+        from: FunctionId,
+    ) -> Result<HashSet<FunctionId>, ModuleError> {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        let mut reachable = HashSet::new();
+        let mut worklist = vec![from];
+        while let Some(id) = worklist.pop() {
+            let Ok(targets) = self.call_targets(&id) else {
+                continue;
+            };
+            if !reachable.insert(id) {
+                continue;
+            }
+            for (_, target) in targets {
+                if let CallTarget::Function(target) = target {
+                    if !reachable.contains(&target) {
+                        worklist.push(target);
+                    }
+                }
+            }
+        }
+        Ok(reachable)
+    }
+
+    /// Get the sections of the bytecode that were loaded into the module.
+    ///
+    /// # Returns
+    /// - The `SectionMask` of loaded sections. This is empty if the module was not
+    ///   built from a reader.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::bytecode_loader::SectionMask;
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// assert_eq!(module.loaded_sections(), SectionMask::NONE);
+    /// ```
+    pub fn loaded_sections(&self) -> SectionMask {
+        self.sections
+    }
+
+    /// Decompile every function in the module. With the `parallel` feature, the
+    /// functions are decompiled in parallel unless `DecompileOptions::parallel` is off.
+    /// Functions in `DecompileOptions::skip_function` are not decompiled, and their
+    /// result is `DecompileResult::Skipped`.
+    ///
+    /// # Arguments
+    /// - `ctx`: The context used to emit each function.
+    /// - `opts`: The options used to build the decompiler for each function.
+    ///
+    /// # Returns
+    /// - The result of decompiling each function, in the order of the functions in
+    ///   the module.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::decompiler::ast::visitors::emit_context::EmitContext;
+    /// use gbf_core::decompiler::decompile_options::DecompileOptions;
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// let results = module
+    ///     .decompile_all(EmitContext::default(), DecompileOptions::default())
+    ///     .unwrap();
+    /// assert_eq!(results.len(), module.len());
+    /// ```
+    pub fn decompile_all(
+        &self,
+        ctx: EmitContext,
+        opts: DecompileOptions,
+    ) -> Result<Vec<(FunctionId, DecompileResult)>, ModuleError> {
+        self.decompile_all_with(ctx, |_| opts.clone())
+    }
+
+    /// Decompile every function in the module with options chosen for each function,
+    /// such as a higher iteration limit for the functions that are known to be large.
+    /// The options are chosen for all functions before any is decompiled. With the
+    /// `parallel` feature, the functions are decompiled in parallel unless
+    /// `DecompileOptions::parallel` is off for any of them.
+    ///
+    /// # Arguments
+    /// - `ctx`: The context used to emit each function.
+    /// - `options_for`: Returns the options used to build the decompiler for a function.
+    ///
+    /// # Returns
+    /// - The result of decompiling each function, in the order of the functions in
+    ///   the module.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::decompiler::ast::visitors::emit_context::EmitContext;
+    /// use gbf_core::decompiler::decompile_options::DecompileOptions;
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// let results = module
+    ///     .decompile_all_with(EmitContext::default(), |function| {
+    ///         match function.id.name.as_deref() {
+    ///             Some("onCreated") => DecompileOptions::new().structure_analysis_max_iterations(1000),
+    ///             _ => DecompileOptions::new(),
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(results.len(), module.len());
+    /// ```
+    pub fn decompile_all_with<F>(
+        &self,
+        ctx: EmitContext,
+        mut options_for: F,
+    ) -> Result<Vec<(FunctionId, DecompileResult)>, ModuleError>
+    where
+        F: FnMut(&Function) -> DecompileOptions,
+    {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        let jobs: Vec<_> = self
+            .functions
+            .iter()
+            .map(|function| (function, options_for(function)))
+            .collect();
+        let decompile = |(function, opts): &(&Function, DecompileOptions)| {
+            if opts.is_skipped(&function.id) {
+                return (function.id.clone(), DecompileResult::Skipped);
+            }
+            let mut decompiler = opts.decompiler((*function).clone());
+            let result = decompiler.decompile(ctx.clone());
+            (function.id.clone(), DecompileResult::Decompiled(result))
+        };
+
+        #[cfg(feature = "parallel")]
+        if jobs.iter().all(|(_, opts)| opts.is_parallel()) {
+            use rayon::prelude::*;
+            return Ok(jobs.par_iter().map(decompile).collect());
+        }
+
+        Ok(jobs.iter().map(decompile).collect())
+    }
+
+    /// Decompile every function in the module with the default options, and report
+    /// how many succeeded along with the kinds of errors the others failed with. This
+    /// can be used to rank modules by how well they decompile.
+    ///
+    /// # Arguments
+    /// - `ctx`: The context used to emit each function.
+    ///
+    /// # Returns
+    /// - The `CoverageReport` of the module.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading the module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::decompiler::ast::visitors::emit_context::EmitContext;
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let module = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// let report = module.decompile_coverage(&EmitContext::default()).unwrap();
+    /// assert_eq!(report.functions, module.len());
+    /// assert_eq!(report.coverage(), 1.0);
+    /// ```
+    pub fn decompile_coverage(&self, ctx: &EmitContext) -> Result<CoverageReport, ModuleError> {
+        let mut report = CoverageReport {
+            module: self.name.clone(),
+            ..Default::default()
+        };
+        for (_, result) in self.decompile_all(ctx.clone(), DecompileOptions::default())? {
+            // The default options do not skip any function
+            let DecompileResult::Decompiled(result) = result else {
+                continue;
+            };
+            report.functions += 1;
+            match result {
+                Ok(_) => report.successful_functions += 1,
+                Err(e) => *report.errors.entry(e.error_type()).or_insert(0) += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Compare this module with another version of it, such as the same script
+    /// before and after an update. Functions are matched by name, and a function has
+    /// changed if its opcodes or operands differ. Jump targets are compared relative
+    /// to the start of their function, so a function that only moved is unchanged.
+    /// A function whose duplicate name was renamed to `name@address` is matched by
+    /// its original name and how many functions of that name come before it, so it
+    /// still matches when its address changes.
+    ///
+    /// # Arguments
+    /// - `other`: The other version of the module.
+    ///
+    /// # Returns
+    /// - The `ModuleDiff` of the two modules, in address order.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` with `BytecodeLoaderError::SectionNotLoaded`
+    ///   if the instructions section was skipped when loading either module.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::module::ModuleBuilder;
+    ///
+    /// let mut old = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// let new = ModuleBuilder::new().name("test.gs2").build().unwrap();
+    /// old.create_function("onCreated", 0x10).unwrap();
+    ///
+    /// let diff = old.diff(&new).unwrap();
+    /// assert_eq!(diff.removed.len(), 1);
+    /// assert!(diff.added.is_empty());
+    /// ```
+    pub fn diff(&self, other: &Module) -> Result<ModuleDiff, ModuleError> {
+        self.expect_section_not_skipped(SectionType::Instructions)?;
+        other.expect_section_not_skipped(SectionType::Instructions)?;
+        let mut diff = ModuleDiff::default();
+        let self_keys = self.diff_keys();
+        let other_keys = other.diff_keys();
+        let other_functions: HashMap<_, _> = other_keys.iter().cloned().collect();
+        for (key, function) in &self_keys {
+            match other_functions.get(key) {
+                Some(other_function) => {
+                    if Self::normalized_instructions(function)
+                        != Self::normalized_instructions(other_function)
+                    {
+                        diff.changed
+                            .push((function.id.clone(), other_function.id.clone()));
+                    }
+                }
+                None => diff.removed.push(function.id.clone()),
+            }
+        }
+        let self_functions: HashSet<_> = self_keys.into_iter().map(|(key, _)| key).collect();
+        diff.added = other_keys
+            .into_iter()
+            .filter(|(key, _)| !self_functions.contains(key))
+            .map(|(_, function)| function.id.clone())
+            .collect();
+        Ok(diff)
+    }
+}
+
+/// Internal API for `Module`.
+impl Module {
+    /// Collect the keys of the translation lookups in a function, along with the
+    /// address of the instruction that pushes each key. The instructions are walked in
+    /// address order, so a key pushed on only one side of a branch may be missed.
+    ///
+    /// # Arguments
+    /// - `function`: The function to search.
+    ///
+    /// # Returns
+    /// - The keys, in the order of the lookups.
+    fn function_translation_keys(function: &Function) -> Vec<(Gs2BytecodeAddress, String)> {
+        let mut instructions: Vec<&Instruction> =
+            function.iter().flat_map(|block| block.iter()).collect();
+        instructions.sort_by_key(|instruction| instruction.address);
+
+        let mut keys = Vec::new();
+        let mut stack: Vec<SimulatedValue> = Vec::new();
+        for instruction in instructions {
+            match (instruction.opcode, &instruction.operand) {
+                (Opcode::GetTranslation, _) => {
+                    if let Some(SimulatedValue::String(address, key)) = stack.last() {
+                        keys.push((*address, key.clone()));
+                    }
+                }
+                (Opcode::PushString, Some(Operand::String(value))) => {
+                    stack.push(SimulatedValue::String(instruction.address, value.clone()));
+                    continue;
+                }
+                (Opcode::PushArray, _) => {
+                    stack.push(SimulatedValue::ArrayMarker);
+                    continue;
+                }
+                (Opcode::Copy, _) => {
+                    if let Some(value) = stack.last().cloned() {
+                        stack.push(value);
+                    }
+                    continue;
+                }
+                (Opcode::Swap, _) => {
+                    if stack.len() >= 2 {
+                        let len = stack.len();
+                        stack.swap(len - 1, len - 2);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let pushes = match instruction.opcode.stack_effect() {
+                StackEffect::Fixed { pops, pushes } => {
+                    stack.truncate(stack.len().saturating_sub(pops));
+                    pushes
+                }
+                StackEffect::Variable { pushes } => {
+                    while let Some(value) = stack.pop() {
+                        if matches!(value, SimulatedValue::ArrayMarker) {
+                            break;
+                        }
+                    }
+                    pushes
+                }
+            };
+            stack.resize(stack.len() + pushes, SimulatedValue::Other);
+        }
+        keys
+    }
+
+    /// Returns an error if the given section was skipped when the module was loaded
+    /// from bytecode. A module that was built by hand has no sections to skip.
+    ///
+    /// # Arguments
+    /// - `section`: The section to check.
+    fn expect_section_not_skipped(&self, section: SectionType) -> Result<(), ModuleError> {
+        if self.loaded && !self.sections.contains(section) {
+            return Err(BytecodeLoaderError::SectionNotLoaded(section).into());
+        }
+        Ok(())
+    }
+
+    /// Get the functions in address order, keyed by the name they were defined with
+    /// and how many functions with that name come before them. Unlike the name,
+    /// this key does not change when a renamed duplicate moves to another address.
+    fn diff_keys(&self) -> Vec<((Option<String>, usize), &Function)> {
+        let mut functions: Vec<&Function> = self.functions.iter().collect();
+        functions.sort_by_key(|function| function.id.address);
+        let mut counts: HashMap<Option<String>, usize> = HashMap::new();
+        functions
+            .into_iter()
+            .map(|function| {
+                let name = function
+                    .id
+                    .name
+                    .as_ref()
+                    .map(|name| match name.rsplit_once('@') {
+                        Some((original, address)) if address == function.id.address.to_string() => {
+                            original.to_string()
+                        }
+                        _ => name.clone(),
+                    });
+                let count = counts.entry(name.clone()).or_insert(0);
+                let key = (name, *count);
+                *count += 1;
+                (key, function)
+            })
+            .collect()
+    }
+
+    /// Get the opcodes and operands of a function in address order, with jump
+    /// targets made relative to the start of the function. Jumps past the end of the
+    /// function, such as the jump over every function at the start of the entry
+    /// function, are treated as jumps to its end.
+    ///
+    /// # Arguments
+    /// - `function`: The function to normalize.
+    fn normalized_instructions(function: &Function) -> Vec<(Opcode, Option<Operand>)> {
+        let mut instructions: Vec<&Instruction> =
+            function.iter().flat_map(|block| block.iter()).collect();
+        instructions.sort_by_key(|instruction| instruction.address);
+        let start = function.id.address as i32;
+        let end = instructions
+            .last()
+            .map_or(start, |instruction| instruction.address as i32 + 1);
+        instructions
+            .into_iter()
+            .map(|instruction| {
+                let operand = match &instruction.operand {
+                    Some(Operand::Number(target)) if instruction.opcode.has_jump_target() => {
+                        Some(Operand::Number((*target).min(end) - start))
+                    }
+                    operand => operand.clone(),
+                };
+                (instruction.opcode, operand)
+            })
+            .collect()
+    }
+
+    /// Create a module that only contains an empty entry function.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the module.
+    fn with_entry_function(name: Option<String>) -> Self {
+        let mut module = Module {
+            name,
+            functions: Vec::new(),
+            id_to_index: HashMap::new(),
+            name_to_id: HashMap::new(),
+            address_to_id: HashMap::new(),
+            sections: SectionMask::NONE,
+            loaded: false,
+            strings: Vec::new(),
+            version: None,
+        };
+
+        // Create entry function
+        let fun_id = FunctionId::new_without_name(module.functions.len(), 0);
+
+        // Create new function struct
+        module.functions.push(Function::new(fun_id.clone()));
+        module.id_to_index.insert(fun_id.clone(), 0);
+        module.name_to_id.insert(None, fun_id.clone());
+        module.address_to_id.insert(0, fun_id);
+        module
+    }
+
+    /// Load bytecode into the module using a loader builder.
+    ///
+    /// # Arguments
+    /// - `loader`: The builder of the loader, configured with the reader and options.
+    ///
+    /// # Errors
+    /// - `ModuleError::BytecodeLoaderError` if the bytecode loader fails to load the bytecode.
+    /// - `ModuleError::EntryModuleDefinedMoreThanOnce` if the entry function is already set.
+    fn load<R: std::io::Read>(
+        &mut self,
+        loader: bytecode_loader::BytecodeLoaderBuilder<R>,
+    ) -> Result<(), ModuleError> {
+        self.populate(loader.build()?)
+    }
+
+    /// Create the functions and blocks of the module from a loader.
+    ///
+    /// # Arguments
+    /// - `loaded_bytecode`: The loader containing the decoded bytecode.
+    ///
+    /// # Errors
+    /// - `ModuleError::DuplicateFunctionName` or `ModuleError::DuplicateFunctionAddress`
+    ///   if a function is defined more than once.
+    fn populate<R: std::io::Read>(
+        &mut self,
+        loaded_bytecode: bytecode_loader::BytecodeLoader<R>,
+    ) -> Result<(), ModuleError> {
+        self.sections = loaded_bytecode.loaded_sections();
+        self.loaded = true;
+        self.version = loaded_bytecode.version();
+        self.strings = loaded_bytecode
+            .get_strings()
+            .map(<[String]>::to_vec)
+            .unwrap_or_default();
+
+        // Without instructions, only declare the functions that the module defines.
+        if !self.sections.contains(SectionType::Instructions) {
+            if self.sections.contains(SectionType::Functions) {
+                let mut functions: Vec<_> = loaded_bytecode
+                    .function_map
+                    .iter()
+                    .filter_map(|(name, address)| name.clone().map(|name| (*address, name)))
+                    .collect();
+                functions.sort();
+                for (address, name) in functions {
+                    self.create_function(name, address)?;
+                }
+            }
+            return Ok(());
+        }
+
+        // Iterate through each instruction in the bytecode
+        for (offset, instruction) in loaded_bytecode.instructions.iter().enumerate() {
+            // Check if instruction is even reachable. If it's not, we can skip it
+            if !loaded_bytecode.is_instruction_reachable(offset) {
+                continue;
+            }
+            let function_name = loaded_bytecode.get_function_name_for_address(offset);
+            // The precondition above guarantees that this will always be true
+            debug_assert!(function_name.is_ok());
+            let function_name = function_name.unwrap().clone();
+
+            if let Some(function_name) = function_name.clone() {
+                if !self.has_function(function_name.clone()) {
+                    let function_name_clone = function_name.clone();
+                    let offset = loaded_bytecode
+                        .function_map
+                        .get(&Some(function_name_clone))
+                        .expect("Function must exist in the function map");
+                    self.create_function(function_name, *offset)?;
+                }
+            }
+
+            let is_entry = function_name.is_none();
+
+            // Get the function reference. If the function is an entry function, we will use the entry function, otherwise we will use the function name
+            let function = if is_entry {
+                self.get_entry_function_mut()
+            } else {
+                self.get_function_by_name_mut(function_name.unwrap())?
+            };
+
+            // Get the start address for the basic block
+            let start_address = loaded_bytecode.find_block_start_address(offset);
+
+            // Create new basic block if it doesn't exist
+            if !function.basic_block_exists_by_address(start_address) {
+                // We won't run into this error because we are not making an entry block here
+                function
+                    .create_block(BasicBlockType::Normal, start_address)
+                    .expect("Block collisions are not possible");
+            }
+
+            // Get the basic block reference
+            let block = function
+                .get_basic_block_by_start_address_mut(start_address)
+                .unwrap();
+
+            // Add the instruction to the basic block
+            block.add_instruction(instruction.clone());
+        }
+
+        // To the entry block, let's create a new basic block with address set to the length of the bytecode
+        // This is the block that will be used to represent the end of the module
+        let entry = self.get_entry_function_mut();
         entry
             .create_block(
                 BasicBlockType::ModuleEnd,
@@ -465,7 +1461,7 @@ impl Module {
         for function in self.functions.iter_mut() {
             let block_data: Vec<_> = function
                 .iter()
-                .map(|block| (block.id, block.last_instruction().cloned()))
+                .map(|block| (block.id, block.terminator().cloned()))
                 .collect();
 
             for (id, terminator) in block_data {
@@ -482,14 +1478,11 @@ impl Module {
     ) {
         if let Some(terminator) = terminator {
             let terminator_opcode = terminator.opcode;
-            let terminator_operand = terminator.operand;
             let terminator_address = terminator.address;
             if terminator_opcode.has_jump_target() {
-                if let Some(branch_address) =
-                    terminator_operand.and_then(|o| o.get_number_value().ok())
-                {
+                if let Some(branch_address) = terminator.jump_target() {
                     let branch_block_id = function
-                        .get_basic_block_id_by_start_address(branch_address as Gs2BytecodeAddress)
+                        .get_basic_block_id_by_start_address(branch_address)
                         .expect("Block must exist");
                     function.add_edge(id, branch_block_id).unwrap();
                 }
@@ -498,7 +1491,11 @@ impl Module {
             // If appropriate, connect the next block
             if terminator_opcode.connects_to_next_block() {
                 let next_block_id = function
-                    .get_basic_block_id_by_start_address(terminator_address + 1)
+                    .get_basic_block_id_by_start_address(
+                        terminator_address
+                            .checked_add(1)
+                            .expect("The loader checks that the next address fits"),
+                    )
                     .expect("Block must exist");
                 function.add_edge(id, next_block_id).unwrap();
             }
@@ -603,6 +1600,7 @@ impl<'a> IntoIterator for &'a mut Module {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decompiler::passes::constant_propagation::ConstantPropagationPass;
 
     #[test]
     fn load_bytecode() {
@@ -625,4 +1623,585 @@ mod tests {
             .build();
         assert!(module.is_err());
     }
+
+    #[test]
+    fn load_bytecode_with_header() {
+        let mut bytecode = b"GS2B".to_vec();
+        bytecode.extend([0x00, 0x00, 0x00, 0x03]);
+        bytecode.extend([
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        let module = ModuleBuilder::new()
+            .reader(Box::new(std::io::Cursor::new(bytecode.clone())))
+            .expect_header(true)
+            .build()
+            .unwrap();
+        assert_eq!(module.version(), Some(3));
+
+        let module = ModuleBuilder::new()
+            .reader(Box::new(std::io::Cursor::new(bytecode)))
+            .build();
+        assert!(module.is_err());
+    }
+
+    #[test]
+    fn collect_literals() {
+        let bytecode = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x00, // Gs1Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // Functions, length 0
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x04, // Strings, length 4
+            0x61, 0x62, 0x63, 0x00, // "abc"
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x12, // Instructions, length 18
+            0x01, 0xF3, 0x01, // Jmp 1
+            0x14, 0xF4, 0x00, 0x07, // PushNumber 7
+            0x14, 0xF6, 0x33, 0x2e, 0x35, 0x00, // PushNumber 3.5
+            0x15, 0xF0, 0x00, // PushString "abc"
+            0x1b, // PushPi
+            0x07, // Ret
+        ];
+        let module = ModuleBuilder::new()
+            .reader(Box::new(std::io::Cursor::new(bytecode.to_vec())))
+            .build()
+            .unwrap();
+        let entry_id = module.get_entry_function().id.clone();
+
+        assert_eq!(
+            module.collect_literals(LiteralFilter::All).unwrap(),
+            vec![
+                (entry_id.clone(), 1, LiteralNode::Number(7)),
+                (entry_id.clone(), 2, LiteralNode::Float("3.5".to_string())),
+                (entry_id.clone(), 3, LiteralNode::String("abc".to_string())),
+            ]
+        );
+        assert_eq!(
+            module.collect_literals(LiteralFilter::Strings).unwrap(),
+            vec![(entry_id.clone(), 3, LiteralNode::String("abc".to_string()))]
+        );
+        assert_eq!(
+            module.collect_literals(LiteralFilter::Numbers).unwrap(),
+            vec![(entry_id.clone(), 1, LiteralNode::Number(7))]
+        );
+        assert_eq!(
+            module.collect_literals(LiteralFilter::Floats).unwrap(),
+            vec![(entry_id, 2, LiteralNode::Float("3.5".to_string()))]
+        );
+    }
+
+    #[test]
+    fn translation_keys() {
+        let bytecode = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x00, // Gs1Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // Functions, length 0
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x0d, // Strings, length 13
+            0x6b, 0x65, 0x79, 0x00, // "key"
+            0x61, 0x62, 0x63, 0x00, // "abc"
+            0x65, 0x63, 0x68, 0x6f, 0x00, // "echo"
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x17, // Instructions, length 23
+            0x17, // PushArray
+            0x15, 0xF0, 0x00, // PushString "key"
+            0x77, // GetTranslation
+            0x16, 0xF0, 0x02, // PushVariable "echo"
+            0x06, // Call
+            0x20, // Pop
+            0x15, 0xF0, 0x01, // PushString "abc"
+            0x20, // Pop
+            0x16, 0xF0, 0x01, // PushVariable abc
+            0x77, // GetTranslation
+            0x20, // Pop
+            0x14, 0xF3, 0x00, // PushNumber 0
+            0x07, // Ret
+        ];
+        let module = ModuleBuilder::new()
+            .reader(Box::new(std::io::Cursor::new(bytecode.to_vec())))
+            .build()
+            .unwrap();
+        let entry_id = module.get_entry_function().id.clone();
+
+        // The lookup is found inside the call, and neither the string "abc" nor the
+        // variable abc is a key
+        assert_eq!(
+            module.translation_keys().unwrap(),
+            vec![(entry_id, 1, "key".to_string())]
+        );
+    }
+
+    #[test]
+    fn string_xrefs() {
+        let bytecode = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x00, // Gs1Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // Functions, length 0
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x08, // Strings, length 8
+            0x6b, 0x65, 0x79, 0x00, // "key"
+            0x61, 0x62, 0x63, 0x00, // "abc"
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x0d, // Instructions, length 13
+            0x15, 0xF0, 0x00, // PushString "key"
+            0x20, // Pop
+            0x15, 0xF0, 0x01, // PushString "abc"
+            0x20, // Pop
+            0x15, 0xF0, 0x00, // PushString "key"
+            0x20, // Pop
+            0x07, // Ret
+        ];
+        let module = ModuleBuilder::new()
+            .reader(Box::new(std::io::Cursor::new(bytecode.to_vec())))
+            .build()
+            .unwrap();
+        let entry_id = module.get_entry_function().id.clone();
+
+        assert_eq!(
+            module.strings().unwrap(),
+            ["key".to_string(), "abc".to_string()]
+        );
+        assert_eq!(
+            module.string_xrefs(0).unwrap(),
+            vec![(entry_id.clone(), 0), (entry_id.clone(), 4)]
+        );
+        assert_eq!(module.string_xrefs(1).unwrap(), vec![(entry_id, 2)]);
+        assert!(module.string_xrefs(2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_functions_only() {
+        let bytecode = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x00, // Gs1Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x09, // Functions, length 9
+            0x00, 0x00, 0x00, 0x01, 0x6d, 0x61, 0x69, 0x6e, 0x00, // "main" at 1
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, // Strings, length 0
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, // Instructions, length 2
+            0xFF, 0xFF, // Garbage that would fail to decode
+        ];
+        let module = ModuleBuilder::new()
+            .reader(Box::new(std::io::Cursor::new(bytecode.to_vec())))
+            .sections(SectionMask::NONE.with(SectionType::Functions))
+            .build()
+            .unwrap();
+
+        assert_eq!(module.len(), 2);
+        assert!(module.has_function("main"));
+        let function = module.get_function_by_name("main").unwrap();
+        assert_eq!(function.len(), 1);
+        assert!(function[0].is_empty());
+        assert!(module.loaded_sections().contains(SectionType::Functions));
+        assert!(!module.loaded_sections().contains(SectionType::Instructions));
+
+        // Accessors that need the skipped sections fail instead of returning nothing
+        let not_loaded = |section| {
+            move |result: Result<(), ModuleError>| {
+                matches!(
+                    result,
+                    Err(ModuleError::BytecodeLoaderError(BytecodeLoaderError::SectionNotLoaded(s)))
+                        if s == section
+                )
+            }
+        };
+        let instructions = not_loaded(SectionType::Instructions);
+        let entry = module.get_entry_function().id.clone();
+        assert!(not_loaded(SectionType::Strings)(
+            module.strings().map(|_| ())
+        ));
+        assert!(instructions(
+            module.collect_literals(LiteralFilter::All).map(|_| ())
+        ));
+        assert!(instructions(module.translation_keys().map(|_| ())));
+        assert!(instructions(module.string_xrefs(0).map(|_| ())));
+        assert!(instructions(module.call_targets(&entry).map(|_| ())));
+        assert!(instructions(module.reachable_functions(entry).map(|_| ())));
+        assert!(instructions(module.diff(&module).map(|_| ())));
+        assert!(instructions(
+            module
+                .decompile_all(EmitContext::default(), DecompileOptions::default())
+                .map(|_| ())
+        ));
+        assert!(instructions(
+            module
+                .decompile_coverage(&EmitContext::default())
+                .map(|_| ())
+        ));
+    }
+
+    #[test]
+    fn load_limits() {
+        let bytecode = vec![
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x00, // Gs1Flags
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // Functions, length 0
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, // Strings, length 0
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x05, // Instructions, length 5
+            0x02, 0xF3, 0x02, // Jeq 2
+            0x1b, // PushPi
+            0x07, // Ret
+        ];
+        let build = |max_instructions: usize, max_blocks: usize| {
+            ModuleBuilder::new()
+                .reader(Box::new(std::io::Cursor::new(bytecode.clone())))
+                .max_instructions(max_instructions)
+                .max_blocks(max_blocks)
+                .build()
+        };
+
+        // Blocks start at 0, 1, 2 and 3
+        assert!(build(3, 4).is_ok());
+        assert!(matches!(
+            build(2, 4),
+            Err(ModuleError::BytecodeLoaderError(
+                BytecodeLoaderError::TooManyInstructions(2)
+            ))
+        ));
+        assert!(matches!(
+            build(3, 3),
+            Err(ModuleError::BytecodeLoaderError(
+                BytecodeLoaderError::TooManyBlocks(3)
+            ))
+        ));
+    }
+
+    #[test]
+    fn from_instructions() {
+        let instructions = vec![
+            Instruction::new(Opcode::PushTrue, 0),
+            Instruction::new_with_operand(Opcode::Jne, 1, Operand::new_number(3)),
+            Instruction::new_with_operand(Opcode::PushNumber, 2, Operand::new_number(1)),
+            Instruction::new(Opcode::Ret, 3),
+            Instruction::new(Opcode::Ret, 4),
+        ];
+        let function_map = HashMap::from([(Some("onCreated".to_string()), 4)]);
+        let module =
+            ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+                .unwrap();
+
+        assert_eq!(module.name, Some("test".to_string()));
+        assert_eq!(module.len(), 2);
+
+        // The entry function has three blocks, plus the module end block.
+        let entry = module.get_entry_function();
+        assert_eq!(entry.len(), 4);
+        let branch = entry.get_basic_block_id_by_start_address(0).unwrap();
+        assert_eq!(entry.get_successors(branch).unwrap().len(), 2);
+
+        let function = module.get_function_by_name("onCreated").unwrap();
+        assert_eq!(function.len(), 1);
+        assert_eq!(function[0].len(), 1);
+
+        // A jump without a target cannot be split into blocks.
+        let instructions = vec![Instruction::new(Opcode::Jmp, 0)];
+        assert!(matches!(
+            ModuleBuilder::from_instructions("test", instructions, HashMap::new(), Vec::new()),
+            Err(ModuleError::BytecodeLoaderError(
+                BytecodeLoaderError::MissingJumpTarget(0)
+            ))
+        ));
+
+        // Addresses must match the position of each instruction.
+        let instructions = vec![Instruction::new(Opcode::Ret, 1)];
+        assert!(matches!(
+            ModuleBuilder::from_instructions("test", instructions, HashMap::new(), Vec::new()),
+            Err(ModuleError::BytecodeLoaderError(
+                BytecodeLoaderError::InstructionAddressMismatch(0, 1)
+            ))
+        ));
+    }
+
+    #[test]
+    fn decompile_all() {
+        let instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(3)),
+            Instruction::new_with_operand(Opcode::PushNumber, 1, Operand::new_number(2)),
+            Instruction::new(Opcode::Ret, 2),
+        ];
+        let function_map = HashMap::from([(Some("onCreated".to_string()), 1)]);
+        let module =
+            ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+                .unwrap();
+
+        let options = DecompileOptions::new()
+            .ast_pass(|| Box::new(ConstantPropagationPass))
+            .parallel(false);
+        let results = module
+            .decompile_all(EmitContext::default(), options.clone())
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, module.get_entry_function().id);
+        assert_eq!(results[1].0.name, Some("onCreated".to_string()));
+        assert!(results[0].1.decompiled().unwrap().is_ok());
+        assert!(results[1]
+            .1
+            .decompiled()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .contains("return 2;"));
+
+        // Decompiling in parallel gives the same results, in the same order.
+        let parallel_results = module
+            .decompile_all(EmitContext::default(), options.parallel(true))
+            .unwrap();
+        let outputs = |results: &[(FunctionId, DecompileResult)]| {
+            results
+                .iter()
+                .map(|(id, result)| {
+                    let output = result.decompiled().and_then(|r| r.as_ref().ok().cloned());
+                    (id.clone(), output)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(outputs(&results), outputs(&parallel_results));
+    }
+
+    #[test]
+    fn decompile_all_with() {
+        let instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(3)),
+            Instruction::new_with_operand(Opcode::PushNumber, 1, Operand::new_number(2)),
+            Instruction::new(Opcode::Ret, 2),
+        ];
+        let function_map = HashMap::from([(Some("onCreated".to_string()), 1)]);
+        let module =
+            ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+                .unwrap();
+
+        // Only run constant propagation on `onCreated`
+        let mut chosen = Vec::new();
+        let results = module
+            .decompile_all_with(EmitContext::default(), |function| {
+                chosen.push(function.id.clone());
+                let options = DecompileOptions::new().parallel(false);
+                match function.id.name.as_deref() {
+                    Some("onCreated") => options.ast_pass(|| Box::new(ConstantPropagationPass)),
+                    _ => options,
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            chosen,
+            results.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>()
+        );
+        assert!(results[1]
+            .1
+            .decompiled()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .contains("return 2;"));
+    }
+
+    #[test]
+    fn decompile_all_skip_functions() {
+        let instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(3)),
+            Instruction::new_with_operand(Opcode::PushNumber, 1, Operand::new_number(2)),
+            Instruction::new(Opcode::Ret, 2),
+        ];
+        let function_map = HashMap::from([(Some("onCreated".to_string()), 1)]);
+        let module =
+            ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+                .unwrap();
+
+        let on_created = module
+            .get_function_by_name("onCreated".to_string())
+            .unwrap()
+            .id
+            .clone();
+        let options = DecompileOptions::new().skip_function(on_created.clone());
+        assert!(options.is_skipped(&on_created));
+
+        let results = module
+            .decompile_all(EmitContext::default(), options)
+            .unwrap();
+        assert!(results[0].1.decompiled().unwrap().is_ok());
+        assert_eq!(results[1].0, on_created);
+        assert!(results[1].1.is_skipped());
+        assert!(results[1].1.decompiled().is_none());
+    }
+
+    #[test]
+    fn decompile_coverage() {
+        // `onCreated` pops from an empty stack, so it fails to decompile
+        let instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(3)),
+            Instruction::new(Opcode::Pop, 1),
+            Instruction::new(Opcode::Ret, 2),
+        ];
+        let function_map = HashMap::from([(Some("onCreated".to_string()), 1)]);
+        let module =
+            ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+                .unwrap();
+
+        let report = module.decompile_coverage(&EmitContext::default()).unwrap();
+        assert_eq!(report.module, Some("test".to_string()));
+        assert_eq!(report.functions, 2);
+        assert_eq!(report.successful_functions, 1);
+        assert_eq!(
+            report.errors,
+            BTreeMap::from([("ExecutionStackEmpty".to_string(), 1)])
+        );
+        assert_eq!(report.coverage(), 0.5);
+    }
+
+    #[test]
+    fn diff() {
+        let old_instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(7)),
+            Instruction::new_with_operand(Opcode::PushNumber, 1, Operand::new_number(2)),
+            Instruction::new(Opcode::Ret, 2),
+            Instruction::new(Opcode::Ret, 3),
+            Instruction::new_with_operand(Opcode::Jmp, 4, Operand::new_number(5)),
+            Instruction::new(Opcode::Ret, 5),
+            Instruction::new(Opcode::Ret, 6),
+        ];
+        let old_functions = HashMap::from([
+            (Some("onChanged".to_string()), 1),
+            (Some("onRemoved".to_string()), 3),
+            (Some("onMoved".to_string()), 4),
+            (Some("onSame".to_string()), 6),
+        ]);
+        let old =
+            ModuleBuilder::from_instructions("test", old_instructions, old_functions, Vec::new())
+                .unwrap();
+
+        // onMoved now starts at a different address, but its jump is to the same place
+        let new_instructions = vec![
+            Instruction::new_with_operand(Opcode::Jmp, 0, Operand::new_number(8)),
+            Instruction::new_with_operand(Opcode::PushNumber, 1, Operand::new_number(3)),
+            Instruction::new(Opcode::Ret, 2),
+            Instruction::new(Opcode::PushTrue, 3),
+            Instruction::new(Opcode::Ret, 4),
+            Instruction::new_with_operand(Opcode::Jmp, 5, Operand::new_number(6)),
+            Instruction::new(Opcode::Ret, 6),
+            Instruction::new(Opcode::Ret, 7),
+        ];
+        let new_functions = HashMap::from([
+            (Some("onChanged".to_string()), 1),
+            (Some("onAdded".to_string()), 3),
+            (Some("onMoved".to_string()), 5),
+            (Some("onSame".to_string()), 7),
+        ]);
+        let new =
+            ModuleBuilder::from_instructions("test", new_instructions, new_functions, Vec::new())
+                .unwrap();
+
+        let diff = old.diff(&new).unwrap();
+        let names = |ids: &[FunctionId]| {
+            ids.iter()
+                .map(|id| id.name.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&diff.added), vec!["onAdded"]);
+        assert_eq!(names(&diff.removed), vec!["onRemoved"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0.address, 1);
+        assert_eq!(diff.changed[0].1.name, Some("onChanged".to_string()));
+
+        assert!(old.diff(&old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diff_renamed_duplicates() {
+        let module = |functions: [(&str, Gs2BytecodeAddress); 2]| {
+            let end = functions[1].1 + 1;
+            let mut instructions = vec![Instruction::new_with_operand(
+                Opcode::Jmp,
+                0,
+                Operand::new_number(end as i32),
+            )];
+            for address in 1..end {
+                instructions.push(Instruction::new(Opcode::Ret, address));
+            }
+            let functions = functions
+                .into_iter()
+                .map(|(name, address)| (Some(name.to_string()), address))
+                .collect();
+            ModuleBuilder::from_instructions("test", instructions, functions, Vec::new()).unwrap()
+        };
+
+        // Both functions moved, so the name the duplicate was renamed to changed too
+        let old = module([("onCreated", 1), ("onCreated@2", 2)]);
+        let new = module([("onCreated", 2), ("onCreated@3", 3)]);
+        let diff = old.diff(&new).unwrap();
+        assert!(diff.is_empty(), "{:?}", diff);
+    }
+
+    #[test]
+    fn reachable_functions() {
+        let call = |name: &str, address: Gs2BytecodeAddress| {
+            vec![
+                Instruction::new(Opcode::PushArray, address),
+                Instruction::new_with_operand(
+                    Opcode::PushVariable,
+                    address + 1,
+                    Operand::new_string(name),
+                ),
+                Instruction::new(Opcode::Call, address + 2),
+                Instruction::new(Opcode::Pop, address + 3),
+            ]
+        };
+        let mut instructions = vec![Instruction::new_with_operand(
+            Opcode::Jmp,
+            0,
+            Operand::new_number(23),
+        )];
+        // onCreated calls foo and echo, foo calls itself, bar is never called
+        instructions.extend(call("foo", 1));
+        instructions.extend(call("echo", 5));
+        instructions.push(Instruction::new(Opcode::Ret, 9));
+        instructions.extend(call("foo", 10));
+        instructions.push(Instruction::new(Opcode::Ret, 14));
+        // bar makes a method call, which is dynamic
+        instructions.push(Instruction::new(Opcode::PushArray, 15));
+        instructions.push(Instruction::new_with_operand(
+            Opcode::PushVariable,
+            16,
+            Operand::new_string("obj"),
+        ));
+        instructions.push(Instruction::new_with_operand(
+            Opcode::PushVariable,
+            17,
+            Operand::new_string("method"),
+        ));
+        instructions.push(Instruction::new(Opcode::AccessMember, 18));
+        instructions.push(Instruction::new(Opcode::Call, 19));
+        instructions.push(Instruction::new(Opcode::Pop, 20));
+        instructions.push(Instruction::new_with_operand(
+            Opcode::PushNumber,
+            21,
+            Operand::new_number(0),
+        ));
+        instructions.push(Instruction::new(Opcode::Ret, 22));
+
+        let function_map = HashMap::from([
+            (Some("onCreated".to_string()), 1),
+            (Some("foo".to_string()), 10),
+            (Some("bar".to_string()), 15),
+        ]);
+        let module =
+            ModuleBuilder::from_instructions("test", instructions, function_map, Vec::new())
+                .unwrap();
+        let id = |name: &str| module.get_function_id_by_name(name).unwrap();
+
+        assert_eq!(
+            module.call_targets(&id("onCreated")).unwrap(),
+            vec![
+                (3, CallTarget::Function(id("foo"))),
+                (7, CallTarget::External("echo".to_string())),
+            ]
+        );
+        assert_eq!(
+            module.call_targets(&id("bar")).unwrap(),
+            vec![(19, CallTarget::Dynamic)]
+        );
+
+        assert_eq!(
+            module.reachable_functions(id("onCreated")).unwrap(),
+            HashSet::from([id("onCreated"), id("foo")])
+        );
+        assert_eq!(
+            module.reachable_functions(id("bar")).unwrap(),
+            HashSet::from([id("bar")])
+        );
+    }
 }
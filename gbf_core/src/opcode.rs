@@ -423,10 +423,218 @@ define_opcodes! {
     ImmFloat = 0xf6,
 }
 
+/// The effect an opcode has on the value stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StackEffect {
+    /// The opcode pops and pushes a fixed number of values.
+    Fixed {
+        /// The number of values popped.
+        pops: usize,
+        /// The number of values pushed.
+        pushes: usize,
+    },
+    /// The opcode pops every value down to and including the most recent `PushArray`
+    /// marker, then pushes a fixed number of values.
+    Variable {
+        /// The number of values pushed.
+        pushes: usize,
+    },
+}
+
+impl Opcode {
+    /// Get the effect this opcode has on the value stack. The marker pushed by
+    /// `PushArray` counts as one value.
+    ///
+    /// # Returns
+    /// - The `StackEffect` of the opcode.
+    ///
+    /// # Example
+    /// ```
+    /// use gbf_core::opcode::{Opcode, StackEffect};
+    ///
+    /// assert_eq!(Opcode::Add.stack_effect(), StackEffect::Fixed { pops: 2, pushes: 1 });
+    /// assert_eq!(Opcode::Call.stack_effect(), StackEffect::Variable { pushes: 1 });
+    /// ```
+    pub fn stack_effect(self) -> StackEffect {
+        let (pops, pushes) = match self {
+            Opcode::Call | Opcode::EndArray | Opcode::Format => {
+                return StackEffect::Variable { pushes: 1 }
+            }
+            Opcode::EndParams => return StackEffect::Variable { pushes: 0 },
+
+            // Control flow
+            Opcode::Jmp
+            | Opcode::IncreaseLoopCounter
+            | Opcode::FunctionStart
+            | Opcode::ShortCircuitEnd
+            | Opcode::WithEnd
+            | Opcode::MarkRegisterVariable => (0, 0),
+            Opcode::Jeq | Opcode::Jne | Opcode::With | Opcode::Ret | Opcode::Pop => (1, 0),
+            // The condition stays on the stack when the jump is taken.
+            Opcode::ShortCircuitAnd | Opcode::ShortCircuitOr => (1, 1),
+            Opcode::ForEach => (2, 2),
+
+            // Pushes
+            Opcode::PushNumber
+            | Opcode::PushString
+            | Opcode::PushVariable
+            | Opcode::PushArray
+            | Opcode::PushTrue
+            | Opcode::PushFalse
+            | Opcode::PushNull
+            | Opcode::Pi
+            | Opcode::This
+            | Opcode::ThisO
+            | Opcode::Player
+            | Opcode::PlayerO
+            | Opcode::Level
+            | Opcode::Temp
+            | Opcode::Params
+            | Opcode::GetRegister => (0, 1),
+
+            // Stack manipulation
+            Opcode::Copy => (1, 2),
+            Opcode::Swap => (2, 2),
+            Opcode::SetRegister => (1, 1),
+
+            // Conversions and unary operations
+            Opcode::ConvertToFloat
+            | Opcode::ConvertToString
+            | Opcode::ConvertToObject
+            | Opcode::ConvertToVariable
+            | Opcode::LogicalNot
+            | Opcode::UnarySubtract
+            | Opcode::BitwiseInvert
+            | Opcode::Inc
+            | Opcode::Dec
+            | Opcode::New
+            | Opcode::NewUninitializedArray => (1, 1),
+
+            // Binary operations
+            Opcode::Add
+            | Opcode::Subtract
+            | Opcode::Multiply
+            | Opcode::Divide
+            | Opcode::Modulo
+            | Opcode::Power
+            | Opcode::Equal
+            | Opcode::NotEqual
+            | Opcode::LessThan
+            | Opcode::GreaterThan
+            | Opcode::LessThanOrEqual
+            | Opcode::GreaterThanOrEqual
+            | Opcode::BitwiseOr
+            | Opcode::BitwiseAnd
+            | Opcode::BitwiseXor
+            | Opcode::ShiftLeft
+            | Opcode::ShiftRight
+            | Opcode::In
+            | Opcode::Join
+            | Opcode::AccessMember
+            | Opcode::ArrayAccess
+            | Opcode::NewObject
+            | Opcode::MultiDimenArray => (2, 1),
+
+            // Assignments
+            Opcode::Assign | Opcode::AssignMultiDimensionalArray => (2, 0),
+            Opcode::AssignArray => (3, 0),
+            Opcode::InRange | Opcode::AssignMultiDimensionalArrayIndex | Opcode::SetArray => (3, 1),
+
+            // Builtin functions, which push their result
+            Opcode::Char
+            | Opcode::Int
+            | Opcode::Abs
+            | Opcode::Sin
+            | Opcode::Cos
+            | Opcode::ArcTan
+            | Opcode::Exp
+            | Opcode::Log
+            | Opcode::VecX
+            | Opcode::VecY
+            | Opcode::Sleep
+            | Opcode::MakeVar
+            | Opcode::GetTranslation
+            | Opcode::ObjLength
+            | Opcode::ObjLink
+            | Opcode::ObjTrim
+            | Opcode::ObjSize
+            | Opcode::ObjClear
+            | Opcode::ObjType
+            | Opcode::ObjIndices => (1, 1),
+            Opcode::Random
+            | Opcode::Min
+            | Opcode::Max
+            | Opcode::GetAngle
+            | Opcode::GetDir
+            | Opcode::ObjTokenize
+            | Opcode::ObjStarts
+            | Opcode::ObjEnds
+            | Opcode::ObjPos
+            | Opcode::ObjCharAt
+            | Opcode::ObjIndex
+            | Opcode::ObjPositions
+            | Opcode::ObjAddString
+            | Opcode::ObjRemoveString
+            | Opcode::ObjDeleteString => (2, 1),
+            Opcode::WaitFor
+            | Opcode::ObjSubstring
+            | Opcode::ObjInsertString
+            | Opcode::ObjReplaceString
+            | Opcode::ObjSubArray => (3, 1),
+
+            // Operand encodings are never executed on their own
+            Opcode::ImmStringByte
+            | Opcode::ImmStringShort
+            | Opcode::ImmStringInt
+            | Opcode::ImmByte
+            | Opcode::ImmShort
+            | Opcode::ImmInt
+            | Opcode::ImmFloat => (0, 0),
+        };
+        StackEffect::Fixed { pops, pushes }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_stack_effect() {
+        let mut variable = Vec::new();
+        for opcode in Opcode::all() {
+            match opcode.stack_effect() {
+                StackEffect::Fixed { pops, pushes } => {
+                    // Nothing pops more than three values, or pushes more than two
+                    assert!(pops <= 3 && pushes <= 2, "{}", opcode);
+                }
+                StackEffect::Variable { .. } => variable.push(*opcode),
+            }
+        }
+        assert_eq!(
+            variable,
+            vec![
+                Opcode::Call,
+                Opcode::EndArray,
+                Opcode::EndParams,
+                Opcode::Format
+            ]
+        );
+
+        assert_eq!(
+            Opcode::PushArray.stack_effect(),
+            StackEffect::Fixed { pops: 0, pushes: 1 }
+        );
+        assert_eq!(
+            Opcode::AssignArray.stack_effect(),
+            StackEffect::Fixed { pops: 3, pushes: 0 }
+        );
+        assert_eq!(
+            Opcode::EndParams.stack_effect(),
+            StackEffect::Variable { pushes: 0 }
+        );
+    }
+
     #[test]
     fn test_valid_conversion() {
         assert_eq!(Opcode::from_byte(0x1).unwrap(), Opcode::Jmp);
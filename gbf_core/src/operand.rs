@@ -17,6 +17,10 @@ pub enum OperandError {
     /// Invalid jump target
     #[error("Invalid jump target: {0}")]
     InvalidJumpTarget(Gs2BytecodeAddress),
+
+    /// A float operand that is not a valid number, such as from corrupt bytecode
+    #[error("Invalid float: {0}")]
+    InvalidFloat(String),
 }
 
 /// Represents an operand, which can be one of several types.
@@ -138,6 +142,122 @@ impl Operand {
             )),
         }
     }
+
+    /// Retrieves the numeric value of a float or number operand. The text of a float
+    /// operand is kept as is, and parsed as an `f64` after trimming whitespace.
+    ///
+    /// # Returns
+    /// - The value of the operand as an `f64`.
+    ///
+    /// # Errors
+    /// - `OperandError::InvalidConversion` if the operand is a string.
+    /// - `OperandError::InvalidFloat` if the text of a float operand is not a number.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::operand::Operand;
+    ///
+    /// assert_eq!(Operand::new_float("3.14").get_float_value().unwrap(), 3.14);
+    /// assert_eq!(Operand::new_number(2).get_float_value().unwrap(), 2.0);
+    /// assert!(Operand::new_float("3.1.4").get_float_value().is_err());
+    /// ```
+    pub fn get_float_value(&self) -> Result<f64, OperandError> {
+        match self {
+            Operand::Float(value) => value
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| OperandError::InvalidFloat(value.clone())),
+            Operand::Number(value) => Ok(*value as f64),
+            Operand::String(_) => Err(OperandError::InvalidConversion(
+                "String".to_string(),
+                "Float".to_string(),
+            )),
+        }
+    }
+
+    /// Creates a float operand from a computed value, such as the result of folding
+    /// two constants. The value is written in the shortest form that parses back to
+    /// the same `f64`, so `0.1 + 0.2` is written as `0.30000000000000004`.
+    ///
+    /// # Arguments
+    /// - `value`: The value of the float operand.
+    ///
+    /// # Returns
+    /// - A new `Operand::Float`.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::operand::Operand;
+    ///
+    /// assert_eq!(Operand::from_float_value(2.5), Operand::new_float("2.5"));
+    /// assert_eq!(Operand::from_float_value(3.0), Operand::new_float("3"));
+    /// ```
+    pub fn from_float_value(value: f64) -> Self {
+        Operand::Float(value.to_string())
+    }
+
+    /// Compares two operands by value rather than by representation, so that
+    /// `Operand::Number(1)` and `Operand::Float("1.0")` are equal. Float operands are
+    /// parsed as `f64` after trimming whitespace; a float that fails to parse is only
+    /// equal to a float with the same text. Strings are never equal to numeric operands.
+    ///
+    /// The derived `PartialEq` remains a strict structural comparison.
+    ///
+    /// # Arguments
+    /// - `other`: The operand to compare against.
+    ///
+    /// # Returns
+    /// - `true` if the operands represent the same value, `false` otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::operand::Operand;
+    ///
+    /// assert!(Operand::new_number(1).value_eq(&Operand::new_float("1.0")));
+    /// assert!(!Operand::new_number(1).value_eq(&Operand::new_string("1")));
+    /// ```
+    pub fn value_eq(&self, other: &Operand) -> bool {
+        match (self, other) {
+            (Operand::String(a), Operand::String(b)) => a == b,
+            (Operand::String(_), _) | (_, Operand::String(_)) => false,
+            (Operand::Number(a), Operand::Number(b)) => a == b,
+            _ => match (self.numeric_value(), other.numeric_value()) {
+                (Some(a), Some(b)) => a == b,
+                _ => self == other,
+            },
+        }
+    }
+
+    /// Returns a canonical string for the operand, for use as a hash or cache key.
+    ///
+    /// Unlike `Display`, which is meant for disassembly and may change, this format is
+    /// stable: the operand type as `num`, `str` or `float`, followed by a colon and the
+    /// value. Numbers are written in decimal, and strings and floats are written as
+    /// stored, without quoting or escaping.
+    ///
+    /// # Returns
+    /// - The canonical string of the operand.
+    ///
+    /// # Examples
+    /// ```
+    /// use gbf_core::operand::Operand;
+    ///
+    /// assert_eq!(Operand::new_number(42).canonical_string(), "num:42");
+    /// assert_eq!(Operand::new_string("abc").canonical_string(), "str:abc");
+    /// assert_eq!(Operand::new_float("3.14").canonical_string(), "float:3.14");
+    /// ```
+    pub fn canonical_string(&self) -> String {
+        match self {
+            Operand::String(value) => format!("str:{}", value),
+            Operand::Float(value) => format!("float:{}", value),
+            Operand::Number(value) => format!("num:{}", value),
+        }
+    }
+
+    /// Gets the numeric value of a number or float operand, if it has one.
+    fn numeric_value(&self) -> Option<f64> {
+        self.get_float_value().ok()
+    }
 }
 
 impl fmt::Display for Operand {
@@ -184,6 +304,27 @@ mod tests {
         assert_eq!(operand.to_string(), "3.14");
     }
 
+    #[test]
+    fn float_value() {
+        let operand = Operand::new_float(" 2.5 ");
+        assert_eq!(operand.get_float_value().unwrap(), 2.5);
+        // The original text is kept for emission
+        assert_eq!(operand.get_string_value().unwrap(), " 2.5 ");
+
+        assert!(matches!(
+            Operand::new_float("1e").get_float_value(),
+            Err(OperandError::InvalidFloat(value)) if value == "1e"
+        ));
+        assert!(matches!(
+            Operand::new_string("1.5").get_float_value(),
+            Err(OperandError::InvalidConversion(..))
+        ));
+
+        let folded = Operand::from_float_value(0.5 + 0.25);
+        assert_eq!(folded.get_string_value().unwrap(), "0.75");
+        assert_eq!(folded.get_float_value().unwrap(), 0.75);
+    }
+
     #[test]
     fn int_operand() {
         let operand = Operand::new_number(42);
@@ -191,6 +332,44 @@ mod tests {
         assert_eq!(operand.to_string(), "0x2a");
     }
 
+    #[test]
+    fn test_value_eq() {
+        let one = Operand::new_number(1);
+        assert!(one.value_eq(&Operand::new_float("1.0")));
+        assert!(one.value_eq(&Operand::new_float("1")));
+        assert!(Operand::new_float("1.0").value_eq(&one));
+        assert!(Operand::new_float("0.50").value_eq(&Operand::new_float(".5")));
+        assert!(!one.value_eq(&Operand::new_float("1.5")));
+        assert!(!one.value_eq(&Operand::new_number(2)));
+
+        // Strings never compare equal to numeric operands
+        assert!(!one.value_eq(&Operand::new_string("1")));
+        assert!(!Operand::new_string("1").value_eq(&Operand::new_float("1")));
+        assert!(Operand::new_string("1").value_eq(&Operand::new_string("1")));
+
+        // Unparseable floats only match the same text
+        assert!(Operand::new_float("abc").value_eq(&Operand::new_float("abc")));
+        assert!(!Operand::new_float("abc").value_eq(&one));
+
+        // Structural equality stays strict
+        assert_ne!(one, Operand::new_float("1.0"));
+    }
+
+    #[test]
+    fn test_canonical_string() {
+        assert_eq!(Operand::new_number(42).canonical_string(), "num:42");
+        assert_eq!(Operand::new_number(-7).canonical_string(), "num:-7");
+        assert_eq!(Operand::new_string("abc").canonical_string(), "str:abc");
+        assert_eq!(Operand::new_string("").canonical_string(), "str:");
+        assert_eq!(Operand::new_float("3.14").canonical_string(), "float:3.14");
+
+        // The same text is distinct across operand types
+        assert_ne!(
+            Operand::new_string("1").canonical_string(),
+            Operand::new_float("1").canonical_string()
+        );
+    }
+
     #[test]
     fn display_trait() {
         let operand = Operand::new_number(123);
@@ -0,0 +1,198 @@
+#![deny(missing_docs)]
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::{
+    bytecode_loader::BytecodeLoaderError,
+    opcode::{Opcode, OpcodeError},
+    operand::Operand,
+};
+
+/// The bytes following an operand opcode, which an `OperandDecoder` reads the operand
+/// from. The loader keeps track of how many bytes are read, so decoders do not need to
+/// report the length of the operand.
+pub trait OperandInput {
+    /// Reads an unsigned 8-bit integer.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
+    fn read_u8(&mut self) -> Result<u8, BytecodeLoaderError>;
+
+    /// Reads an unsigned 16-bit integer.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
+    fn read_u16(&mut self) -> Result<u16, BytecodeLoaderError>;
+
+    /// Reads an unsigned 32-bit integer.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
+    fn read_u32(&mut self) -> Result<u32, BytecodeLoaderError>;
+
+    /// Reads a null-terminated string.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::GraalIo` if an I/O error occurs.
+    fn read_string(&mut self) -> Result<String, BytecodeLoaderError>;
+
+    /// Resolves an index into the strings section.
+    ///
+    /// # Errors
+    /// - `BytecodeLoaderError::StringIndexOutOfBounds` if the index is out of bounds.
+    fn resolve_string(&self, string_index: usize) -> Result<&str, BytecodeLoaderError>;
+}
+
+/// An operand decoded by an `OperandDecoder`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedOperand {
+    /// The operand, which is attached to the previous instruction.
+    pub operand: Operand,
+
+    /// The index into the strings section the operand was resolved from, if any.
+    pub string_index: Option<usize>,
+}
+
+impl DecodedOperand {
+    /// Creates a new `DecodedOperand` that was not resolved from the strings section.
+    ///
+    /// # Arguments
+    /// - `operand`: The decoded operand.
+    ///
+    /// # Returns
+    /// - A new `DecodedOperand` instance.
+    pub fn new(operand: Operand) -> Self {
+        Self {
+            operand,
+            string_index: None,
+        }
+    }
+
+    /// Creates a new `DecodedOperand` that was resolved from the strings section.
+    ///
+    /// # Arguments
+    /// - `operand`: The decoded operand.
+    /// - `string_index`: The index into the strings section.
+    ///
+    /// # Returns
+    /// - A new `DecodedOperand` instance.
+    pub fn with_string_index(operand: Operand, string_index: usize) -> Self {
+        Self {
+            operand,
+            string_index: Some(string_index),
+        }
+    }
+}
+
+/// Decodes the operand that follows an operand opcode. Operand opcodes do not create an
+/// instruction; their operand is attached to the previous instruction instead.
+///
+/// The loader looks up a decoder for each opcode it reads, first in the decoders
+/// registered with `BytecodeLoaderBuilder::operand_decoder`, then in
+/// `default_operand_decoders`. A custom operand encoding can be supported by
+/// registering a decoder for its opcode:
+///
+/// ```
+/// use gbf_core::bytecode_loader::{BytecodeLoaderBuilder, BytecodeLoaderError};
+/// use gbf_core::opcode::Opcode;
+/// use gbf_core::operand::Operand;
+/// use gbf_core::operand_decoder::{DecodedOperand, OperandDecoder, OperandInput};
+///
+/// /// Decodes `ImmShort` as little-endian.
+/// struct LittleEndianShort;
+///
+/// impl OperandDecoder for LittleEndianShort {
+///     fn decode(
+///         &self,
+///         _opcode: Opcode,
+///         input: &mut dyn OperandInput,
+///     ) -> Result<DecodedOperand, BytecodeLoaderError> {
+///         let value = input.read_u16()?.swap_bytes() as i16;
+///         Ok(DecodedOperand::new(Operand::new_number(value as i32)))
+///     }
+/// }
+///
+/// let reader = std::io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00]);
+/// let builder =
+///     BytecodeLoaderBuilder::new(reader).operand_decoder(Opcode::ImmShort, LittleEndianShort);
+/// ```
+pub trait OperandDecoder: Send + Sync {
+    /// Decodes one operand.
+    ///
+    /// # Arguments
+    /// - `opcode`: The operand opcode that was read.
+    /// - `input`: The bytes following the opcode.
+    ///
+    /// # Returns
+    /// - The decoded operand.
+    ///
+    /// # Errors
+    /// - Returns a `BytecodeLoaderError` if the operand could not be decoded.
+    fn decode(
+        &self,
+        opcode: Opcode,
+        input: &mut dyn OperandInput,
+    ) -> Result<DecodedOperand, BytecodeLoaderError>;
+}
+
+/// Decodes the immediate operands: string indices, integers, and floats.
+#[derive(Debug, Default)]
+pub struct ImmediateOperandDecoder;
+
+impl OperandDecoder for ImmediateOperandDecoder {
+    fn decode(
+        &self,
+        opcode: Opcode,
+        input: &mut dyn OperandInput,
+    ) -> Result<DecodedOperand, BytecodeLoaderError> {
+        let string_index = match opcode {
+            Opcode::ImmStringByte => input.read_u8()? as usize,
+            Opcode::ImmStringShort => input.read_u16()? as usize,
+            Opcode::ImmStringInt => input.read_u32()? as usize,
+            Opcode::ImmByte => {
+                let value = input.read_u8()? as i8;
+                return Ok(DecodedOperand::new(Operand::new_number(value as i32)));
+            }
+            Opcode::ImmShort => {
+                let value = input.read_u16()? as i16;
+                return Ok(DecodedOperand::new(Operand::new_number(value as i32)));
+            }
+            Opcode::ImmInt => {
+                let value = input.read_u32()? as i32;
+                return Ok(DecodedOperand::new(Operand::new_number(value)));
+            }
+            Opcode::ImmFloat => {
+                let value = input.read_string()?;
+                return Ok(DecodedOperand::new(Operand::new_float(value)));
+            }
+            _ => return Err(OpcodeError::InvalidOpcode(opcode as u8).into()),
+        };
+        let string = input.resolve_string(string_index)?;
+        Ok(DecodedOperand::with_string_index(
+            Operand::new_string(string),
+            string_index,
+        ))
+    }
+}
+
+static DEFAULT_OPERAND_DECODERS: OnceLock<HashMap<Opcode, Box<dyn OperandDecoder>>> =
+    OnceLock::new();
+
+/// Gets the default operand decoders, which decode the immediate opcodes.
+pub fn default_operand_decoders() -> &'static HashMap<Opcode, Box<dyn OperandDecoder>> {
+    DEFAULT_OPERAND_DECODERS.get_or_init(|| {
+        let mut decoders: HashMap<Opcode, Box<dyn OperandDecoder>> = HashMap::new();
+        for opcode in [
+            Opcode::ImmStringByte,
+            Opcode::ImmStringShort,
+            Opcode::ImmStringInt,
+            Opcode::ImmByte,
+            Opcode::ImmShort,
+            Opcode::ImmInt,
+            Opcode::ImmFloat,
+        ] {
+            decoders.insert(opcode, Box::new(ImmediateOperandDecoder));
+        }
+        decoders
+    })
+}
@@ -35,6 +35,10 @@ pub const GBF_DARK_GRAY: &str = "#1e1e1e";
 /// Max iterations for the structure analysis
 pub const STRUCTURE_ANALYSIS_MAX_ITERATIONS: usize = 1000;
 
+/// The number of most recent snapshots the structure analysis keeps when it only
+/// captures snapshots on failure
+pub const STRUCTURE_ANALYSIS_FAILURE_SNAPSHOTS: usize = 64;
+
 /// Escapes a string using `std::ascii::escape_default`.
 ///
 /// # Arguments
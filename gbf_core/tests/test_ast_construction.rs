@@ -1,7 +1,27 @@
-use common::{load_bytecode, load_expected_output};
+use common::{get_all_bytecode_files, load_bytecode, load_expected_output};
+use gbf_core::basic_block::BasicBlockType;
+use gbf_core::bytecode_writer::BytecodeWriter;
 use gbf_core::decompiler::{
-    ast::visitors::emit_context::EmitContext, function_decompiler::FunctionDecompilerBuilder,
+    ast::{
+        emit,
+        visitors::emit_context::{
+            CommentStyle, EmitContext, EmitContextBuilder, HeaderInfo, IndentStyle,
+        },
+    },
+    function_decompiler::{DecompilerWarning, FunctionDecompilerBuilder, FunctionDecompilerError},
+    passes::{
+        constant_propagation::ConstantPropagationPass, self_assignment::SelfAssignmentPass,
+        string_concat::StringConcatPass,
+    },
+    roundtrip::{roundtrip_check, RoundtripError},
+    structure_analysis::EntryPredecessorHandling,
 };
+use gbf_core::function::{Function, FunctionId};
+use gbf_core::instruction::Instruction;
+use gbf_core::opcode::Opcode;
+use gbf_core::operand::Operand;
+use gbf_core::utils::STRUCTURE_ANALYSIS_FAILURE_SNAPSHOTS;
+use std::collections::HashMap;
 pub mod common;
 
 #[test]
@@ -30,3 +50,1314 @@ fn load_simple_gs2() {
     // once the decompiler is more stable.
     assert!(decompiled.is_ok());
 }
+
+#[test]
+fn decompile_goto_fallback() {
+    let reader = load_bytecode("loops.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("loops.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("nestedLoops".to_string())
+        .unwrap();
+
+    // Stop structure analysis before it can reduce the function
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .structure_analysis_max_iterations(0)
+        .build();
+    assert!(decompiler.decompile(EmitContext::default()).is_err());
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .structure_analysis_max_iterations(0)
+        .build();
+    let decompiled = decompiler
+        .decompile(EmitContext::builder().allow_goto(true).build())
+        .unwrap();
+
+    // The loop back edges become gotos to labeled blocks
+    assert!(decompiled.contains("blk_0xD3:"));
+    assert!(decompiled.contains("goto blk_0xD3;"));
+    assert!(decompiled.contains("goto blk_0xF9;"));
+}
+
+#[test]
+fn decompile_goto_fallback_irreducible() {
+    let reader = load_bytecode("irreducible.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("irreducible.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("irreducible".to_string())
+        .unwrap();
+
+    // The loop can be entered at either of its blocks, so it cannot be structured
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let output = decompiler
+        .decompile_rich(EmitContext::builder().allow_goto(true).build())
+        .unwrap();
+    assert!(matches!(
+        output.warnings.as_slice(),
+        [DecompilerWarning::GotoFallback { .. }]
+    ));
+
+    let mut labels: Vec<_> = output
+        .source
+        .lines()
+        .filter_map(|line| line.trim().strip_suffix(':'))
+        .collect();
+    let mut gotos: Vec<_> = output
+        .source
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("goto "))
+        .filter_map(|target| target.strip_suffix(';'))
+        .collect();
+    labels.sort();
+    gotos.sort();
+    gotos.dedup();
+    assert_eq!(
+        labels,
+        vec!["blk_0x13", "blk_0x1F", "blk_0x8"],
+        "{}",
+        output.source
+    );
+    assert_eq!(gotos, labels, "{}", output.source);
+}
+
+#[test]
+fn decompile_rich_output() {
+    let reader = load_bytecode("loops.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("loops.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("nestedLoops".to_string())
+        .unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .structure_analysis_max_iterations(0)
+        .build();
+    let output = decompiler
+        .decompile_rich(EmitContext::builder().allow_goto(true).build())
+        .unwrap();
+
+    assert!(output.source.contains("goto blk_0xD3;"));
+    assert!(matches!(
+        output.warnings.as_slice(),
+        [DecompilerWarning::GotoFallback { .. }]
+    ));
+    assert!(output.line_map.is_none());
+    assert_eq!(output.metrics.attempts.len(), 1);
+    assert!(output.metrics.attempts[0].reached_max_iterations);
+    assert!(output.metrics.regions_before > 1);
+}
+
+#[test]
+fn decompile_metrics_report() {
+    let reader = load_bytecode("loops.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("loops.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("nestedLoops".to_string())
+        .unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let report = decompiler.metrics_report().unwrap();
+    assert!(!report.decompile_success);
+    assert_eq!(report.function, function.metrics_report().unwrap());
+    assert_eq!(report.function.loops, 2);
+
+    decompiler.decompile(EmitContext::default()).unwrap();
+    let report = decompiler.metrics_report().unwrap();
+    assert!(report.decompile_success);
+    assert_eq!(report.regions_after, 1);
+    assert_eq!(report.attempts.len(), 1);
+}
+
+#[test]
+fn decompile_snapshots_on_failure_only() {
+    let reader = load_bytecode("loops.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("loops.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("nestedLoops".to_string())
+        .unwrap();
+
+    // A successful run keeps no snapshots
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .snapshots_on_failure_only(true)
+        .build();
+    decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiler
+        .get_structure_analysis_snapshots()
+        .unwrap()
+        .is_empty());
+
+    // A failed run keeps the most recent snapshots
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .snapshots_on_failure_only(true)
+        .structure_analysis_max_iterations(0)
+        .build();
+    decompiler
+        .decompile(EmitContext::builder().allow_goto(true).build())
+        .unwrap();
+    let snapshots = decompiler.get_structure_analysis_snapshots().unwrap();
+    assert!(!snapshots.is_empty());
+    assert!(snapshots.len() <= STRUCTURE_ANALYSIS_FAILURE_SNAPSHOTS);
+    assert!(snapshots[0].starts_with("digraph"));
+}
+
+#[test]
+fn decompile_with_early_exit() {
+    let reader = load_bytecode("with-early-exit.gs2bc").unwrap();
+    let expected = load_expected_output("with-early-exit.gs2").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("with-early-exit.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("onCreated".to_string())
+        .unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+
+    // The code after the `with` scope must not end up inside it, or in an `else`
+    assert_eq!(decompiled.trim(), expected.trim());
+}
+
+#[test]
+fn decompile_annotate_regions() {
+    let reader = load_bytecode("with-early-exit.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("with-early-exit.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("onCreated".to_string())
+        .unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(!decompiled.contains("/*"));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler
+        .decompile(EmitContextBuilder::default().annotate_regions(true).build())
+        .unwrap();
+    assert!(decompiled.contains("// ControlFlow: With"));
+    assert!(!decompiled.contains("/*"));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler
+        .decompile(
+            EmitContextBuilder::default()
+                .annotate_regions(true)
+                .comment_style(CommentStyle::Block)
+                .build(),
+        )
+        .unwrap();
+    assert!(decompiled.contains("/* ControlFlow: With */"));
+    assert!(decompiled.contains("/* ControlFlow: Jne */"));
+    assert!(decompiled.contains("/* Linear */"));
+    assert!(decompiled.contains("/* Tail */"));
+}
+
+#[test]
+fn decompile_constant_propagation() {
+    let reader = load_bytecode("simple.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("simple.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_entry_function();
+
+    // Literals are assigned to temporaries unless they are propagated
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("lit = "));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(!decompiled.contains("lit"));
+    assert!(decompiled.contains("player.chat = \"Hello, World!\";"));
+    assert!(decompiled.contains("temp.x = 0;"));
+}
+
+#[test]
+fn decompile_provenance_comments() {
+    let reader = load_bytecode("simple.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("simple.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_entry_function();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(!decompiled.contains("// from"));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler
+        .decompile(
+            EmitContextBuilder::default()
+                .provenance_comments(true)
+                .build(),
+        )
+        .unwrap();
+    assert!(decompiled.contains("lit = \"Hello, World!\"; // from PushString@0x03"));
+    assert!(decompiled.contains("PushNumber@0x16, Add@0x17, Assign@0x18"));
+}
+
+#[test]
+fn decompile_counted_for_loop() {
+    let reader = load_bytecode("counted-loop.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("counted-loop.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module.get_function_by_name("countUp".to_string()).unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("for (temp.i = temp.start; temp.i < temp.end; temp.i++)"));
+    assert!(!decompiled.contains("while"));
+
+    let function = module
+        .get_function_by_name("countDown".to_string())
+        .unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("for (temp.i = temp.end; temp.i > temp.start; temp.i--)"));
+}
+
+#[test]
+fn decompile_leftover_stack_value() {
+    let reader = load_bytecode("leftover-stack.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("leftover-stack.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module.get_function_by_name("leftOver".to_string()).unwrap();
+
+    // The product is never consumed, so it is dropped by default
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(!decompiled.contains("temp.x * lit"), "{}", decompiled);
+
+    // When asked to, it is emitted before the return
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .emit_leftover_stack_values(true)
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    let product = decompiled.find("temp.x * lit;").unwrap();
+    let ret = decompiled.find("return lit;").unwrap();
+    assert!(product < ret);
+
+    // The leftover value is reported, which fails strict decompilation
+    assert!(matches!(
+        decompiler.warnings(),
+        [DecompilerWarning::StackImbalance { depth: 1, nodes, .. }] if nodes == &["temp.x * lit"]
+    ));
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .warnings_as_errors(true)
+        .build();
+    assert!(matches!(
+        decompiler.decompile(EmitContext::default()),
+        Err(FunctionDecompilerError::WarningsAsErrors { .. })
+    ));
+}
+
+#[test]
+fn decompile_assignment_expression() {
+    let reader = load_bytecode("assign-expression.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("assign-expression.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+
+    // The call is only made once, and its value is assigned inside the condition
+    assert_eq!(decompiled.matches("getValue()").count(), 1);
+    assert!(decompiled.contains("if ((temp.x = fn_call) > lit)"));
+    assert!(!decompiled.contains("temp.x = fn_call;"));
+}
+
+#[test]
+fn decompile_unimplemented_opcode_placeholder() {
+    let reader = load_bytecode("unimplemented-opcode.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("unimplemented-opcode.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+
+    // Without placeholders, the whole function fails
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    assert!(decompiler.decompile(EmitContext::default()).is_err());
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let output = decompiler
+        .decompile_rich(
+            EmitContext::builder()
+                .placeholder_unimplemented(true)
+                .comment_style(CommentStyle::Block)
+                .build(),
+        )
+        .unwrap();
+
+    // The rest of the block is still decompiled around the placeholder
+    assert!(output.source.contains("/* unimplemented: Exp@0x0a */"));
+    assert!(output.source.contains("temp.y = unimplemented;"));
+    assert!(output.source.contains("echo(temp.y);"));
+    assert!(matches!(
+        output.warnings.as_slice(),
+        [DecompilerWarning::UnimplementedOpcode { address: 0x0a, .. }]
+    ));
+}
+
+#[test]
+fn decompile_return_block_with_stray_edge() {
+    let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    let entry = function.get_entry_basic_block_id();
+    let stray = function.create_block(BasicBlockType::Exit, 2).unwrap();
+    function
+        .get_entry_basic_block_mut()
+        .add_instruction(Instruction::new_with_operand(
+            Opcode::PushNumber,
+            0,
+            Operand::new_number(1),
+        ));
+    function
+        .get_entry_basic_block_mut()
+        .add_instruction(Instruction::new(Opcode::Ret, 1));
+    let stray_block = function.get_basic_block_by_id_mut(stray).unwrap();
+    stray_block.add_instruction(Instruction::new_with_operand(
+        Opcode::PushNumber,
+        2,
+        Operand::new_number(2),
+    ));
+    stray_block.add_instruction(Instruction::new(Opcode::Ret, 3));
+    // The entry block returns, so this edge should never have been made
+    function.add_edge(entry, stray).unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function).build();
+    let output = decompiler.decompile_rich(EmitContext::default()).unwrap();
+
+    // The block behind the stray edge is dead code, so it is not decompiled
+    assert!(output.source.contains("lit = 1;"));
+    assert!(!output.source.contains("lit = 2;"));
+    assert_eq!(
+        output.warnings,
+        vec![
+            DecompilerWarning::StrayReturnEdges {
+                block_id: entry,
+                targets: vec![stray],
+            },
+            DecompilerWarning::UnreachableBlock { block_id: stray },
+        ]
+    );
+}
+
+#[test]
+fn decompile_goto_fallback_unreachable_block() {
+    let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    let entry = function.get_entry_basic_block_id();
+    let stray = function.create_block(BasicBlockType::Exit, 2).unwrap();
+    for (block_id, address) in [(entry, 0), (stray, 2)] {
+        let block = function.get_basic_block_by_id_mut(block_id).unwrap();
+        block.add_instruction(Instruction::new_with_operand(
+            Opcode::PushNumber,
+            address,
+            Operand::new_number(1),
+        ));
+        block.add_instruction(Instruction::new(Opcode::Ret, address + 1));
+    }
+    function.add_edge(entry, stray).unwrap();
+
+    // The goto body is built from the regions, and the unreachable block has none
+    let mut decompiler = FunctionDecompilerBuilder::new(function)
+        .structure_analysis_max_iterations(0)
+        .build();
+    let output = decompiler
+        .decompile_rich(EmitContext::builder().allow_goto(true).build())
+        .unwrap();
+    assert!(!output.source.contains("blk_0x2"), "{}", output.source);
+    assert!(output
+        .warnings
+        .contains(&DecompilerWarning::UnreachableBlock { block_id: stray }));
+}
+
+#[test]
+fn decompile_instructions_after_return() {
+    let mut function = Function::new(FunctionId::new_without_name(0, 0));
+    let entry = function.get_entry_basic_block_id();
+    let block = function.get_entry_basic_block_mut();
+    block.add_instruction(Instruction::new_with_operand(
+        Opcode::PushNumber,
+        0,
+        Operand::new_number(1),
+    ));
+    block.add_instruction(Instruction::new(Opcode::Ret, 1));
+    block.add_instruction(Instruction::new_with_operand(
+        Opcode::PushNumber,
+        2,
+        Operand::new_number(2),
+    ));
+    block.add_instruction(Instruction::new(Opcode::Ret, 3));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function).build();
+    let output = decompiler.decompile_rich(EmitContext::default()).unwrap();
+
+    // The instructions after the first return never execute
+    assert!(output.source.contains("lit = 1;"));
+    assert!(!output.source.contains("lit = 2;"));
+    assert_eq!(
+        output.warnings,
+        vec![DecompilerWarning::UnreachableInstructions {
+            block_id: entry,
+            count: 2,
+        }]
+    );
+}
+
+#[test]
+fn decompile_popped_values() {
+    let reader = load_bytecode("popped-values.gs2bc").unwrap();
+    let expected = load_expected_output("popped-values.gs2").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("popped-values.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+
+    // The ignored call result is a statement of its own, and the pure value is dropped
+    assert_eq!(decompiled.trim(), expected.trim());
+}
+
+#[test]
+fn decompile_named_register_locals() {
+    let reader = load_bytecode("loops.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("loops.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    // `temp.i` is marked as a register variable, and read back from the register
+    let function = module
+        .get_function_by_name("whileLoop".to_string())
+        .unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("while (temp.i < "), "{}", decompiled);
+    assert!(decompiled.contains("echo(temp.i);"), "{}", decompiled);
+    assert!(decompiled.contains("temp.i++;"), "{}", decompiled);
+    assert!(!decompiled.contains("set_register"), "{}", decompiled);
+}
+
+#[test]
+fn decompile_loop_at_function_entry() {
+    // while (x) { echo(x); } return 1;
+    let instructions = vec![
+        Instruction::new_with_operand(Opcode::PushVariable, 0, Operand::new_string("x")),
+        Instruction::new_with_operand(Opcode::Jne, 1, Operand::new_number(8)),
+        Instruction::new(Opcode::PushArray, 2),
+        Instruction::new_with_operand(Opcode::PushVariable, 3, Operand::new_string("x")),
+        Instruction::new_with_operand(Opcode::PushVariable, 4, Operand::new_string("echo")),
+        Instruction::new(Opcode::Call, 5),
+        Instruction::new(Opcode::Pop, 6),
+        Instruction::new_with_operand(Opcode::Jmp, 7, Operand::new_number(0)),
+        Instruction::new_with_operand(Opcode::PushNumber, 8, Operand::new_number(1)),
+        Instruction::new(Opcode::Ret, 9),
+    ];
+    let mut bytes = Vec::new();
+    BytecodeWriter::new(&HashMap::new(), &[], &instructions)
+        .write(&mut bytes)
+        .unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("entry_loop.gs2".to_string())
+        .reader(Box::new(std::io::Cursor::new(bytes)))
+        .build()
+        .unwrap();
+    let function = module.get_entry_function();
+
+    // The loop header is the entry block, so it has a predecessor
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("while (x)"), "{}", decompiled);
+    assert!(decompiled.contains("echo(x);"), "{}", decompiled);
+    assert!(decompiled.contains("return "), "{}", decompiled);
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .entry_predecessor_handling(EntryPredecessorHandling::Error)
+        .build();
+    assert!(matches!(
+        decompiler.decompile(EmitContext::default()),
+        Err(FunctionDecompilerError::StructureAnalysisError { .. })
+    ));
+}
+
+#[test]
+fn decompile_goto_fallback_jeq() {
+    // while (x) {} return 1;
+    let instructions = vec![
+        Instruction::new_with_operand(Opcode::PushVariable, 0, Operand::new_string("x")),
+        Instruction::new_with_operand(Opcode::Jeq, 1, Operand::new_number(0)),
+        Instruction::new_with_operand(Opcode::PushNumber, 2, Operand::new_number(1)),
+        Instruction::new(Opcode::Ret, 3),
+    ];
+    let mut bytes = Vec::new();
+    BytecodeWriter::new(&HashMap::new(), &[], &instructions)
+        .write(&mut bytes)
+        .unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("jeq.gs2".to_string())
+        .reader(Box::new(std::io::Cursor::new(bytes)))
+        .build()
+        .unwrap();
+    let function = module.get_entry_function();
+
+    // The loop header is the entry block, which structure analysis is told to reject
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .entry_predecessor_handling(EntryPredecessorHandling::Error)
+        .build();
+    let decompiled = decompiler
+        .decompile(EmitContext::builder().allow_goto(true).build())
+        .unwrap();
+
+    // `Jeq` jumps when `x` holds, so the negation it was decompiled with is unwrapped
+    assert!(
+        decompiled.contains("blk_0x0:\nif (x) \n{\n    goto blk_0x0;\n}\n"),
+        "{}",
+        decompiled
+    );
+}
+
+#[test]
+fn decompile_function_parameters() {
+    let reader = load_bytecode("short-circuit.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("short-circuit.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module
+        .get_function_by_name("returnAndShortCircuit".to_string())
+        .unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    assert!(decompiler.parameters().is_empty());
+
+    decompiler.decompile(EmitContext::default()).unwrap();
+    let parameters: Vec<_> = decompiler
+        .parameters()
+        .iter()
+        .map(|parameter| emit(parameter.clone()))
+        .collect();
+    assert_eq!(parameters, vec!["b", "b2"]);
+}
+
+#[test]
+fn decompile_member_array_chain() {
+    let reader = load_bytecode("member-array-chain.gs2bc").unwrap();
+    let expected = load_expected_output("member-array-chain.gs2").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("member-array-chain.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module.get_function_by_name("chain".to_string()).unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert_eq!(decompiled.trim(), expected.trim());
+}
+
+#[test]
+fn decompile_block_region_map() {
+    let reader = load_bytecode("counted-loop.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("counted-loop.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module.get_function_by_name("countUp".to_string()).unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    assert!(decompiler.block_region_map().is_empty());
+    assert!(decompiler.dump_block_region_map().is_empty());
+
+    decompiler.decompile(EmitContext::default()).unwrap();
+    assert_eq!(decompiler.block_region_map().len(), function.len());
+    let dump = decompiler.dump_block_region_map();
+    assert_eq!(dump.lines().count(), function.len());
+    assert!(dump.starts_with("Block at address 0x1 -> RegionId("));
+}
+
+#[test]
+fn decompile_auto_retry() {
+    let reader = load_bytecode("irreducible.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("irreducible.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("irreducible".to_string())
+        .unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .structure_analysis_max_iterations(1)
+        .build();
+    assert!(decompiler.decompile(EmitContext::default()).is_err());
+    assert_eq!(decompiler.structure_analysis_attempts().len(), 1);
+
+    // Each retry doubles the maximum, until it is enough to finish
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .structure_analysis_max_iterations(1)
+        .auto_retry(2, 3)
+        .build();
+    assert!(decompiler.decompile(EmitContext::default()).is_ok());
+    let attempts = decompiler.structure_analysis_attempts();
+    assert_eq!(attempts.len(), 3);
+    assert_eq!(attempts[0].max_iterations, 1);
+    assert!(attempts[0].reached_max_iterations);
+    assert_eq!(attempts[1].max_iterations, 2);
+    assert!(attempts[1].reached_max_iterations);
+    assert_eq!(attempts[2].max_iterations, 4);
+    assert!(!attempts[2].reached_max_iterations);
+}
+
+#[test]
+fn decompile_preserve_casts() {
+    let reader = load_bytecode("arithmetic.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("arithmetic.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("add".to_string()).unwrap();
+
+    // Casts are dropped by default
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("return a + b;"));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler
+        .decompile(EmitContext::builder().preserve_casts(true).build())
+        .unwrap();
+    assert!(decompiled.contains("return (float)a + (float)b;"));
+}
+
+#[test]
+fn decompile_expand_named_constants() {
+    let reader = load_bytecode("arithmetic.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("arithmetic.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("calculateCircleArea".to_string())
+        .unwrap();
+
+    // Named constants are kept by default
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("return pi * (radius ^ lit);"));
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler
+        .decompile(EmitContext::builder().expand_named_constants(true).build())
+        .unwrap();
+    assert!(decompiled.contains("return 3.141592653589793 * (radius ^ lit);"));
+}
+
+#[test]
+fn decompile_header() {
+    let reader = load_bytecode("arithmetic.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("arithmetic.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("divide".to_string()).unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let plain = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(plain.starts_with("function divide"));
+
+    let header = HeaderInfo {
+        module_hash: Some("deadbeef".to_string()),
+        options: Some("constant_propagation".to_string()),
+        ..Default::default()
+    };
+    let context = EmitContext::builder().emit_header(header).build();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(context.clone()).unwrap();
+    let expected_header = format!(
+        "// Decompiled by gbf {}\n// Module: deadbeef\n// Function: divide @ {:#x}\n// Options: constant_propagation\n",
+        gbf_core::utils::VERSION,
+        function.id.address
+    );
+    assert_eq!(decompiled, expected_header + &plain);
+    assert_eq!(decompiler.re_emit(context).unwrap(), decompiled);
+}
+
+#[test]
+fn decompile_temp_prefix() {
+    let reader = load_bytecode("simple.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("simple.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_entry_function();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let plain = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(plain.contains("lit = \"Hello, World!\";"));
+
+    let context = EmitContext::builder().temp_prefix("tmp").build();
+    let renamed = decompiler.re_emit(context).unwrap();
+    assert!(renamed.contains("tmp0 = \"Hello, World!\";"));
+    assert!(!renamed.contains("lit"));
+
+    // The stored AST keeps the generated names
+    assert_eq!(decompiler.re_emit(EmitContext::default()).unwrap(), plain);
+}
+
+#[test]
+fn decompile_function_attributes() {
+    let reader = load_bytecode("arithmetic.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("arithmetic.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("divide".to_string()).unwrap();
+    let attributes = HashMap::from([(function.id.clone(), vec!["public".to_string()])]);
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .function_attributes(attributes.clone())
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.starts_with("public\nfunction divide("));
+
+    // Functions without an entry have no attribute line
+    let entry = module.get_entry_function();
+    let mut decompiler = FunctionDecompilerBuilder::new(entry.clone())
+        .function_attributes(attributes)
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(!decompiled.contains("public"));
+}
+
+#[test]
+fn decompile_re_emit() {
+    let reader = load_bytecode("arithmetic.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("arithmetic.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("divide".to_string()).unwrap();
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    assert!(decompiler.ast().is_none());
+    assert!(decompiler.re_emit(EmitContext::default()).is_none());
+
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiler.ast().is_some());
+    assert_eq!(
+        decompiler.re_emit(EmitContext::default()).unwrap(),
+        decompiled
+    );
+
+    let context = EmitContext::builder()
+        .indent_step(2)
+        .indent_style(IndentStyle::KAndR)
+        .build();
+    let reformatted = decompiler.re_emit(context.clone()).unwrap();
+    assert_ne!(reformatted, decompiled);
+    let mut fresh = FunctionDecompilerBuilder::new(function.clone()).build();
+    assert_eq!(reformatted, fresh.decompile(context).unwrap());
+}
+
+#[test]
+fn decompile_return_array() {
+    let reader = load_bytecode("return-array.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("return-array.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    for (name, expected) in [
+        ("pair", "return {temp.a, temp.b};"),
+        ("nested", "return {temp.a, {1, 2}};"),
+        ("empty", "return {};"),
+    ] {
+        let function = module.get_function_by_name(name.to_string()).unwrap();
+        let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .build();
+        let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+        assert!(decompiled.contains(expected), "{}", decompiled);
+    }
+}
+
+#[test]
+fn decompile_warnings_as_errors() {
+    let reader = load_bytecode("unimplemented-opcode.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("unimplemented-opcode.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+    let emit_context = EmitContext::builder()
+        .placeholder_unimplemented(true)
+        .build();
+
+    // Warnings are lenient by default
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    assert!(decompiler.decompile(emit_context.clone()).is_ok());
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .warnings_as_errors(true)
+        .build();
+    let result = decompiler.decompile(emit_context);
+    assert!(matches!(
+        result,
+        Err(FunctionDecompilerError::WarningsAsErrors { ref warnings, .. })
+            if matches!(warnings.as_slice(), [DecompilerWarning::UnimplementedOpcode { .. }])
+    ));
+
+    // Functions without warnings still decompile
+    let reader = load_bytecode("simple.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("simple.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    for function in module.iter() {
+        let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+            .warnings_as_errors(true)
+            .build();
+        assert!(decompiler.decompile(EmitContext::default()).is_ok());
+    }
+}
+
+#[test]
+fn decompile_branch_polarity() {
+    let reader = load_bytecode("branch-polarity.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("branch-polarity.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    // `Jne` jumps when its condition is false, and `Jeq` when it is true
+    for (name, expected) in [
+        ("jneFallthrough", "if (temp.a)"),
+        ("jeqFallthrough", "if (!temp.a)"),
+        ("jneBranch", "if (!temp.a)"),
+        ("jeqBranch", "if (temp.a)"),
+        ("jneBranchTail", "if (temp.a)"),
+        ("jeqBranchTail", "if (!temp.a)"),
+    ] {
+        let function = module.get_function_by_name(name.to_string()).unwrap();
+        let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .build();
+        let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+        let condition = decompiled
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("if"))
+            .unwrap();
+        assert_eq!(condition, expected, "{}", decompiled);
+    }
+}
+
+#[test]
+fn decompile_braceless_single_statements() {
+    let reader = load_bytecode("branch-polarity.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("branch-polarity.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let function = module
+        .get_function_by_name("jneBranchTail".to_string())
+        .unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let context = EmitContext::builder()
+        .braces_for_single_statements(false)
+        .build();
+    let decompiled = decompiler.decompile(context).unwrap();
+    assert!(
+        decompiled.contains("if (temp.a) return temp.c;"),
+        "{}",
+        decompiled
+    );
+    assert!(decompiled.contains("else return temp.b;"), "{}", decompiled);
+
+    // The default output is unchanged
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("if (temp.a) \n"), "{}", decompiled);
+}
+
+#[test]
+fn decompile_zero_comparisons() {
+    let reader = load_bytecode("zero-comparison.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("zero-comparison.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let decompile = |name: &str, simplify_zero_comparisons: bool| {
+        let function = module.get_function_by_name(name.to_string()).unwrap();
+        let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .build();
+        let context = EmitContext::builder()
+            .simplify_zero_comparisons(simplify_zero_comparisons)
+            .build();
+        decompiler.decompile(context).unwrap()
+    };
+
+    for (name, expected, simplified) in [
+        ("notEqualZero", "if ((temp.a % 2) != 0)", "if (temp.a % 2)"),
+        ("equalZero", "if ((temp.a % 2) == 0)", "if (!(temp.a % 2))"),
+    ] {
+        assert!(decompile(name, false).contains(expected));
+        let decompiled = decompile(name, true);
+        assert!(decompiled.contains(simplified), "{}", decompiled);
+        assert!(!decompiled.contains(expected), "{}", decompiled);
+    }
+
+    // A variable may hold a string, which is not tested the same way
+    let decompiled = decompile("unknownOperand", true);
+    assert!(decompiled.contains("if (temp.a == 0)"), "{}", decompiled);
+
+    // A comparison used as a value keeps its result
+    let decompiled = decompile("zeroValue", true);
+    assert!(
+        decompiled.contains("temp.c = temp.a != 0;"),
+        "{}",
+        decompiled
+    );
+}
+
+#[test]
+fn decompile_objtype() {
+    let reader = load_bytecode("objtype.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("objtype.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    // Type queries are pure, so they are used in place instead of through a temporary
+    for (name, expected) in [
+        ("typeOf", "temp.t = temp.a.type();"),
+        ("isString", "return temp.a.type() == 1;"),
+    ] {
+        let function = module.get_function_by_name(name.to_string()).unwrap();
+        let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .build();
+        let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+        assert!(decompiled.contains(expected), "{}", decompiled);
+        assert!(!decompiled.contains("builtin_fn_call"), "{}", decompiled);
+    }
+}
+
+#[test]
+fn decompile_max_phi_nodes() {
+    let reader = load_bytecode("ternary.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("ternary.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("onCreated".to_string())
+        .unwrap();
+
+    // The ternary merges two values into a phi node
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .max_phi_nodes(1)
+        .build();
+    let output = decompiler.decompile_rich(EmitContext::default()).unwrap();
+    assert_eq!(output.metrics.phi_nodes, 1);
+    assert_eq!(decompiler.phi_node_count(), 1);
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .max_phi_nodes(0)
+        .build();
+    assert!(matches!(
+        decompiler.decompile(EmitContext::default()),
+        Err(FunctionDecompilerError::TooManyPhiNodes { limit: 0, .. })
+    ));
+}
+
+#[test]
+fn decompile_self_assignments() {
+    let reader = load_bytecode("self-assignment.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("self-assignment.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+
+    let decompile = |name: &str| {
+        let function = module.get_function_by_name(name.to_string()).unwrap();
+        let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .ast_pass(Box::new(SelfAssignmentPass))
+            .build();
+        decompiler.decompile(EmitContext::default()).unwrap()
+    };
+
+    let decompiled = decompile("droppable");
+    assert!(!decompiled.contains("temp.a[temp.i] = temp.a[temp.i];"));
+    assert!(!decompiled.contains("this.x = this.x;"));
+    assert!(decompiled.contains("return temp.a;"));
+
+    // Each call result is its own temporary, so the two sides already differ
+    let decompiled = decompile("kept");
+    assert!(decompiled.contains("temp.a[fn_call] = temp.a[fn_call];"));
+
+    // Both sides are the same, but each creates a new array
+    let decompiled = decompile("keptNewArray");
+    assert!(
+        decompiled.contains("temp.a[new [2]] = temp.a[new [2]];"),
+        "{}",
+        decompiled
+    );
+}
+
+#[test]
+fn decompile_string_concat_chain() {
+    let reader = load_bytecode("string-concat.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("string-concat.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+
+    // The literals are only merged once they have been propagated into the chain
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(ConstantPropagationPass))
+        .ast_pass(Box::new(StringConcatPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(
+        decompiled.contains("temp.s = \"Hello, world \" @ temp.name @ \"!\";"),
+        "{}",
+        decompiled
+    );
+
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone())
+        .ast_pass(Box::new(StringConcatPass))
+        .build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(decompiled.contains("temp.s = lit @ lit @ temp.name @ lit;"));
+}
+
+#[test]
+fn decompile_compound_assignment_targets() {
+    let decompile = |file: &str| {
+        let reader = load_bytecode(file).unwrap();
+        let module = gbf_core::module::ModuleBuilder::new()
+            .name(file.to_string())
+            .reader(Box::new(reader))
+            .build()
+            .unwrap();
+        let function = module.get_function_by_name("onTest".to_string()).unwrap();
+        FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .build()
+            .decompile(EmitContext::default())
+            .unwrap()
+    };
+
+    let decompiled = decompile("compound-array.gs2bc");
+    assert!(decompiled.contains("temp.a[temp.i]++;"), "{}", decompiled);
+    assert!(
+        decompiled.contains("temp.a[temp.i] += 3;"),
+        "{}",
+        decompiled
+    );
+    // Each call result is a different value, so the element is not the target
+    assert!(
+        decompiled.contains("temp.a[fn_call] = temp.a[fn_call] + 1;"),
+        "{}",
+        decompiled
+    );
+
+    let decompiled = decompile("compound-member.gs2bc");
+    assert!(decompiled.contains("temp.obj.x -= 2;"), "{}", decompiled);
+    assert!(
+        decompiled.contains("temp.obj.x = temp.obj.y - 2;"),
+        "{}",
+        decompiled
+    );
+}
+
+#[test]
+fn decompile_short_circuit_grouping() {
+    let reader = load_bytecode("short-circuit-grouping.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("short-circuit-grouping.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    assert!(
+        decompiled.contains("temp.x = temp.a && temp.b || temp.c;"),
+        "{}",
+        decompiled
+    );
+    assert!(
+        decompiled.contains("temp.y = temp.a && (temp.b || temp.c);"),
+        "{}",
+        decompiled
+    );
+
+    // Chains of the same operation end at the same address, and are left-associative
+    let reader = load_bytecode("short-circuit.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("short-circuit.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module
+        .get_function_by_name("onCreated".to_string())
+        .unwrap();
+    let mut decompiler = FunctionDecompilerBuilder::new(function.clone()).build();
+    let decompiled = decompiler.decompile(EmitContext::default()).unwrap();
+    for expected in [
+        "temp.simpleAndThree = temp.thingOne && temp.thingTwo && temp.thingThree;",
+        "temp.simpleOrThree = temp.thingOne || temp.thingTwo || temp.thingThree;",
+        "temp.complexTwo = temp.thingOne && temp.thingTwo || temp.thingThree && temp.thingOne;",
+    ] {
+        assert!(decompiled.contains(expected), "{}", decompiled);
+    }
+    assert!(!decompiled.contains("phi<"), "{}", decompiled);
+    assert_eq!(decompiler.phi_node_count(), 0);
+}
+
+#[test]
+fn decompile_short_circuit_assignment() {
+    let reader = load_bytecode("short-circuit-assignment.gs2bc").unwrap();
+    let module = gbf_core::module::ModuleBuilder::new()
+        .name("short-circuit-assignment.gs2".to_string())
+        .reader(Box::new(reader))
+        .build()
+        .unwrap();
+    let function = module.get_function_by_name("onTest".to_string()).unwrap();
+    let decompile = |emit_context: EmitContext| {
+        FunctionDecompilerBuilder::new(function.clone())
+            .ast_pass(Box::new(ConstantPropagationPass))
+            .build()
+            .decompile(emit_context)
+            .unwrap()
+    };
+
+    let decompiled = decompile(EmitContext::default());
+    assert!(
+        decompiled.contains("temp.x ||= \"default\";"),
+        "{}",
+        decompiled
+    );
+    assert!(decompiled.contains("temp.y &&= temp.z;"), "{}", decompiled);
+
+    let decompiled = decompile(
+        EmitContextBuilder::default()
+            .use_compound_assignment(false)
+            .build(),
+    );
+    assert!(
+        decompiled.contains("temp.x = temp.x || \"default\";"),
+        "{}",
+        decompiled
+    );
+    assert!(
+        decompiled.contains("temp.y = temp.y && temp.z;"),
+        "{}",
+        decompiled
+    );
+}
+
+#[test]
+fn roundtrip_all_bytecode() {
+    for file in get_all_bytecode_files().unwrap() {
+        let reader = load_bytecode(&file).unwrap();
+        let module = gbf_core::module::ModuleBuilder::new()
+            .name(file.clone())
+            .reader(Box::new(reader))
+            .build()
+            .unwrap();
+
+        for function in &module {
+            // Functions the decompiler does not support yet are covered elsewhere
+            match roundtrip_check(function) {
+                Ok(()) | Err(RoundtripError::Decompile(_)) => {}
+                Err(e) => panic!("{} in {:?}: {}", file, function.id, e),
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use common::{get_all_bytecode_files, load_bytecode};
-use gbf_core::cfg_dot::{CfgDotConfig, DotRenderableGraph};
+use gbf_core::{
+    cfg_dot::{CfgDotConfig, DotRenderableGraph},
+    cfg_mermaid::{CfgMermaidConfig, MermaidRenderableGraph},
+};
 pub mod common;
 
 #[test]
@@ -20,3 +23,74 @@ fn test_all_cfg_render() {
         }
     }
 }
+
+#[test]
+fn test_all_cfg_render_mermaid() {
+    for fname in get_all_bytecode_files().unwrap() {
+        let reader = load_bytecode(&fname).unwrap();
+        let module = gbf_core::module::ModuleBuilder::new()
+            .name(fname.clone())
+            .reader(Box::new(reader))
+            .build()
+            .unwrap();
+
+        for function in module.iter() {
+            let result = function.render_mermaid(CfgMermaidConfig::default());
+
+            assert!(result.starts_with("flowchart"));
+            assert_eq!(
+                result.matches(" --> ").count(),
+                function
+                    .render_dot(CfgDotConfig::default())
+                    .matches(" -> ")
+                    .count()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_all_max_stack_depth() {
+    for fname in get_all_bytecode_files().unwrap() {
+        let reader = load_bytecode(&fname).unwrap();
+        let module = gbf_core::module::ModuleBuilder::new()
+            .name(fname.clone())
+            .reader(Box::new(reader))
+            .build()
+            .unwrap();
+
+        for function in module.iter() {
+            // every path into a block should agree on the stack depth
+            assert!(
+                function.max_stack_depth().is_ok(),
+                "{}: {}",
+                fname,
+                function.id
+            );
+        }
+    }
+}
+
+#[test]
+fn test_all_jump_targets_start_blocks() {
+    for fname in get_all_bytecode_files().unwrap() {
+        let reader = load_bytecode(&fname).unwrap();
+        let module = gbf_core::module::ModuleBuilder::new()
+            .name(fname.clone())
+            .reader(Box::new(reader))
+            .build()
+            .unwrap();
+
+        for function in module.iter() {
+            for target in function.jump_targets() {
+                assert!(
+                    function.basic_block_exists_by_address(target),
+                    "{}: {} jumps to {:#x}, which does not start a block",
+                    fname,
+                    function.id,
+                    target
+                );
+            }
+        }
+    }
+}
@@ -13,10 +13,9 @@ use consts::{ExitCode, GBF_SUITE_INPUT_DIR_ENV_VAR};
 use dotenv::dotenv;
 use gbf_core::{
     decompiler::{
-        ast::visitors::emit_context::EmitContextBuilder,
-        function_decompiler::FunctionDecompilerBuilder,
+        ast::visitors::emit_context::EmitContextBuilder, decompile_options::DecompileOptions,
     },
-    module::ModuleBuilder,
+    module::{DecompileResult, ModuleBuilder},
 };
 
 use rayon::prelude::*;
@@ -30,6 +29,7 @@ struct DecompStats {
     total_scripts: usize,
     total_functions: usize,
     successful_functions: usize,
+    skipped_functions: usize,
     error_counts: HashMap<String, usize>,
 }
 
@@ -39,6 +39,7 @@ impl DecompStats {
             total_scripts: 0,
             total_functions: 0,
             successful_functions: 0,
+            skipped_functions: 0,
             error_counts: HashMap::new(),
         }
     }
@@ -52,6 +53,10 @@ impl DecompStats {
         self.successful_functions += 1;
     }
 
+    fn add_skipped(&mut self) {
+        self.skipped_functions += 1;
+    }
+
     fn add_error(&mut self, error: String) {
         *self.error_counts.entry(error).or_insert(0) += 1;
     }
@@ -72,10 +77,12 @@ impl DecompStats {
             "Decompilation Statistics:\n\
             Total Scripts: {}\n\
             Total Functions: {}\n\
+            Skipped Functions: {}\n\
             Total Coverage: {:.1}%\n\
             Top 10 most common errors:{}",
             self.total_scripts,
             self.total_functions,
+            self.skipped_functions,
             coverage,
             if top_errors.is_empty() {
                 "\nNone".to_string()
@@ -163,37 +170,35 @@ fn process_module(path: &path::Path) -> Result<(), Box<dyn std::error::Error>> {
     // Also note how many functions are in this module.
     STATS.lock().unwrap().add_script(module.len());
 
-    // Decompile each function in parallel using rayon
-    module.par_iter().for_each(|func| {
-        let func_name = func.id.name.clone().unwrap_or_else(|| "entry".to_string());
-        log::info!(
-            "Decompiling function {} in module {}",
-            func_name,
-            module_name
-        );
-
-        let mut decompiler = FunctionDecompilerBuilder::new(func.clone())
-            .structure_analysis_max_iterations(100)
-            .structure_debug_mode(true)
-            .build();
-
-        let res = decompiler.decompile(
-            EmitContextBuilder::default()
-                .include_ssa_versions(true)
-                .build(),
-        );
-
+    // Decompile each function in parallel
+    log::info!("Decompiling functions in module {}", module_name);
+    let options = DecompileOptions::new()
+        .structure_analysis_max_iterations(100)
+        .structure_debug_mode(true);
+    let results = module.decompile_all(
+        EmitContextBuilder::default()
+            .include_ssa_versions(true)
+            .build(),
+        options,
+    )?;
+
+    for (function_id, res) in results {
+        let func_name = function_id.name.unwrap_or_else(|| "entry".to_string());
         match res {
-            Ok(_) => {
+            DecompileResult::Skipped => {
+                STATS.lock().unwrap().add_skipped();
+                log::info!("Skipped function {}", func_name);
+            }
+            DecompileResult::Decompiled(Ok(_)) => {
                 STATS.lock().unwrap().add_success();
                 log::info!("Decompiled function {}", func_name);
             }
-            Err(e) => {
+            DecompileResult::Decompiled(Err(e)) => {
                 STATS.lock().unwrap().add_error(e.to_string());
                 log::error!("Error decompiling function {}: {}", func_name, e);
             }
         }
-    });
+    }
 
     Ok(())
 }
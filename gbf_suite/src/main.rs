@@ -14,8 +14,8 @@ use dotenv::dotenv;
 use gbf_core::{
     cfg_dot::{CfgDotConfig, DotRenderableGraph},
     decompiler::{
-        ast::visitors::emit_context::EmitContextBuilder,
-        function_decompiler::{FunctionDecompilerBuilder, FunctionDecompilerErrorDetails},
+        ast::visitors::emit_context::EmitContextBuilder, decompile_options::DecompileOptions,
+        function_decompiler::FunctionDecompilerErrorDetails,
     },
     module::ModuleBuilder,
     utils::VERSION,
@@ -149,6 +149,10 @@ async fn process_module(
         decompile_success: true,
     };
 
+    let options = DecompileOptions::new()
+        .structure_analysis_max_iterations(100)
+        .structure_debug_mode(true);
+
     for func in module.iter() {
         let func_basic_block_dot = func.render_dot(CfgDotConfig::default());
         let func_basic_block_dot_key = uploader.upload_graphviz_dot(func_basic_block_dot).await?;
@@ -160,10 +164,7 @@ async fn process_module(
 
         let time = Instant::now();
 
-        let mut decompiler = FunctionDecompilerBuilder::new(func.clone())
-            .structure_analysis_max_iterations(100)
-            .structure_debug_mode(true)
-            .build();
+        let mut decompiler = options.decompiler(func.clone());
 
         let res = decompiler.decompile(
             EmitContextBuilder::default()